@@ -0,0 +1,136 @@
+//! Enemy archetypes loaded from `assets/enemies.toml`: one `[grunt]`, `[guard]`,
+//! `[scout]` and `[boss]` section with the stats, attack pattern and sprite of each type.
+//!
+//! ```text
+//! [guard]
+//! health = 150
+//! speed = 0.9            # pixels por frame persiguiendo
+//! attack = "ranged"      # melee, ranged, burst
+//! damage = 10
+//! accuracy = 0.7         # probabilidad de acertar
+//! range = 400            # distancia máxima de ataque
+//! keep_distance = 220    # se para a esta distancia (0 = hasta el cuerpo a cuerpo)
+//! cooldown = 60          # frames entre ataques
+//! burst = 1              # disparos por ráfaga
+//! sight = 550            # distancia a la que ve al jugador
+//! hearing = 900          # distancia (por los pasillos) a la que oye un disparo
+//! cover_below = 0.5      # se cubre con menos de esta fracción de vida (0 = nunca)
+//! flee_below = 0.0       # huye con menos de esta fracción de vida (0 = nunca)
+//! patrol = false         # patrulla cuando no ha visto al jugador, o espera quieto
+//! sprite = "enemy_guard" # textura del atlas (assets/textures/enemy_guard.png)
+//! tint = [255, 255, 255] # color que multiplica el sprite
+//! scale = 1.05           # altura en bloques
+//! ```
+
+use std::path::Path;
+
+use raylib::prelude::Color;
+use crate::{
+    data_file::{self, DataError, ParseError, Table, err, parse_tables},
+    sprites::EnemyKind,
+};
+
+pub const ARCHETYPES_PATH: &str = "assets/enemies.toml";
+
+/// How an archetype attacks once the player is in range.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AttackPattern {
+    Melee,  // golpe cuerpo a cuerpo, sin línea de tiro larga
+    Ranged, // un disparo por ataque
+    Burst,  // ráfaga de `burst` disparos seguidos
+}
+
+#[derive(Clone, Debug)]
+pub struct Archetype {
+    pub health: i32,
+    pub speed: f32,
+    pub attack: AttackPattern,
+    pub damage: i32,
+    pub accuracy: f32,
+    pub range: f32,
+    pub keep_distance: f32,
+    pub cooldown: i32,
+    pub burst: i32,
+    pub sight: f32,
+    pub hearing: f32,
+    pub cover_below: f32,
+    pub flee_below: f32,
+    pub patrol: bool,
+    pub sprite: String,
+    pub tint: Color,
+    pub scale: f32,
+}
+
+const KEYS: [&str; 17] = [
+    "health", "speed", "attack", "damage", "accuracy", "range", "keep_distance", "cooldown", "burst",
+    "sight", "hearing", "cover_below", "flee_below", "patrol", "sprite", "tint", "scale",
+];
+
+impl Archetype {
+    fn from_table(table: &Table, has_sprite: &impl Fn(&str) -> bool) -> Result<Self, ParseError> {
+        table.check_keys(&KEYS)?;
+        let attack = table.require("attack")?;
+        let sprite = table.require("sprite")?;
+        if !has_sprite(sprite.str()?) {
+            return err(sprite.line, sprite.value_col, format!("no hay ninguna textura '{}'", sprite.str()?));
+        }
+        let [r, g, b] = table.require("tint")?.rgb()?;
+        let archetype = Self {
+            health: table.require("health")?.non_negative()?.max(1) as i32,
+            speed: table.require("speed")?.positive_float()? as f32,
+            attack: match attack.str()? {
+                "melee" => AttackPattern::Melee,
+                "ranged" => AttackPattern::Ranged,
+                "burst" => AttackPattern::Burst,
+                other => return err(attack.line, attack.value_col, format!("ataque desconocido '{}' (melee, ranged, burst)", other)),
+            },
+            damage: table.require("damage")?.non_negative()? as i32,
+            accuracy: table.require("accuracy")?.fraction()? as f32,
+            range: table.require("range")?.positive_float()? as f32,
+            keep_distance: table.require("keep_distance")?.float()?.max(0.0) as f32,
+            cooldown: table.require("cooldown")?.non_negative()? as i32,
+            burst: table.require("burst")?.non_negative()?.max(1) as i32,
+            sight: table.require("sight")?.positive_float()? as f32,
+            hearing: table.require("hearing")?.float()?.max(0.0) as f32,
+            cover_below: table.require("cover_below")?.fraction()? as f32,
+            flee_below: table.require("flee_below")?.fraction()? as f32,
+            patrol: table.require("patrol")?.bool()?,
+            sprite: sprite.str()?.to_string(),
+            tint: Color::new(r, g, b, 255),
+            scale: table.require("scale")?.positive_float()? as f32,
+        };
+        if archetype.keep_distance > archetype.range {
+            let e = table.require("keep_distance")?;
+            return err(e.line, e.value_col, "'keep_distance' no puede ser mayor que 'range'");
+        }
+        Ok(archetype)
+    }
+}
+
+/// One archetype per `EnemyKind`.
+pub struct Archetypes {
+    list: Vec<Archetype>, // en el orden de EnemyKind::ALL
+}
+
+impl Archetypes {
+    /// Loads and validates the archetype file; `has_sprite` tells which texture keys exist.
+    pub fn load<P: AsRef<Path>>(path: P, has_sprite: impl Fn(&str) -> bool) -> Result<Self, DataError> {
+        data_file::load(path, |text| {
+            let lines: Vec<&str> = text.lines().collect();
+            let names: Vec<&str> = EnemyKind::ALL.iter().map(|k| k.name()).collect();
+            let (tables, _) = parse_tables(&lines, &names, &[], None)?;
+            let list = EnemyKind::ALL
+                .iter()
+                .map(|kind| match tables.iter().find(|t| t.name == kind.name()) {
+                    Some(table) => Archetype::from_table(table, &has_sprite),
+                    None => err(lines.len().max(1), 1, format!("falta la sección [{}]", kind.name())),
+                })
+                .collect::<Result<_, _>>()?;
+            Ok(Self { list })
+        })
+    }
+
+    pub fn get(&self, kind: EnemyKind) -> &Archetype {
+        &self.list[kind as usize]
+    }
+}
@@ -1,4 +1,5 @@
 use raylib::prelude::*;
+use std::collections::HashMap;
 use std::sync::OnceLock;
 
 static AUDIO_DEVICE: OnceLock<RaylibAudio> = OnceLock::new();
@@ -25,6 +26,35 @@ pub fn load_music<'a>(path: &str) -> Option<Music<'a>> {
     None
 }
 
+/// Short sound effects by name, loaded from `assets/audios/<name>.wav`. Sounds whose
+/// file can't be loaded are skipped, so playing them does nothing.
+pub struct Sounds {
+    list: HashMap<&'static str, Sound<'static>>,
+}
+
+impl Sounds {
+    pub fn load(names: &[&'static str]) -> Self {
+        let device = init_audio();
+        let mut list = HashMap::new();
+        for &name in names {
+            if list.contains_key(name) { continue; }
+            match device.new_sound(&format!("assets/audios/{}.wav", name)) {
+                Ok(sound) => { list.insert(name, sound); }
+                Err(_) => eprintln!("Failed to load sound {}", name),
+            }
+        }
+        Self { list }
+    }
+
+    pub fn set_volume(&self, volume: f32) {
+        for sound in self.list.values() { sound.set_volume(volume); }
+    }
+
+    pub fn play(&self, name: &str) {
+        if let Some(sound) = self.list.get(name) { sound.play(); }
+    }
+}
+
 /// Update a music stream (call every frame)
 pub fn update_music(m: &Music) { m.update_stream(); }
 
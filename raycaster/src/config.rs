@@ -0,0 +1,380 @@
+//! Player settings kept between runs in `settings.cfg`: field of view, turn sensitivity,
+//! movement speed, volumes, render quality and the key bound to every action. Same
+//! format as the level headers; missing fields keep their default so an older file
+//! still loads, and a missing file means all defaults.
+
+use std::fmt::Write as _;
+use std::path::Path;
+use raylib::prelude::*;
+use crate::data_file::{self, err, parse_tables, DataError, Entry, ParseError};
+
+pub const SETTINGS_PATH: &str = "settings.cfg";
+
+pub const FOV_RANGE: (i32, i32) = (40, 120); // grados
+pub const SENSITIVITY_RANGE: (f32, f32) = (0.2, 3.0);
+const MOVE_SPEED_RANGE: (f32, f32) = (0.5, 10.0); // pixels por frame
+pub const RENDER_SCALE_MAX: usize = 6;
+const BASE_TURN_SPEED: f32 = 0.05; // radianes por frame con sensibilidad 1
+
+/// Something the player can do in game, each bound to one key.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    Forward,
+    Back,
+    StrafeLeft,
+    StrafeRight,
+    TurnLeft,
+    TurnRight,
+    Fire,
+    Reload,
+    Use,
+    Weapon1,
+    Weapon2,
+    Weapon3,
+    Weapon4,
+    Mute,
+    QualityUp,
+    QualityDown,
+}
+
+impl Action {
+    pub const ALL: [Action; 16] = [
+        Action::Forward,
+        Action::Back,
+        Action::StrafeLeft,
+        Action::StrafeRight,
+        Action::TurnLeft,
+        Action::TurnRight,
+        Action::Fire,
+        Action::Reload,
+        Action::Use,
+        Action::Weapon1,
+        Action::Weapon2,
+        Action::Weapon3,
+        Action::Weapon4,
+        Action::Mute,
+        Action::QualityUp,
+        Action::QualityDown,
+    ];
+
+    /// Weapon slots, in `WeaponKind::ALL` order.
+    pub const WEAPONS: [Action; 4] = [Action::Weapon1, Action::Weapon2, Action::Weapon3, Action::Weapon4];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Field name in the `[keys]` section.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Forward => "forward",
+            Self::Back => "back",
+            Self::StrafeLeft => "strafe_left",
+            Self::StrafeRight => "strafe_right",
+            Self::TurnLeft => "turn_left",
+            Self::TurnRight => "turn_right",
+            Self::Fire => "fire",
+            Self::Reload => "reload",
+            Self::Use => "use",
+            Self::Weapon1 => "weapon_1",
+            Self::Weapon2 => "weapon_2",
+            Self::Weapon3 => "weapon_3",
+            Self::Weapon4 => "weapon_4",
+            Self::Mute => "mute",
+            Self::QualityUp => "quality_up",
+            Self::QualityDown => "quality_down",
+        }
+    }
+
+    /// Text shown in the options screen.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Forward => "Move forward",
+            Self::Back => "Move back",
+            Self::StrafeLeft => "Strafe left",
+            Self::StrafeRight => "Strafe right",
+            Self::TurnLeft => "Turn left",
+            Self::TurnRight => "Turn right",
+            Self::Fire => "Fire",
+            Self::Reload => "Reload",
+            Self::Use => "Open door",
+            Self::Weapon1 => "Pistol",
+            Self::Weapon2 => "Shotgun",
+            Self::Weapon3 => "Machine gun",
+            Self::Weapon4 => "Plasma",
+            Self::Mute => "Mute music",
+            Self::QualityUp => "Better quality",
+            Self::QualityDown => "Faster render",
+        }
+    }
+
+    fn default_key(self) -> KeyboardKey {
+        match self {
+            Self::Forward => KeyboardKey::KEY_W,
+            Self::Back => KeyboardKey::KEY_S,
+            Self::StrafeLeft => KeyboardKey::KEY_A,
+            Self::StrafeRight => KeyboardKey::KEY_D,
+            Self::TurnLeft => KeyboardKey::KEY_LEFT,
+            Self::TurnRight => KeyboardKey::KEY_RIGHT,
+            Self::Fire => KeyboardKey::KEY_SPACE,
+            Self::Reload => KeyboardKey::KEY_R,
+            Self::Use => KeyboardKey::KEY_E,
+            Self::Weapon1 => KeyboardKey::KEY_ONE,
+            Self::Weapon2 => KeyboardKey::KEY_TWO,
+            Self::Weapon3 => KeyboardKey::KEY_THREE,
+            Self::Weapon4 => KeyboardKey::KEY_FOUR,
+            Self::Mute => KeyboardKey::KEY_M,
+            Self::QualityUp => KeyboardKey::KEY_Z,
+            Self::QualityDown => KeyboardKey::KEY_X,
+        }
+    }
+}
+
+/// Keys that can be bound, with their name in the settings file. ESC is left out: it
+/// always pauses and goes back in menus.
+pub const BINDABLE_KEYS: [(KeyboardKey, &str); 52] = [
+    (KeyboardKey::KEY_A, "A"), (KeyboardKey::KEY_B, "B"), (KeyboardKey::KEY_C, "C"),
+    (KeyboardKey::KEY_D, "D"), (KeyboardKey::KEY_E, "E"), (KeyboardKey::KEY_F, "F"),
+    (KeyboardKey::KEY_G, "G"), (KeyboardKey::KEY_H, "H"), (KeyboardKey::KEY_I, "I"),
+    (KeyboardKey::KEY_J, "J"), (KeyboardKey::KEY_K, "K"), (KeyboardKey::KEY_L, "L"),
+    (KeyboardKey::KEY_M, "M"), (KeyboardKey::KEY_N, "N"), (KeyboardKey::KEY_O, "O"),
+    (KeyboardKey::KEY_P, "P"), (KeyboardKey::KEY_Q, "Q"), (KeyboardKey::KEY_R, "R"),
+    (KeyboardKey::KEY_S, "S"), (KeyboardKey::KEY_T, "T"), (KeyboardKey::KEY_U, "U"),
+    (KeyboardKey::KEY_V, "V"), (KeyboardKey::KEY_W, "W"), (KeyboardKey::KEY_X, "X"),
+    (KeyboardKey::KEY_Y, "Y"), (KeyboardKey::KEY_Z, "Z"),
+    (KeyboardKey::KEY_ZERO, "0"), (KeyboardKey::KEY_ONE, "1"), (KeyboardKey::KEY_TWO, "2"),
+    (KeyboardKey::KEY_THREE, "3"), (KeyboardKey::KEY_FOUR, "4"), (KeyboardKey::KEY_FIVE, "5"),
+    (KeyboardKey::KEY_SIX, "6"), (KeyboardKey::KEY_SEVEN, "7"), (KeyboardKey::KEY_EIGHT, "8"),
+    (KeyboardKey::KEY_NINE, "9"),
+    (KeyboardKey::KEY_UP, "UP"), (KeyboardKey::KEY_DOWN, "DOWN"),
+    (KeyboardKey::KEY_LEFT, "LEFT"), (KeyboardKey::KEY_RIGHT, "RIGHT"),
+    (KeyboardKey::KEY_SPACE, "SPACE"), (KeyboardKey::KEY_ENTER, "ENTER"),
+    (KeyboardKey::KEY_TAB, "TAB"), (KeyboardKey::KEY_BACKSPACE, "BACKSPACE"),
+    (KeyboardKey::KEY_LEFT_SHIFT, "LEFT_SHIFT"), (KeyboardKey::KEY_RIGHT_SHIFT, "RIGHT_SHIFT"),
+    (KeyboardKey::KEY_LEFT_CONTROL, "LEFT_CONTROL"), (KeyboardKey::KEY_RIGHT_CONTROL, "RIGHT_CONTROL"),
+    (KeyboardKey::KEY_LEFT_ALT, "LEFT_ALT"), (KeyboardKey::KEY_RIGHT_ALT, "RIGHT_ALT"),
+    (KeyboardKey::KEY_COMMA, ","), (KeyboardKey::KEY_PERIOD, "."),
+];
+
+pub fn key_name(key: KeyboardKey) -> &'static str {
+    BINDABLE_KEYS.iter().find(|(k, _)| *k == key).map_or("?", |(_, name)| name)
+}
+
+fn key_from_name(name: &str) -> Option<KeyboardKey> {
+    BINDABLE_KEYS.iter().find(|(_, n)| n.eq_ignore_ascii_case(name)).map(|(k, _)| *k)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settings {
+    pub fov_degrees: i32,
+    pub sensitivity: f32, // multiplica la velocidad de giro
+    pub move_speed: f32,  // pixels por frame
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub render_scale: usize, // 1 = resolución nativa
+    keys: [KeyboardKey; Action::ALL.len()],
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            fov_degrees: 60,
+            sensitivity: 1.0,
+            move_speed: 3.0,
+            music_volume: 0.5,
+            sfx_volume: 1.0,
+            render_scale: 1,
+            keys: Action::ALL.map(Action::default_key),
+        }
+    }
+}
+
+impl Settings {
+    /// Settings from `SETTINGS_PATH`, or the defaults if the file is missing or broken.
+    pub fn current() -> Self {
+        Self::load(SETTINGS_PATH).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            Self::default()
+        })
+    }
+
+    /// Reads the settings file; a file that doesn't exist yet gives the defaults.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, DataError> {
+        if !path.as_ref().exists() { return Ok(Self::default()); }
+        data_file::load(path, Self::parse)
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        std::fs::write(path, self.to_text())
+    }
+
+    pub fn fov(&self) -> f32 {
+        (self.fov_degrees as f32).to_radians()
+    }
+
+    pub fn turn_speed(&self) -> f32 {
+        BASE_TURN_SPEED * self.sensitivity
+    }
+
+    pub fn key(&self, action: Action) -> KeyboardKey {
+        self.keys[action.index()]
+    }
+
+    /// Binds `key` to `action`; the action that had it before gets `action`'s old key.
+    pub fn bind(&mut self, action: Action, key: KeyboardKey) {
+        let old = self.key(action);
+        if let Some(other) = self.keys.iter_mut().find(|k| **k == key) { *other = old; }
+        self.keys[action.index()] = key;
+    }
+
+    pub fn down(&self, rl: &RaylibHandle, action: Action) -> bool {
+        rl.is_key_down(self.key(action))
+    }
+
+    pub fn pressed(&self, rl: &RaylibHandle, action: Action) -> bool {
+        rl.is_key_pressed(self.key(action))
+    }
+
+    fn to_text(&self) -> String {
+        let mut out = String::new();
+        // `write!` sobre un String no falla
+        let _ = writeln!(out, "# Dungeon Delver: configuración (se reescribe desde Options)");
+        // `{}` escribe el float más corto que se vuelve a leer igual
+        let _ = writeln!(out, "[game]\nfov = {}\nsensitivity = {}\nmove_speed = {}\n",
+            self.fov_degrees, self.sensitivity, self.move_speed);
+        let _ = writeln!(out, "[audio]\nmusic_volume = {}\nsfx_volume = {}\n", self.music_volume, self.sfx_volume);
+        let _ = writeln!(out, "[video]\nrender_scale = {}\n", self.render_scale);
+        out.push_str("[keys]\n");
+        for action in Action::ALL {
+            let _ = writeln!(out, "{} = \"{}\"", action.name(), key_name(self.key(action)));
+        }
+        out
+    }
+
+    fn parse(text: &str) -> Result<Self, ParseError> {
+        let lines: Vec<&str> = text.lines().collect();
+        let (tables, _) = parse_tables(&lines, &["game", "audio", "video", "keys"], &[], None)?;
+        let mut settings = Self::default();
+        for t in &tables {
+            match t.name.as_str() {
+                "game" => {
+                    t.check_keys(&["fov", "sensitivity", "move_speed"])?;
+                    if let Some(e) = t.get("fov") {
+                        settings.fov_degrees = in_range(e, FOV_RANGE.0 as f64, FOV_RANGE.1 as f64)?.round() as i32;
+                    }
+                    if let Some(e) = t.get("sensitivity") {
+                        settings.sensitivity = in_range(e, SENSITIVITY_RANGE.0 as f64, SENSITIVITY_RANGE.1 as f64)? as f32;
+                    }
+                    if let Some(e) = t.get("move_speed") {
+                        settings.move_speed = in_range(e, MOVE_SPEED_RANGE.0 as f64, MOVE_SPEED_RANGE.1 as f64)? as f32;
+                    }
+                }
+                "audio" => {
+                    t.check_keys(&["music_volume", "sfx_volume"])?;
+                    if let Some(e) = t.get("music_volume") { settings.music_volume = e.fraction()? as f32; }
+                    if let Some(e) = t.get("sfx_volume") { settings.sfx_volume = e.fraction()? as f32; }
+                }
+                "video" => {
+                    t.check_keys(&["render_scale"])?;
+                    if let Some(e) = t.get("render_scale") {
+                        settings.render_scale = in_range(e, 1.0, RENDER_SCALE_MAX as f64)? as usize;
+                    }
+                }
+                _ => {
+                    t.check_keys(&Action::ALL.map(Action::name))?;
+                    // Primero se leen todas y luego se comprueba que no se repitan
+                    let mut bound: Vec<(&Entry, KeyboardKey)> = Vec::new();
+                    for action in Action::ALL {
+                        let Some(e) = t.get(action.name()) else { continue };
+                        let name = e.str()?;
+                        let key = key_from_name(name)
+                            .ok_or_else(|| (e.line, e.value_col, format!("tecla desconocida '{}'", name)))?;
+                        if let Some((prev, _)) = bound.iter().find(|(_, k)| *k == key) {
+                            return err(e.line, e.value_col, format!("la tecla {} ya está asignada a '{}'", key_name(key), prev.key));
+                        }
+                        bound.push((e, key));
+                        settings.keys[action.index()] = key;
+                    }
+                    // Una acción sin tecla en el archivo cuya tecla por defecto ahora usa otra
+                    if let Some(action) = Action::ALL.into_iter().find(|a| {
+                        t.get(a.name()).is_none() && bound.iter().any(|(_, k)| *k == settings.key(*a))
+                    }) {
+                        return err(t.line, 1, format!("'{}' no tiene tecla propia; asígnale una en [keys]", action.name()));
+                    }
+                }
+            }
+        }
+        Ok(settings)
+    }
+}
+
+/// Number between `min` and `max`.
+fn in_range(entry: &Entry, min: f64, max: f64) -> Result<f64, ParseError> {
+    let v = entry.float()?;
+    if !(min..=max).contains(&v) {
+        return err(entry.line, entry.value_col, format!("'{}' va de {} a {}", entry.key, min, max));
+    }
+    Ok(v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_at(text: &str) -> (usize, usize) {
+        let (line, column, _) = Settings::parse(text).err().unwrap();
+        (line, column)
+    }
+
+    #[test]
+    fn round_trip_keeps_every_value() {
+        let mut settings = Settings {
+            fov_degrees: 75,
+            sensitivity: 1.25,
+            move_speed: 3.75,
+            music_volume: 0.35,
+            render_scale: 2,
+            ..Settings::default()
+        };
+        settings.bind(Action::Fire, KeyboardKey::KEY_W);
+        assert_eq!(Settings::parse(&settings.to_text()).unwrap(), settings);
+        assert_eq!(Settings::parse("").unwrap(), Settings::default());
+    }
+
+    #[test]
+    fn unknown_keys_and_sections() {
+        assert_eq!(error_at("[game]\nfov = 70\njump = 1\n"), (3, 1));
+        assert_eq!(error_at("[keys]\njump = \"J\"\n"), (2, 1));
+        assert!(Settings::parse("[cheats]\ngod = true\n").is_err());
+    }
+
+    #[test]
+    fn values_out_of_range() {
+        assert_eq!(error_at("[game]\nfov = 200\n"), (2, 7));
+        assert_eq!(error_at("[game]\nsensitivity = 0.1\n"), (2, 15));
+        assert_eq!(error_at("[game]\nmove_speed = 20\n"), (2, 14));
+        assert_eq!(error_at("[game]\nmove_speed = 0.2\n"), (2, 14));
+        assert_eq!(error_at("[audio]\nsfx_volume = 2\n"), (2, 14));
+        assert_eq!(error_at("[video]\nrender_scale = 0\n"), (2, 16));
+        assert_eq!(Settings::parse("[game]\nmove_speed = 10\n").unwrap().move_speed, 10.0);
+    }
+
+    #[test]
+    fn bad_key_names() {
+        assert_eq!(error_at("[keys]\nfire = \"F13\"\n"), (2, 8));
+        assert_eq!(error_at("[keys]\nfire = \"SHIFT\"\n"), (2, 8));
+        assert_eq!(Settings::parse("[keys]\nfire = \"left_shift\"\n").unwrap().key(Action::Fire), KeyboardKey::KEY_LEFT_SHIFT);
+        assert_eq!(error_at("[keys]\nfire = 3\n"), (2, 8));
+    }
+
+    #[test]
+    fn duplicate_bindings() {
+        let (line, column, message) = Settings::parse("[keys]\nfire = \"Q\"\nuse = \"Q\"\n").err().unwrap();
+        assert_eq!((line, column), (3, 7));
+        assert!(message.contains("ya está asignada a 'fire'"), "{}", message);
+        // `forward` no aparece pero su tecla por defecto (W) la usa ahora `fire`
+        assert_eq!(error_at("[keys]\nfire = \"W\"\n"), (1, 1));
+        let swapped = Settings::parse("[keys]\nfire = \"W\"\nforward = \"SPACE\"\n").unwrap();
+        assert_eq!(swapped.key(Action::Forward), KeyboardKey::KEY_SPACE);
+    }
+}
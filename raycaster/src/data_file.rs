@@ -0,0 +1,346 @@
+//! Small TOML subset shared by the game's data files (level headers, enemy archetypes,
+//! saved games):
+//! `[section]` and `[[array]]` tables of `key = value` lines, with strings, integers,
+//! floats, booleans, arrays and `#` comments. Every entry keeps its line and column so
+//! errors can point at them.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Problem in a data file, with the 1-based line and column it points at.
+#[derive(Clone, Debug)]
+pub struct DataError {
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for DataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}: {}", self.path.display(), self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for DataError {}
+
+/// Error position and message, before the path is attached.
+pub type ParseError = (usize, usize, String);
+
+pub fn err<T>(line: usize, column: usize, message: impl Into<String>) -> Result<T, ParseError> {
+    Err((line, column, message.into()))
+}
+
+#[derive(Clone, Debug)]
+enum Value {
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    fn type_name(&self) -> &'static str {
+        match self {
+            Value::Str(_) => "texto",
+            Value::Int(_) => "entero",
+            Value::Float(_) => "número",
+            Value::Bool(_) => "booleano",
+            Value::Array(_) => "lista",
+        }
+    }
+}
+
+/// `key = value` with the position of the key and of the value.
+pub struct Entry {
+    pub key: String,
+    value: Value,
+    pub line: usize,
+    pub key_col: usize,
+    pub value_col: usize,
+}
+
+impl Entry {
+    pub fn expected<T>(&self, what: &str) -> Result<T, ParseError> {
+        err(self.line, self.value_col, format!("'{}' espera {}, no {}", self.key, what, self.value.type_name()))
+    }
+
+    pub fn str(&self) -> Result<&str, ParseError> {
+        match &self.value { Value::Str(s) => Ok(s), _ => self.expected("un texto") }
+    }
+
+    pub fn int(&self) -> Result<i64, ParseError> {
+        match self.value { Value::Int(i) => Ok(i), _ => self.expected("un entero") }
+    }
+
+    pub fn float(&self) -> Result<f64, ParseError> {
+        match self.value {
+            Value::Int(i) => Ok(i as f64),
+            Value::Float(f) => Ok(f),
+            _ => self.expected("un número"),
+        }
+    }
+
+    pub fn bool(&self) -> Result<bool, ParseError> {
+        match self.value { Value::Bool(b) => Ok(b), _ => self.expected("true o false") }
+    }
+
+    pub fn non_negative(&self) -> Result<i64, ParseError> {
+        let v = self.int()?;
+        if v < 0 { return err(self.line, self.value_col, format!("'{}' no puede ser negativo", self.key)); }
+        Ok(v)
+    }
+
+    pub fn positive_float(&self) -> Result<f64, ParseError> {
+        let v = self.float()?;
+        if v <= 0.0 { return err(self.line, self.value_col, format!("'{}' tiene que ser mayor que 0", self.key)); }
+        Ok(v)
+    }
+
+    /// Number between 0 and 1.
+    pub fn fraction(&self) -> Result<f64, ParseError> {
+        let v = self.float()?;
+        if !(0.0..=1.0).contains(&v) { return err(self.line, self.value_col, format!("'{}' va de 0 a 1", self.key)); }
+        Ok(v)
+    }
+
+    /// `[r, g, b]` with components from 0 to 255.
+    pub fn rgb(&self) -> Result<[u8; 3], ParseError> {
+        match &self.value {
+            Value::Array(items) => match items.as_slice() {
+                [Value::Int(r), Value::Int(g), Value::Int(b)] if [r, g, b].iter().all(|c| (0..=255).contains(*c)) => {
+                    Ok([*r as u8, *g as u8, *b as u8])
+                }
+                _ => self.expected("[r, g, b] con enteros de 0 a 255"),
+            },
+            _ => self.expected("[r, g, b]"),
+        }
+    }
+
+    /// `[x, y]` pair of numbers.
+    pub fn point(&self) -> Result<(f64, f64), ParseError> {
+        let number = |v: &Value| match *v {
+            Value::Int(i) => Some(i as f64),
+            Value::Float(f) => Some(f),
+            _ => None,
+        };
+        match &self.value {
+            Value::Array(items) => match items.as_slice() {
+                [x, y] => match (number(x), number(y)) {
+                    (Some(x), Some(y)) => Ok((x, y)),
+                    _ => self.expected("[x, y] con dos números"),
+                },
+                _ => self.expected("[x, y] con dos números"),
+            },
+            _ => self.expected("[x, y]"),
+        }
+    }
+
+    /// `[column, row]` tile coordinates.
+    pub fn tile(&self) -> Result<(usize, usize), ParseError> {
+        match &self.value {
+            Value::Array(items) => match items.as_slice() {
+                [Value::Int(x), Value::Int(y)] if *x >= 0 && *y >= 0 => Ok((*x as usize, *y as usize)),
+                _ => self.expected("[columna, fila] con enteros no negativos"),
+            },
+            _ => self.expected("[columna, fila]"),
+        }
+    }
+}
+
+/// `[name]` or `[[name]]` section and its entries.
+pub struct Table {
+    pub name: String,
+    pub line: usize,
+    entries: Vec<Entry>,
+}
+
+impl Table {
+    pub fn get(&self, key: &str) -> Option<&Entry> {
+        self.entries.iter().find(|e| e.key == key)
+    }
+
+    pub fn require(&self, key: &str) -> Result<&Entry, ParseError> {
+        self.get(key).ok_or_else(|| (self.line, 1, format!("a [{}] le falta '{}'", self.name, key)))
+    }
+
+    /// Rejects keys other than `allowed`.
+    pub fn check_keys(&self, allowed: &[&str]) -> Result<(), ParseError> {
+        match self.entries.iter().find(|e| !allowed.contains(&e.key.as_str())) {
+            Some(e) => err(e.line, e.key_col, format!("campo desconocido '{}' en [{}]", e.key, self.name)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Cursor over one header line; columns are 1-based char positions.
+struct Cursor {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+}
+
+impl Cursor {
+    fn new(text: &str, line: usize) -> Self {
+        Self { chars: text.chars().collect(), pos: 0, line }
+    }
+
+    fn col(&self) -> usize {
+        self.pos + 1
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek().is_some_and(|c| c == ' ' || c == '\t') { self.pos += 1; }
+    }
+
+    /// Only spaces or a comment left.
+    fn at_end(&mut self) -> bool {
+        self.skip_spaces();
+        matches!(self.peek(), None | Some('#'))
+    }
+
+    fn value(&mut self) -> Result<Value, ParseError> {
+        self.skip_spaces();
+        let start = self.col();
+        match self.peek() {
+            None | Some('#') => err(self.line, start, "falta el valor"),
+            Some('"') => {
+                self.pos += 1;
+                let mut s = String::new();
+                loop {
+                    match self.peek() {
+                        None => return err(self.line, start, "texto sin cerrar"),
+                        Some('"') => { self.pos += 1; return Ok(Value::Str(s)); }
+                        Some('\\') => {
+                            self.pos += 1;
+                            match self.peek() {
+                                Some('n') => s.push('\n'),
+                                Some(c @ ('"' | '\\')) => s.push(c),
+                                _ => return err(self.line, self.col(), "escape no válido"),
+                            }
+                            self.pos += 1;
+                        }
+                        Some(c) => { s.push(c); self.pos += 1; }
+                    }
+                }
+            }
+            Some('[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.skip_spaces();
+                    if self.peek() == Some(']') { self.pos += 1; return Ok(Value::Array(items)); }
+                    items.push(self.value()?);
+                    self.skip_spaces();
+                    match self.peek() {
+                        Some(',') => self.pos += 1,
+                        Some(']') => {}
+                        _ => return err(self.line, self.col(), "se esperaba ',' o ']'"),
+                    }
+                }
+            }
+            Some(_) => {
+                let end = self.chars[self.pos..]
+                    .iter()
+                    .position(|c| matches!(c, ' ' | '\t' | ',' | ']' | '#'))
+                    .map_or(self.chars.len(), |n| self.pos + n);
+                let word: String = self.chars[self.pos..end].iter().collect();
+                self.pos = end;
+                match word.as_str() {
+                    "true" => Ok(Value::Bool(true)),
+                    "false" => Ok(Value::Bool(false)),
+                    _ => word.parse::<i64>().map(Value::Int)
+                        .or_else(|_| word.parse::<f64>().map(Value::Float))
+                        .or_else(|_| err(self.line, start, format!("valor no válido '{}' (¿faltan comillas?)", word))),
+                }
+            }
+        }
+    }
+}
+
+/// Splits `lines` into tables. Only `sections` (`[name]`, each at most once) and
+/// `arrays` (`[[name]]`) are accepted. If `stop_at` is met, parsing stops there and
+/// its line index is returned so the caller can read the rest as raw text.
+pub fn parse_tables(
+    lines: &[&str],
+    sections: &[&str],
+    arrays: &[&str],
+    stop_at: Option<&str>,
+) -> Result<(Vec<Table>, Option<usize>), ParseError> {
+    let mut tables: Vec<Table> = Vec::new();
+    for (i, raw) in lines.iter().enumerate() {
+        let line_no = i + 1;
+        let mut cur = Cursor::new(raw, line_no);
+        if cur.at_end() { continue; }
+        let col = cur.col();
+        let rest: String = cur.chars[cur.pos..].iter().collect();
+        let rest = rest.split('#').next().unwrap_or("").trim_end();
+
+        if let Some(name) = rest.strip_prefix("[[").and_then(|r| r.strip_suffix("]]")) {
+            let name = name.trim();
+            if !arrays.contains(&name) {
+                return err(line_no, col + 2, format!("lista desconocida [[{}]]", name));
+            }
+            tables.push(Table { name: name.to_string(), line: line_no, entries: Vec::new() });
+            continue;
+        }
+        if let Some(name) = rest.strip_prefix('[').and_then(|r| r.strip_suffix(']')) {
+            let name = name.trim();
+            if !sections.contains(&name) && stop_at != Some(name) {
+                return err(line_no, col + 1, format!("sección desconocida [{}]", name));
+            }
+            if let Some(prev) = tables.iter().find(|t| t.name == name) {
+                return err(line_no, col + 1, format!("sección [{}] repetida (ya está en la línea {})", name, prev.line));
+            }
+            if stop_at == Some(name) { return Ok((tables, Some(line_no))); }
+            tables.push(Table { name: name.to_string(), line: line_no, entries: Vec::new() });
+            continue;
+        }
+
+        let Some(table) = tables.last_mut() else {
+            return err(line_no, col, "campo fuera de una sección");
+        };
+        let Some(eq) = cur.chars[cur.pos..].iter().position(|&c| c == '=').map(|n| cur.pos + n) else {
+            return err(line_no, col, format!("se esperaba 'campo = valor', no '{}'", rest));
+        };
+        let key: String = cur.chars[cur.pos..eq].iter().collect::<String>().trim().to_string();
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return err(line_no, col, format!("nombre de campo no válido '{}'", key));
+        }
+        if table.get(&key).is_some() {
+            return err(line_no, col, format!("campo '{}' repetido", key));
+        }
+        cur.pos = eq + 1;
+        cur.skip_spaces();
+        let value_col = cur.col();
+        let value = cur.value()?;
+        if !cur.at_end() {
+            return err(line_no, cur.col(), "texto sobrante después del valor");
+        }
+        table.entries.push(Entry { key, value, line: line_no, key_col: col, value_col });
+    }
+    Ok((tables, None))
+}
+
+/// Reads the file at `path` and parses it, attaching the path to any error.
+pub fn load<T, P: AsRef<Path>>(path: P, parse: impl FnOnce(&str) -> Result<T, ParseError>) -> Result<T, DataError> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path).map_err(|e| DataError {
+        path: path.to_path_buf(),
+        line: 0,
+        column: 0,
+        message: e.to_string(),
+    })?;
+    parse(&text).map_err(|(line, column, message)| DataError {
+        path: path.to_path_buf(),
+        line,
+        column,
+        message,
+    })
+}
+
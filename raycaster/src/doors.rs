@@ -0,0 +1,247 @@
+//! Sliding doors, locked doors and the colored keys that open them.
+//!
+//! Map chars: `=` is a plain door, `R`/`G`/`Y` are doors locked with the red, green or
+//! yellow key, and `r`/`g`/`y` are the keys themselves (pickups). A door is drawn as a
+//! thin panel through the middle of its cell that slides sideways while it opens; once
+//! fully open its cell becomes `' '` in the maze, so movement and line of sight treat it
+//! like any empty tile, and it gets its char back when it starts closing.
+
+use raylib::prelude::*;
+use crate::{maze::{Maze, block_size, is_wall}, player::Player, sprites::Enemy};
+
+const OPEN_STEP: f32 = 0.04; // fracción abierta por frame (~25 frames en abrirse)
+const STAY_OPEN: i32 = 180;  // frames abierta antes de cerrarse sola (~3 s)
+const REACH: f32 = 72.0;     // alcance de la tecla de usar, en pixels
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum KeyColor {
+    Red,
+    Green,
+    Yellow,
+}
+
+impl KeyColor {
+    pub const ALL: [KeyColor; 3] = [KeyColor::Red, KeyColor::Green, KeyColor::Yellow];
+
+    /// Key pickup char (`r`, `g`, `y`).
+    pub fn from_key_char(ch: char) -> Option<Self> {
+        match ch {
+            'r' => Some(Self::Red),
+            'g' => Some(Self::Green),
+            'y' => Some(Self::Yellow),
+            _ => None,
+        }
+    }
+
+    /// Inverse of `from_key_char`.
+    pub fn key_char(self) -> char {
+        match self {
+            Self::Red => 'r',
+            Self::Green => 'g',
+            Self::Yellow => 'y',
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Red => "roja",
+            Self::Green => "verde",
+            Self::Yellow => "amarilla",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Self::Red => Color::new(220, 40, 40, 255),
+            Self::Green => Color::new(40, 200, 70, 255),
+            Self::Yellow => Color::new(240, 210, 40, 255),
+        }
+    }
+}
+
+/// Door char → `Some(lock)`: `Some(None)` for a plain door, `Some(Some(color))` if locked.
+pub fn door_lock(ch: char) -> Option<Option<KeyColor>> {
+    match ch {
+        '=' => Some(None),
+        'R' => Some(Some(KeyColor::Red)),
+        'G' => Some(Some(KeyColor::Green)),
+        'Y' => Some(Some(KeyColor::Yellow)),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DoorState {
+    Closed,
+    Opening,
+    Open,
+    Closing,
+}
+
+impl DoorState {
+    pub const ALL: [DoorState; 4] = [DoorState::Closed, DoorState::Opening, DoorState::Open, DoorState::Closing];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Closed => "closed",
+            Self::Opening => "opening",
+            Self::Open => "open",
+            Self::Closing => "closing",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| s.name() == name)
+    }
+}
+
+pub struct Door {
+    pub x: usize,
+    pub y: usize,
+    pub ch: char,
+    pub lock: Option<KeyColor>,
+    pub along_x: bool, // el panel va de oeste a este (paredes a izquierda y derecha)
+    pub open: f32,     // 0 = cerrada, 1 = abierta
+    state: DoorState,
+    timer: i32,
+}
+
+impl Door {
+    pub fn state(&self) -> DoorState {
+        self.state
+    }
+
+    /// Frames left before an open door starts closing.
+    pub fn timer(&self) -> i32 {
+        self.timer
+    }
+}
+
+/// True if the player or a living enemy overlaps cell (`x`, `y`).
+fn occupied(x: usize, y: usize, player: &Player, enemies: &[Enemy]) -> bool {
+    let b = block_size() as f32;
+    let cell = |p: Vector2, r: f32| {
+        [(-r, -r), (r, -r), (-r, r), (r, r)].iter().any(|(dx, dy)| {
+            ((p.x + dx) / b) as usize == x && ((p.y + dy) / b) as usize == y
+        })
+    };
+    cell(player.pos, 10.0) || enemies.iter().any(|e| e.alive && cell(e.pos, 0.0))
+}
+
+/// What happened when the player pressed the use key.
+pub enum UseResult {
+    Nothing,
+    Opened,
+    Locked(KeyColor),
+}
+
+pub struct Doors {
+    list: Vec<Door>,
+}
+
+impl Doors {
+    pub fn from_maze(maze: &Maze) -> Self {
+        let mut list = Vec::new();
+        for (y, row) in maze.iter().enumerate() {
+            for (x, &ch) in row.iter().enumerate() {
+                let Some(lock) = door_lock(ch) else { continue };
+                let (xi, yi) = (x as isize, y as isize);
+                let along_x = is_wall(maze, xi - 1, yi) && is_wall(maze, xi + 1, yi);
+                list.push(Door { x, y, ch, lock, along_x, open: 0.0, state: DoorState::Closed, timer: 0 });
+            }
+        }
+        Self { list }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Door> {
+        self.list.iter()
+    }
+
+    pub fn at(&self, x: i32, y: i32) -> Option<&Door> {
+        self.list.iter().find(|d| d.x as i32 == x && d.y as i32 == y)
+    }
+
+    /// Puts the door at (`x`, `y`), if any, back as it was saved and updates its maze cell.
+    pub fn restore(&mut self, maze: &mut Maze, x: usize, y: usize, state: DoorState, open: f32, timer: i32) {
+        let Some(door) = self.list.iter_mut().find(|d| d.x == x && d.y == y) else { return; };
+        door.state = state;
+        door.open = match state {
+            DoorState::Closed => 0.0,
+            DoorState::Open => 1.0,
+            DoorState::Opening | DoorState::Closing => open.clamp(0.0, 1.0),
+        };
+        door.timer = timer.clamp(0, STAY_OPEN);
+        maze[y][x] = if state == DoorState::Open { ' ' } else { door.ch };
+    }
+
+    /// Fully opens every door that is not open but has the player or a living enemy in
+    /// its cell, so nobody ends up inside a closed door.
+    pub fn open_occupied(&mut self, maze: &mut Maze, player: &Player, enemies: &[Enemy]) {
+        for door in &mut self.list {
+            if door.state != DoorState::Open && occupied(door.x, door.y, player, enemies) {
+                door.state = DoorState::Open;
+                door.open = 1.0;
+                door.timer = STAY_OPEN;
+                maze[door.y][door.x] = ' ';
+            }
+        }
+    }
+
+    /// Use key: open the first door in front of the player, if any and if unlocked.
+    pub fn try_open(&mut self, player: &Player, maze: &Maze) -> UseResult {
+        let b = block_size() as f32;
+        let mut dist = b * 0.25;
+        while dist <= REACH {
+            let px = player.pos.x + player.a.cos() * dist;
+            let py = player.pos.y + player.a.sin() * dist;
+            let (cx, cy) = ((px / b).floor() as i32, (py / b).floor() as i32);
+            if let Some(door) = self.list.iter_mut().find(|d| d.x as i32 == cx && d.y as i32 == cy) {
+                if let Some(color) = door.lock {
+                    if !player.has_key(color) { return UseResult::Locked(color); }
+                }
+                if matches!(door.state, DoorState::Closed | DoorState::Closing) {
+                    door.state = DoorState::Opening;
+                    return UseResult::Opened;
+                }
+                return UseResult::Nothing;
+            }
+            if is_wall(maze, cx as isize, cy as isize) { break; }
+            dist += b * 0.25;
+        }
+        UseResult::Nothing
+    }
+
+    /// Animate doors and keep the maze in sync: a fully open door is `' '`, and it only
+    /// closes again when neither the player nor an enemy stands in its cell.
+    pub fn update(&mut self, maze: &mut Maze, player: &Player, enemies: &[Enemy]) {
+        for door in &mut self.list {
+            match door.state {
+                DoorState::Closed => {}
+                DoorState::Opening => {
+                    door.open = (door.open + OPEN_STEP).min(1.0);
+                    if door.open >= 1.0 {
+                        door.state = DoorState::Open;
+                        door.timer = STAY_OPEN;
+                        maze[door.y][door.x] = ' ';
+                    }
+                }
+                DoorState::Open => {
+                    if door.timer > 0 {
+                        door.timer -= 1;
+                    } else if !occupied(door.x, door.y, player, enemies) {
+                        door.state = DoorState::Closing;
+                        maze[door.y][door.x] = door.ch;
+                    }
+                }
+                DoorState::Closing => {
+                    door.open = (door.open - OPEN_STEP).max(0.0);
+                    if door.open <= 0.0 { door.state = DoorState::Closed; }
+                }
+            }
+        }
+    }
+}
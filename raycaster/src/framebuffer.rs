@@ -1,15 +1,96 @@
+use rayon::prelude::*;
 use raylib::prelude::*;
 
+/// CPU-side RGBA8 framebuffer. The 3D view is rendered here at `screen / render_scale`
+/// and uploaded once per frame as a single texture stretched over the window.
 pub struct Framebuffer {
     pub width: u32,
     pub height: u32,
+    pixels: Vec<u8>,           // RGBA8, fila por fila
+    texture: Option<Texture2D>, // se crea en el primer swap (necesita el hilo de raylib)
 }
+
 impl Framebuffer {
     pub fn new(width: u32, height: u32) -> Self {
-        Self { width, height }
+        let (width, height) = (width.max(1), height.max(1));
+        Self { width, height, pixels: vec![0; (width * height * 4) as usize], texture: None }
+    }
+
+    /// Changes the size, dropping the GPU texture so the next swap recreates it.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        let (width, height) = (width.max(1), height.max(1));
+        if (width, height) == (self.width, self.height) { return; }
+        *self = Self::new(width, height);
+    }
+
+    /// Rows as mutable byte slices (`width * 4` bytes each), filled in parallel by rayon.
+    pub fn par_rows_mut(&mut self) -> rayon::slice::ChunksExactMut<'_, u8> {
+        self.pixels.par_chunks_exact_mut(self.width as usize * 4)
+    }
+
+    /// Opaque write; out-of-bounds coordinates are ignored.
+    #[inline]
+    pub fn set_pixel(&mut self, x: i32, y: i32, c: Color) {
+        if let Some(i) = self.index(x, y) {
+            self.pixels[i..i + 4].copy_from_slice(&[c.r, c.g, c.b, 255]);
+        }
+    }
+
+    /// Alpha-blended write (`c.a` over the current pixel).
+    #[inline]
+    pub fn blend_pixel(&mut self, x: i32, y: i32, c: Color) {
+        if c.a == 255 { return self.set_pixel(x, y, c); }
+        if let Some(i) = self.index(x, y) {
+            let a = c.a as u32;
+            for (dst, src) in self.pixels[i..i + 3].iter_mut().zip([c.r, c.g, c.b]) {
+                *dst = ((src as u32 * a + *dst as u32 * (255 - a)) / 255) as u8;
+            }
+        }
+    }
+
+    pub fn fill_rect(&mut self, x: i32, y: i32, w: i32, h: i32, c: Color) {
+        for py in y.max(0)..(y + h).min(self.height as i32) {
+            for px in x.max(0)..(x + w).min(self.width as i32) {
+                self.blend_pixel(px, py, c);
+            }
+        }
+    }
+
+    pub fn rect_lines(&mut self, x: i32, y: i32, w: i32, h: i32, c: Color) {
+        self.fill_rect(x, y, w, 1, c);
+        self.fill_rect(x, y + h - 1, w, 1, c);
+        self.fill_rect(x, y + 1, 1, h - 2, c);
+        self.fill_rect(x + w - 1, y + 1, 1, h - 2, c);
+    }
+
+    pub fn fill_circle(&mut self, cx: i32, cy: i32, radius: f32, c: Color) {
+        let r = radius.ceil() as i32;
+        for dy in -r..=r {
+            for dx in -r..=r {
+                if (dx * dx + dy * dy) as f32 <= radius * radius {
+                    self.blend_pixel(cx + dx, cy + dy, c);
+                }
+            }
+        }
+    }
+
+    /// Uploads the pixels to the GPU texture and draws it stretched over the whole screen.
+    pub fn swap_buffers(&mut self, d: &mut RaylibDrawHandle, th: &RaylibThread) {
+        if self.texture.is_none() {
+            let img = Image::gen_image_color(self.width as i32, self.height as i32, Color::BLACK);
+            self.texture = d.load_texture_from_image(th, &img).ok();
+        }
+        let Some(texture) = self.texture.as_mut() else { return; };
+        let _ = texture.update_texture(&self.pixels);
+
+        let src = Rectangle::new(0.0, 0.0, self.width as f32, self.height as f32);
+        let dst = Rectangle::new(0.0, 0.0, d.get_screen_width() as f32, d.get_screen_height() as f32);
+        d.draw_texture_pro(&*texture, src, dst, Vector2::zero(), 0.0, Color::WHITE);
+    }
+
+    #[inline]
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 { return None; }
+        Some((y as usize * self.width as usize + x as usize) * 4)
     }
-    pub fn clear(&mut self) {}
-    pub fn set_pixel(&mut self, _x: u32, _y: u32) {}
-    pub fn set_current_color(&mut self, _c: Color) {}
-    pub fn swap_buffers(&self, _rl: &mut RaylibHandle, _th: &RaylibThread) {}
 }
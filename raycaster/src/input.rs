@@ -1,13 +1,14 @@
 use raylib::prelude::*;
+use crate::config::{Action, Settings};
 use crate::player::Player;
 use crate::maze::{Maze, is_wall, block_size};
 
-pub fn process(rl: &RaylibHandle, player: &mut Player, maze: &Maze) {
-    let move_speed = 3.0;
-    let rot_speed = 0.05;
+pub fn process(rl: &RaylibHandle, player: &mut Player, maze: &Maze, settings: &Settings) {
+    let move_speed = settings.move_speed;
+    let rot_speed = settings.turn_speed();
 
     // W/S - Adelante/Atrás
-    if rl.is_key_down(KeyboardKey::KEY_W) {
+    if settings.down(rl, Action::Forward) {
         let new_x = player.pos.x + player.a.cos() * move_speed;
         let new_y = player.pos.y + player.a.sin() * move_speed;
         if can_move(new_x, new_y, maze) {
@@ -15,7 +16,7 @@ pub fn process(rl: &RaylibHandle, player: &mut Player, maze: &Maze) {
             player.pos.y = new_y;
         }
     }
-    if rl.is_key_down(KeyboardKey::KEY_S) {
+    if settings.down(rl, Action::Back) {
         let new_x = player.pos.x - player.a.cos() * move_speed;
         let new_y = player.pos.y - player.a.sin() * move_speed;
         if can_move(new_x, new_y, maze) {
@@ -25,7 +26,7 @@ pub fn process(rl: &RaylibHandle, player: &mut Player, maze: &Maze) {
     }
 
     // A/D - Movimiento lateral (strafe izquierda/derecha)
-    if rl.is_key_down(KeyboardKey::KEY_A) {
+    if settings.down(rl, Action::StrafeLeft) {
         // Mover perpendicular a la dirección (90 grados a la izquierda)
         let strafe_angle = player.a - std::f32::consts::PI / 2.0;
         let new_x = player.pos.x + strafe_angle.cos() * move_speed;
@@ -35,7 +36,7 @@ pub fn process(rl: &RaylibHandle, player: &mut Player, maze: &Maze) {
             player.pos.y = new_y;
         }
     }
-    if rl.is_key_down(KeyboardKey::KEY_D) {
+    if settings.down(rl, Action::StrafeRight) {
         // Mover perpendicular a la dirección (90 grados a la derecha)
         let strafe_angle = player.a + std::f32::consts::PI / 2.0;
         let new_x = player.pos.x + strafe_angle.cos() * move_speed;
@@ -47,10 +48,10 @@ pub fn process(rl: &RaylibHandle, player: &mut Player, maze: &Maze) {
     }
 
     // Flechas izquierda/derecha - Rotar cámara
-    if rl.is_key_down(KeyboardKey::KEY_LEFT) {
+    if settings.down(rl, Action::TurnLeft) {
         player.a -= rot_speed;
     }
-    if rl.is_key_down(KeyboardKey::KEY_RIGHT) {
+    if settings.down(rl, Action::TurnRight) {
         player.a += rot_speed;
     }
 
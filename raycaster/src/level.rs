@@ -0,0 +1,347 @@
+//! Level files: a small TOML-style header with the level metadata and entities, followed
+//! by the ASCII map under `[map]`.
+//!
+//! ```text
+//! # Nivel 1
+//! [level]
+//! name = "Tutorial"
+//! next = "level2.txt"        # relativo a este archivo, o "WIN"
+//! required_coins = 10
+//! music = "assets/audios/dungeon_delver.mp3"
+//!
+//! [player]
+//! facing = 0                 # grados: 0 = este, 90 = sur
+//!
+//! [fog]
+//! enabled = true
+//! radius = 400               # radio de exploración en pixels
+//!
+//! [enemies]
+//! random = 20                # enemigos extra en casillas vacías al azar
+//! kind = "grunt"
+//!
+//! [[enemy]]
+//! at = [20, 3]               # columna, fila
+//! kind = "guard"
+//!
+//! [[pickup]]
+//! at = [5, 9]
+//! kind = "health"            # coin, key_*, health, armor, ammo_*, treasure
+//!
+//! [map]
+//! AAAAA
+//! AP XA
+//! AAAAA
+//! ```
+//!
+//! The player starts on the `P` tile. Coins (`X`) and keys (`r`/`g`/`y`) in the map
+//! become pickups like the `[[pickup]]` entries. A file with no `[section]` line is read
+//! as a bare map with default settings (`#` is a wall there, not a comment). A level can't
+//! ask for more coins than it has. Every error carries the line and column it refers to.
+
+use std::path::Path;
+
+use raylib::prelude::Vector2;
+
+use crate::{
+    data_file::{self, DataError, Entry, ParseError, err, parse_tables},
+    doors::door_lock,
+    maze::{Maze, block_size, is_open_tile, is_wall},
+    pickups::{Pickup, PickupKind},
+    sprites::EnemyKind,
+};
+
+/// Checks that every char of `maze` (read `first_line` lines into the file) is a known
+/// map symbol and that every door sits between two walls.
+pub fn check_map(maze: &Maze, first_line: usize) -> Result<(), ParseError> {
+    for (y, row) in maze.iter().enumerate() {
+        for (x, &ch) in row.iter().enumerate() {
+            let known = is_open_tile(ch) || door_lock(ch).is_some() || matches!(ch, '#' | 'A'..='F' | 'a'..='f');
+            if !known {
+                return err(first_line + y + 1, x + 1, format!("símbolo de mapa desconocido '{}'", ch));
+            }
+            if door_lock(ch).is_none() { continue; }
+            let (xi, yi) = (x as isize, y as isize);
+            let framed_x = is_wall(maze, xi - 1, yi) && is_wall(maze, xi + 1, yi);
+            let framed_y = is_wall(maze, xi, yi - 1) && is_wall(maze, xi, yi + 1);
+            if !framed_x && !framed_y {
+                return err(first_line + y + 1, x + 1,
+                    "la puerta necesita paredes a ambos lados (izquierda y derecha, o arriba y abajo)");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Where the game goes after collecting the required coins.
+#[derive(Clone, Debug, PartialEq)]
+pub enum NextLevel {
+    Level(String),
+    Win,
+}
+
+pub struct EnemySpawn {
+    pub pos: Vector2, // centro de la casilla, en pixels
+    pub kind: EnemyKind,
+}
+
+pub struct Level {
+    pub name: String,
+    pub maze: Maze,
+    pub player_start: Vector2,
+    pub player_facing: f32, // radianes
+    pub enemies: Vec<EnemySpawn>,
+    pub pickups: Vec<Pickup>,
+    pub random_enemies: usize,
+    pub random_kind: EnemyKind,
+    pub required_coins: i32,
+    pub music: Option<String>,
+    pub next: NextLevel,
+    pub fog_enabled: bool,
+    pub fog_radius: f32,
+}
+
+const SECTIONS: [&str; 4] = ["level", "player", "fog", "enemies"];
+const ARRAYS: [&str; 2] = ["enemy", "pickup"];
+
+fn enemy_kind(entry: &Entry) -> Result<EnemyKind, ParseError> {
+    let name = entry.str()?;
+    EnemyKind::from_name(name).ok_or_else(|| {
+        let known: Vec<_> = EnemyKind::ALL.iter().map(|k| k.name()).collect();
+        (entry.line, entry.value_col, format!("tipo de enemigo desconocido '{}' (tipos: {})", name, known.join(", ")))
+    })
+}
+
+fn pickup_kind(entry: &Entry) -> Result<PickupKind, ParseError> {
+    let name = entry.str()?;
+    PickupKind::from_name(name).ok_or_else(|| {
+        let known: Vec<_> = PickupKind::ALL.iter().map(|k| k.name()).collect();
+        (entry.line, entry.value_col, format!("objeto desconocido '{}' (tipos: {})", name, known.join(", ")))
+    })
+}
+
+impl Level {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, DataError> {
+        let path = path.as_ref();
+        data_file::load(path, |text| Self::parse(text, path))
+    }
+
+    fn parse(text: &str, path: &Path) -> Result<Self, ParseError> {
+        let lines: Vec<&str> = text.lines().collect();
+        // Sin ninguna línea `[sección]` el archivo entero es el mapa; '[' no es un símbolo
+        // de mapa, mientras que '#' sí (pared), así que un comentario no basta
+        let has_header = lines.iter().any(|l| {
+            let l = l.trim();
+            l.starts_with('[') && l.ends_with(']')
+        });
+        let (tables, map_line) = if has_header {
+            match parse_tables(&lines, &SECTIONS, &ARRAYS, Some("map"))? {
+                (tables, Some(line)) => (tables, line),
+                (_, None) => return err(lines.len().max(1), 1, "falta la sección [map] con el mapa"),
+            }
+        } else {
+            (Vec::new(), 0)
+        };
+
+        let mut level = Self::parse_map(&lines[map_line..], map_line)?;
+        let b = block_size() as f32;
+        let center = |(x, y): (usize, usize)| Vector2::new((x as f32 + 0.5) * b, (y as f32 + 0.5) * b);
+        let (width, height) = (level.maze[0].len(), level.maze.len());
+        // Las entidades van en casillas dentro del mapa y libres
+        let free_tile = |level: &Level, entry: &Entry| -> Result<(usize, usize), ParseError> {
+            let (x, y) = entry.tile()?;
+            if x >= width || y >= height {
+                return err(entry.line, entry.value_col, format!("[{}, {}] está fuera del mapa ({}x{})", x, y, width, height));
+            }
+            if level.maze[y][x] != ' ' {
+                return err(entry.line, entry.value_col, format!("[{}, {}] no es una casilla vacía ('{}')", x, y, level.maze[y][x]));
+            }
+            if let Some(other) = level.pickups.iter().find(|p| p.pos == center((x, y))) {
+                return err(entry.line, entry.value_col, format!("[{}, {}] ya tiene un objeto ({})", x, y, other.kind.name()));
+            }
+            Ok((x, y))
+        };
+
+        for table in &tables {
+            match table.name.as_str() {
+                "level" => {
+                    table.check_keys(&["name", "next", "required_coins", "music"])?;
+                    if let Some(e) = table.get("name") { level.name = e.str()?.to_string(); }
+                    if let Some(e) = table.get("required_coins") { level.required_coins = e.non_negative()? as i32; }
+                    if let Some(e) = table.get("music") { level.music = Some(e.str()?.to_string()); }
+                    if let Some(e) = table.get("next") {
+                        level.next = match e.str()? {
+                            "WIN" => NextLevel::Win,
+                            file => {
+                                let next = path.parent().unwrap_or(Path::new("")).join(file);
+                                if !next.is_file() {
+                                    return err(e.line, e.value_col, format!("no existe el nivel '{}'", next.display()));
+                                }
+                                NextLevel::Level(next.to_string_lossy().into_owned())
+                            }
+                        };
+                    }
+                }
+                "player" => {
+                    table.check_keys(&["facing"])?;
+                    if let Some(e) = table.get("facing") { level.player_facing = (e.float()? as f32).to_radians(); }
+                }
+                "fog" => {
+                    table.check_keys(&["enabled", "radius"])?;
+                    if let Some(e) = table.get("enabled") { level.fog_enabled = e.bool()?; }
+                    if let Some(e) = table.get("radius") { level.fog_radius = e.float()? as f32; }
+                }
+                "enemies" => {
+                    table.check_keys(&["random", "kind"])?;
+                    if let Some(e) = table.get("random") { level.random_enemies = e.non_negative()? as usize; }
+                    if let Some(e) = table.get("kind") { level.random_kind = enemy_kind(e)?; }
+                }
+                "enemy" => {
+                    table.check_keys(&["at", "kind"])?;
+                    let tile = free_tile(&level, table.require("at")?)?;
+                    let kind = match table.get("kind") { Some(e) => enemy_kind(e)?, None => EnemyKind::Grunt };
+                    level.enemies.push(EnemySpawn { pos: center(tile), kind });
+                }
+                "pickup" => {
+                    table.check_keys(&["at", "kind"])?;
+                    let kind = pickup_kind(table.require("kind")?)?;
+                    let tile = free_tile(&level, table.require("at")?)?;
+                    level.pickups.push(Pickup { pos: center(tile), kind });
+                }
+                _ => unreachable!("parse_tables only accepts known sections"),
+            }
+        }
+
+        // Sin monedas suficientes el nivel no se podría terminar
+        let coins = level.pickups.iter().filter(|p| p.kind == PickupKind::Coin).count() as i32;
+        let required = tables.iter().find(|t| t.name == "level").and_then(|t| t.get("required_coins"));
+        match required {
+            Some(e) if level.required_coins > coins => {
+                return err(e.line, e.value_col, format!("pide {} monedas pero el mapa solo tiene {}", level.required_coins, coins));
+            }
+            Some(_) => {}
+            None => level.required_coins = level.required_coins.min(coins),
+        }
+        Ok(level)
+    }
+
+    /// Reads the map grid (`first_line` lines into the file) and checks its chars, the
+    /// player start and door placement. Short rows are padded with walls.
+    fn parse_map(lines: &[&str], first_line: usize) -> Result<Self, ParseError> {
+        // Ignorar líneas vacías al final
+        let rows = lines.iter().rposition(|l| !l.trim().is_empty()).map_or(0, |n| n + 1);
+        if rows == 0 {
+            return err(first_line.max(1), 1, "el mapa está vacío");
+        }
+        let mut maze: Maze = lines[..rows].iter().map(|l| l.chars().collect()).collect();
+        let width = maze.iter().map(Vec::len).max().unwrap_or(0);
+        for row in &mut maze { row.resize(width, 'A'); }
+        check_map(&maze, first_line)?;
+
+        let mut start = None;
+        for (y, row) in maze.iter().enumerate() {
+            for (x, &ch) in row.iter().enumerate() {
+                if ch == 'P' || ch == 'p' {
+                    if let Some((px, py)) = start {
+                        return err(first_line + y + 1, x + 1, format!("segundo inicio de jugador 'P' (el primero está en la línea {}, columna {})",
+                            first_line + py + 1, px + 1));
+                    }
+                    start = Some((x, y));
+                }
+            }
+        }
+
+        let Some(start) = start else {
+            return err(first_line + 1, 1, "el mapa no tiene inicio de jugador 'P'");
+        };
+        let b = block_size() as f32;
+        let center = |(x, y): (usize, usize)| Vector2::new((x as f32 + 0.5) * b, (y as f32 + 0.5) * b);
+        // Monedas y llaves del mapa: pasan a ser objetos y su casilla queda vacía
+        let mut pickups = Vec::new();
+        for (y, row) in maze.iter_mut().enumerate() {
+            for (x, ch) in row.iter_mut().enumerate() {
+                if let Some(kind) = PickupKind::from_map_char(*ch) {
+                    pickups.push(Pickup { pos: center((x, y)), kind });
+                    *ch = ' ';
+                }
+            }
+        }
+        Ok(Self {
+            name: String::new(),
+            maze,
+            player_start: center(start),
+            player_facing: 0.0,
+            enemies: Vec::new(),
+            pickups,
+            random_enemies: 20,
+            random_kind: EnemyKind::Grunt,
+            required_coins: 10,
+            music: None,
+            next: NextLevel::Win,
+            fog_enabled: true,
+            fog_radius: 400.0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Result<Level, ParseError> {
+        Level::parse(text, Path::new("test.txt"))
+    }
+
+    #[test]
+    fn bare_map_uses_defaults() {
+        let level = parse("AAAAAA\nAP XXA\nAAAAAA\n").unwrap();
+        assert_eq!(level.maze.len(), 3);
+        assert_eq!(level.pickups.len(), 2);
+        assert_eq!(level.required_coins, 2); // 10 por defecto, pero solo hay 2
+        assert_eq!(level.next, NextLevel::Win);
+    }
+
+    #[test]
+    fn bare_map_may_start_with_hash_walls() {
+        let level = parse("######\n#P X #\n######\n").unwrap();
+        assert_eq!(level.maze[0], vec!['#'; 6]);
+        assert_eq!(level.player_start, Vector2::new(1.5 * block_size() as f32, 1.5 * block_size() as f32));
+    }
+
+    #[test]
+    fn header_with_map() {
+        let text = "# Prueba\n[level]\nname = \"Prueba\"\nrequired_coins = 1\n\n[fog]\nenabled = false\n\n[[enemy]]\nat = [3, 1]\nkind = \"guard\"\n\n[map]\nAAAAA\nAPX A\nAAAAA\n";
+        let level = parse(text).unwrap();
+        assert_eq!(level.name, "Prueba");
+        assert_eq!(level.required_coins, 1);
+        assert!(!level.fog_enabled);
+        assert_eq!(level.enemies.len(), 1);
+        assert_eq!(level.enemies[0].kind, EnemyKind::Guard);
+        assert_eq!(level.maze[1], vec!['A', 'P', ' ', ' ', 'A']);
+    }
+
+    #[test]
+    fn unknown_keys_and_sections() {
+        assert_eq!(parse("[level]\nnombre = \"x\"\n[map]\nAPA\n").err().unwrap(),
+            (2, 1, "campo desconocido 'nombre' en [level]".to_string()));
+        assert_eq!(parse("[nivel]\n[map]\nAPA\n").err().unwrap().0, 1);
+    }
+
+    #[test]
+    fn bad_map_char_points_at_line_and_column() {
+        let (line, column, message) = parse("[level]\nrequired_coins = 0\n[map]\nAAAA\nAP?A\nAAAA\n").err().unwrap();
+        assert_eq!((line, column), (5, 3));
+        assert!(message.contains("'?'"), "{}", message);
+        // Sin cabecera las líneas cuentan desde el principio del archivo
+        assert_eq!(parse("AAAA\nAPA%\n").err().unwrap().0, 2);
+        assert_eq!(parse("AAAA\nAPA%\n").err().unwrap().1, 4);
+    }
+
+    #[test]
+    fn required_coins_must_be_available() {
+        let (line, column, message) = parse("[level]\nrequired_coins = 3\n[map]\nAAAAA\nAPXXA\nAAAAA\n").err().unwrap();
+        assert_eq!((line, column), (2, 18));
+        assert_eq!(message, "pide 3 monedas pero el mapa solo tiene 2");
+        assert!(parse("[level]\nrequired_coins = 2\n[map]\nAAAAA\nAPXXA\nAAAAA\n").is_ok());
+    }
+}
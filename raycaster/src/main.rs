@@ -3,9 +3,17 @@ mod math;
 mod input;
 mod player;
 mod maze;
+mod data_file;
+mod level;
+mod doors;
+mod nav;
+mod archetypes;
 mod raycaster;
 mod textures;
 mod sprites;
+mod pickups;
+mod weapons;
+mod save;
 mod audio;
 mod ui;
 mod config;
@@ -24,7 +32,11 @@ fn main() {
     rl.set_target_fps(60);
     rl.set_exit_key(None);
 
-    let mut screen: Box<dyn Screen> = Box::new(MenuScreen::default());
+    // Un settings.cfg roto se avisa en el menú y el juego usa los valores por defecto
+    let mut screen: Box<dyn Screen> = match config::Settings::load(config::SETTINGS_PATH) {
+        Ok(_) => Box::new(MenuScreen::default()),
+        Err(e) => Box::new(MenuScreen::with_error(e.to_string())),
+    };
 
     while !rl.window_should_close() {
         // Check if menu wants to quit
@@ -38,7 +50,7 @@ fn main() {
 
         let mut d = rl.begin_drawing(&th);
         d.clear_background(Color::BLACK);
-        screen.draw_raylib(&mut d);
+        screen.draw_raylib(&mut d, &th);
         drop(d);
 
         if let Some(next) = screen.next() {
@@ -2,20 +2,52 @@ use std::{fs, path::Path};
 
 pub type Maze = Vec<Vec<char>>;
 
-pub fn load_maze<P: AsRef<Path>>(path: P) -> Maze {
-    let s = fs::read_to_string(path).expect("map file");
-    s.lines().map(|l| l.chars().collect()).collect()
+pub fn block_size() -> usize { 64 } // tamaño del bloque en pixels
+
+/// Optional per-tile floor and ceiling layers, stored next to the map as
+/// `levelN.floor.txt` and `levelN.ceiling.txt` with the same grid as the maze.
+/// A missing file or cell uses the default `floor`/`ceiling` texture.
+#[derive(Default)]
+pub struct Surfaces {
+    floor: Maze,
+    ceiling: Maze,
 }
 
-pub fn block_size() -> usize { 64 } // tamaño del bloque en pixels
+impl Surfaces {
+    pub fn load<P: AsRef<Path>>(level_path: P) -> Self {
+        let layer = |ext: &str| {
+            fs::read_to_string(level_path.as_ref().with_extension(ext))
+                .map(|s| s.lines().map(|l| l.chars().collect()).collect())
+                .unwrap_or_default()
+        };
+        Self { floor: layer("floor.txt"), ceiling: layer("ceiling.txt") }
+    }
+
+    pub fn floor_at(&self, x: i32, y: i32) -> char {
+        layer_at(&self.floor, x, y)
+    }
+
+    pub fn ceiling_at(&self, x: i32, y: i32) -> char {
+        layer_at(&self.ceiling, x, y)
+    }
+}
+
+fn layer_at(layer: &Maze, x: i32, y: i32) -> char {
+    if x < 0 || y < 0 { return ' '; }
+    layer.get(y as usize).and_then(|row| row.get(x as usize)).copied().unwrap_or(' ')
+}
+
+/// Tiles you can walk and see through: empty, player start (P/p), coins (X/x) and keys.
+/// Walls and closed doors are everything else.
+pub fn is_open_tile(ch: char) -> bool {
+    matches!(ch, ' ' | 'P' | 'p' | 'X' | 'x') || crate::doors::KeyColor::from_key_char(ch).is_some()
+}
 
 pub fn is_wall(maze: &Maze, i: isize, j: isize) -> bool {
     if i < 0 || j < 0 { return true; }
     let (i, j) = (i as usize, j as usize);
     if j >= maze.len() || i >= maze[j].len() { return true; }
-    let ch = maze[j][i];
-    // Espacios, jugador (P/p) y monedas (X/x) no son paredes
-    ch != ' ' && ch != 'P' && ch != 'p' && ch != 'X' && ch != 'x'
+    !is_open_tile(maze[j][i])
 }
 
 /// Sistema de fog of war para el mapa
@@ -34,6 +66,23 @@ impl FogOfWar {
         }
     }
 
+    /// Restaurar la exploración de una partida guardada
+    pub fn from_explored(explored: Vec<Vec<f32>>) -> Self {
+        let height = explored.len();
+        let width = explored.first().map_or(0, Vec::len);
+        Self { explored, width, height }
+    }
+
+    /// Exploración por casilla, por filas
+    pub fn explored(&self) -> &[Vec<f32>] {
+        &self.explored
+    }
+
+    /// Marcar todo el mapa como explorado (niveles con `[fog] enabled = false`)
+    pub fn reveal_all(&mut self) {
+        for row in &mut self.explored { row.fill(1.0); }
+    }
+
     /// Actualizar fog of war basado en la posición del jugador
     pub fn update(&mut self, player_pos: raylib::prelude::Vector2, maze: &Maze, visibility_radius: f32) {
         let block = block_size() as f32;
@@ -87,7 +136,7 @@ impl FogOfWar {
             }
             
             let ch = maze[y as usize][x as usize];
-            if ch != ' ' && ch != 'X' && ch != 'x' && ch != 'P' && ch != 'p' && !(x == x0 && y == y0) {
+            if !(is_open_tile(ch) || (x == x0 && y == y0)) {
                 return false;
             }
             
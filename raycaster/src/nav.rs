@@ -0,0 +1,197 @@
+//! Enemy navigation over the maze grid.
+//!
+//! Every frame one Dijkstra flood fill runs from the player's tile (a flow field): each
+//! open tile stores its path cost to the player, so every enemy finds its shortest path
+//! by walking downhill, which is what A* would return for each of them but at the cost
+//! of a single search. Paths are smoothed by heading for the farthest tile ahead that
+//! can be reached in a straight line. Short local trips (patrols, cover, fleeing) use
+//! a breadth-first `Neighbourhood` around the enemy instead.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use raylib::prelude::Vector2;
+use crate::maze::{Maze, block_size, is_wall};
+
+const STRAIGHT: u32 = 10; // coste de un paso recto
+const DIAGONAL: u32 = 14; // ~10 * sqrt(2)
+const LOOKAHEAD: usize = 6; // casillas del camino que se miran al suavizar
+
+/// 8 neighbours with their cost; diagonals come last.
+const NEIGHBOURS: [(i32, i32, u32); 8] = [
+    (1, 0, STRAIGHT), (-1, 0, STRAIGHT), (0, 1, STRAIGHT), (0, -1, STRAIGHT),
+    (1, 1, DIAGONAL), (1, -1, DIAGONAL), (-1, 1, DIAGONAL), (-1, -1, DIAGONAL),
+];
+
+/// True if a body of `radius` pixels centred at `pos` overlaps no wall or closed door.
+pub fn is_walkable(maze: &Maze, pos: Vector2, radius: f32) -> bool {
+    let b = block_size() as f32;
+    [(-radius, -radius), (radius, -radius), (-radius, radius), (radius, radius)]
+        .iter()
+        .all(|(dx, dy)| !is_wall(maze, ((pos.x + dx) / b).floor() as isize, ((pos.y + dy) / b).floor() as isize))
+}
+
+/// True if a body of `radius` can move from `a` to `b` in a straight line.
+pub fn clear_path(maze: &Maze, a: Vector2, b: Vector2, radius: f32) -> bool {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let steps = ((dx * dx + dy * dy).sqrt() / 8.0).ceil().max(1.0) as i32;
+    (0..=steps).all(|i| {
+        let t = i as f32 / steps as f32;
+        is_walkable(maze, Vector2::new(a.x + dx * t, a.y + dy * t), radius)
+    })
+}
+
+/// Path cost from every open tile to the target tile (`u32::MAX` = unreachable).
+pub struct FlowField {
+    width: usize,
+    height: usize,
+    cost: Vec<u32>,
+}
+
+impl FlowField {
+    pub fn new() -> Self {
+        Self { width: 0, height: 0, cost: Vec::new() }
+    }
+
+    /// Recomputes the field toward `target` (world pixels) on the current maze, so doors
+    /// that opened or closed this frame are taken into account.
+    pub fn rebuild(&mut self, maze: &Maze, target: Vector2) {
+        self.height = maze.len();
+        self.width = maze.iter().map(Vec::len).max().unwrap_or(0);
+        self.cost.clear();
+        self.cost.resize(self.width * self.height, u32::MAX);
+
+        let Some(start) = self.tile_of(target) else { return; };
+        let mut open = BinaryHeap::new();
+        self.cost[start.1 * self.width + start.0] = 0;
+        open.push(Reverse((0, start)));
+
+        while let Some(Reverse((cost, (x, y)))) = open.pop() {
+            if cost > self.cost[y * self.width + x] { continue; } // entrada vieja
+            for &(dx, dy, step) in &NEIGHBOURS {
+                let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                if is_wall(maze, nx as isize, ny as isize) { continue; }
+                // Sin cortar esquinas: una diagonal necesita libres las dos rectas
+                if dx != 0 && dy != 0
+                    && (is_wall(maze, nx as isize, y as isize) || is_wall(maze, x as isize, ny as isize)) {
+                    continue;
+                }
+                let (nx, ny) = (nx as usize, ny as usize);
+                let next = cost + step;
+                if next < self.cost[ny * self.width + nx] {
+                    self.cost[ny * self.width + nx] = next;
+                    open.push(Reverse((next, (nx, ny))));
+                }
+            }
+        }
+    }
+
+    /// Point an enemy at `pos` should head to: the farthest tile centre along its
+    /// downhill path that a body of `radius` can reach in a straight line, or the target
+    /// itself once it is in straight reach. `None` if the target can't be reached.
+    pub fn next_waypoint(&self, maze: &Maze, pos: Vector2, target: Vector2, radius: f32) -> Option<Vector2> {
+        let mut tile = self.tile_of(pos)?;
+        if self.cost_at(tile) == u32::MAX { return None; }
+        if clear_path(maze, pos, target, radius) { return Some(target); }
+
+        let b = block_size() as f32;
+        let center = |(x, y): (usize, usize)| Vector2::new((x as f32 + 0.5) * b, (y as f32 + 0.5) * b);
+        let mut waypoint = None;
+        for _ in 0..LOOKAHEAD {
+            let Some(next) = self.downhill(tile) else { break; };
+            tile = next;
+            // El primer paso siempre vale; los siguientes solo si se llega en línea recta
+            if waypoint.is_some() && !clear_path(maze, pos, center(tile), radius) { break; }
+            waypoint = Some(center(tile));
+            if self.cost_at(tile) == 0 { break; }
+        }
+        waypoint
+    }
+
+    /// Walking distance in pixels from `pos` to the target, if reachable.
+    pub fn distance(&self, pos: Vector2) -> Option<f32> {
+        let cost = self.cost_at(self.tile_of(pos)?);
+        (cost != u32::MAX).then(|| cost as f32 / STRAIGHT as f32 * block_size() as f32)
+    }
+
+    /// Cheapest neighbour of `tile`, if it is closer to the target.
+    fn downhill(&self, (x, y): (usize, usize)) -> Option<(usize, usize)> {
+        let mut best = ((x, y), self.cost_at((x, y)));
+        for &(dx, dy, step) in &NEIGHBOURS {
+            let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+            if nx < 0 || ny < 0 || nx as usize >= self.width || ny as usize >= self.height { continue; }
+            let n = (nx as usize, ny as usize);
+            let cost = self.cost_at(n);
+            // Solo vecinos conectados (su coste + el paso es el nuestro)
+            if cost < best.1 && cost.saturating_add(step) == self.cost_at((x, y)) { best = (n, cost); }
+        }
+        (best.0 != (x, y)).then_some(best.0)
+    }
+
+    fn cost_at(&self, (x, y): (usize, usize)) -> u32 {
+        self.cost[y * self.width + x]
+    }
+
+    fn tile_of(&self, pos: Vector2) -> Option<(usize, usize)> {
+        let b = block_size() as f32;
+        let (x, y) = ((pos.x / b).floor(), (pos.y / b).floor());
+        if x < 0.0 || y < 0.0 || x as usize >= self.width || y as usize >= self.height { return None; }
+        Some((x as usize, y as usize))
+    }
+}
+
+/// Open tiles within a few steps of a point, in breadth-first order (nearest first),
+/// each with the tile it was reached from so a route can be rebuilt.
+pub struct Neighbourhood {
+    tiles: Vec<(usize, usize)>,
+    parent: Vec<usize>,
+}
+
+impl Neighbourhood {
+    pub fn around(maze: &Maze, pos: Vector2, max_steps: usize) -> Self {
+        let b = block_size() as f32;
+        let start = ((pos.x / b).floor() as usize, (pos.y / b).floor() as usize);
+        let mut tiles = vec![start];
+        let mut parent = vec![0];
+        let mut steps = vec![0];
+        let mut i = 0;
+        while i < tiles.len() {
+            let (x, y) = tiles[i];
+            if steps[i] < max_steps {
+                for &(dx, dy, _) in &NEIGHBOURS[..4] {
+                    let (nx, ny) = (x as isize + dx as isize, y as isize + dy as isize);
+                    if is_wall(maze, nx, ny) || tiles.contains(&(nx as usize, ny as usize)) { continue; }
+                    tiles.push((nx as usize, ny as usize));
+                    parent.push(i);
+                    steps.push(steps[i] + 1);
+                }
+            }
+            i += 1;
+        }
+        Self { tiles, parent }
+    }
+
+    /// Tile centres in breadth-first order; index 0 is the starting tile.
+    pub fn centers(&self) -> impl Iterator<Item = Vector2> + '_ {
+        let b = block_size() as f32;
+        self.tiles.iter().map(move |&(x, y)| Vector2::new((x as f32 + 0.5) * b, (y as f32 + 0.5) * b))
+    }
+
+    /// Tile centres to walk through to reach tile `i`, starting with the first step.
+    pub fn route(&self, mut i: usize) -> Vec<Vector2> {
+        let centers: Vec<Vector2> = self.centers().collect();
+        let mut route = Vec::new();
+        while i != 0 {
+            route.push(centers[i]);
+            i = self.parent[i];
+        }
+        route.reverse();
+        route
+    }
+}
+
+impl Default for FlowField {
+    fn default() -> Self {
+        Self::new()
+    }
+}
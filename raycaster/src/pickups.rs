@@ -0,0 +1,165 @@
+//! Items lying on the floor: coins, keys, health packs, ammo, armor and treasure.
+//!
+//! Coins (`X`) and keys (`r`/`g`/`y`) can still be drawn in the map; the level loader
+//! turns them into pickups and clears their tiles. Every other kind is placed with a
+//! `[[pickup]]` entry. The player takes an item by walking within `PICKUP_RADIUS` of
+//! it; health, armor and ammo stay on the floor while the player can't use them.
+
+use raylib::prelude::{Color, Vector2};
+use crate::{
+    doors::KeyColor,
+    player::Player,
+    weapons::{WeaponKind, Weapons},
+};
+
+const PICKUP_RADIUS: f32 = 28.0; // distancia a la que se recoge, en pixels
+const HEALTH_AMOUNT: i32 = 25;
+const ARMOR_AMOUNT: i32 = 50;
+const TREASURE_VALUE: i32 = 100;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PickupKind {
+    Coin,
+    Key(KeyColor),
+    Health,
+    Armor,
+    Ammo(WeaponKind),
+    Treasure,
+}
+
+impl PickupKind {
+    /// Every kind, in the order their names are listed in errors and docs.
+    pub const ALL: [PickupKind; 11] = [
+        PickupKind::Coin,
+        PickupKind::Key(KeyColor::Red),
+        PickupKind::Key(KeyColor::Green),
+        PickupKind::Key(KeyColor::Yellow),
+        PickupKind::Health,
+        PickupKind::Armor,
+        PickupKind::Ammo(WeaponKind::Pistol),
+        PickupKind::Ammo(WeaponKind::Shotgun),
+        PickupKind::Ammo(WeaponKind::MachineGun),
+        PickupKind::Ammo(WeaponKind::Plasma),
+        PickupKind::Treasure,
+    ];
+
+    /// Name used in level files.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Coin => "coin",
+            Self::Key(KeyColor::Red) => "key_red",
+            Self::Key(KeyColor::Green) => "key_green",
+            Self::Key(KeyColor::Yellow) => "key_yellow",
+            Self::Health => "health",
+            Self::Armor => "armor",
+            Self::Ammo(WeaponKind::Pistol) => "ammo_pistol",
+            Self::Ammo(WeaponKind::Shotgun) => "ammo_shotgun",
+            Self::Ammo(WeaponKind::MachineGun) => "ammo_machinegun",
+            Self::Ammo(WeaponKind::Plasma) => "ammo_plasma",
+            Self::Treasure => "treasure",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.name() == name)
+    }
+
+    /// Pickup drawn directly in the map grid (`X` coin, `r`/`g`/`y` keys).
+    pub fn from_map_char(ch: char) -> Option<Self> {
+        match ch {
+            'X' | 'x' => Some(Self::Coin),
+            _ => KeyColor::from_key_char(ch).map(Self::Key),
+        }
+    }
+
+    /// Color of the item on the minimap and of the screen flash when it is taken.
+    pub fn color(self) -> Color {
+        match self {
+            Self::Coin | Self::Treasure => Color::new(255, 215, 0, 255),
+            Self::Key(color) => color.color(),
+            Self::Health => Color::new(230, 60, 60, 255),
+            Self::Armor => Color::new(60, 200, 120, 255),
+            Self::Ammo(_) => Color::new(200, 170, 90, 255),
+        }
+    }
+
+    /// Key of the sound played when the item is taken (`assets/audios/<key>.wav`).
+    pub fn sound(self) -> &'static str {
+        match self {
+            Self::Coin => "pickup_coin",
+            Self::Key(_) => "pickup_key",
+            Self::Health => "pickup_health",
+            Self::Armor => "pickup_armor",
+            Self::Ammo(_) => "pickup_ammo",
+            Self::Treasure => "pickup_treasure",
+        }
+    }
+
+    /// Message shown when the item is taken; coins only update the counter.
+    pub fn message(self) -> Option<String> {
+        match self {
+            Self::Coin => None,
+            Self::Key(color) => Some(format!("Llave {} recogida", color.name())),
+            Self::Health => Some(format!("+{} de vida", HEALTH_AMOUNT)),
+            Self::Armor => Some(format!("+{} de armadura", ARMOR_AMOUNT)),
+            Self::Ammo(weapon) => Some(format!("Munición de {} +{}", weapon.stats().name, weapon.stats().magazine)),
+            Self::Treasure => Some(format!("Tesoro +{}", TREASURE_VALUE)),
+        }
+    }
+
+    /// Give the item to the player. Returns `false` (and leaves it on the floor) if the
+    /// player has no use for it right now.
+    fn apply(self, player: &mut Player, weapons: &mut Weapons) -> bool {
+        match self {
+            Self::Coin => true, // las cuenta el nivel
+            Self::Key(color) => {
+                player.give_key(color);
+                true
+            }
+            Self::Health => {
+                if player.health >= player.max_health { return false; }
+                player.heal(HEALTH_AMOUNT);
+                true
+            }
+            Self::Armor => {
+                if player.armor >= player.max_armor { return false; }
+                player.add_armor(ARMOR_AMOUNT);
+                true
+            }
+            Self::Ammo(weapon) => weapons.add_ammo(weapon, weapon.stats().magazine),
+            Self::Treasure => {
+                player.score += TREASURE_VALUE;
+                true
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Pickup {
+    pub pos: Vector2, // centro de la casilla, en pixels
+    pub kind: PickupKind,
+}
+
+pub struct Pickups {
+    pub list: Vec<Pickup>,
+}
+
+impl Pickups {
+    pub fn new(list: Vec<Pickup>) -> Self {
+        Self { list }
+    }
+
+    /// Take every item within reach of the player that it can use, and return their kinds.
+    pub fn collect(&mut self, player: &mut Player, weapons: &mut Weapons) -> Vec<PickupKind> {
+        let mut taken = Vec::new();
+        self.list.retain(|item| {
+            let (dx, dy) = (item.pos.x - player.pos.x, item.pos.y - player.pos.y);
+            if dx * dx + dy * dy > PICKUP_RADIUS * PICKUP_RADIUS { return true; }
+            if !item.kind.apply(player, weapons) { return true; }
+            taken.push(item.kind);
+            false
+        });
+        taken
+    }
+}
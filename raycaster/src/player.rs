@@ -1,4 +1,5 @@
 use raylib::prelude::*;
+use crate::doors::KeyColor;
 #[derive(Clone, Copy)]
 pub struct Player {
     pub pos: Vector2, // world-space (pixels)
@@ -6,7 +7,11 @@ pub struct Player {
     pub fov: f32,
     pub health: i32,      // Salud actual
     pub max_health: i32,  // Salud máxima
+    pub armor: i32,       // absorbe parte del daño
+    pub max_armor: i32,
+    pub score: i32,       // puntos de tesoro
     pub shot_effect_timer: i32, // frames para mostrar efecto de disparo
+    pub keys: [bool; 3], // llaves recogidas, por `KeyColor::index`
 }
 impl Player {
     pub fn new(pos: Vector2) -> Self {
@@ -16,13 +21,19 @@ impl Player {
             fov: std::f32::consts::PI / 3.0,
             health: 100,
             max_health: 100,
+            armor: 0,
+            max_armor: 100,
+            score: 0,
             shot_effect_timer: 0,
+            keys: [false; 3],
         }
     }
     
-    // Método para recibir daño
+    // Método para recibir daño: la armadura absorbe dos tercios mientras dure
     pub fn take_damage(&mut self, damage: i32) {
-        self.health = (self.health - damage).max(0);
+        let absorbed = (damage * 2 / 3).min(self.armor);
+        self.armor -= absorbed;
+        self.health = (self.health - (damage - absorbed)).max(0);
     }
     
     // Método para curar
@@ -30,6 +41,18 @@ impl Player {
         self.health = (self.health + amount).min(self.max_health);
     }
     
+    pub fn add_armor(&mut self, amount: i32) {
+        self.armor = (self.armor + amount).min(self.max_armor);
+    }
+    
+    pub fn has_key(&self, color: KeyColor) -> bool {
+        self.keys[color.index()]
+    }
+
+    pub fn give_key(&mut self, color: KeyColor) {
+        self.keys[color.index()] = true;
+    }
+
     // Verificar si está vivo
     pub fn is_alive(&self) -> bool {
         self.health > 0
@@ -1,13 +1,18 @@
 use crate::{
-    maze::Maze,
+    doors::{Door, Doors},
+    framebuffer::Framebuffer,
+    maze::{Maze, Surfaces, is_open_tile},
+    pickups::{Pickup, PickupKind},
     player::Player,
-    textures::TextureManager,
+    textures::{TextureManager, surface_key_from_char, texel, wall_key_from_char_at},
 };
+use image::RgbaImage;
+use rayon::prelude::*;
 use raylib::prelude::*;
 
 /// One wall hit returned by DDA
 struct Hit {
-    distance: f32, // perpendicular distance in *cell units*
+    distance: f32, // length of the ray to the hit in *cell units* (euclidean, not fisheye-corrected)
     cell_x: i32,
     cell_y: i32,
     side: i32,     // 0 = hit on X-side (vertical wall), 1 = hit on Y-side (horizontal wall)
@@ -15,95 +20,105 @@ struct Hit {
     wall_x: f32,   // 0..1 coordinate along the wall for texturing
 }
 
+/// One framebuffer column after casting its ray: everything the row pass needs to shade it.
+struct Column<'a> {
+    dir_x: f32,
+    dir_y: f32,
+    distance: f32,
+    wall: Option<&'a RgbaImage>,
+    wall_u: f32,     // coordenada horizontal en la textura de la pared
+    wall_top: f32,   // fila (sin recortar) donde empieza la pared
+    wall_h: f32,     // altura proyectada de la pared
+    wall_start: i32, // filas visibles de la pared, inclusivas
+    wall_end: i32,
+    shade: f32,      // lado oscuro * niebla, igual en toda la columna
+}
+
+/// Render walls, floor and ceiling into `fb`. Rays are cast one per column in parallel,
+/// then rows are shaded in parallel from those columns, so each pixel is written once.
 pub fn draw_world(
-    d: &mut RaylibDrawHandle,
+    fb: &mut Framebuffer,
     maze: &Maze,
+    doors: &Doors,
     player: &Player,
     zbuffer: &mut [f32],
     tex: &TextureManager,
-    render_scale: usize,
+    surfaces: &Surfaces,
 ) {
-    let screen_w = d.get_screen_width().max(1) as usize;
-    let screen_h = d.get_screen_height().max(1) as usize;
-
-    d.clear_background(Color::BLACK);
+    let (screen_w, screen_h) = (fb.width as usize, fb.height as i32);
 
     let fov = player.fov;
     let half_w = screen_w as f32 * 0.5;
     let half_h = screen_h as f32 * 0.5;
     let proj_plane = half_w / (fov * 0.5).tan();
 
-    let step_x = render_scale.max(1);
-    let step_y = render_scale.max(1);
-
-    let mut sx = 0usize;
-    let mut col_index = 0usize;
-    
-    while sx < screen_w {
+    let columns: Vec<Column> = (0..screen_w).into_par_iter().map(|sx| {
         let camera_x = (sx as f32 - half_w) / half_w;
         let ray_angle = player.a + camera_x * (fov * 0.5);
+        let hit = cast_ray_dda(maze, doors, player, ray_angle);
 
-        let hit = cast_ray_dda(maze, player, ray_angle);
-        if col_index < zbuffer.len() { zbuffer[col_index] = hit.distance; }
+        let line_h = proj_plane / hit.distance.max(1e-4);
+        let line_i = line_h.min(screen_h as f32) as i32;
+        let side_k = if hit.side == 1 { 0.85 } else { 1.0 };
+        Column {
+            dir_x: ray_angle.cos(),
+            dir_y: ray_angle.sin(),
+            distance: hit.distance,
+            wall: tex.image(wall_key_from_char_at(hit.impact, hit.cell_x, hit.cell_y)),
+            wall_u: hit.wall_x,
+            wall_top: half_h - line_h * 0.5,
+            wall_h: line_h,
+            wall_start: ((screen_h - line_i) / 2).max(0),
+            wall_end: ((screen_h + line_i) / 2).min(screen_h - 1),
+            shade: side_k * fog_factor(hit.distance, 0.015),
+        }
+    }).collect();
 
-        let line_h_f = proj_plane / hit.distance.max(1e-4);
-        let mut line_h = line_h_f as i32;
-        if line_h > screen_h as i32 { line_h = screen_h as i32; }
-        
-        let mut draw_start = (screen_h as i32 - line_h) / 2;
-        if draw_start < 0 { draw_start = 0; }
-        let mut draw_end = (screen_h as i32 + line_h) / 2;
-        if draw_end >= screen_h as i32 { draw_end = screen_h as i32 - 1; }
+    for (z, col) in zbuffer.iter_mut().zip(&columns) { *z = col.distance; }
 
-        // WALL - Renderizado simple y correcto
-        let wall_key = crate::textures::wall_key_from_char_at(hit.impact, hit.cell_x, hit.cell_y);
-        let (tw, th) = tex.size_of(wall_key);
-        let tx_u32 = ((hit.wall_x * (tw as f32 - 1.0)) as u32).min(tw - 1);
+    // Floor/ceiling casting: walls use `line_h = proj_plane / distance` around the horizon,
+    // so the floor seen at row `y` lies `0.5 * proj_plane / (y - half_h)` cells along each
+    // column's ray; the ceiling mirrors it.
+    let b = crate::maze::block_size() as f32;
+    let (pos_x, pos_y) = (player.pos.x / b, player.pos.y / b);
+    fb.par_rows_mut().enumerate().for_each(|(sy, row)| {
+        let y = sy as i32;
+        let row_center = sy as f32 + 0.5;
+        let dist = 0.5 * proj_plane / (row_center - half_h).abs().max(0.5);
+        let row_shade = fog_factor(dist, 0.015);
+        // Vecinos en la fila suelen caer en la misma casilla: recordar la última textura
+        let mut floor_img = (None, None);
+        let mut ceiling_img = (None, None);
 
-        // Renderizado simple: una franja vertical por columna
-        if render_scale > 1 {
-            // Para cada bloque vertical, muestrear el centro de ese bloque
-            let wall_height = draw_end - draw_start + 1;
-            
-            for sy in (draw_start..=draw_end).step_by(step_y) {
-                // Calcular qué parte de la textura corresponde a esta altura
-                let rel_y = sy - draw_start;
-                let v = rel_y as f32 / wall_height as f32;
-                let ty = ((v * (th as f32 - 1.0)) as u32).min(th - 1);
-                
-                let mut color = tex.get_pixel_color(wall_key, tx_u32, ty);
-                if hit.side == 1 { darken(&mut color, 0.85); }
-                fog_with_distance(&mut color, hit.distance, 0.015);
-                
-                // Dibujar bloque
-                let block_height = step_y.min((draw_end - sy + 1) as usize) as i32;
-                d.draw_rectangle(
-                    sx as i32,
-                    sy,
-                    step_x as i32,
-                    block_height,
-                    color
-                );
-            }
-        } else {
-            // Render scale 1: pixel perfecto
-            let wall_height = draw_end - draw_start + 1;
-            for sy in draw_start..=draw_end {
-                let v = (sy - draw_start) as f32 / wall_height as f32;
-                let ty = ((v * (th as f32 - 1.0)) as u32).min(th - 1);
-                let mut color = tex.get_pixel_color(wall_key, tx_u32, ty);
-                if hit.side == 1 { darken(&mut color, 0.85); }
-                fog_with_distance(&mut color, hit.distance, 0.015);
-                d.draw_pixel(sx as i32, sy, color);
-            }
+        for (col, px) in columns.iter().zip(row.chunks_exact_mut(4)) {
+            let mut color = if y < col.wall_start || y > col.wall_end {
+                let world_x = pos_x + col.dir_x * dist;
+                let world_y = pos_y + col.dir_y * dist;
+                let (cell_x, cell_y) = (world_x.floor() as i32, world_y.floor() as i32);
+                let (cached, ch, default) = if y > col.wall_end {
+                    (&mut floor_img, surfaces.floor_at(cell_x, cell_y), "floor")
+                } else {
+                    (&mut ceiling_img, surfaces.ceiling_at(cell_x, cell_y), "ceiling")
+                };
+                if cached.0 != Some(ch) {
+                    *cached = (Some(ch), tex.image(surface_key_from_char(ch, default)));
+                }
+                let mut c = texel(cached.1, world_x - cell_x as f32, world_y - cell_y as f32);
+                darken(&mut c, row_shade);
+                c
+            } else {
+                let v = (row_center - col.wall_top) / col.wall_h;
+                let mut c = texel(col.wall, col.wall_u, v);
+                darken(&mut c, col.shade);
+                c
+            };
+            color.a = 255;
+            px.copy_from_slice(&[color.r, color.g, color.b, color.a]);
         }
-
-        sx += step_x;
-        col_index += 1;
-    }
+    });
 }
 
-fn cast_ray_dda(maze: &Maze, player: &Player, angle: f32) -> Hit {
+fn cast_ray_dda(maze: &Maze, doors: &Doors, player: &Player, angle: f32) -> Hit {
     let b = crate::maze::block_size() as f32;
     let pos_x = player.pos.x / b;
     let pos_y = player.pos.y / b;
@@ -140,8 +155,13 @@ fn cast_ray_dda(maze: &Maze, player: &Player, angle: f32) -> Hit {
             impact = '#';
             break;
         }
+        // Puertas: el panel está hundido a media casilla; si el rayo no lo toca, sigue
+        if let Some(door) = doors.at(map_x, map_y) {
+            if let Some(hit) = door_hit(door, pos_x, pos_y, ray_dir_x, ray_dir_y) { return hit; }
+            continue;
+        }
         let ch = maze[map_y as usize][map_x as usize];
-        if ch != ' ' && ch != 'X' && ch != 'x' && ch != 'P' && ch != 'p' {
+        if !is_open_tile(ch) {
             impact = ch;
             break;
         }
@@ -164,140 +184,158 @@ fn cast_ray_dda(maze: &Maze, player: &Player, angle: f32) -> Hit {
     Hit { distance: perp_dist, cell_x: map_x, cell_y: map_y, side, impact, wall_x }
 }
 
+/// Intersect a ray (in cell units) with a door panel: a plane through the middle of the
+/// door's cell, covering the `[open, 1]` part of it, so the texture slides with the panel.
+fn door_hit(door: &Door, pos_x: f32, pos_y: f32, dir_x: f32, dir_y: f32) -> Option<Hit> {
+    let (cell_x, cell_y) = (door.x as f32, door.y as f32);
+    // (posición y dirección perpendiculares al panel, y a lo largo del panel)
+    let (plane, origin, dir, along_origin, along_dir, along_base) = if door.along_x {
+        (cell_y + 0.5, pos_y, dir_y, pos_x, dir_x, cell_x)
+    } else {
+        (cell_x + 0.5, pos_x, dir_x, pos_y, dir_y, cell_y)
+    };
+    if dir.abs() < 1e-6 { return None; }
+    let t = (plane - origin) / dir;
+    if t <= 0.0 { return None; }
+    let u = along_origin + along_dir * t - along_base;
+    if !(0.0..1.0).contains(&u) || u < door.open { return None; }
+    Some(Hit {
+        distance: t.max(0.0001),
+        cell_x: door.x as i32,
+        cell_y: door.y as i32,
+        side: if door.along_x { 1 } else { 0 },
+        impact: door.ch,
+        wall_x: u - door.open,
+    })
+}
+
 fn darken(c: &mut Color, k: f32) {
     c.r = ((c.r as f32) * k) as u8;
     c.g = ((c.g as f32) * k) as u8;
     c.b = ((c.b as f32) * k) as u8;
 }
 
-fn apply_fog(c: &mut Color, fog: f32) {
-    let k = (1.0 - fog).clamp(0.0, 1.0);
-    darken(c, k);
-}
-
-pub fn fog_with_distance(c: &mut Color, dist: f32, density: f32) {
+/// Brightness multiplier left by the fog at `dist` cells.
+fn fog_factor(dist: f32, density: f32) -> f32 {
     let fog = (1.0 - (-density * dist).exp()).clamp(0.0, 1.0);
     // Fog muy reducido para mejor visibilidad
     let adjusted_fog = fog * 0.5; // Solo 50% del fog original
-    apply_fog(c, adjusted_fog);
+    (1.0 - adjusted_fog).clamp(0.0, 1.0)
 }
 
-/// Render coins as billboard sprites in the 3D world (cambios mínimos)
-pub fn draw_coins(
-    d: &mut RaylibDrawHandle,
-    maze: &Maze,
-    player: &Player,
-    zbuffer: &[f32],
-    tex: &TextureManager,
-    render_scale: usize,
-) {
-    let screen_w = d.get_screen_width().max(1) as usize;
-    let screen_h = d.get_screen_height().max(1) as usize;
+/// Screen-space footprint of a billboard standing at a world position.
+struct Billboard {
+    screen_x: i32,
+    depth_cells: f32, // profundidad perpendicular, comparable con el zbuffer
+    left: i32,        // esquina superior izquierda sin recortar
+    top: i32,
+    size: i32,        // sprites cuadrados
+}
+
+/// Project a billboard of `scale` blocks tall whose top sits `lift` sprite-heights below
+/// the centred position. Returns `None` when it is behind the camera or out of the FOV.
+fn project_billboard(fb: &Framebuffer, player: &Player, world: (f32, f32), scale: f32, lift: f32) -> Option<Billboard> {
     let block_size = crate::maze::block_size() as f32;
-    
-    // Recolectar monedas
-    let mut coins = Vec::new();
-    for (y, row) in maze.iter().enumerate() {
-        for (x, &ch) in row.iter().enumerate() {
-            if ch == 'X' || ch == 'x' {
-                let world_x = (x as f32 + 0.5) * block_size;
-                let world_y = (y as f32 + 0.5) * block_size;
-                coins.push((world_x, world_y));
-            }
-        }
-    }
-    
-    // Orden: lejos -> cerca (lo dejo igual que tenías)
-    coins.sort_by(|a, b| {
-        let da = ((a.0 - player.pos.x).powi(2) + (a.1 - player.pos.y).powi(2)).sqrt();
-        let db = ((b.0 - player.pos.x).powi(2) + (b.1 - player.pos.y).powi(2)).sqrt();
-        db.partial_cmp(&da).unwrap_or(std::cmp::Ordering::Equal)
-    });
-    
     let fov = player.fov;
-    let half_w = screen_w as f32 * 0.5;
-    let half_h = screen_h as f32 * 0.5;
+    let half_w = fb.width as f32 * 0.5;
+    let half_h = fb.height as f32 * 0.5;
     let proj_plane = half_w / (fov * 0.5).tan();
-    
-    // Textura
-    let coin_key = "coin";
-    let (tex_w_u32, tex_h_u32) = tex.size_of(coin_key);
-    let (tex_w, tex_h) = (tex_w_u32 as usize, tex_h_u32 as usize);
 
-    // === NUEVO: vector "forward" del jugador ===
-    let fwd_x = player.a.cos();
-    let fwd_y = player.a.sin();
+    // Vector del jugador al sprite
+    let dx = world.0 - player.pos.x;
+    let dy = world.1 - player.pos.y;
 
-    for (coin_x, coin_y) in coins {
-        // Vector del jugador a la moneda
-        let dx = coin_x - player.pos.x;
-        let dy = coin_y - player.pos.y;
+    let mut angle_diff = dy.atan2(dx) - player.a;
+    while angle_diff > std::f32::consts::PI { angle_diff -= 2.0 * std::f32::consts::PI; }
+    while angle_diff < -std::f32::consts::PI { angle_diff += 2.0 * std::f32::consts::PI; }
+    if angle_diff.abs() > fov * 0.6 { return None; }
 
-        // Ángulo relativo (igual que lo tenías)
-        let angle_to_coin = dy.atan2(dx);
-        let mut angle_diff = angle_to_coin - player.a;
-        while angle_diff > std::f32::consts::PI { angle_diff -= 2.0 * std::f32::consts::PI; }
-        while angle_diff < -std::f32::consts::PI { angle_diff += 2.0 * std::f32::consts::PI; }
+    // Profundidad perpendicular (no distancia radial)
+    let depth_world = dx * player.a.cos() + dy * player.a.sin();
+    if depth_world <= 1e-4 { return None; } // detrás de la cámara
+    let depth_cells = depth_world / block_size;
 
-        // Culling por FOV (mantengo tu margen)
-        if angle_diff.abs() > fov * 0.6 { continue; }
+    let screen_x = ((angle_diff / (fov * 0.5)) * half_w + half_w) as i32;
+    let size = (scale * proj_plane / depth_cells.max(1e-4)) as i32;
+    if size < 1 { return None; }
+    Some(Billboard {
+        screen_x,
+        depth_cells,
+        left: screen_x - size / 2,
+        top: half_h as i32 - size / 2 + (size as f32 * lift) as i32,
+        size,
+    })
+}
 
-        // === NUEVO: profundidad perpendicular (no distancia radial) ===
-        let depth_world = dx * fwd_x + dy * fwd_y; // dot hacia adelante
-        if depth_world <= 1e-4 { continue; }       // detrás de la cámara
-        let depth_cells = depth_world / block_size;
+/// Draw a billboard's texture into `fb`, column by column against the zbuffer.
+/// `tint` runs on every opaque texel before fog (e.g. the enemy muzzle flash).
+fn draw_billboard(
+    fb: &mut Framebuffer,
+    zbuffer: &[f32],
+    img: Option<&RgbaImage>,
+    bb: &Billboard,
+    fog_density: f32,
+    tint: impl Fn(&mut Color),
+) {
+    let x0 = bb.left.max(0);
+    let x1 = (bb.left + bb.size).min(fb.width as i32);
+    let y0 = bb.top.max(0);
+    let y1 = (bb.top + bb.size).min(fb.height as i32);
+    let shade = fog_factor(bb.depth_cells, fog_density);
 
-        // Proyección X
-        let screen_x = ((angle_diff / (fov * 0.5)) * half_w + half_w) as i32;
+    for sx in x0..x1 {
+        // Detrás de una pared
+        if zbuffer.get(sx as usize).is_some_and(|&z| bb.depth_cells > z * 0.98) { continue; }
+        let u = (sx - bb.left) as f32 / bb.size as f32;
+        for sy in y0..y1 {
+            let v = (sy - bb.top) as f32 / bb.size as f32;
+            let mut color = texel(img, u, v);
+            if color.a < 10 { continue; }
+            tint(&mut color);
+            darken(&mut color, shade);
+            fb.blend_pixel(sx, sy, color);
+        }
+    }
+}
 
-        // === CAMBIO: tamaño con profundidad perpendicular en celdas ===
-        let sprite_height = (0.6 * proj_plane / depth_cells.max(1e-4)) as i32;
-        if sprite_height < 1 { continue; }
-        let sprite_width = sprite_height; // cuadrado
-        
-        // Posiciones de dibujo (igual que lo tenías)
-        let draw_start_y = ((half_h as i32) - sprite_height / 2 + sprite_height / 6).max(0);
-        let draw_end_y = (draw_start_y + sprite_height).min(screen_h as i32 - 1);
-        let draw_start_x = (screen_x - sprite_width / 2).max(0);
-        let draw_end_x = (screen_x + sprite_width / 2).min(screen_w as i32 - 1);
-        if draw_end_x <= draw_start_x { continue; }
-        
-        // Dibujar con zbuffer
-        let rs = render_scale.max(1);
-        for sx in (draw_start_x..=draw_end_x).step_by(rs) {
-            let col_index = (sx as usize) / rs;
+/// Render pickups lying on the floor (coins, keys, health, armor, ammo, treasure) as billboard sprites in the 3D world
+pub fn draw_pickups(
+    fb: &mut Framebuffer,
+    pickups: &[Pickup],
+    player: &Player,
+    zbuffer: &[f32],
+    tex: &TextureManager,
+) {
+    // Orden: lejos -> cerca
+    let dist = |p: &Pickup| (p.pos.x - player.pos.x).powi(2) + (p.pos.y - player.pos.y).powi(2);
+    let mut order: Vec<&Pickup> = pickups.iter().collect();
+    order.sort_by(|a, b| dist(b).partial_cmp(&dist(a)).unwrap_or(std::cmp::Ordering::Equal));
 
-            // === CAMBIO: comparar zbuffer contra profundidad perpendicular en celdas ===
-            if col_index < zbuffer.len() && depth_cells > zbuffer[col_index] * 0.98 {
-                continue; // detrás de una pared
+    let key_img = tex.image("key");
+    for item in order {
+        let pos = (item.pos.x, item.pos.y);
+        match item.kind {
+            // Monedas más pequeñas y un poco por debajo del centro
+            PickupKind::Coin => {
+                let Some(bb) = project_billboard(fb, player, pos, 0.6, 1.0 / 6.0) else { continue; };
+                draw_billboard(fb, zbuffer, tex.image("coin"), &bb, 0.08, |_| {});
             }
-            
-            // Texcoord X - ARREGLADO: agregar paréntesis correctos
-            let tx = ((((sx - (screen_x - sprite_width / 2)) as f32 / sprite_width as f32)
-                * (tex_w as f32 - 1.0)) as usize)
-                .min(tex_w.saturating_sub(1));
-            
-            for sy in (draw_start_y..=draw_end_y).step_by(rs) {
-                // Texcoord Y - ARREGLADO: agregar paréntesis correctos
-                let ty = ((((sy - draw_start_y) as f32 / sprite_height as f32)
-                    * (tex_h as f32 - 1.0)) as usize)
-                    .min(tex_h.saturating_sub(1));
-                
-                // Sample
-                let mut color = tex.sample_at(coin_key, tx, ty);
-
-                // Alfa (lo dejo bajo para bordes suaves)
-                if color.a < 10 { continue; }
-                
-                // === CAMBIO: fog usando profundidad en celdas - ARREGLADO: llamar directamente ===
-                fog_with_distance(&mut color, depth_cells, 0.08);
-                
-                // Dibujo según render_scale
-                if rs > 1 {
-                    d.draw_rectangle(sx, sy, rs as i32, rs as i32, color);
-                } else {
-                    d.draw_pixel(sx, sy, color);
-                }
+            // Llaves: silueta blanca teñida con su color, cerca del suelo
+            PickupKind::Key(color) => {
+                let Some(bb) = project_billboard(fb, player, pos, 0.4, 0.6) else { continue; };
+                let k = color.color();
+                draw_billboard(fb, zbuffer, key_img, &bb, 0.08, |c| {
+                    c.r = (c.r as u16 * k.r as u16 / 255) as u8;
+                    c.g = (c.g as u16 * k.g as u16 / 255) as u8;
+                    c.b = (c.b as u16 * k.b as u16 / 255) as u8;
+                });
+            }
+            // El resto: textura con el nombre del objeto, apoyada en el suelo
+            kind => {
+                let scale = if matches!(kind, PickupKind::Ammo(_)) { 0.3 } else { 0.4 };
+                let lift = (1.0 - scale) / (2.0 * scale);
+                let Some(bb) = project_billboard(fb, player, pos, scale, lift) else { continue; };
+                draw_billboard(fb, zbuffer, tex.image(kind.name()), &bb, 0.08, |_| {});
             }
         }
     }
@@ -305,20 +343,17 @@ pub fn draw_coins(
 
 /// Render enemies as billboard sprites in the 3D world
 pub fn draw_enemies(
-    d: &mut RaylibDrawHandle,
-    enemies: &[crate::sprites::Enemy],
+    fb: &mut Framebuffer,
+    enemies: &crate::sprites::EnemySystem,
     player: &Player,
     zbuffer: &[f32],
     tex: &TextureManager,
-    render_scale: usize,
     fog_of_war: &crate::maze::FogOfWar,
 ) {
-    let screen_w = d.get_screen_width().max(1) as usize;
-    let screen_h = d.get_screen_height().max(1) as usize;
     let block_size = crate::maze::block_size() as f32;
     
     // Sort enemies by distance (farthest first)
-    let mut sorted_enemies: Vec<_> = enemies.iter()
+    let mut sorted_enemies: Vec<_> = enemies.list.iter()
         .filter(|e| {
             if !e.alive { return false; }
             
@@ -336,103 +371,42 @@ pub fn draw_enemies(
         .collect();
     
     sorted_enemies.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-    
-    let fov = player.fov;
-    let half_w = screen_w as f32 * 0.5;
-    let half_h = screen_h as f32 * 0.5;
-    let proj_plane = half_w / (fov * 0.5).tan();
-    
-    let enemy_key = "enemy";
-    let (tex_w_u32, tex_h_u32) = tex.size_of(enemy_key);
-    let (tex_w, tex_h) = (tex_w_u32 as usize, tex_h_u32 as usize);
-    
-    let fwd_x = player.a.cos();
-    let fwd_y = player.a.sin();
-    
+
     for (enemy, _) in sorted_enemies {
-        let dx = enemy.pos.x - player.pos.x;
-        let dy = enemy.pos.y - player.pos.y; // CORREGIDO: era pos_y
-        
-        let angle_to_enemy = dy.atan2(dx);
-        let mut angle_diff = angle_to_enemy - player.a;
-        while angle_diff > std::f32::consts::PI { angle_diff -= 2.0 * std::f32::consts::PI; }
-        while angle_diff < -std::f32::consts::PI { angle_diff += 2.0 * std::f32::consts::PI; }
-        
-        if angle_diff.abs() > fov * 0.6 { continue; }
-        
-        let depth_world = dx * fwd_x + dy * fwd_y;
-        if depth_world <= 1e-4 { continue; }
-        let depth_cells = depth_world / block_size;
-        
-        let screen_x = ((angle_diff / (fov * 0.5)) * half_w + half_w) as i32;
-        
-        // Enemies are full block height
-        let sprite_height = (1.0 * proj_plane / depth_cells.max(1e-4)) as i32;
-        if sprite_height < 1 { continue; }
-        let sprite_width = sprite_height;
-        
-        let draw_start_y = ((half_h as i32) - sprite_height / 2).max(0);
-        let draw_end_y = (draw_start_y + sprite_height).min(screen_h as i32 - 1);
-        let draw_start_x = (screen_x - sprite_width / 2).max(0);
-        let draw_end_x = (screen_x + sprite_width / 2).min(screen_w as i32 - 1);
-        if draw_end_x <= draw_start_x { continue; }
-        
-        let rs = render_scale.max(1);
-        
-        // Draw enemy sprite
-        for sx in (draw_start_x..=draw_end_x).step_by(rs) {
-            let col_index = (sx as usize) / rs;
-            
-            if col_index < zbuffer.len() && depth_cells > zbuffer[col_index] * 0.98 {
-                continue;
-            }
-            
-            let tx = ((((sx - (screen_x - sprite_width / 2)) as f32 / sprite_width as f32)
-                * (tex_w as f32 - 1.0)) as usize)
-                .min(tex_w.saturating_sub(1));
-            
-            for sy in (draw_start_y..=draw_end_y).step_by(rs) {
-                let ty = ((((sy - draw_start_y) as f32 / sprite_height as f32)
-                    * (tex_h as f32 - 1.0)) as usize)
-                    .min(tex_h.saturating_sub(1));
-                
-                let mut color = tex.sample_at(enemy_key, tx, ty);
-                
-                if color.a < 10 { continue; }
-                
-                // Muzzle flash effect - brighten enemy when shooting
-                if enemy.muzzle_flash > 0 {
-                    let flash_intensity = enemy.muzzle_flash as f32 / 5.0;
-                    color.r = (color.r as f32 + 100.0 * flash_intensity).min(255.0) as u8;
-                    color.g = (color.g as f32 + 80.0 * flash_intensity).min(255.0) as u8;
-                    color.b = (color.b as f32 + 20.0 * flash_intensity).min(255.0) as u8;
-                }
-                
-                fog_with_distance(&mut color, depth_cells, 0.08);
-                
-                if rs > 1 {
-                    d.draw_rectangle(sx, sy, rs as i32, rs as i32, color);
-                } else {
-                    d.draw_pixel(sx, sy, color);
-                }
+        // Sprite, tint and height come from the archetype; feet stay on the floor
+        let arch = enemies.archetypes.get(enemy.kind);
+        let lift = (1.0 - arch.scale) / (2.0 * arch.scale);
+        let Some(bb) = project_billboard(fb, player, (enemy.pos.x, enemy.pos.y), arch.scale, lift) else { continue; };
+
+        // Muzzle flash effect - brighten enemy when shooting
+        let flash_intensity = enemy.muzzle_flash as f32 / 5.0;
+        let tint = arch.tint;
+        draw_billboard(fb, zbuffer, tex.image(&arch.sprite), &bb, 0.08, |color| {
+            color.r = (color.r as u16 * tint.r as u16 / 255) as u8;
+            color.g = (color.g as u16 * tint.g as u16 / 255) as u8;
+            color.b = (color.b as u16 * tint.b as u16 / 255) as u8;
+            if enemy.muzzle_flash > 0 {
+                color.r = (color.r as f32 + 100.0 * flash_intensity).min(255.0) as u8;
+                color.g = (color.g as f32 + 80.0 * flash_intensity).min(255.0) as u8;
+                color.b = (color.b as f32 + 20.0 * flash_intensity).min(255.0) as u8;
             }
-        }
+        });
         
         // Draw shot effect overlay if enemy is shooting
         if enemy.shot_effect_timer > 0 {
             let intensity = (enemy.shot_effect_timer as f32 / 6.0) * 0.3; // Más sutil para enemigos
-            draw_enemy_shot_effect(d, tex, screen_x, draw_start_y, sprite_width, sprite_height, intensity);
+            draw_enemy_shot_effect(fb, bb.screen_x, bb.top.max(0), bb.size, intensity);
         }
         
-        // Draw health bar above enemy
-        let health_bar_y = draw_start_y - 15;
+        // Draw health bar above enemy (sized with the sprite, since it lives in the framebuffer)
+        let health_bar_height = (bb.size / 40).max(1);
+        let health_bar_y = bb.top.max(0) - (bb.size / 12).max(2) - health_bar_height;
         if health_bar_y > 0 {
-            let health_bar_width = (sprite_width as f32 * 0.6) as i32;
-            let health_bar_height = 4;
-            let health_bar_x = screen_x - health_bar_width / 2;
+            let health_bar_width = (bb.size as f32 * 0.6) as i32;
+            let health_bar_x = bb.screen_x - health_bar_width / 2;
             
             // Background (red)
-            d.draw_rectangle(health_bar_x, health_bar_y, health_bar_width, health_bar_height, Color::new(100, 0, 0, 200));
+            fb.fill_rect(health_bar_x, health_bar_y, health_bar_width, health_bar_height, Color::new(100, 0, 0, 200));
             
             // Current health (green/yellow/red based on percentage)
             let health_percentage = enemy.health as f32 / enemy.max_health as f32;
@@ -446,27 +420,38 @@ pub fn draw_enemies(
                 Color::new(255, 0, 0, 220)
             };
             
-            d.draw_rectangle(health_bar_x, health_bar_y, current_health_width, health_bar_height, health_color);
+            fb.fill_rect(health_bar_x, health_bar_y, current_health_width, health_bar_height, health_color);
             
             // Border
-            d.draw_rectangle_lines(health_bar_x - 1, health_bar_y - 1, health_bar_width + 2, health_bar_height + 2, Color::BLACK);
+            fb.rect_lines(health_bar_x - 1, health_bar_y - 1, health_bar_width + 2, health_bar_height + 2, Color::BLACK);
         }
     }
 }
 
-/// Draw shot effect overlay for an enemy shooting (MÁS OPTIMIZADO)
+/// Draw shot effect overlay for an enemy shooting: a translucent circle
 fn draw_enemy_shot_effect(
-    d: &mut RaylibDrawHandle,
-    tex: &TextureManager,
+    fb: &mut Framebuffer,
     center_x: i32,
     center_y: i32,
-    width: i32,
-    height: i32,
+    size: i32,
     intensity: f32,
 ) {
-    // OPTIMIZACIÓN: Efecto más simple - solo un círculo con color
-    let radius = (width.min(height) as f32 * 0.3) as f32;
+    let radius = size as f32 * 0.3;
     let alpha = (intensity * 150.0) as u8;
-    let color = Color::new(255, 200, 100, alpha);
-    d.draw_circle(center_x, center_y, radius, color);
+    fb.fill_circle(center_x, center_y, radius, Color::new(255, 200, 100, alpha));
+}
+
+/// Render the player's plasma bolts as glowing billboards at eye height (no fog).
+pub fn draw_projectiles(
+    fb: &mut Framebuffer,
+    projectiles: &[crate::weapons::Projectile],
+    player: &Player,
+    zbuffer: &[f32],
+    tex: &TextureManager,
+) {
+    let bolt = tex.image("plasma_bolt");
+    for p in projectiles {
+        let Some(bb) = project_billboard(fb, player, (p.pos.x, p.pos.y), 0.25, 0.0) else { continue; };
+        draw_billboard(fb, zbuffer, bolt, &bb, 0.0, |_| {});
+    }
 }
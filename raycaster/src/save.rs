@@ -0,0 +1,433 @@
+//! Saved games: the state of a level in progress, written to one of `SLOTS` files in
+//! `saves/` with the same TOML-style syntax as the level files.
+//!
+//! ```text
+//! [save]
+//! version = 1                 # SAVE_VERSION del juego que la escribió
+//! level = "assets/maps/level2.txt"
+//! name = "Intermedio"
+//! coins = 4
+//!
+//! [player]
+//! pos = [160.0, 96.0]         # pixels
+//! angle = 0.0                 # radianes
+//! health = 80
+//! armor = 0
+//! score = 100
+//! keys = "ry"                 # llaves recogidas, con sus letras del mapa
+//!
+//! [weapons]
+//! current = "shotgun"
+//!
+//! [[ammo]]                    # una por arma
+//! weapon = "pistol"
+//! loaded = 12
+//! reserve = 48
+//!
+//! [[enemy]]
+//! kind = "guard"
+//! pos = [1056.0, 608.0]
+//! health = 150
+//! facing = 1.57
+//! aware = true
+//! last_known = [900.0, 608.0] # donde vio u oyó al jugador por última vez
+//!
+//! [[door]]                    # solo las que no están cerradas
+//! at = [12, 4]                # casilla
+//! state = "open"              # opening, open, closing
+//! open = 1.0                  # fracción abierta
+//! timer = 95                  # frames que le quedan abierta
+//!
+//! [[pickup]]
+//! kind = "coin"
+//! pos = [288.0, 96.0]
+//!
+//! [map]
+//! ...                         # el mapa, con todas las puertas en su sitio
+//! [fog]
+//! ...                         # exploración por casilla, de 0 a f
+//! ```
+//!
+//! The version is checked before anything else, so a save from another version is
+//! rejected with a clear message instead of a parse error.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use raylib::prelude::Vector2;
+use crate::{
+    data_file::{self, DataError, Entry, ParseError, err, parse_tables},
+    doors::{DoorState, KeyColor, door_lock},
+    level::check_map,
+    maze::{Maze, block_size, is_open_tile},
+    pickups::{Pickup, PickupKind},
+    player::Player,
+    sprites::EnemyKind,
+    weapons::{Ammo, WeaponKind},
+};
+
+pub const SAVE_VERSION: i64 = 1;
+pub const SLOTS: usize = 3;
+const SAVE_DIR: &str = "saves";
+
+const SECTIONS: [&str; 3] = ["save", "player", "weapons"];
+const ARRAYS: [&str; 4] = ["ammo", "enemy", "door", "pickup"];
+
+/// An enemy as it was when the game was saved.
+pub struct SavedEnemy {
+    pub kind: EnemyKind,
+    pub pos: Vector2,
+    pub health: i32,
+    pub facing: f32,
+    pub aware: bool, // había detectado al jugador
+    pub last_known: Vector2,
+}
+
+/// A door that was not closed when the game was saved.
+pub struct SavedDoor {
+    pub x: usize,
+    pub y: usize,
+    pub state: DoorState,
+    pub open: f32,
+    pub timer: i32,
+}
+
+pub struct SaveGame {
+    pub level_path: String,
+    pub level_name: String,
+    pub coins: i32,
+    pub player: Player,
+    pub weapon: WeaponKind,
+    pub ammo: [Ammo; 4], // por `WeaponKind::index`
+    pub enemies: Vec<SavedEnemy>,
+    pub doors: Vec<SavedDoor>,
+    pub pickups: Vec<Pickup>,
+    pub maze: Maze,
+    pub fog: Vec<Vec<f32>>, // exploración por casilla, de 0 a 1
+}
+
+/// File of save slot `slot` (0-based).
+pub fn slot_path(slot: usize) -> PathBuf {
+    Path::new(SAVE_DIR).join(format!("slot{}.sav", slot + 1))
+}
+
+/// One-line description of each slot for the menus: `None` if empty, the error if the
+/// file can't be loaded.
+pub fn slot_summaries() -> Vec<Option<Result<String, String>>> {
+    (0..SLOTS)
+        .map(|slot| {
+            let path = slot_path(slot);
+            if !path.is_file() { return None; }
+            Some(SaveGame::load(&path).map(|s| s.summary()).map_err(|e| e.message))
+        })
+        .collect()
+}
+
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn point(entry: &Entry) -> Result<Vector2, ParseError> {
+    let (x, y) = entry.point()?;
+    Ok(Vector2::new(x as f32, y as f32))
+}
+
+fn weapon_kind(entry: &Entry) -> Result<WeaponKind, ParseError> {
+    let name = entry.str()?;
+    WeaponKind::from_name(name).ok_or_else(|| (entry.line, entry.value_col, format!("arma desconocida '{}'", name)))
+}
+
+impl SaveGame {
+    pub fn summary(&self) -> String {
+        format!("{} - {} monedas - {} HP", self.level_name, self.coins, self.player.health)
+    }
+
+    /// Writes the save to `path`, creating its directory if needed.
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() { std::fs::create_dir_all(dir)?; }
+        std::fs::write(path, self.to_text())
+    }
+
+    fn to_text(&self) -> String {
+        let mut out = String::new();
+        let p = &self.player;
+        let keys: String = KeyColor::ALL.iter().filter(|k| p.has_key(**k)).map(|k| k.key_char()).collect();
+        // `write!` sobre un String no falla
+        let _ = writeln!(out, "# Dungeon Delver: partida guardada");
+        let _ = writeln!(out, "[save]\nversion = {}\nlevel = {}\nname = {}\ncoins = {}\n",
+            SAVE_VERSION, quote(&self.level_path), quote(&self.level_name), self.coins);
+        let _ = writeln!(out, "[player]\npos = [{:.2}, {:.2}]\nangle = {:.4}\nhealth = {}\narmor = {}\nscore = {}\nkeys = {}\n",
+            p.pos.x, p.pos.y, p.a, p.health, p.armor, p.score, quote(&keys));
+        let _ = writeln!(out, "[weapons]\ncurrent = {}\n", quote(self.weapon.name()));
+        for kind in WeaponKind::ALL {
+            let ammo = self.ammo[kind.index()];
+            let _ = writeln!(out, "[[ammo]]\nweapon = {}\nloaded = {}\nreserve = {}\n", quote(kind.name()), ammo.loaded, ammo.reserve);
+        }
+        for e in &self.enemies {
+            let _ = writeln!(out, "[[enemy]]\nkind = {}\npos = [{:.2}, {:.2}]\nhealth = {}\nfacing = {:.4}\naware = {}\nlast_known = [{:.2}, {:.2}]\n",
+                quote(e.kind.name()), e.pos.x, e.pos.y, e.health, e.facing, e.aware, e.last_known.x, e.last_known.y);
+        }
+        for d in &self.doors {
+            let _ = writeln!(out, "[[door]]\nat = [{}, {}]\nstate = {}\nopen = {:.3}\ntimer = {}\n",
+                d.x, d.y, quote(d.state.name()), d.open, d.timer);
+        }
+        for item in &self.pickups {
+            let _ = writeln!(out, "[[pickup]]\nkind = {}\npos = [{:.2}, {:.2}]\n", quote(item.kind.name()), item.pos.x, item.pos.y);
+        }
+        out.push_str("[map]\n");
+        for row in &self.maze {
+            out.extend(row.iter());
+            out.push('\n');
+        }
+        out.push_str("[fog]\n");
+        for row in &self.fog {
+            out.extend(row.iter().map(|v| char::from_digit((v.clamp(0.0, 1.0) * 15.0).round() as u32, 16).unwrap_or('0')));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Checks that the save still fits `level_maze`, the map of its level as it is now.
+    pub fn check_level(&self, level_maze: &Maze) -> Result<(), DataError> {
+        let size = |m: &Maze| (m.len(), m.first().map_or(0, Vec::len));
+        if size(&self.maze) == size(level_maze) { return Ok(()); }
+        Err(DataError {
+            path: self.level_path.clone().into(),
+            line: 0,
+            column: 0,
+            message: "el nivel ha cambiado desde que se guardó la partida".to_string(),
+        })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, DataError> {
+        data_file::load(path, Self::parse)
+    }
+
+    fn parse(text: &str) -> Result<Self, ParseError> {
+        let lines: Vec<&str> = text.lines().collect();
+        Self::check_version(&lines)?;
+
+        let (tables, map_line) = match parse_tables(&lines, &SECTIONS, &ARRAYS, Some("map"))? {
+            (tables, Some(line)) => (tables, line),
+            (_, None) => return err(lines.len().max(1), 1, "falta la sección [map] con el mapa"),
+        };
+        let table = |name: &str| match tables.iter().find(|t| t.name == name) {
+            Some(t) => Ok(t),
+            None => err(1, 1, format!("falta la sección [{}]", name)),
+        };
+
+        let (maze, fog) = Self::parse_grids(&lines, map_line)?;
+
+        let save = table("save")?;
+        save.check_keys(&["version", "level", "name", "coins"])?;
+        let player_table = table("player")?;
+        player_table.check_keys(&["pos", "angle", "health", "armor", "score", "keys"])?;
+        let pos = player_table.require("pos")?;
+        let mut player = Player::new(point(pos)?);
+        player.a = player_table.require("angle")?.float()? as f32;
+        player.health = (player_table.require("health")?.non_negative()? as i32).clamp(1, player.max_health);
+        player.armor = (player_table.require("armor")?.non_negative()? as i32).min(player.max_armor);
+        player.score = player_table.require("score")?.non_negative()? as i32;
+        let keys = player_table.require("keys")?;
+        for ch in keys.str()?.chars() {
+            match KeyColor::from_key_char(ch) {
+                Some(color) => player.give_key(color),
+                None => return err(keys.line, keys.value_col, format!("llave desconocida '{}' (r, g, y)", ch)),
+            }
+        }
+
+        let weapons = table("weapons")?;
+        weapons.check_keys(&["current"])?;
+        let mut ammo = [Ammo { loaded: 0, reserve: 0 }; 4];
+        let mut enemies = Vec::new();
+        let mut doors = Vec::new();
+        let mut pickups = Vec::new();
+        for t in &tables {
+            match t.name.as_str() {
+                "ammo" => {
+                    t.check_keys(&["weapon", "loaded", "reserve"])?;
+                    let kind = weapon_kind(t.require("weapon")?)?;
+                    ammo[kind.index()] = Ammo {
+                        loaded: (t.require("loaded")?.non_negative()? as i32).min(kind.stats().magazine),
+                        reserve: (t.require("reserve")?.non_negative()? as i32).min(kind.stats().max_reserve),
+                    };
+                }
+                "enemy" => {
+                    t.check_keys(&["kind", "pos", "health", "facing", "aware", "last_known"])?;
+                    let kind = t.require("kind")?;
+                    enemies.push(SavedEnemy {
+                        kind: EnemyKind::from_name(kind.str()?)
+                            .ok_or_else(|| (kind.line, kind.value_col, format!("tipo de enemigo desconocido '{}'", kind.str().unwrap_or(""))))?,
+                        pos: point(t.require("pos")?)?,
+                        health: t.require("health")?.non_negative()?.max(1) as i32,
+                        facing: t.require("facing")?.float()? as f32,
+                        aware: t.require("aware")?.bool()?,
+                        last_known: point(t.require("last_known")?)?,
+                    });
+                }
+                "door" => {
+                    t.check_keys(&["at", "state", "open", "timer"])?;
+                    let at = t.require("at")?;
+                    let (x, y) = at.tile()?;
+                    if maze.get(y).and_then(|row| row.get(x)).and_then(|&ch| door_lock(ch)).is_none() {
+                        return err(at.line, at.value_col, format!("no hay ninguna puerta en [{}, {}] del mapa", x, y));
+                    }
+                    let state = t.require("state")?;
+                    doors.push(SavedDoor {
+                        x,
+                        y,
+                        state: DoorState::from_name(state.str()?)
+                            .ok_or_else(|| (state.line, state.value_col, format!("estado de puerta desconocido '{}'", state.str().unwrap_or(""))))?,
+                        open: t.require("open")?.fraction()? as f32,
+                        timer: t.require("timer")?.non_negative()? as i32,
+                    });
+                }
+                "pickup" => {
+                    t.check_keys(&["kind", "pos"])?;
+                    let kind = t.require("kind")?;
+                    pickups.push(Pickup {
+                        kind: PickupKind::from_name(kind.str()?)
+                            .ok_or_else(|| (kind.line, kind.value_col, format!("objeto desconocido '{}'", kind.str().unwrap_or(""))))?,
+                        pos: point(t.require("pos")?)?,
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        // El jugador tiene que estar en una casilla libre o en una puerta abierta
+        let b = block_size() as f32;
+        let (tx, ty) = ((player.pos.x / b).floor(), (player.pos.y / b).floor());
+        let tile = (tx >= 0.0 && ty >= 0.0).then(|| maze.get(ty as usize)?.get(tx as usize)).flatten();
+        let open_door = doors.iter().any(|d| d.state == DoorState::Open && (d.x as f32, d.y as f32) == (tx, ty));
+        if !tile.is_some_and(|&ch| is_open_tile(ch)) && !open_door {
+            return err(pos.line, pos.value_col, "el jugador está dentro de una pared o fuera del mapa");
+        }
+
+        Ok(Self {
+            level_path: save.require("level")?.str()?.to_string(),
+            level_name: save.require("name")?.str()?.to_string(),
+            coins: save.require("coins")?.non_negative()? as i32,
+            player,
+            weapon: weapon_kind(weapons.require("current")?)?,
+            ammo,
+            enemies,
+            doors,
+            pickups,
+            maze,
+            fog,
+        })
+    }
+
+    /// Reads only the `[save]` section at the top and rejects other save versions.
+    fn check_version(lines: &[&str]) -> Result<(), ParseError> {
+        let is_header = |l: &&str| l.trim_start().starts_with('[');
+        let Some(start) = lines.iter().position(&is_header) else {
+            return err(1, 1, "no es una partida guardada (falta [save])");
+        };
+        let end = lines[start + 1..].iter().position(is_header).map_or(lines.len(), |n| start + 1 + n);
+        let (tables, _) = parse_tables(&lines[..end], &["save"], &[], None)
+            .or_else(|_| err(start + 1, 1, "no es una partida guardada (falta [save])"))?;
+        let save = tables.first().ok_or((start + 1, 1, "no es una partida guardada (falta [save])".to_string()))?;
+        let version = save.require("version")?;
+        if version.int()? != SAVE_VERSION {
+            return err(version.line, version.value_col, format!(
+                "partida de una versión incompatible (v{}, este juego lee v{})", version.int()?, SAVE_VERSION));
+        }
+        Ok(())
+    }
+
+    /// The maze rows after `[map]` (at `map_line`, 1-based) and the fog rows after `[fog]`,
+    /// which must have the same size.
+    fn parse_grids(lines: &[&str], map_line: usize) -> Result<(Maze, Vec<Vec<f32>>), ParseError> {
+        let rest = &lines[map_line..];
+        let Some(fog_at) = rest.iter().position(|l| l.trim() == "[fog]") else {
+            return err(lines.len().max(1), 1, "falta la sección [fog]");
+        };
+        let maze: Maze = rest[..fog_at].iter().map(|l| l.chars().collect()).collect();
+        if maze.is_empty() || maze.iter().any(|row| row.len() != maze[0].len()) {
+            return err(map_line + 1, 1, "el mapa guardado está vacío o tiene filas de distinto largo");
+        }
+        check_map(&maze, map_line)?;
+        let fog_line = map_line + fog_at + 1;
+        let rows: Vec<&str> = rest[fog_at + 1..].iter().copied().filter(|l| !l.is_empty()).collect();
+        if rows.len() != maze.len() {
+            return err(fog_line, 1, format!("[fog] tiene {} filas y el mapa {}", rows.len(), maze.len()));
+        }
+        let mut fog = Vec::new();
+        for (y, row) in rows.iter().enumerate() {
+            let values: Option<Vec<f32>> = row.chars().map(|c| c.to_digit(16).map(|d| d as f32 / 15.0)).collect();
+            match values {
+                Some(values) if values.len() == maze[0].len() => fog.push(values),
+                _ => return err(fog_line + y + 1, 1, "fila de [fog] no válida (una cifra de 0 a f por casilla)"),
+            }
+        }
+        Ok((maze, fog))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAVE: &str = "[save]\nversion = 1\nlevel = \"assets/maps/test.txt\"\nname = \"Prueba\"\ncoins = 2\n\n\
+        [player]\npos = [224.0, 96.0]\nangle = 1.5\nhealth = 80\narmor = 10\nscore = 30\nkeys = \"r\"\n\n\
+        [weapons]\ncurrent = \"shotgun\"\n\n\
+        [[ammo]]\nweapon = \"shotgun\"\nloaded = 2\nreserve = 10\n\n\
+        [[enemy]]\nkind = \"guard\"\npos = [288.0, 96.0]\nhealth = 50\nfacing = 3.1416\naware = true\nlast_known = [160.0, 96.0]\n\n\
+        [[door]]\nat = [3, 1]\nstate = \"open\"\nopen = 1.0\ntimer = 40\n\n\
+        [[pickup]]\nkind = \"coin\"\npos = [96.0, 96.0]\n\n\
+        [map]\nAAAAAA\nAP = A\nAAAAAA\n[fog]\n000000\n0fff80\n000000\n";
+
+    #[test]
+    fn round_trip() {
+        let save = SaveGame::parse(SAVE).unwrap();
+        assert_eq!(save.player.pos, Vector2::new(224.0, 96.0));
+        assert!(save.player.has_key(KeyColor::Red));
+        assert_eq!(save.weapon, WeaponKind::Shotgun);
+        assert_eq!(save.enemies[0].last_known, Vector2::new(160.0, 96.0));
+        assert_eq!((save.doors[0].x, save.doors[0].y, save.doors[0].state), (3, 1, DoorState::Open));
+        assert_eq!(save.fog[1][4], 8.0 / 15.0);
+
+        let text = save.to_text();
+        let again = SaveGame::parse(&text).unwrap();
+        assert_eq!(again.to_text(), text);
+    }
+
+    #[test]
+    fn other_versions_are_rejected() {
+        for version in ["2", "0", "\"1\""] {
+            let text = SAVE.replace("version = 1", &format!("version = {}", version));
+            let (line, column, _) = SaveGame::parse(&text).err().unwrap();
+            assert_eq!((line, column), (2, 11), "version = {}", version);
+        }
+        assert!(SaveGame::parse("[save]\nlevel = \"x\"\n").is_err());
+        assert!(SaveGame::parse("AAAA\nAPXA\nAAAA\n").is_err());
+    }
+
+    #[test]
+    fn grids_must_match_each_other_and_the_level() {
+        let (line, _, message) = SaveGame::parse(&SAVE.replace("0fff80\n", "0fff8\n")).err().unwrap();
+        assert_eq!((line, message.as_str()), (47, "fila de [fog] no válida (una cifra de 0 a f por casilla)"));
+        assert!(SaveGame::parse(&SAVE.replace("[fog]\n000000\n", "[fog]\n")).is_err());
+
+        let save = SaveGame::parse(SAVE).unwrap();
+        assert!(save.check_level(&save.maze).is_ok());
+        let mut wider = save.maze.clone();
+        wider.iter_mut().for_each(|row| row.push('A'));
+        assert!(save.check_level(&wider).is_err());
+        assert!(save.check_level(&save.maze[..2].to_vec()).is_err());
+    }
+
+    #[test]
+    fn map_and_positions_are_checked() {
+        let (line, column, _) = SaveGame::parse(&SAVE.replace("AP = A", "AP ?=A")).err().unwrap();
+        assert_eq!((line, column), (43, 4));
+        // Sin la puerta abierta, el jugador estaría dentro de ella
+        let (line, column, _) = SaveGame::parse(&SAVE.replace("state = \"open\"", "state = \"closing\"")).err().unwrap();
+        assert_eq!((line, column), (8, 7));
+        let (line, column, _) = SaveGame::parse(&SAVE.replace("at = [3, 1]", "at = [2, 1]")).err().unwrap();
+        assert_eq!((line, column), (32, 6));
+    }
+}
@@ -2,136 +2,291 @@ use raylib::prelude::*;
 use super::Screen;
 use crate::{
     player::Player,
-    maze::{Maze, load_maze},
+    doors::{DoorState, Doors, UseResult},
+    maze::{Maze, Surfaces},
+    archetypes::{ARCHETYPES_PATH, Archetypes},
+    config::{self, Action, Settings},
+    data_file::DataError,
+    level::{Level, NextLevel},
     ui, input,
+    framebuffer::Framebuffer,
     textures::TextureManager,
     raycaster,
     audio,
     sprites::EnemySystem,
+    pickups::{PickupKind, Pickups},
+    weapons::{FireResult, WeaponKind, Weapons},
+    save::{self, SaveGame, SavedDoor, SavedEnemy},
+    screens::pause::{PauseAction, PauseMenu},
 };
 
 pub struct GameScreen {
     maze: Maze,
+    doors: Doors,
+    surfaces: Surfaces, // texturas de suelo/techo por casilla
     player: Player,
     enemies: EnemySystem,
+    weapons: Weapons,
+    pickups: Pickups,
+    sounds: audio::Sounds, // efectos de sonido al recoger objetos
     level_path: String,
+    level_name: String,
+    settings: Settings, // configuración leída al empezar el nivel
     tex: TextureManager,
     zbuffer: Vec<f32>,
+    framebuffer: Framebuffer, // vista 3D a resolución / render_scale
     fog_of_war: crate::maze::FogOfWar, // Nuevo campo
+    fog_radius: f32, // radio de exploración en pixels
     music: Option<raylib::prelude::Music<'static>>,
     muted: bool,
     music_volume: f32,
-    render_scale: usize,
-    scale_cooldown: i32, // frames until next auto-scale decision
+    render_scale: usize, // 1 = resolución nativa
     paused: bool,
+    pause_menu: PauseMenu,
+    load_save: Option<SaveGame>, // partida elegida en el menú de pausa
     quit_to_menu: bool, // flag to return to menu from pause
     coins_collected: i32, // contador de monedas recolectadas
-    advance_to: Option<String>, // path of next level or "WIN"
+    required_coins: i32, // monedas para pasar de nivel
+    message: Option<(String, i32)>, // aviso en pantalla y frames restantes
+    pickup_flash: Option<(Color, i32)>, // destello al recoger un objeto y frames restantes
+    next_level: NextLevel,
+    advance_to: Option<NextLevel>, // set once the required coins are collected
     game_over: bool, // nuevo flag
 }
 
 impl GameScreen {
-    pub fn new(level_path: String) -> Self {
-        let maze = load_maze(&level_path);
-        let player = Player::new(Vector2::new(96.0, 96.0));
+    /// Loads the level and enemy files; a malformed one is reported instead of starting the game.
+    pub fn new(level_path: String) -> Result<Self, DataError> {
+        let level = Level::load(&level_path)?;
+        let maze = level.maze;
+        let doors = Doors::from_maze(&maze);
+        let surfaces = Surfaces::load(&level_path);
+        let settings = Settings::current();
+        let mut player = Player::new(level.player_start);
+        player.a = level.player_facing;
+        player.fov = settings.fov();
         let tex = TextureManager::from_assets().expect("load textures");
+        let archetypes = Archetypes::load(ARCHETYPES_PATH, |key| tex.image(key).is_some())?;
         
         // Crear fog of war
-        let fog_of_war = crate::maze::FogOfWar::new(
+        let mut fog_of_war = crate::maze::FogOfWar::new(
             maze[0].len(),
             maze.len()
         );
+        if !level.fog_enabled { fog_of_war.reveal_all(); }
         
-        // Spawn enemies - 20 or more per level
-        let mut enemies = EnemySystem::new();
-        let enemy_count = if level_path.contains("level1") {
-            20
-        } else if level_path.contains("level2") {
-            25
-        } else {
-            30 // level 3 has most enemies
-        };
-        enemies.spawn_from_maze(&maze, enemy_count);
+        // Enemigos declarados en el nivel y los extra al azar
+        let mut enemies = EnemySystem::new(archetypes);
+        for spawn in &level.enemies {
+            enemies.spawn(spawn.pos, spawn.kind);
+        }
+        enemies.spawn_from_maze(&maze, level.random_enemies, level.random_kind);
         
         let zbuffer = vec![f32::INFINITY; 2048];
-        // Empezar con render_scale 3 para balance entre calidad y FPS
-        let render_scale = 3usize; // Balance: no muy pixeleado pero buen FPS
-        let scale_cooldown = 120i32; // ~2 seconds at 60fps
-        // load music stream from global audio device (if possible)
-        let mut music = audio::load_music("assets/audios/dungeon_delver.mp3");
-        let default_volume = 0.5;
+        // El framebuffer se ajusta a la pantalla en el primer draw
+        let framebuffer = Framebuffer::new(1, 1);
+        // load music stream from global audio device (if the level has any)
+        let music = level.music.as_deref().and_then(audio::load_music);
         if let Some(m) = music.as_ref() {
-            m.set_volume(default_volume);
+            m.set_volume(settings.music_volume);
+            audio::play_music(m);
         }
-        let paused = false;
-        let quit_to_menu = false;
-        let coins_collected = 0;
-        let advance_to = None;
-        let game_over = false;
+        let sounds = audio::Sounds::load(&PickupKind::ALL.map(PickupKind::sound));
+        sounds.set_volume(settings.sfx_volume);
 
-        if let Some(mut m) = music {
-            audio::play_music(&m);
-            Self { 
-                maze, 
-                player, 
-                enemies, 
-                level_path, 
-                tex, 
-                zbuffer, 
-                fog_of_war, // Agregar aquí
-                music: Some(m), 
-                muted: false, 
-                music_volume: default_volume, 
-                render_scale, 
-                scale_cooldown, 
-                paused, 
-                quit_to_menu, 
-                coins_collected, 
-                advance_to, 
-                game_over 
-            }
-        } else {
-            Self { 
-                maze, 
-                player, 
-                enemies, 
-                level_path, 
-                tex, 
-                zbuffer, 
-                fog_of_war, // Agregar aquí también
-                music: None, 
-                muted: false, 
-                music_volume: default_volume, 
-                render_scale, 
-                scale_cooldown, 
-                paused, 
-                quit_to_menu, 
-                coins_collected, 
-                advance_to, 
-                game_over 
-            }
+        Ok(Self { 
+            maze, 
+            doors, 
+            surfaces, 
+            player, 
+            enemies, 
+            weapons: Weapons::new(),
+            pickups: Pickups::new(level.pickups),
+            sounds,
+            level_path, 
+            level_name: level.name.clone(),
+            tex, 
+            zbuffer, 
+            framebuffer, 
+            fog_of_war,
+            fog_radius: level.fog_radius,
+            music, 
+            muted: false, 
+            music_volume: settings.music_volume, 
+            render_scale: settings.render_scale, 
+            paused: false, 
+            pause_menu: PauseMenu::new(),
+            load_save: None,
+            quit_to_menu: false, 
+            coins_collected: 0, 
+            required_coins: level.required_coins,
+            // El nombre del nivel se muestra al empezar
+            message: (!level.name.is_empty()).then_some((level.name, 120)), 
+            pickup_flash: None,
+            next_level: level.next,
+            advance_to: None, 
+            game_over: false,
+            settings,
+        })
+    }
+
+    /// Restores a saved game: loads its level for the settings that aren't saved (music,
+    /// next level, floor textures) and replaces the state with the saved one.
+    pub fn from_save(save: SaveGame) -> Result<Self, DataError> {
+        let mut game = Self::new(save.level_path.clone())?;
+        save.check_level(&game.maze)?;
+        game.doors = Doors::from_maze(&save.maze);
+        game.maze = save.maze;
+        for door in save.doors {
+            game.doors.restore(&mut game.maze, door.x, door.y, door.state, door.open, door.timer);
+        }
+        game.fog_of_war = crate::maze::FogOfWar::from_explored(save.fog);
+        // El campo de visión es de la configuración, no de la partida
+        game.player = Player { fov: game.player.fov, ..save.player };
+        game.coins_collected = save.coins;
+        game.weapons.current = save.weapon;
+        game.weapons.ammo = save.ammo;
+        game.pickups = Pickups::new(save.pickups);
+        game.enemies.list.clear();
+        for saved in save.enemies {
+            game.enemies.spawn(saved.pos, saved.kind);
+            let enemy = game.enemies.list.last_mut().expect("just spawned");
+            enemy.health = saved.health.min(enemy.max_health);
+            enemy.facing = saved.facing;
+            if saved.aware { enemy.hear(saved.last_known); }
+        }
+        // Nadie puede quedar dentro de una puerta cerrada
+        game.doors.open_occupied(&mut game.maze, &game.player, &game.enemies.list);
+        game.message = Some(("Partida cargada".to_string(), 120));
+        Ok(game)
+    }
+
+    /// Current state as a save: the map keeps every door char and the doors that are not
+    /// closed are stored apart with their state.
+    fn to_save(&self) -> SaveGame {
+        let mut maze = self.maze.clone();
+        for door in self.doors.iter() {
+            maze[door.y][door.x] = door.ch;
+        }
+        let doors = self.doors.iter().filter(|d| d.state() != DoorState::Closed).map(|d| SavedDoor {
+            x: d.x,
+            y: d.y,
+            state: d.state(),
+            open: d.open,
+            timer: d.timer(),
+        }).collect();
+        SaveGame {
+            level_path: self.level_path.clone(),
+            level_name: self.level_name.clone(),
+            coins: self.coins_collected,
+            player: self.player,
+            weapon: self.weapons.current,
+            ammo: self.weapons.ammo,
+            enemies: self.enemies.list.iter().filter(|e| e.alive).map(|e| SavedEnemy {
+                kind: e.kind,
+                pos: e.pos,
+                health: e.health,
+                facing: e.facing,
+                aware: e.is_aware(),
+                last_known: e.last_known(),
+            }).collect(),
+            doors,
+            pickups: self.pickups.list.clone(),
+            maze,
+            fog: self.fog_of_war.explored().to_vec(),
         }
     }
 }
 
-impl Screen for GameScreen {
-    fn update(&mut self, rl: &RaylibHandle) {
-        // Toggle pause with ESC
-        if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
-            self.paused = !self.paused;
+/// Starts `level_path`, or goes back to the menu showing why the level could not be loaded.
+pub fn open_level(level_path: String) -> Box<dyn Screen> {
+    match GameScreen::new(level_path) {
+        Ok(game) => Box::new(game),
+        Err(e) => {
+            eprintln!("{}", e);
+            Box::new(crate::screens::menu::MenuScreen::with_error(e.to_string()))
+        }
+    }
+}
+
+/// Resumes a saved game, or goes back to the menu showing why it could not be restored.
+pub fn open_save(save: SaveGame) -> Box<dyn Screen> {
+    match GameScreen::from_save(save) {
+        Ok(game) => Box::new(game),
+        Err(e) => {
+            eprintln!("{}", e);
+            Box::new(crate::screens::menu::MenuScreen::with_error(e.to_string()))
         }
+    }
+}
 
-        // Handle quit to menu when paused
-        if self.paused && rl.is_key_pressed(KeyboardKey::KEY_Q) {
-            self.quit_to_menu = true;
+impl Screen for GameScreen {
+    fn update(&mut self, rl: &RaylibHandle) {
+        // Pause with ESC; while paused the pause menu takes the input
+        if self.paused {
+            match self.pause_menu.update(rl) {
+                PauseAction::None => {}
+                PauseAction::Resume => self.paused = false,
+                PauseAction::Quit => self.quit_to_menu = true,
+                PauseAction::Save(slot) => {
+                    let text = match self.to_save().write(save::slot_path(slot)) {
+                        Ok(()) => format!("Partida guardada en la ranura {}", slot + 1),
+                        Err(e) => format!("No se pudo guardar: {}", e),
+                    };
+                    self.pause_menu.notify(text);
+                }
+                PauseAction::Load(slot) => {
+                    let path = save::slot_path(slot);
+                    if !path.is_file() {
+                        self.pause_menu.notify(format!("La ranura {} está vacía", slot + 1));
+                    } else {
+                        match SaveGame::load(&path) {
+                            Ok(game) => self.load_save = Some(game),
+                            Err(e) => self.pause_menu.notify(e.to_string()),
+                        }
+                    }
+                }
+            }
+        } else if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) && self.player.is_alive() {
+            self.paused = true;
+            self.pause_menu = PauseMenu::new();
         }
 
         // Only process game input when not paused and alive
         if !self.paused && self.player.is_alive() {
-            input::process(rl, &mut self.player, &self.maze);
+            // Weapons: 1-4 switch, R reloads, SPACE fires (held for automatic weapons).
+            // Fire before moving so hitscan uses the zbuffer of the frame on screen.
+            for (action, kind) in Action::WEAPONS.into_iter().zip(WeaponKind::ALL) {
+                if self.settings.pressed(rl, action) { self.weapons.select(kind); }
+            }
+            if self.settings.pressed(rl, Action::Reload) {
+                self.weapons.start_reload();
+            }
+            let pressed = self.settings.pressed(rl, Action::Fire);
+            let held = self.settings.down(rl, Action::Fire);
+            match self.weapons.fire(pressed, held, &self.player, &mut self.enemies, &self.zbuffer) {
+                FireResult::Fired { hits } => {
+                    self.enemies.hear_gunshot(self.player.pos);
+                    if hits > 0 {
+                        self.player.trigger_shot_effect(); // Activar efecto visual
+                    }
+                }
+                FireResult::Empty => self.message = Some(("Sin munición".to_string(), 60)),
+                FireResult::Nothing => {}
+            }
+            
+            input::process(rl, &mut self.player, &self.maze, &self.settings);
             
             // Actualizar fog of war
-            self.fog_of_war.update(self.player.pos, &self.maze, 400.0); // Radio de visión 400 pixeles
+            self.fog_of_war.update(self.player.pos, &self.maze, self.fog_radius);
+            
+            // Use key (E): open the door in front
+            if self.settings.pressed(rl, Action::Use) {
+                if let UseResult::Locked(color) = self.doors.try_open(&self.player, &self.maze) {
+                    self.message = Some((format!("Necesitas la llave {}", color.name()), 120));
+                }
+            }
+            self.doors.update(&mut self.maze, &self.player, &self.enemies.list);
             
             // Update shot effect timer
             self.player.update_shot_effect();
@@ -139,54 +294,35 @@ impl Screen for GameScreen {
             // Update enemies (now they can shoot at player)
             self.enemies.update(&mut self.player, &self.maze);
             
-            // Shoot enemies with SPACE
-            if rl.is_key_pressed(KeyboardKey::KEY_SPACE) {
-                if let Some(enemy) = self.enemies.get_enemy_at_crosshair(&self.player, 500.0) {
-                    enemy.take_damage(34); // Kill in 3 shots
-                    self.player.trigger_shot_effect(); // Activar efecto visual
-                }
-            }
-            
-            // Check for coin collection
-            let block_size = crate::maze::block_size() as f32;
-            let tile_x = (self.player.pos.x / block_size) as usize;
-            let tile_y = (self.player.pos.y / block_size) as usize;
+            // Recargas, cambios de arma y proyectiles en vuelo
+            self.weapons.update(&mut self.enemies, &self.maze);
             
-            // Check if player is on a coin tile
-            if tile_y < self.maze.len() && tile_x < self.maze[tile_y].len() {
-                let tile_char = self.maze[tile_y][tile_x];
-                if tile_char == 'X' || tile_char == 'x' {
-                    // Collect the coin
-                    self.maze[tile_y][tile_x] = ' ';
+            // Pick up the items within reach
+            for kind in self.pickups.collect(&mut self.player, &mut self.weapons) {
+                self.sounds.play(kind.sound());
+                self.pickup_flash = Some((kind.color(), 10));
+                if let Some(text) = kind.message() {
+                    self.message = Some((text, 120));
+                }
+                if kind == PickupKind::Coin {
                     self.coins_collected += 1;
-                    // If player collected 10 coins, prepare to advance
-                    if self.coins_collected >= 10 && self.advance_to.is_none() {
-                        // Determine next level path (simple sequence)
-                        let next = if self.level_path.contains("level1") {
-                            "assets/maps/level2.txt".into()
-                        } else if self.level_path.contains("level2") {
-                            "assets/maps/level3.txt".into()
-                        } else {
-                            "WIN".into()
-                        };
-                        self.advance_to = Some(next);
+                    // With enough coins, advance to the level's `next`
+                    if self.coins_collected >= self.required_coins && self.advance_to.is_none() {
+                        self.advance_to = Some(self.next_level.clone());
                     }
                 }
             }
             
             // manual override for render scale: Z = finer (lower scale), X = coarser (higher scale)
-            if rl.is_key_pressed(KeyboardKey::KEY_Z) {
+            if self.settings.pressed(rl, Action::QualityUp) {
                 if self.render_scale > 1 { self.render_scale -= 1; }
             }
-            if rl.is_key_pressed(KeyboardKey::KEY_X) {
-                if self.render_scale < 6 { self.render_scale += 1; }
+            if self.settings.pressed(rl, Action::QualityDown) {
+                if self.render_scale < config::RENDER_SCALE_MAX { self.render_scale += 1; }
             }
 
-            // decrement cooldown used for automatic scaling decisions
-            if self.scale_cooldown > 0 { self.scale_cooldown -= 1; }
-            
             // Toggle mute with M
-            if rl.is_key_pressed(KeyboardKey::KEY_M) {
+            if self.settings.pressed(rl, Action::Mute) {
                 self.muted = !self.muted;
                 if let Some(m) = &self.music {
                     if self.muted { m.set_volume(0.0); } else { m.set_volume(self.music_volume); }
@@ -194,6 +330,15 @@ impl Screen for GameScreen {
             }
         }
         
+        if let Some((_, frames)) = &mut self.message {
+            *frames -= 1;
+            if *frames <= 0 { self.message = None; }
+        }
+        if let Some((_, frames)) = &mut self.pickup_flash {
+            *frames -= 1;
+            if *frames <= 0 { self.pickup_flash = None; }
+        }
+
         // Check if player died
         if !self.player.is_alive() && !self.game_over {
             self.game_over = true;
@@ -210,6 +355,10 @@ impl Screen for GameScreen {
     }
 
     fn next(&mut self) -> Option<Box<dyn Screen>> {
+        if let Some(game) = self.load_save.take() {
+            return Some(open_save(game));
+        }
+        
         if self.quit_to_menu {
             self.quit_to_menu = false;
             return Some(Box::new(crate::screens::menu::MenuScreen::default()));
@@ -221,67 +370,63 @@ impl Screen for GameScreen {
         }
         
         // Advance to next level if flagged
-        if let Some(next) = self.advance_to.take() {
-            if next == "WIN" {
-                return Some(Box::new(crate::screens::win::WinScreen::new()));
-            } else {
-                return Some(Box::new(crate::screens::game::GameScreen::new(next)));
-            }
+        match self.advance_to.take() {
+            Some(NextLevel::Win) => return Some(Box::new(crate::screens::win::WinScreen::new())),
+            Some(NextLevel::Level(next)) => return Some(open_level(next)),
+            None => {}
         }
         None
     }
 
-    fn draw_raylib(&mut self, d: &mut RaylibDrawHandle) {
-        // make sure zbuffer matches current screen width divided by render_scale
+    fn draw_raylib(&mut self, d: &mut RaylibDrawHandle, thread: &RaylibThread) {
+        // framebuffer and zbuffer follow the screen size divided by render_scale
         let w = d.get_screen_width().max(1) as usize;
-        let scaled_w = (w + self.render_scale - 1) / self.render_scale; // ceil division
+        let h = d.get_screen_height().max(1) as usize;
+        let scaled_w = w.div_ceil(self.render_scale);
+        let scaled_h = h.div_ceil(self.render_scale);
+        self.framebuffer.resize(scaled_w as u32, scaled_h as u32);
         if self.zbuffer.len() != scaled_w { self.zbuffer.resize(scaled_w, f32::INFINITY); }
 
-        // 3D world (pass render_scale to let renderer draw blocks instead of single pixels)
-        raycaster::draw_world(d, &self.maze, &self.player, &mut self.zbuffer, &self.tex, self.render_scale);
-        
-        // Draw coins as sprites
-        raycaster::draw_coins(d, &self.maze, &self.player, &self.zbuffer, &self.tex, self.render_scale);
-        
-        // Draw enemies as sprites (solo los visibles)
-        raycaster::draw_enemies(d, &self.enemies.list, &self.player, &self.zbuffer, &self.tex, self.render_scale, &self.fog_of_war);
+        // 3D world, pickups and enemies (solo los visibles) go to the framebuffer
+        raycaster::draw_world(&mut self.framebuffer, &self.maze, &self.doors, &self.player, &mut self.zbuffer, &self.tex, &self.surfaces);
+        raycaster::draw_pickups(&mut self.framebuffer, &self.pickups.list, &self.player, &self.zbuffer, &self.tex);
+        raycaster::draw_enemies(&mut self.framebuffer, &self.enemies, &self.player, &self.zbuffer, &self.tex, &self.fog_of_war);
+        raycaster::draw_projectiles(&mut self.framebuffer, &self.weapons.projectiles, &self.player, &self.zbuffer, &self.tex);
+
+        // One texture upload and one draw call for the whole 3D view
+        self.framebuffer.swap_buffers(d, thread);
         
         // HUD (FPS/HP/minimap)
         let fps_i = d.get_fps();
-        let fps = fps_i as f32;
         // Draw HUD on the top-right to avoid overlapping the minimap
         let sw = d.get_screen_width();
         let hud_x = sw - 300;
         d.draw_text(&format!("FPS: {}", fps_i), hud_x, 10, 20, Color::LIGHTGRAY);
         // music status
         let music_status = if self.muted { "Muted".to_string() } else { format!("On {}%", (self.music_volume * 100.0) as i32) };
-        d.draw_text(&format!("Music: {} (M to toggle)", music_status), hud_x, 30, 20, Color::LIGHTGRAY);
-        d.draw_text(&format!("Render scale: {} (Z/X to change)", self.render_scale), hud_x, 50, 20, Color::LIGHTGRAY);
+        let key = |action| config::key_name(self.settings.key(action));
+        d.draw_text(&format!("Music: {} ({} to toggle)", music_status, key(Action::Mute)), hud_x, 30, 20, Color::LIGHTGRAY);
+        d.draw_text(&format!("Render scale: {} ({}/{} to change)", self.render_scale, key(Action::QualityUp), key(Action::QualityDown)), hud_x, 50, 20, Color::LIGHTGRAY);
         // Coin counter
-        d.draw_text(&format!("Coins: {}", self.coins_collected), hud_x, 70, 20, Color::GOLD);
-        
-        // === MODIFICADO: HUD de juego con pistol view ===
-        ui::draw_game_hud(d, &self.player, &self.tex);
+        d.draw_text(&format!("Coins: {}/{}", self.coins_collected, self.required_coins), hud_x, 70, 20, Color::GOLD);
+        ui::draw_keys_hud(d, &self.player, hud_x, 95);
+        d.draw_text(&format!("Treasure: {}", self.player.score), hud_x, 120, 20, Color::GOLD);
+        if let Some((text, _)) = &self.message {
+            let tw = d.measure_text(text, 24);
+            d.draw_text(text, (sw - tw) / 2, d.get_screen_height() / 2 + 60, 24, Color::WHITE);
+        }
         
-        // Auto-adjust render scale más conservador para mantener calidad
-        if self.scale_cooldown <= 0 {
-            if fps < 58.0 && self.render_scale < 8 {
-                self.render_scale += 1;
-                self.scale_cooldown = 60;
-            } else if fps > 62.0 && self.render_scale > 1 {
-                self.render_scale -= 1;
-                self.scale_cooldown = 120; // Más lento al bajar para mantener calidad
-            }
+        // === MODIFICADO: HUD de juego con vista del arma ===
+        ui::draw_game_hud(d, &self.player, &self.weapons, &self.tex);
+        if let Some((color, frames)) = self.pickup_flash {
+            ui::draw_pickup_flash(d, color, frames as f32 / 10.0);
         }
-        ui::draw_minimap(d, &self.maze, &self.player, Some(&self.fog_of_war)); // Pasar fog of war al minimap
         
-        // Draw pause overlay if paused
+        ui::draw_minimap(d, &self.maze, &self.pickups.list, &self.player, Some(&self.fog_of_war)); // Pasar fog of war al minimap
+        
+        // Draw pause menu if paused
         if self.paused {
-            let w = d.get_screen_width();
-            let h = d.get_screen_height();
-            d.draw_rectangle(0, 0, w, h, Color::new(0, 0, 0, 150));
-            d.draw_text("Paused", w/2 - 60, h/2 - 40, 40, Color::WHITE);
-            d.draw_text("Press ESC to resume, Q to quit to menu", w/2 - 180, h/2 + 10, 20, Color::LIGHTGRAY);
+            self.pause_menu.draw(d);
         }
     }
 
@@ -4,6 +4,7 @@ use super::Screen;
 pub struct GameOverScreen {
     level_path: String, // Para reiniciar el nivel
     restart: bool,
+    load: bool,
     quit_to_menu: bool,
 }
 
@@ -12,6 +13,7 @@ impl GameOverScreen {
         Self {
             level_path,
             restart: false,
+            load: false,
             quit_to_menu: false,
         }
     }
@@ -24,6 +26,11 @@ impl Screen for GameOverScreen {
             self.restart = true;
         }
         
+        // Press L to pick a saved game
+        if rl.is_key_pressed(KeyboardKey::KEY_L) {
+            self.load = true;
+        }
+        
         // Press ESC to go back to menu
         if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
             self.quit_to_menu = true;
@@ -33,7 +40,12 @@ impl Screen for GameOverScreen {
     fn next(&mut self) -> Option<Box<dyn Screen>> {
         if self.restart {
             self.restart = false;
-            return Some(Box::new(crate::screens::game::GameScreen::new(self.level_path.clone())));
+            return Some(crate::screens::game::open_level(self.level_path.clone()));
+        }
+        
+        if self.load {
+            self.load = false;
+            return Some(Box::new(crate::screens::load::LoadScreen::default()));
         }
         
         if self.quit_to_menu {
@@ -44,7 +56,7 @@ impl Screen for GameOverScreen {
         None
     }
 
-    fn draw_raylib(&mut self, d: &mut raylib::prelude::RaylibDrawHandle) {
+    fn draw_raylib(&mut self, d: &mut raylib::prelude::RaylibDrawHandle, _thread: &raylib::prelude::RaylibThread) {
         let w = d.get_screen_width();
         let h = d.get_screen_height();
         
@@ -87,19 +99,30 @@ impl Screen for GameOverScreen {
             Color::WHITE
         );
         
+        let load_text = "Press L to load a saved game";
+        let load_size = 25;
+        let load_width = d.measure_text(load_text, load_size);
+        d.draw_text(
+            load_text,
+            w / 2 - load_width / 2,
+            h / 2 + 80,
+            load_size,
+            Color::WHITE
+        );
+        
         let menu_text = "Press ESC to return to menu";
         let menu_size = 25;
         let menu_width = d.measure_text(menu_text, menu_size);
         d.draw_text(
             menu_text,
             w / 2 - menu_width / 2,
-            h / 2 + 80,
+            h / 2 + 120,
             menu_size,
             Color::LIGHTGRAY
         );
         
         // Skull decoration (simple ASCII art)
-        let skull_y = h / 2 + 150;
+        let skull_y = h / 2 + 170;
         d.draw_text("☠", w / 2 - 30, skull_y, 60, Color::new(150, 0, 0, 255));
     }
 
@@ -0,0 +1,77 @@
+use raylib::prelude::*;
+use super::Screen;
+use crate::{save::{self, SaveGame}, ui};
+
+/// Main menu's "Load Game": pick one of the save slots.
+pub struct LoadScreen {
+    selected: usize,
+    slots: Vec<Option<Result<String, String>>>, // resumen de cada ranura
+    error: Option<String>,
+    load: Option<SaveGame>,
+    back: bool,
+}
+
+impl Default for LoadScreen {
+    fn default() -> Self {
+        Self { selected: 0, slots: save::slot_summaries(), error: None, load: None, back: false }
+    }
+}
+
+impl Screen for LoadScreen {
+    fn update(&mut self, rl: &RaylibHandle) {
+        if rl.is_key_pressed(KeyboardKey::KEY_DOWN) {
+            self.selected = (self.selected + 1) % save::SLOTS;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_UP) {
+            self.selected = (self.selected + save::SLOTS - 1) % save::SLOTS;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+            self.back = true;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+            let path = save::slot_path(self.selected);
+            if !path.is_file() {
+                self.error = Some(format!("La ranura {} está vacía", self.selected + 1));
+                return;
+            }
+            match SaveGame::load(&path) {
+                Ok(game) => self.load = Some(game),
+                Err(e) => self.error = Some(e.to_string()),
+            }
+        }
+    }
+
+    fn next(&mut self) -> Option<Box<dyn Screen>> {
+        if let Some(game) = self.load.take() {
+            return Some(crate::screens::game::open_save(game));
+        }
+        if self.back {
+            return Some(Box::new(crate::screens::menu::MenuScreen::default()));
+        }
+        None
+    }
+
+    fn draw_raylib(&mut self, d: &mut RaylibDrawHandle, _thread: &RaylibThread) {
+        let w = d.get_screen_width();
+        let h = d.get_screen_height();
+        d.clear_background(Color::new(20, 20, 40, 255));
+
+        let title = "LOAD GAME";
+        let title_width = d.measure_text(title, 60);
+        d.draw_text(title, w / 2 - title_width / 2, h / 4, 60, Color::GOLD);
+
+        ui::draw_slot_list(d, "Choose a slot", &self.slots, self.selected, h / 2 - 60);
+
+        if let Some(error) = &self.error {
+            let err_width = d.measure_text(error, 20);
+            d.draw_text(error, w / 2 - err_width / 2, h - 130, 20, Color::RED);
+        }
+        let instructions = "ENTER to load, ESC to go back";
+        let inst_width = d.measure_text(instructions, 20);
+        d.draw_text(instructions, w / 2 - inst_width / 2, h - 80, 20, Color::LIGHTGRAY);
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
@@ -7,6 +7,7 @@ pub struct MenuScreen {
     start: bool,
     quit: bool,
     tex: Option<TextureManager>,
+    error: Option<String>, // por qué no se pudo cargar el último nivel
 }
 
 impl Default for MenuScreen {
@@ -17,6 +18,7 @@ impl Default for MenuScreen {
             start: false,
             quit: false,
             tex,
+            error: None,
         }
     }
 }
@@ -24,13 +26,13 @@ impl Default for MenuScreen {
 impl Screen for MenuScreen {
     fn update(&mut self, rl: &raylib::RaylibHandle) {
         if rl.is_key_pressed(KeyboardKey::KEY_DOWN) {
-            self.selected = (self.selected + 1) % 4; // Ahora 4 opciones
+            self.selected = (self.selected + 1) % 6; // Ahora 6 opciones
         }
         if rl.is_key_pressed(KeyboardKey::KEY_UP) {
-            self.selected = if self.selected == 0 { 3 } else { self.selected - 1 };
+            self.selected = if self.selected == 0 { 5 } else { self.selected - 1 };
         }
         if rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
-            if self.selected == 3 {
+            if self.selected == 5 {
                 self.quit = true;
             } else {
                 self.start = true;
@@ -40,18 +42,24 @@ impl Screen for MenuScreen {
 
     fn next(&mut self) -> Option<Box<dyn Screen>> {
         if self.start {
+            if self.selected == 3 {
+                return Some(Box::new(crate::screens::load::LoadScreen::default()));
+            }
+            if self.selected == 4 {
+                return Some(Box::new(crate::screens::options::OptionsScreen::default()));
+            }
             let level = match self.selected {
                 0 => "assets/maps/level1.txt",
                 1 => "assets/maps/level2.txt",
                 2 => "assets/maps/level3.txt",
                 _ => "assets/maps/level1.txt",
             };
-            return Some(Box::new(crate::screens::game::GameScreen::new(level.into())));
+            return Some(crate::screens::game::open_level(level.into()));
         }
         None
     }
 
-    fn draw_raylib(&mut self, d: &mut raylib::prelude::RaylibDrawHandle) {
+    fn draw_raylib(&mut self, d: &mut raylib::prelude::RaylibDrawHandle, _thread: &raylib::prelude::RaylibThread) {
         let screen_w = d.get_screen_width();
         let screen_h = d.get_screen_height();
         
@@ -109,9 +117,9 @@ impl Screen for MenuScreen {
         );
         
         // Menu options
-        let options = ["Level 1", "Level 2", "Level 3", "Exit"];
-        let start_y = screen_h / 2;
-        let spacing = 60;
+        let options = ["Level 1", "Level 2", "Level 3", "Load Game", "Options", "Exit"];
+        let start_y = screen_h / 2 - 40;
+        let spacing = 50;
         
         for (i, option) in options.iter().enumerate() {
             let size = 40;
@@ -137,6 +145,14 @@ impl Screen for MenuScreen {
             );
         }
         
+        // Error del último nivel (archivo:línea:columna: mensaje)
+        if let Some(error) = &self.error {
+            let err_size = 20;
+            let err_width = d.measure_text(error, err_size);
+            d.draw_rectangle(screen_w / 2 - err_width / 2 - 10, screen_h - 130, err_width + 20, 34, Color::new(0, 0, 0, 180));
+            d.draw_text(error, screen_w / 2 - err_width / 2, screen_h - 123, err_size, Color::RED);
+        }
+
         // Instructions
         let instructions = "Use Arrow Keys to navigate, ENTER to select";
         let inst_size = 20;
@@ -156,6 +172,11 @@ impl Screen for MenuScreen {
 }
 
 impl MenuScreen {
+    /// Menu showing an error, e.g. a level file that failed to load.
+    pub fn with_error(message: String) -> Self {
+        Self { error: Some(message), ..Self::default() }
+    }
+
     pub fn should_quit(&self) -> bool {
         self.quit
     }
@@ -3,7 +3,7 @@ use raylib::prelude::*;
 pub trait Screen {
     fn update(&mut self, rl: &RaylibHandle);
     fn next(&mut self) -> Option<Box<dyn Screen>>;
-    fn draw_raylib(&mut self, d: &mut RaylibDrawHandle);
+    fn draw_raylib(&mut self, d: &mut RaylibDrawHandle, thread: &RaylibThread);
     fn as_any(&self) -> &dyn std::any::Any;
 }
 
@@ -11,3 +11,6 @@ pub mod menu;
 pub mod game;
 pub mod win;
 pub mod gameover;
+pub mod pause;
+pub mod load;
+pub mod options;
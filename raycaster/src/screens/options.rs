@@ -0,0 +1,147 @@
+use raylib::prelude::*;
+use super::Screen;
+use crate::config::{self, Action, Settings, SETTINGS_PATH};
+
+#[derive(Clone, Copy, PartialEq)]
+enum Row {
+    Fov,
+    Sensitivity,
+    MusicVolume,
+    SfxVolume,
+    RenderScale,
+    Key(Action),
+    Reset,
+    Back,
+}
+
+/// Main menu's "Options": change the settings with LEFT/RIGHT and rebind actions with
+/// ENTER. They are written to the settings file when leaving.
+pub struct OptionsScreen {
+    settings: Settings,
+    rows: Vec<Row>,
+    selected: usize,
+    waiting: bool, // esperando la tecla nueva para la acción seleccionada
+    error: Option<String>,
+    back: bool,
+}
+
+impl Default for OptionsScreen {
+    fn default() -> Self {
+        // Un archivo roto se muestra y se sustituye al salir
+        let (settings, error) = match Settings::load(SETTINGS_PATH) {
+            Ok(settings) => (settings, None),
+            Err(e) => (Settings::default(), Some(e.to_string())),
+        };
+        let mut rows = vec![Row::Fov, Row::Sensitivity, Row::MusicVolume, Row::SfxVolume, Row::RenderScale];
+        rows.extend(Action::ALL.map(Row::Key));
+        rows.extend([Row::Reset, Row::Back]);
+        Self { settings, rows, selected: 0, waiting: false, error, back: false }
+    }
+}
+
+impl OptionsScreen {
+    /// Move the selected setting one step down (`-1`) or up (`1`).
+    fn adjust(&mut self, step: i32) {
+        let s = &mut self.settings;
+        let tenth = |v: f32, min: f32, max: f32| ((v * 10.0).round() / 10.0 + step as f32 * 0.1).clamp(min, max);
+        match self.rows[self.selected] {
+            Row::Fov => s.fov_degrees = (s.fov_degrees + step * 5).clamp(config::FOV_RANGE.0, config::FOV_RANGE.1),
+            Row::Sensitivity => s.sensitivity = tenth(s.sensitivity, config::SENSITIVITY_RANGE.0, config::SENSITIVITY_RANGE.1),
+            Row::MusicVolume => s.music_volume = tenth(s.music_volume, 0.0, 1.0),
+            Row::SfxVolume => s.sfx_volume = tenth(s.sfx_volume, 0.0, 1.0),
+            Row::RenderScale => s.render_scale = (s.render_scale as i32 + step).clamp(1, config::RENDER_SCALE_MAX as i32) as usize,
+            _ => {}
+        }
+    }
+
+    fn row_text(&self, row: Row) -> (String, String) {
+        let s = &self.settings;
+        match row {
+            Row::Fov => ("Field of view".into(), format!("{} deg", s.fov_degrees)),
+            Row::Sensitivity => ("Turn sensitivity".into(), format!("{:.1}x", s.sensitivity)),
+            Row::MusicVolume => ("Music volume".into(), format!("{}%", (s.music_volume * 100.0).round() as i32)),
+            Row::SfxVolume => ("Effects volume".into(), format!("{}%", (s.sfx_volume * 100.0).round() as i32)),
+            Row::RenderScale => ("Render scale".into(), if s.render_scale == 1 { "1 (native)".into() } else { s.render_scale.to_string() }),
+            Row::Key(action) if self.waiting => (action.label().into(), "press a key...".into()),
+            Row::Key(action) => (action.label().into(), config::key_name(s.key(action)).into()),
+            Row::Reset => ("Reset to defaults".into(), String::new()),
+            Row::Back => ("Save and back".into(), String::new()),
+        }
+    }
+}
+
+impl Screen for OptionsScreen {
+    fn update(&mut self, rl: &RaylibHandle) {
+        // Rebinding: the next bindable key goes to the action, ESC cancels
+        if self.waiting {
+            if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+                self.waiting = false;
+            } else if let Some((key, _)) = config::BINDABLE_KEYS.iter().find(|(k, _)| rl.is_key_pressed(*k)) {
+                if let Row::Key(action) = self.rows[self.selected] { self.settings.bind(action, *key); }
+                self.waiting = false;
+            }
+            return;
+        }
+
+        let count = self.rows.len();
+        if rl.is_key_pressed(KeyboardKey::KEY_DOWN) { self.selected = (self.selected + 1) % count; }
+        if rl.is_key_pressed(KeyboardKey::KEY_UP) { self.selected = (self.selected + count - 1) % count; }
+        if rl.is_key_pressed(KeyboardKey::KEY_LEFT) { self.adjust(-1); }
+        if rl.is_key_pressed(KeyboardKey::KEY_RIGHT) { self.adjust(1); }
+        if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) { self.back = true; }
+        if rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+            match self.rows[self.selected] {
+                Row::Key(_) => self.waiting = true,
+                Row::Reset => self.settings = Settings::default(),
+                Row::Back => self.back = true,
+                _ => {}
+            }
+        }
+    }
+
+    fn next(&mut self) -> Option<Box<dyn Screen>> {
+        if !self.back { return None; }
+        let menu = match self.settings.write(SETTINGS_PATH) {
+            Ok(()) => crate::screens::menu::MenuScreen::default(),
+            Err(e) => crate::screens::menu::MenuScreen::with_error(format!("No se pudo guardar {}: {}", SETTINGS_PATH, e)),
+        };
+        Some(Box::new(menu))
+    }
+
+    fn draw_raylib(&mut self, d: &mut RaylibDrawHandle, _thread: &RaylibThread) {
+        let w = d.get_screen_width();
+        let h = d.get_screen_height();
+        d.clear_background(Color::new(20, 20, 40, 255));
+
+        let title = "OPTIONS";
+        let title_width = d.measure_text(title, 50);
+        d.draw_text(title, w / 2 - title_width / 2, 30, 50, Color::GOLD);
+
+        // Ajustes, una fila por acción y los botones, en una sola lista
+        for (i, row) in self.rows.iter().enumerate() {
+            let y = 100 + i as i32 * 24;
+            let (label, value) = self.row_text(*row);
+            let color = if i == self.selected { Color::YELLOW } else { Color::WHITE };
+            if i == self.selected { d.draw_text(">", w / 2 - 270, y, 20, Color::YELLOW); }
+            d.draw_text(&label, w / 2 - 240, y, 20, color);
+            let value_color = if i == self.selected && self.waiting { Color::ORANGE } else { Color::LIGHTGRAY };
+            d.draw_text(&value, w / 2 + 80, y, 20, value_color);
+        }
+
+        if let Some(error) = &self.error {
+            let err_width = d.measure_text(error, 20);
+            d.draw_text(error, w / 2 - err_width / 2, h - 70, 20, Color::RED);
+        }
+        let instructions = if self.waiting {
+            "Press the new key, ESC to cancel"
+        } else {
+            "LEFT/RIGHT to change, ENTER to rebind, ESC to save and go back"
+        };
+        let inst_width = d.measure_text(instructions, 20);
+        d.draw_text(instructions, w / 2 - inst_width / 2, h - 40, 20, Color::LIGHTGRAY);
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
@@ -1,33 +1,105 @@
 use raylib::prelude::*;
-use super::Screen;
+use crate::{save, ui};
 
-#[derive(Default)]
-pub struct PauseScreen {
-    resume: bool,
-    quit: bool,
+/// What the player picked in the pause menu this frame.
+pub enum PauseAction {
+    None,
+    Resume,
+    Save(usize), // ranura
+    Load(usize),
+    Quit,
 }
 
-impl Screen for PauseScreen {
-    fn update(&mut self, rl: &RaylibHandle) {
-        if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) { self.resume = true; }
-        if rl.is_key_pressed(KeyboardKey::KEY_Q) { self.quit = true; }
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Page {
+    Main,
+    SaveSlots,
+    LoadSlots,
+}
+
+const OPTIONS: [&str; 4] = ["Resume", "Save Game", "Load Game", "Quit to Menu"];
+
+/// Pause overlay drawn on top of the game: resume, save to a slot, load a slot or quit.
+pub struct PauseMenu {
+    selected: usize,
+    page: Page,
+    slot: usize,
+    slots: Vec<Option<Result<String, String>>>, // resumen de cada ranura
+    notice: Option<String>, // resultado de guardar o cargar
+}
+
+impl PauseMenu {
+    pub fn new() -> Self {
+        Self { selected: 0, page: Page::Main, slot: 0, slots: save::slot_summaries(), notice: None }
+    }
+
+    /// Show the result of a save or load and go back to the main page.
+    pub fn notify(&mut self, text: String) {
+        self.notice = Some(text);
+        self.slots = save::slot_summaries();
+        self.page = Page::Main;
     }
-    fn draw_raylib(&mut self, d: &mut RaylibDrawHandle) {
+
+    pub fn update(&mut self, rl: &RaylibHandle) -> PauseAction {
+        let count = if self.page == Page::Main { OPTIONS.len() } else { save::SLOTS };
+        let cursor = if self.page == Page::Main { &mut self.selected } else { &mut self.slot };
+        if rl.is_key_pressed(KeyboardKey::KEY_DOWN) { *cursor = (*cursor + 1) % count; }
+        if rl.is_key_pressed(KeyboardKey::KEY_UP) { *cursor = (*cursor + count - 1) % count; }
+
+        match self.page {
+            Page::Main => {
+                if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) { return PauseAction::Resume; }
+                if rl.is_key_pressed(KeyboardKey::KEY_Q) { return PauseAction::Quit; }
+                if rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+                    self.notice = None;
+                    match self.selected {
+                        0 => return PauseAction::Resume,
+                        1 => self.page = Page::SaveSlots,
+                        2 => self.page = Page::LoadSlots,
+                        _ => return PauseAction::Quit,
+                    }
+                }
+            }
+            page => {
+                if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) { self.page = Page::Main; }
+                if rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+                    if page == Page::SaveSlots { return PauseAction::Save(self.slot); }
+                    return PauseAction::Load(self.slot);
+                }
+            }
+        }
+        PauseAction::None
+    }
+
+    pub fn draw(&self, d: &mut RaylibDrawHandle) {
         let w = d.get_screen_width();
         let h = d.get_screen_height();
-        d.draw_rectangle(0, 0, w, h, Color::new(0,0,0,150));
-        d.draw_text("Paused", w/2 - 60, h/2 - 40, 40, Color::WHITE);
-        d.draw_text("Press ESC to resume, Q to quit to menu", w/2 - 180, h/2 + 10, 20, Color::LIGHTGRAY);
+        d.draw_rectangle(0, 0, w, h, Color::new(0, 0, 0, 150));
+        d.draw_text("Paused", w/2 - 60, h/2 - 160, 40, Color::WHITE);
+
+        match self.page {
+            Page::Main => {
+                for (i, option) in OPTIONS.iter().enumerate() {
+                    let y = h/2 - 90 + i as i32 * 40;
+                    let color = if i == self.selected { Color::YELLOW } else { Color::WHITE };
+                    if i == self.selected { d.draw_text(">", w/2 - 120, y, 30, Color::YELLOW); }
+                    d.draw_text(option, w/2 - 90, y, 30, color);
+                }
+                d.draw_text("ESC to resume, Q to quit to menu", w/2 - 160, h/2 + 80, 20, Color::LIGHTGRAY);
+            }
+            Page::SaveSlots => ui::draw_slot_list(d, "Save to slot", &self.slots, self.slot, h/2 - 100),
+            Page::LoadSlots => ui::draw_slot_list(d, "Load slot", &self.slots, self.slot, h/2 - 100),
+        }
+
+        if let Some(notice) = &self.notice {
+            let tw = d.measure_text(notice, 20);
+            d.draw_text(notice, (w - tw) / 2, h/2 + 120, 20, Color::ORANGE);
+        }
     }
-    fn next(&mut self) -> Option<Box<dyn Screen>> {
-        if self.quit {
-            // go back to menu
-            self.quit = false;
-            Some(Box::new(crate::screens::menu::MenuScreen::default()))
-        } else if self.resume {
-            self.resume = false;
-            // signal to pop pause: return None but GameScreen expects to handle resume via its own state
-            None
-        } else { None }
+}
+
+impl Default for PauseMenu {
+    fn default() -> Self {
+        Self::new()
     }
 }
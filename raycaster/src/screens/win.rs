@@ -27,7 +27,7 @@ impl Screen for WinScreen {
         None
     }
 
-    fn draw_raylib(&mut self, d: &mut RaylibDrawHandle) {
+    fn draw_raylib(&mut self, d: &mut RaylibDrawHandle, _thread: &RaylibThread) {
         let w = d.get_screen_width();
         let h = d.get_screen_height();
         
@@ -1,85 +1,173 @@
 use raylib::prelude::*;
-use crate::{player::Player, textures::TextureManager, maze::Maze};
+use crate::{
+    player::Player,
+    maze::{Maze, block_size, is_open_tile},
+    nav::{FlowField, Neighbourhood, clear_path, is_walkable},
+    archetypes::{Archetype, Archetypes, AttackPattern},
+};
 use rand::Rng;
 
-#[derive(Clone, Copy, Debug)]
+const ENEMY_RADIUS: f32 = 12.0;      // cuerpo del enemigo para chocar con paredes
+const SEPARATION_RADIUS: f32 = 40.0; // distancia a la que se empujan entre ellos
+const SEPARATION_PUSH: f32 = 0.6;    // pixels por frame con dos enemigos encima
+
+const FOV_HALF: f32 = 1.05;   // medio cono de visión (~60°) si aún no ha detectado al jugador
+const REACTION: i32 = 20;     // frames en alerta antes de perseguir
+const GIVE_UP: i32 = 360;     // frames sin ver al jugador antes de volver a patrullar
+const COVER_TIME: i32 = 150;  // frames escondido antes de volver a salir
+const FLEE_TIME: i32 = 300;   // frames huyendo
+const BURST_GAP: i32 = 8;     // frames entre disparos de una ráfaga
+const MELEE_REACH: f32 = 28.0; // un enemigo cuerpo a cuerpo se acerca hasta aquí
+const PATROL_STEPS: usize = 4; // casillas de la vuelta de patrulla
+const COVER_STEPS: usize = 5;  // casillas en las que busca cobertura
+const FLEE_STEPS: usize = 6;   // casillas en las que busca dónde huir
+const SEARCH_STEPS: usize = 16; // casillas que recorre hasta donde vio u oyó al jugador
+
+/// Enemy types a level can spawn; their stats live in `assets/enemies.toml`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EnemyKind {
+    Grunt,
+    Guard,
+    Scout,
+    Boss,
+}
+
+impl EnemyKind {
+    pub const ALL: [EnemyKind; 4] = [EnemyKind::Grunt, EnemyKind::Guard, EnemyKind::Scout, EnemyKind::Boss];
+
+    /// Name used in level and archetype files.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Grunt => "grunt",
+            Self::Guard => "guard",
+            Self::Scout => "scout",
+            Self::Boss => "boss",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.name() == name)
+    }
+}
+
+/// Behaviour states. Unaware enemies idle or patrol and only see inside their view cone;
+/// once they spot the player or hear a shot they stay aware until they lose track of him.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EnemyState {
+    Idle,
+    Patrol,
+    Alerted,   // acaba de detectar algo y se gira antes de reaccionar
+    Chase,
+    Attack,
+    TakeCover,
+    Flee,
+}
+
+#[derive(Clone, Debug)]
 pub struct Enemy {
     pub pos: Vector2,
+    pub kind: EnemyKind,
     pub health: i32,
     pub max_health: i32,
     pub alive: bool,
+    pub state: EnemyState,
+    pub facing: f32,            // hacia dónde mira, en radianes
+    state_timer: i32,           // frames restantes del estado actual
+    last_known: Vector2,        // última posición vista u oída del jugador
+    lost_timer: i32,            // frames sin ver al jugador
+    route: Vec<Vector2>,        // casillas a recorrer al patrullar, cubrirse o huir
+    took_cover: bool,           // solo se cubre una vez
+    fled: bool,                 // solo huye una vez
+    burst_left: i32,            // disparos que quedan de la ráfaga
     pub shoot_cooldown: i32,
     pub muzzle_flash: i32, // frames remaining for muzzle flash effect
     pub shot_effect_timer: i32, // efecto de disparo visual (como el jugador)
 }
 
 impl Enemy {
-    pub fn new(pos: Vector2) -> Self {
+    pub fn new(pos: Vector2, kind: EnemyKind, health: i32) -> Self {
+        let mut rng = rand::thread_rng();
         Self {
             pos,
-            health: 100,
-            max_health: 100,
+            kind,
+            health,
+            max_health: health,
             alive: true,
+            state: EnemyState::Idle,
+            facing: rng.gen_range(0.0..std::f32::consts::TAU),
+            state_timer: rng.gen_range(30..150),
+            last_known: pos,
+            lost_timer: 0,
+            route: Vec::new(),
+            took_cover: false,
+            fled: false,
+            burst_left: 0,
             shoot_cooldown: 0,
             muzzle_flash: 0,
             shot_effect_timer: 0,
         }
     }
     
-    pub fn take_damage(&mut self, damage: i32) {
+    /// Hit for `damage` by a shot fired at `origin`; a wounded enemy always turns to it.
+    pub fn take_damage(&mut self, damage: i32, origin: Vector2) {
+        if !self.alive { return; }
         self.health -= damage;
         if self.health <= 0 {
             self.alive = false;
+            return;
         }
+        self.hear(origin);
+    }
+
+    /// Heard a gunshot fired at `origin`.
+    pub fn hear(&mut self, origin: Vector2) {
+        self.last_known = origin;
+        self.lost_timer = 0;
+        if !self.is_aware() { self.enter(EnemyState::Alerted, REACTION); }
+    }
+
+    /// Where the player was last seen or heard.
+    pub fn last_known(&self) -> Vector2 {
+        self.last_known
+    }
+
+    pub fn is_aware(&self) -> bool {
+        !matches!(self.state, EnemyState::Idle | EnemyState::Patrol)
+    }
+
+    fn enter(&mut self, state: EnemyState, frames: i32) {
+        self.state = state;
+        self.state_timer = frames;
+        self.route.clear();
     }
     
-    /// Move towards player with collision detection
-    pub fn move_towards_player(&mut self, player: &Player, maze: &Maze, speed: f32) {
-        let dx = player.pos.x - self.pos.x;
-        let dy = player.pos.y - self.pos.y;
-        let distance = (dx * dx + dy * dy).sqrt();
-        
-        if distance < 1.0 { return; } // Too close, don't move
-        
-        // Normalize direction
-        let dir_x = dx / distance;
-        let dir_y = dy / distance;
-        
-        // Calculate new position
-        let new_x = self.pos.x + dir_x * speed;
-        let new_y = self.pos.y + dir_y * speed;
-        
-        // Check collision with walls
-        let block_size = crate::maze::block_size() as f32;
-        let tile_x = (new_x / block_size) as usize;
-        let tile_y = (new_y / block_size) as usize;
-        
-        let can_move_x = tile_y < maze.len() && tile_x < maze[tile_y].len() 
-            && (maze[tile_y][tile_x] == ' ' || maze[tile_y][tile_x] == 'X' || maze[tile_y][tile_x] == 'x' || maze[tile_y][tile_x] == 'P' || maze[tile_y][tile_x] == 'p');
-        
-        let tile_y2 = (self.pos.y / block_size) as usize;
-        let can_move_y = tile_y2 < maze.len() && tile_x < maze[tile_y2].len()
-            && (maze[tile_y2][tile_x] == ' ' || maze[tile_y2][tile_x] == 'X' || maze[tile_y2][tile_x] == 'x' || maze[tile_y2][tile_x] == 'P' || maze[tile_y2][tile_x] == 'p');
-        
-        // Move if no collision
-        if can_move_x {
-            self.pos.x = new_x;
+    /// Move by `step` pixels, sliding along walls (each axis is tried separately)
+    pub fn move_by(&mut self, step: Vector2, maze: &Maze) {
+        let moved_x = Vector2::new(self.pos.x + step.x, self.pos.y);
+        if is_walkable(maze, moved_x, ENEMY_RADIUS) {
+            self.pos = moved_x;
         }
-        
-        let tile_x2 = (self.pos.x / block_size) as usize;
-        if can_move_y && tile_y < maze.len() && tile_x2 < maze[tile_y].len()
-            && (maze[tile_y][tile_x2] == ' ' || maze[tile_y][tile_x2] == 'X' || maze[tile_y][tile_x2] == 'x' || maze[tile_y][tile_x2] == 'P' || maze[tile_y][tile_x2] == 'p') {
-            self.pos.y = new_y;
+        let moved_y = Vector2::new(self.pos.x, self.pos.y + step.y);
+        if is_walkable(maze, moved_y, ENEMY_RADIUS) {
+            self.pos = moved_y;
         }
     }
     
-    /// Check if enemy can see player (simple line of sight)
-    pub fn can_see_player(&self, player: &Player, maze: &Maze) -> bool {
+    /// Check if enemy can see player within `sight` pixels (simple line of sight)
+    pub fn can_see_player(&self, player: &Player, maze: &Maze, sight: f32) -> bool {
         let dx = player.pos.x - self.pos.x;
         let dy = player.pos.y - self.pos.y;
         let distance = (dx * dx + dy * dy).sqrt();
         
-        if distance > 500.0 { return false; } // Too far
+        if distance > sight { return false; } // Too far
+        
+        // Unaware enemies only look ahead
+        if !self.is_aware() {
+            let mut angle_diff = dy.atan2(dx) - self.facing;
+            while angle_diff > std::f32::consts::PI { angle_diff -= 2.0 * std::f32::consts::PI; }
+            while angle_diff < -std::f32::consts::PI { angle_diff += 2.0 * std::f32::consts::PI; }
+            if angle_diff.abs() > FOV_HALF { return false; }
+        }
         
         // Simple raycast to check line of sight
         let steps = (distance / 10.0) as i32;
@@ -100,66 +188,244 @@ impl Enemy {
             }
             
             let ch = maze[tile_y][tile_x];
-            if ch != ' ' && ch != 'X' && ch != 'x' && ch != 'P' && ch != 'p' {
+            if !is_open_tile(ch) {
                 return false; // Wall blocking
             }
         }
         
         true
     }
-    
-    /// Try to shoot at player
-    pub fn try_shoot(&mut self, player: &mut Player, maze: &Maze) -> bool {
-        if self.shoot_cooldown > 0 {
-            return false;
+
+    /// State transitions for this frame.
+    fn think(&mut self, arch: &Archetype, player: &Player, maze: &Maze, flow: &FlowField) {
+        let distance = (player.pos.x - self.pos.x).hypot(player.pos.y - self.pos.y);
+        let sees = self.can_see_player(player, maze, arch.sight);
+        if sees {
+            self.last_known = player.pos;
+            self.lost_timer = 0;
+        } else {
+            self.lost_timer += 1;
         }
-        
-        if !self.can_see_player(player, maze) {
-            return false;
+        if self.state_timer > 0 { self.state_timer -= 1; }
+
+        // Herido: huir o cubrirse pasa por delante de todo lo demás
+        let health = self.health as f32 / self.max_health as f32;
+        if !self.fled && health < arch.flee_below {
+            self.fled = true;
+            self.enter(EnemyState::Flee, FLEE_TIME);
+            self.route = flee_route(maze, self.pos, flow);
+        } else if !self.took_cover && health < arch.cover_below && sees && self.state != EnemyState::Flee {
+            self.took_cover = true;
+            self.enter(EnemyState::TakeCover, COVER_TIME);
+            self.route = cover_route(maze, self.pos, player.pos);
         }
-        
-        let dx = player.pos.x - self.pos.x;
-        let dy = player.pos.y - self.pos.y;
-        let distance = (dx * dx + dy * dy).sqrt();
-        
-        // Shoot if close enough
-        if distance < 400.0 {
-            let mut rng = rand::thread_rng();
-            let accuracy = 0.7; // 70% accuracy
-            
-            if rng.gen::<f32>() < accuracy {
-                player.take_damage(10); // 10 damage per hit
+
+        match self.state {
+            EnemyState::Idle | EnemyState::Patrol if sees => self.enter(EnemyState::Alerted, REACTION),
+            EnemyState::Idle => {
+                if arch.patrol && self.state_timer == 0 {
+                    self.enter(EnemyState::Patrol, 0);
+                    self.route = patrol_route(maze, self.pos);
+                }
+            }
+            EnemyState::Patrol => {
+                if self.route.is_empty() { self.enter(EnemyState::Idle, rand::thread_rng().gen_range(60..180)); }
+            }
+            EnemyState::Alerted => {
+                self.facing = (self.last_known.y - self.pos.y).atan2(self.last_known.x - self.pos.x);
+                if self.state_timer == 0 { self.enter(EnemyState::Chase, 0); }
+            }
+            EnemyState::Chase => {
+                if sees && distance <= arch.range {
+                    self.enter(EnemyState::Attack, 0);
+                } else if self.lost_timer > GIVE_UP {
+                    self.enter(EnemyState::Idle, 120);
+                }
+            }
+            EnemyState::Attack => {
+                if !sees || distance > arch.range * 1.15 { self.enter(EnemyState::Chase, 0); }
+            }
+            EnemyState::TakeCover | EnemyState::Flee => {
+                // Sin sitio a donde ir (acorralado) o se acabó el tiempo: vuelve a pelear
+                if self.route.is_empty() && (self.state_timer == 0 || self.state == EnemyState::Flee) {
+                    self.enter(EnemyState::Chase, 0);
+                }
             }
-            
-            self.shoot_cooldown = 60; // 1 second cooldown at 60fps
-            self.muzzle_flash = 5; // Show flash for 5 frames
-            self.shot_effect_timer = 6; // Efecto visual de disparo
-            return true;
         }
-        
-        false
+    }
+
+    /// Movement for this frame in pixels, before separation.
+    fn steer(&mut self, arch: &Archetype, player: &Player, maze: &Maze, flow: &FlowField) -> Vector2 {
+        let distance = (player.pos.x - self.pos.x).hypot(player.pos.y - self.pos.y);
+
+        match self.state {
+            EnemyState::Idle | EnemyState::Alerted => Vector2::zero(),
+            EnemyState::Patrol => self.follow_route(arch.speed * 0.5),
+            EnemyState::TakeCover | EnemyState::Flee => self.follow_route(arch.speed * 1.2),
+            EnemyState::Chase => self.pursue(player, maze, flow, arch.speed),
+            EnemyState::Attack => match arch.attack {
+                AttackPattern::Melee if distance > MELEE_REACH => self.pursue(player, maze, flow, arch.speed),
+                AttackPattern::Melee => Vector2::zero(),
+                // A distancia: acercarse hasta keep_distance y retroceder si el jugador se echa encima
+                _ if distance > arch.keep_distance.max(MELEE_REACH) => self.pursue(player, maze, flow, arch.speed * 0.6),
+                _ if distance < arch.keep_distance * 0.5 => toward(player.pos, self.pos, arch.speed * 0.5),
+                _ => Vector2::zero(),
+            },
+        }
+    }
+
+    /// Movement toward the player: down the flow field while it is in sight, otherwise
+    /// along a route to where it was last seen or heard.
+    fn pursue(&mut self, player: &Player, maze: &Maze, flow: &FlowField, speed: f32) -> Vector2 {
+        // `think` deja lost_timer a 0 solo si lo ha visto este frame
+        if self.lost_timer == 0 {
+            self.route.clear();
+            return match flow.next_waypoint(maze, self.pos, player.pos, ENEMY_RADIUS) {
+                Some(waypoint) => toward(self.pos, waypoint, speed),
+                None => Vector2::zero(),
+            };
+        }
+        // La ruta se rehace si el jugador se oyó en otra casilla
+        let b = block_size() as f32;
+        let tile = |p: Vector2| ((p.x / b).floor() as i32, (p.y / b).floor() as i32);
+        let target = tile(self.last_known);
+        if tile(self.pos) != target && self.route.last().map(|&end| tile(end)) != Some(target) {
+            self.route = search_route(maze, self.pos, self.last_known);
+            // Sin camino hasta allí pierde el rastro y espera donde está
+            if self.route.is_empty() { self.last_known = self.pos; }
+        }
+        self.follow_route(speed)
+    }
+
+    /// Direction to the next point of `route`, dropping the points already reached.
+    fn follow_route(&mut self, speed: f32) -> Vector2 {
+        while let Some(next) = self.route.first() {
+            let (dx, dy) = (next.x - self.pos.x, next.y - self.pos.y);
+            let len = (dx * dx + dy * dy).sqrt();
+            if len > 4.0 { return Vector2::new(dx / len * speed, dy / len * speed); }
+            self.route.remove(0);
+        }
+        Vector2::zero()
+    }
+
+    /// Attacks while in the attack state, following the archetype's pattern.
+    fn attack(&mut self, arch: &Archetype, player: &mut Player) {
+        if self.state != EnemyState::Attack || self.shoot_cooldown > 0 { return; }
+        self.facing = (player.pos.y - self.pos.y).atan2(player.pos.x - self.pos.x);
+        let distance = (player.pos.x - self.pos.x).hypot(player.pos.y - self.pos.y);
+        let mut rng = rand::thread_rng();
+
+        match arch.attack {
+            AttackPattern::Melee => {
+                if distance > MELEE_REACH + ENEMY_RADIUS + 10.0 { return; }
+                if rng.gen::<f32>() < arch.accuracy { player.take_damage(arch.damage); }
+                self.shoot_cooldown = arch.cooldown;
+                self.muzzle_flash = 5; // destello del golpe
+            }
+            AttackPattern::Ranged | AttackPattern::Burst => {
+                let burst = if arch.attack == AttackPattern::Burst { arch.burst } else { 1 };
+                if self.burst_left == 0 { self.burst_left = burst; }
+                if rng.gen::<f32>() < arch.accuracy { player.take_damage(arch.damage); }
+                self.burst_left -= 1;
+                self.shoot_cooldown = if self.burst_left > 0 { BURST_GAP } else { arch.cooldown };
+                self.muzzle_flash = 5; // Show flash for 5 frames
+                self.shot_effect_timer = 6; // Efecto visual de disparo
+            }
+        }
+    }
+}
+
+/// A short random walk around `pos`.
+fn patrol_route(maze: &Maze, pos: Vector2) -> Vec<Vector2> {
+    let around = Neighbourhood::around(maze, pos, PATROL_STEPS);
+    let count = around.centers().count();
+    if count < 2 { return Vec::new(); }
+    around.route(rand::thread_rng().gen_range(1..count))
+}
+
+/// Step of `speed` pixels from `from` toward `to` (zero once there).
+fn toward(from: Vector2, to: Vector2, speed: f32) -> Vector2 {
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    let len = (dx * dx + dy * dy).sqrt();
+    if len < 1.0 { Vector2::zero() } else { Vector2::new(dx / len * speed, dy / len * speed) }
+}
+
+/// Route to the tile holding `target`, if it is within `SEARCH_STEPS` steps.
+fn search_route(maze: &Maze, pos: Vector2, target: Vector2) -> Vec<Vector2> {
+    let around = Neighbourhood::around(maze, pos, SEARCH_STEPS);
+    let b = block_size() as f32;
+    let tile = |p: Vector2| ((p.x / b).floor(), (p.y / b).floor());
+    let found = around.centers().position(|c| tile(c) == tile(target));
+    found.map(|i| around.route(i)).unwrap_or_default()
+}
+
+/// Route to the nearest tile the player can't see from `threat`.
+fn cover_route(maze: &Maze, pos: Vector2, threat: Vector2) -> Vec<Vector2> {
+    let around = Neighbourhood::around(maze, pos, COVER_STEPS);
+    let hidden = around.centers().position(|c| !clear_path(maze, c, threat, 0.0));
+    hidden.map(|i| around.route(i)).unwrap_or_default()
+}
+
+/// Route to the nearby tile farthest from the player along the corridors.
+fn flee_route(maze: &Maze, pos: Vector2, flow: &FlowField) -> Vec<Vector2> {
+    let around = Neighbourhood::around(maze, pos, FLEE_STEPS);
+    let farthest = around
+        .centers()
+        .enumerate()
+        .filter_map(|(i, c)| flow.distance(c).map(|d| (i, d)))
+        .max_by(|a, b| a.1.total_cmp(&b.1));
+    match farthest {
+        Some((i, _)) if i > 0 => around.route(i),
+        _ => Vec::new(),
     }
 }
 
 pub struct EnemySystem {
     pub list: Vec<Enemy>,
+    pub archetypes: Archetypes,
+    flow: FlowField, // caminos hacia el jugador, recalculados cada frame
+}
+
+/// Push away from the other enemies closer than `SEPARATION_RADIUS`, stronger the closer
+/// they are, so groups spread out instead of stacking on one tile.
+fn separation(positions: &[Vector2], i: usize) -> Vector2 {
+    let mut push = Vector2::zero();
+    for (j, other) in positions.iter().enumerate() {
+        if j == i { continue; }
+        let (dx, dy) = (positions[i].x - other.x, positions[i].y - other.y);
+        let dist = (dx * dx + dy * dy).sqrt();
+        if dist >= SEPARATION_RADIUS { continue; }
+        // En el mismo punto: separar en una dirección fija según el índice
+        let (nx, ny) = if dist < 0.01 { ((i as f32).cos(), (i as f32).sin()) } else { (dx / dist, dy / dist) };
+        let strength = 1.0 - dist / SEPARATION_RADIUS;
+        push.x += nx * strength;
+        push.y += ny * strength;
+    }
+    let len = (push.x * push.x + push.y * push.y).sqrt();
+    let k = SEPARATION_PUSH / len.max(1.0);
+    Vector2::new(push.x * k, push.y * k)
 }
 
 impl EnemySystem {
-    pub fn new() -> Self {
-        Self { list: Vec::new() }
+    pub fn new(archetypes: Archetypes) -> Self {
+        Self { list: Vec::new(), archetypes, flow: FlowField::new() }
     }
     
-    /// Spawn enemies from maze (looks for 'E' or random positions)
-    pub fn spawn_from_maze(&mut self, maze: &Maze, count: usize) {
+    pub fn spawn(&mut self, pos: Vector2, kind: EnemyKind) {
+        let health = self.archetypes.get(kind).health;
+        self.list.push(Enemy::new(pos, kind, health));
+    }
+
+    /// Spawn `count` enemies of `kind` on random empty tiles
+    pub fn spawn_from_maze(&mut self, maze: &Maze, count: usize, kind: EnemyKind) {
         let block_size = crate::maze::block_size() as f32;
         
         // First, collect all valid spawn positions (empty spaces)
         let mut valid_positions = Vec::new();
         for (y, row) in maze.iter().enumerate() {
             for (x, &ch) in row.iter().enumerate() {
-                // Empty spaces or explicitly marked 'E' for enemy
-                if ch == ' ' || ch == 'E' || ch == 'e' {
+                // Solo casillas vacías ('E' es una pared)
+                if ch == ' ' {
                     let world_x = (x as f32 + 0.5) * block_size;
                     let world_y = (y as f32 + 0.5) * block_size;
                     valid_positions.push(Vector2::new(world_x, world_y));
@@ -173,7 +439,7 @@ impl EnemySystem {
         valid_positions.shuffle(&mut rng);
         
         for pos in valid_positions.iter().take(count) {
-            self.list.push(Enemy::new(*pos));
+            self.spawn(*pos, kind);
         }
     }
     
@@ -182,7 +448,12 @@ impl EnemySystem {
         // Remove dead enemies
         self.list.retain(|e| e.alive);
         
-        for enemy in &mut self.list {
+        // One flow field toward the player shared by every enemy
+        self.flow.rebuild(maze, player.pos);
+        let positions: Vec<Vector2> = self.list.iter().map(|e| e.pos).collect();
+        
+        for (i, enemy) in self.list.iter_mut().enumerate() {
+            let arch = self.archetypes.get(enemy.kind);
             // Decrease cooldowns
             if enemy.shoot_cooldown > 0 {
                 enemy.shoot_cooldown -= 1;
@@ -194,69 +465,25 @@ impl EnemySystem {
                 enemy.shot_effect_timer -= 1;
             }
             
-            let dx = player.pos.x - enemy.pos.x;
-            let dy = player.pos.y - enemy.pos.y;
-            let distance = (dx * dx + dy * dy).sqrt();
+            enemy.think(arch, player, maze, &self.flow);
             
-            // AI behavior based on distance
-            if distance > 150.0 {
-                // Move towards player if far away
-                enemy.move_towards_player(player, maze, 1.0);
-            } else if distance > 100.0 {
-                // Stop and shoot if at medium distance
-                enemy.try_shoot(player, maze);
-            } else {
-                // Move slowly and shoot if very close
-                enemy.move_towards_player(player, maze, 0.3);
-                enemy.try_shoot(player, maze);
-            }
+            // Move as the state wants, keeping apart from the others
+            let intent = enemy.steer(arch, player, maze, &self.flow);
+            let push = separation(&positions, i);
+            if intent.x != 0.0 || intent.y != 0.0 { enemy.facing = intent.y.atan2(intent.x); }
+            enemy.move_by(Vector2::new(intent.x + push.x, intent.y + push.y), maze);
+            
+            enemy.attack(arch, player);
         }
     }
     
-    /// Get enemy at crosshair (for shooting)
-    pub fn get_enemy_at_crosshair(&mut self, player: &Player, max_distance: f32) -> Option<&mut Enemy> {
-        let fwd_x = player.a.cos();
-        let fwd_y = player.a.sin();
-        
-        let mut closest_enemy: Option<(usize, f32)> = None;
-        
-        for (i, enemy) in self.list.iter().enumerate() {
-            if !enemy.alive { continue; }
-            
-            let dx = enemy.pos.x - player.pos.x;
-            let dy = enemy.pos.y - player.pos.y;
-            let distance = (dx * dx + dy * dy).sqrt();
-            
-            if distance > max_distance { continue; }
-            
-            // Check if enemy is in front of player
-            let depth = dx * fwd_x + dy * fwd_y;
-            if depth <= 0.0 { continue; }
-            
-            // Check angle (narrow cone for aiming)
-            let angle_to_enemy = dy.atan2(dx);
-            let mut angle_diff = angle_to_enemy - player.a;
-            while angle_diff > std::f32::consts::PI { angle_diff -= 2.0 * std::f32::consts::PI; }
-            while angle_diff < -std::f32::consts::PI { angle_diff += 2.0 * std::f32::consts::PI; }
-            
-            // Aim cone (about 5 degrees)
-            if angle_diff.abs() > 0.087 { continue; }
-            
-            if let Some((_, closest_dist)) = closest_enemy {
-                if distance < closest_dist {
-                    closest_enemy = Some((i, distance));
-                }
-            } else {
-                closest_enemy = Some((i, distance));
+    /// The player fired from `origin`: enemies close enough along the corridors hear it.
+    pub fn hear_gunshot(&mut self, origin: Vector2) {
+        for enemy in &mut self.list {
+            let hearing = self.archetypes.get(enemy.kind).hearing;
+            if self.flow.distance(enemy.pos).is_some_and(|d| d <= hearing) {
+                enemy.hear(origin);
             }
         }
-        
-        closest_enemy.map(|(i, _)| &mut self.list[i])
-    }
-}
-
-impl Default for EnemySystem {
-    fn default() -> Self {
-        Self::new()
     }
 }
@@ -1,6 +1,7 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, path::Path};
 use image::{GenericImageView, RgbaImage};
 use raylib::prelude::Color;
+use crate::{doors::{KeyColor, door_lock}, pickups::PickupKind, sprites::EnemyKind, weapons::WeaponKind};
 
 /// Simple texture manager: keeps RGBA images in memory and lets you sample pixels.
 pub struct TextureManager {
@@ -20,23 +21,37 @@ impl TextureManager {
         atlas.insert("floor",  image::open("assets/textures/floor.png")?.to_rgba8());
         atlas.insert("ceiling", image::open("assets/textures/ceiling.png")?.to_rgba8());
         atlas.insert("coin", image::open("assets/textures/coin.png")?.to_rgba8());
-        atlas.insert("enemy", image::open("assets/textures/enemy.png")?.to_rgba8());
+        let enemy = image::open("assets/textures/enemy.png")?.to_rgba8();
+        for kind in EnemyKind::ALL {
+            let key = enemy_key(kind);
+            let path = format!("assets/textures/{key}.png");
+            // Un tipo sin PNG propio usa el soldado genérico
+            let image = if Path::new(&path).exists() { image::open(&path)?.to_rgba8() } else { enemy.clone() };
+            atlas.insert(key, image);
+        }
+        atlas.insert("enemy", enemy);
         atlas.insert("pistol_view", image::open("assets/textures/pistol_view.png")?.to_rgba8());
         atlas.insert("menu", image::open("assets/textures/menu.png")?.to_rgba8());
         atlas.insert("shot_effect", image::open("assets/textures/shot_effect.png")?.to_rgba8());
-        Ok(Self { atlas })
-    }
-
-    #[inline]
-    pub fn get_pixel_color(&self, key: &str, tx: u32, ty: u32) -> Color {
-        if let Some(img) = self.atlas.get(key) {
-            let (w, h) = img.dimensions();
-            let x = tx.min(w.saturating_sub(1));
-            let y = ty.min(h.saturating_sub(1));
-            let p = img.get_pixel(x, y).0;
-            return Color::new(p[0], p[1], p[2], p[3]);
+        // Puertas y llaves se generan en código (no hay PNG para ellas)
+        atlas.insert("door", door_texture(None));
+        for color in KeyColor::ALL {
+            atlas.insert(door_key(color), door_texture(Some(color.color())));
         }
-        Color::MAGENTA
+        atlas.insert("key", key_texture());
+        // Objetos del suelo: la clave es el nombre del tipo en los niveles
+        atlas.insert("health", health_texture());
+        atlas.insert("armor", armor_texture());
+        atlas.insert("treasure", treasure_texture());
+        for weapon in WeaponKind::ALL {
+            atlas.insert(PickupKind::Ammo(weapon).name(), ammo_texture(weapon));
+        }
+        // Armas sin PNG propio y el proyectil de plasma
+        atlas.insert("shotgun_view", weapon_view_texture(WeaponKind::Shotgun));
+        atlas.insert("machinegun_view", weapon_view_texture(WeaponKind::MachineGun));
+        atlas.insert("plasma_view", weapon_view_texture(WeaponKind::Plasma));
+        atlas.insert("plasma_bolt", plasma_bolt_texture());
+        Ok(Self { atlas })
     }
 
     /// Sample texture using usize coordinates (useful where texture sizes are usize).
@@ -56,6 +71,24 @@ impl TextureManager {
     pub fn size_of(&self, key: &str) -> (u32, u32) {
         if let Some(img) = self.atlas.get(key) { img.dimensions() } else { (1, 1) }
     }
+
+    /// Direct access to an image, so hot loops can resolve the key once.
+    #[inline]
+    pub fn image(&self, key: &str) -> Option<&RgbaImage> {
+        self.atlas.get(key)
+    }
+}
+
+/// Sample `img` at normalized coordinates (`u`, `v` in 0..1, clamped to the edges).
+/// A missing image samples as magenta, like `sample_at`.
+#[inline]
+pub fn texel(img: Option<&RgbaImage>, u: f32, v: f32) -> Color {
+    let Some(img) = img else { return Color::MAGENTA; };
+    let (w, h) = img.dimensions();
+    let x = ((u.max(0.0) * w as f32) as u32).min(w.saturating_sub(1));
+    let y = ((v.max(0.0) * h as f32) as u32).min(h.saturating_sub(1));
+    let p = img.get_pixel(x, y).0;
+    Color::new(p[0], p[1], p[2], p[3])
 }
 
 /// Map wall char ('#','A','B','C',...) to texture key inside the atlas.
@@ -75,6 +108,11 @@ pub fn wall_key_from_char(ch: char) -> &'static str {
 /// Choose a wall texture key based on map char and cell coordinates.
 /// This produces variety: A and D are predominant; B/C/E/F appear as alternates.
 pub fn wall_key_from_char_at(ch: char, cell_x: i32, cell_y: i32) -> &'static str {
+    // Doors have their own textures
+    if let Some(lock) = door_lock(ch) {
+        return lock.map_or("door", door_key);
+    }
+
     // If map explicitly encodes a specific wall, keep it
     match ch.to_ascii_uppercase() {
         'A' | 'B' | 'C' | 'D' | 'E' | 'F' => return wall_key_from_char(ch),
@@ -98,3 +136,238 @@ pub fn wall_key_from_char_at(ch: char, cell_x: i32, cell_y: i32) -> &'static str
         }
     }
 }
+
+/// Texture key for a floor/ceiling layer char: `A`–`F` reuse the wall textures,
+/// anything else (space, `.`) keeps `default` (`"floor"` or `"ceiling"`).
+pub fn surface_key_from_char(ch: char, default: &'static str) -> &'static str {
+    match ch.to_ascii_uppercase() {
+        'A' | 'B' | 'C' | 'D' | 'E' | 'F' => wall_key_from_char(ch),
+        _ => default,
+    }
+}
+
+/// Atlas key of the sprite of each enemy type, loaded from `assets/textures/<key>.png`.
+fn enemy_key(kind: EnemyKind) -> &'static str {
+    match kind {
+        EnemyKind::Grunt => "enemy_grunt",
+        EnemyKind::Guard => "enemy_guard",
+        EnemyKind::Scout => "enemy_scout",
+        EnemyKind::Boss => "enemy_boss",
+    }
+}
+
+/// Atlas key of the door locked with `color`.
+fn door_key(color: KeyColor) -> &'static str {
+    match color {
+        KeyColor::Red => "door_red",
+        KeyColor::Green => "door_green",
+        KeyColor::Yellow => "door_yellow",
+    }
+}
+
+/// Steel door panel: riveted frame, two recessed panels and a handle slot.
+/// Locked doors get a band and a keyhole plate in their key's color.
+fn door_texture(lock: Option<Color>) -> RgbaImage {
+    const S: u32 = 64;
+    RgbaImage::from_fn(S, S, |x, y| {
+        // Ruido fijo por pixel para que el acero no sea plano
+        let noise = ((x.wrapping_mul(73) ^ y.wrapping_mul(151)).wrapping_mul(2654435761) >> 28) as u8;
+        let mut c = [96 + noise, 100 + noise, 110 + noise];
+        let frame = !(4..S - 4).contains(&x) || !(4..S - 4).contains(&y);
+        let panel = (10..S - 10).contains(&x) && ((10..28).contains(&y) || (36..S - 10).contains(&y));
+        let rivet = (x == 6 || x == S - 7) && (y % 12 == 6);
+        if frame { c = [70, 72, 80]; }
+        if panel { c = [c[0] - 18, c[1] - 18, c[2] - 16]; }
+        if rivet { c = [170, 170, 180]; }
+        if (48..52).contains(&x) && (26..38).contains(&y) { c = [30, 30, 34]; } // ranura del tirador
+        if let Some(k) = lock {
+            if (29..35).contains(&y) && !frame { c = [k.r, k.g, k.b]; }
+            let plate = (26..38).contains(&x) && (24..40).contains(&y);
+            let keyhole = (30..34).contains(&x) && (28..36).contains(&y);
+            if plate { c = if keyhole { [10, 10, 10] } else { [k.r, k.g, k.b] }; }
+        }
+        image::Rgba([c[0], c[1], c[2], 255])
+    })
+}
+
+/// White key silhouette on a transparent background; it is tinted with the key color
+/// when drawn.
+fn key_texture() -> RgbaImage {
+    const S: u32 = 64;
+    RgbaImage::from_fn(S, S, |x, y| {
+        let (fx, fy) = (x as f32 - 18.0, y as f32 - 32.0);
+        let r = (fx * fx + fy * fy).sqrt();
+        let bow = (6.0..12.0).contains(&r);
+        let shaft = (28..58).contains(&x) && (29..35).contains(&y);
+        let teeth = ((44..48).contains(&x) || (52..57).contains(&x)) && (35..43).contains(&y);
+        if bow || shaft || teeth {
+            let edge = if bow { r > 10.5 } else { y == 29 || y == 34 };
+            let v = if edge { 170 } else { 235 };
+            image::Rgba([v, v, v, 255])
+        } else {
+            image::Rgba([0, 0, 0, 0])
+        }
+    })
+}
+
+/// Medkit: white box with a red cross, resting on the bottom edge.
+fn health_texture() -> RgbaImage {
+    const S: u32 = 64;
+    RgbaImage::from_fn(S, S, |x, y| {
+        if !(8..56).contains(&x) || !(20..S).contains(&y) { return image::Rgba([0, 0, 0, 0]); }
+        let edge = x == 8 || x == 55 || y == 20 || y == S - 1;
+        let cross = ((26..38).contains(&x) && (28..56).contains(&y)) || ((18..46).contains(&x) && (36..48).contains(&y));
+        let c = if edge { [150, 150, 155] } else if cross { [210, 30, 30] } else if y < 24 { [250, 250, 250] } else { [225, 225, 230] };
+        image::Rgba([c[0], c[1], c[2], 255])
+    })
+}
+
+/// Green armor vest: shoulders at the top, a darker collar and a light plate in the middle.
+fn armor_texture() -> RgbaImage {
+    const S: u32 = 64;
+    RgbaImage::from_fn(S, S, |x, y| {
+        let (fx, fy) = (x as f32 - 31.5, y as f32);
+        let body = (10.0..S as f32).contains(&fy) && fx.abs() < 18.0 + (fy - 10.0).min(8.0) * 0.5;
+        let collar = fy < 22.0 && fx.abs() < 8.0;
+        let arm_hole = (14.0..30.0).contains(&fy) && fx.abs() > 18.0;
+        if !body || collar || arm_hole { return image::Rgba([0, 0, 0, 0]); }
+        let plate = (28.0..52.0).contains(&fy) && fx.abs() < 10.0;
+        let shade = 1.0 - fx.abs() / 40.0;
+        let c = if plate { [120.0, 230.0, 150.0] } else { [40.0, 150.0, 80.0] };
+        image::Rgba([(c[0] * shade) as u8, (c[1] * shade) as u8, (c[2] * shade) as u8, 255])
+    })
+}
+
+/// Gold chalice: cup, stem and foot, with a highlight on the left.
+fn treasure_texture() -> RgbaImage {
+    const S: u32 = 64;
+    RgbaImage::from_fn(S, S, |x, y| {
+        let (fx, fy) = (x as f32 - 31.5, y as f32);
+        let hw = if fy < 12.0 {
+            None
+        } else if fy < 36.0 {
+            Some(16.0 - (fy - 12.0) * (fy - 12.0) / 48.0) // copa
+        } else if fy < 52.0 {
+            Some(3.5) // pie
+        } else {
+            Some(14.0) // base
+        };
+        match hw {
+            Some(hw) if fx.abs() < hw => {
+                let [r, g, b] = shade_round([240, 190, 40], fx, hw);
+                image::Rgba([r, g, b, 255])
+            }
+            _ => image::Rgba([0, 0, 0, 0]),
+        }
+    })
+}
+
+/// Ammo box with a band in the weapon's color: brass, shell red, olive and plasma cyan.
+fn ammo_texture(kind: WeaponKind) -> RgbaImage {
+    const S: u32 = 64;
+    let band = match kind {
+        WeaponKind::Pistol => [220, 180, 60],
+        WeaponKind::Shotgun => [200, 40, 30],
+        WeaponKind::MachineGun => [150, 160, 70],
+        WeaponKind::Plasma => [80, 210, 255],
+    };
+    RgbaImage::from_fn(S, S, |x, y| {
+        if !(6..58).contains(&x) || !(28..S).contains(&y) { return image::Rgba([0, 0, 0, 0]); }
+        let edge = x == 6 || x == 57 || y == 28 || y == S - 1;
+        let lid = y < 34;
+        let c = if edge { [40, 45, 35] } else if (42..50).contains(&y) { band } else if lid { [110, 115, 90] } else { [85, 90, 70] };
+        image::Rgba([c[0], c[1], c[2], 255])
+    })
+}
+
+/// Half-width of a part that narrows toward the top of the view: `hw_top` at `y0` to
+/// `hw_bottom` at `y1` (a cheap perspective for a gun pointing into the screen).
+fn taper(y: f32, y0: f32, y1: f32, hw_top: f32, hw_bottom: f32) -> Option<f32> {
+    if y < y0 || y >= y1 { return None; }
+    Some(hw_top + (hw_bottom - hw_top) * (y - y0) / (y1 - y0))
+}
+
+/// Color of a round part at horizontal offset `dx` from its axis: lit on the left,
+/// darker toward the edges, with a dark outline.
+fn shade_round(base: [u8; 3], dx: f32, hw: f32) -> [u8; 3] {
+    let t = (dx / hw).clamp(-1.0, 1.0);
+    if t.abs() > 0.9 { return [base[0] / 3, base[1] / 3, base[2] / 3]; }
+    let light = 0.75 + 0.35 * (1.0 - t * t).sqrt() - 0.15 * t;
+    base.map(|c| (c as f32 * light).min(255.0) as u8)
+}
+
+/// Procedural first-person sprite for the weapons without a PNG, drawn from behind with
+/// the muzzle toward the top centre; the background is transparent.
+fn weapon_view_texture(kind: WeaponKind) -> RgbaImage {
+    const S: u32 = 256;
+    const STEEL: [u8; 3] = [70, 72, 78];
+    const DARK: [u8; 3] = [40, 40, 44];
+    const WOOD: [u8; 3] = [120, 72, 36];
+    RgbaImage::from_fn(S, S, |x, y| {
+        let (fx, fy) = (x as f32 + 0.5, y as f32 + 0.5);
+        let mut color: Option<[u8; 3]> = None;
+        // Las piezas se pintan de atrás (arriba) hacia delante (abajo)
+        let part = |cx: f32, hw: Option<f32>, base: [u8; 3]| {
+            hw.filter(|hw| (fx - cx).abs() < *hw).map(|hw| shade_round(base, fx - cx, hw))
+        };
+        match kind {
+            WeaponKind::Shotgun => {
+                color = part(116.0, taper(fy, 40.0, 200.0, 9.0, 16.0), STEEL).or(color);
+                color = part(140.0, taper(fy, 40.0, 200.0, 9.0, 16.0), STEEL).or(color);
+                color = part(128.0, taper(fy, 120.0, 196.0, 30.0, 42.0), WOOD).or(color);
+                color = part(128.0, taper(fy, 190.0, 256.0, 44.0, 58.0), DARK).or(color);
+                // Vetas de la madera y bocas de los cañones
+                let grain = (fy * 0.35 + (fx * 0.09).sin() * 3.0).sin() > 0.7;
+                if grain && (120.0..190.0).contains(&fy) && (fx - 128.0).abs() < 34.0 {
+                    color = color.map(|c| c.map(|v| v.saturating_sub(25)));
+                }
+                if (40.0..46.0).contains(&fy) && ((fx - 116.0).abs() < 5.0 || (fx - 140.0).abs() < 5.0) { color = Some([8, 8, 8]); }
+            }
+            WeaponKind::MachineGun => {
+                color = part(128.0, taper(fy, 16.0, 150.0, 6.0, 10.0), STEEL).or(color);
+                color = part(128.0, taper(fy, 60.0, 160.0, 13.0, 18.0), DARK).or(color);
+                // Camisa de refrigeración con agujeros
+                if (64.0..156.0).contains(&fy) && ((fy - 64.0) % 16.0) < 6.0 && (fx - 128.0).abs() < 6.0 { color = Some([15, 15, 15]); }
+                color = part(128.0, taper(fy, 150.0, 256.0, 36.0, 56.0), STEEL).or(color);
+                // Cargador a la izquierda
+                if (170.0..256.0).contains(&fy) && (58.0..84.0).contains(&(fx + (fy - 170.0) * 0.2)) { color = Some(shade_round(DARK, fx + (fy - 170.0) * 0.2 - 71.0, 13.0)); }
+                if (10.0..18.0).contains(&fy) && (fx - 128.0).abs() < 2.0 { color = Some(DARK); } // punto de mira
+            }
+            WeaponKind::Plasma => {
+                color = part(128.0, taper(fy, 50.0, 256.0, 24.0, 64.0), [78, 88, 110]).or(color);
+                // Bobinas que brillan
+                if (70.0..230.0).contains(&fy) && ((fy - 70.0) % 22.0) < 6.0 {
+                    if let Some(hw) = taper(fy, 50.0, 256.0, 24.0, 64.0) {
+                        if (fx - 128.0).abs() < hw * 0.85 { color = Some([90, 220, 255]); }
+                    }
+                }
+                let (dx, dy) = (fx - 128.0, fy - 52.0);
+                let r = (dx * dx + dy * dy).sqrt();
+                if r < 14.0 { color = Some(if r < 8.0 { [220, 250, 255] } else { [80, 200, 255] }); }
+            }
+            WeaponKind::Pistol => {} // tiene su PNG
+        }
+        match color {
+            Some(c) => image::Rgba([c[0], c[1], c[2], 255]),
+            None => image::Rgba([0, 0, 0, 0]),
+        }
+    })
+}
+
+/// Glowing plasma ball, white-hot in the centre and fading to transparent cyan.
+fn plasma_bolt_texture() -> RgbaImage {
+    const S: u32 = 32;
+    RgbaImage::from_fn(S, S, |x, y| {
+        let (dx, dy) = (x as f32 - 15.5, y as f32 - 15.5);
+        let r = (dx * dx + dy * dy).sqrt() / 16.0;
+        if r >= 1.0 { return image::Rgba([0, 0, 0, 0]); }
+        let core = (1.0 - r * 2.0).max(0.0);
+        let alpha = ((1.0 - r) * 1.6).min(1.0);
+        image::Rgba([
+            (80.0 + 175.0 * core) as u8,
+            (200.0 + 55.0 * core) as u8,
+            255,
+            (alpha * 255.0) as u8,
+        ])
+    })
+}
@@ -1,7 +1,7 @@
 use raylib::prelude::*;
-use crate::{maze::{Maze, block_size}, player::Player}; // ← agrega block_size
+use crate::{maze::{Maze, block_size}, player::Player, doors::{KeyColor, door_lock}, pickups::Pickup, weapons::Weapons}; // ← agrega block_size
 
-pub fn draw_minimap(d: &mut RaylibDrawHandle, maze: &Maze, player: &Player, fog_of_war: Option<&crate::maze::FogOfWar>) {
+pub fn draw_minimap(d: &mut RaylibDrawHandle, maze: &Maze, pickups: &[Pickup], player: &Player, fog_of_war: Option<&crate::maze::FogOfWar>) {
     let scale = 4;
     let origin_x = 0;
     let origin_y = 0;
@@ -26,7 +26,12 @@ pub fn draw_minimap(d: &mut RaylibDrawHandle, maze: &Maze, player: &Player, fog_
                 );
             } else {
                 let wall = matches!(c, '#' | 'A' | 'B' | 'C' | 'D' | 'E' | 'F');
-                let base_color = if wall { Color::DARKBLUE } else { Color::DARKGRAY };
+                let base_color = match door_lock(c) {
+                    Some(Some(key)) => key.color(),
+                    Some(None) => Color::BROWN,
+                    None if wall => Color::DARKBLUE,
+                    None => Color::DARKGRAY,
+                };
                 
                 // Aplicar exploración al color
                 let alpha = (exploration * 255.0) as u8;
@@ -43,23 +48,22 @@ pub fn draw_minimap(d: &mut RaylibDrawHandle, maze: &Maze, player: &Player, fog_
         }
     }
     
-    // draw coins on minimap (solo las visibles)
-    for (j, row) in maze.iter().enumerate() {
-        for (i, &c) in row.iter().enumerate() {
-            if c == 'X' || c == 'x' {
-                let exploration = if let Some(fow) = fog_of_war {
-                    fow.get_exploration(i, j)
-                } else {
-                    1.0
-                };
-                
-                if exploration > 0.3 {
-                    let cx = origin_x + i as i32 * scale + scale/2;
-                    let cy = origin_y + j as i32 * scale + scale/2;
-                    let alpha = (exploration * 255.0) as u8;
-                    d.draw_circle(cx, cy, (scale/2) as f32, Color::new(255, 215, 0, alpha));
-                }
-            }
+    // draw pickups on minimap (solo los visibles)
+    let tile_size = block_size() as f32;
+    for item in pickups {
+        let (i, j) = ((item.pos.x / tile_size) as usize, (item.pos.y / tile_size) as usize);
+        let exploration = if let Some(fow) = fog_of_war {
+            fow.get_exploration(i, j)
+        } else {
+            1.0
+        };
+        
+        if exploration > 0.3 {
+            let cx = origin_x + i as i32 * scale + scale/2;
+            let cy = origin_y + j as i32 * scale + scale/2;
+            let alpha = (exploration * 255.0) as u8;
+            let base = item.kind.color();
+            d.draw_circle(cx, cy, (scale/2) as f32, Color::new(base.r, base.g, base.b, alpha));
         }
     }
     
@@ -73,6 +77,18 @@ pub fn draw_minimap(d: &mut RaylibDrawHandle, maze: &Maze, player: &Player, fog_
     );
 }
 
+/// Draw the keys the player carries as small colored tags (empty outline if missing)
+pub fn draw_keys_hud(d: &mut RaylibDrawHandle, player: &Player, x: i32, y: i32) {
+    d.draw_text("Keys:", x, y, 20, Color::LIGHTGRAY);
+    for (i, key) in KeyColor::ALL.iter().enumerate() {
+        let kx = x + 60 + i as i32 * 26;
+        if player.has_key(*key) {
+            d.draw_rectangle(kx, y + 2, 20, 16, key.color());
+        }
+        d.draw_rectangle_lines(kx, y + 2, 20, 16, key.color());
+    }
+}
+
 /// Draw health bar HUD
 pub fn draw_health_hud(d: &mut RaylibDrawHandle, player: &crate::player::Player) {
     let screen_w = d.get_screen_width();
@@ -114,6 +130,15 @@ pub fn draw_health_hud(d: &mut RaylibDrawHandle, player: &crate::player::Player)
     
     // Etiqueta "HEALTH"
     d.draw_text("HEALTH", bar_x, bar_y - 20, 16, Color::LIGHTGRAY);
+    
+    // Armadura: barra fina encima, solo si queda algo
+    if player.armor > 0 {
+        let armor_y = bar_y - 34;
+        let armor_width = (bar_width as f32 * player.armor as f32 / player.max_armor as f32) as i32;
+        d.draw_rectangle(bar_x - 2, armor_y - 2, bar_width + 4, 12, Color::BLACK);
+        d.draw_rectangle(bar_x, armor_y, armor_width, 8, Color::new(60, 200, 120, 255));
+        d.draw_text(&format!("ARMOR {}", player.armor), bar_x + 80, bar_y - 20, 16, Color::new(60, 200, 120, 255));
+    }
 }
 
 /// Draw crosshair in the center of the screen
@@ -202,38 +227,64 @@ pub fn draw_damage_overlay(d: &mut RaylibDrawHandle, player: &crate::player::Pla
     }
 }
 
-/// Draw pistol view at bottom center of screen (MÁXIMO OPTIMIZADO)
-pub fn draw_pistol_view(d: &mut RaylibDrawHandle, tex: &crate::textures::TextureManager) {
+/// Draw the current weapon at bottom center of screen (MÁXIMO OPTIMIZADO)
+pub fn draw_weapon_view(d: &mut RaylibDrawHandle, weapons: &Weapons, tex: &crate::textures::TextureManager) {
     let screen_w = d.get_screen_width();
     let screen_h = d.get_screen_height();
     
-    let pistol_key = "pistol_view";
-    let (tex_w, tex_h) = tex.size_of(pistol_key);
+    let weapon_key = weapons.stats().sprite;
+    let (tex_w, tex_h) = tex.size_of(weapon_key);
     
-    // Pistola más pequeña: 20% del ancho
-    let pistol_display_width = (screen_w as f32 * 0.2) as i32;
-    let pistol_display_height = (pistol_display_width as f32 * (tex_h as f32 / tex_w as f32)) as i32;
+    // Arma más pequeña: 20% del ancho
+    let weapon_display_width = (screen_w as f32 * 0.2) as i32;
+    let weapon_display_height = (weapon_display_width as f32 * (tex_h as f32 / tex_w as f32)) as i32;
     
-    let pistol_x = (screen_w - pistol_display_width) / 2;
-    let pistol_y = screen_h - pistol_display_height;
+    // Bajar el arma al cambiarla, recargar o disparar
+    let drop = (weapons.view_drop() * weapon_display_height as f32) as i32;
+    let weapon_x = (screen_w - weapon_display_width) / 2;
+    let weapon_y = screen_h - weapon_display_height + drop;
     
-    let scale_x = tex_w as f32 / pistol_display_width as f32;
-    let scale_y = tex_h as f32 / pistol_display_height as f32;
+    let scale_x = tex_w as f32 / weapon_display_width as f32;
+    let scale_y = tex_h as f32 / weapon_display_height as f32;
     
     // MÁXIMA OPTIMIZACIÓN: Dibujar cada 4 pixeles con bloques 4x4
-    for dy in (0..pistol_display_height).step_by(4) {
-        for dx in (0..pistol_display_width).step_by(4) {
+    for dy in (0..weapon_display_height - drop).step_by(4) {
+        for dx in (0..weapon_display_width).step_by(4) {
             let tx = (dx as f32 * scale_x) as usize;
             let ty = (dy as f32 * scale_y) as usize;
             
-            let color = tex.sample_at(pistol_key, tx, ty);
+            let color = tex.sample_at(weapon_key, tx, ty);
             if color.a < 10 { continue; }
             
-            d.draw_rectangle(pistol_x + dx, pistol_y + dy, 4, 4, color);
+            d.draw_rectangle(weapon_x + dx, weapon_y + dy, 4, 4, color);
         }
     }
 }
 
+/// Draw weapon name and ammo at the bottom right corner
+pub fn draw_ammo_hud(d: &mut RaylibDrawHandle, weapons: &Weapons) {
+    let screen_w = d.get_screen_width();
+    let screen_h = d.get_screen_height();
+    let x = screen_w - 220;
+    let y = screen_h - 80;
+    
+    let ammo = weapons.current_ammo();
+    d.draw_text(weapons.stats().name, x, y, 20, Color::LIGHTGRAY);
+    let ammo_color = if ammo.loaded == 0 { Color::RED } else { Color::WHITE };
+    d.draw_text(&format!("{} / {}", ammo.loaded, ammo.reserve), x, y + 24, 30, ammo_color);
+    if weapons.reload > 0 {
+        d.draw_text("RECARGANDO", x, y - 24, 20, Color::ORANGE);
+    }
+}
+
+/// Draw pickup flash: a short full-screen tint in the color of the item just taken
+pub fn draw_pickup_flash(d: &mut RaylibDrawHandle, color: Color, intensity: f32) {
+    let screen_w = d.get_screen_width();
+    let screen_h = d.get_screen_height();
+    let alpha = (intensity.clamp(0.0, 1.0) * 60.0) as u8;
+    d.draw_rectangle(0, 0, screen_w, screen_h, Color::new(color.r, color.g, color.b, alpha));
+}
+
 /// Draw shot effect - SIMPLIFICADO AL MÁXIMO
 pub fn draw_shot_effect(d: &mut RaylibDrawHandle, _tex: &crate::textures::TextureManager, intensity: f32) {
     let screen_w = d.get_screen_width();
@@ -244,8 +295,8 @@ pub fn draw_shot_effect(d: &mut RaylibDrawHandle, _tex: &crate::textures::Textur
     d.draw_rectangle(0, 0, screen_w, screen_h, Color::new(255, 255, 200, alpha));
 }
 
-/// Draw complete game HUD (health + crosshair + damage overlay + pistol + shot effect)
-pub fn draw_game_hud(d: &mut RaylibDrawHandle, player: &crate::player::Player, tex: &crate::textures::TextureManager) {
+/// Draw complete game HUD (health + ammo + crosshair + damage overlay + weapon + shot effect)
+pub fn draw_game_hud(d: &mut RaylibDrawHandle, player: &crate::player::Player, weapons: &Weapons, tex: &crate::textures::TextureManager) {
     draw_damage_overlay(d, player);
     
     // Draw shot effect if active
@@ -254,7 +305,31 @@ pub fn draw_game_hud(d: &mut RaylibDrawHandle, player: &crate::player::Player, t
         draw_shot_effect(d, tex, intensity);
     }
     
-    draw_pistol_view(d, tex);
+    draw_weapon_view(d, weapons, tex);
     draw_health_hud(d, player);
+    draw_ammo_hud(d, weapons);
     draw_crosshair(d);
 }
+
+/// Draw the save slots centered from `y`: the summary of each one, "Empty", or the
+/// reason it can't be loaded
+pub fn draw_slot_list(d: &mut RaylibDrawHandle, title: &str, slots: &[Option<Result<String, String>>], selected: usize, y: i32) {
+    let screen_w = d.get_screen_width();
+    let title_width = d.measure_text(title, 30);
+    d.draw_text(title, screen_w / 2 - title_width / 2, y, 30, Color::WHITE);
+    
+    for (i, slot) in slots.iter().enumerate() {
+        let row_y = y + 50 + i as i32 * 40;
+        let (text, color) = match slot {
+            None => (format!("Slot {}: Empty", i + 1), Color::GRAY),
+            Some(Ok(summary)) => (format!("Slot {}: {}", i + 1, summary), Color::WHITE),
+            Some(Err(_)) => (format!("Slot {}: (no se puede cargar)", i + 1), Color::RED),
+        };
+        let color = if i == selected { Color::YELLOW } else { color };
+        let text_width = d.measure_text(&text, 24);
+        if i == selected {
+            d.draw_text(">", screen_w / 2 - text_width / 2 - 30, row_y, 24, Color::YELLOW);
+        }
+        d.draw_text(&text, screen_w / 2 - text_width / 2, row_y, 24, color);
+    }
+}
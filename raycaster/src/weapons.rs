@@ -0,0 +1,313 @@
+//! The player's weapons: stats, ammo, fire rate, reload and switching, and how shots
+//! are resolved.
+//!
+//! Hitscan shots (pistol, shotgun pellets, machine gun) look up the wall distance of
+//! their screen column in the z-buffer, so an enemy only takes the hit if it stands in
+//! front of the wall the ray reached. Plasma bolts are projectiles that fly through the
+//! maze until they hit a wall or an enemy.
+
+use rand::Rng;
+use raylib::prelude::Vector2;
+use crate::{
+    maze::{Maze, block_size, is_wall},
+    player::Player,
+    sprites::EnemySystem,
+};
+
+const SWITCH_TIME: i32 = 20;   // frames para bajar un arma y subir la otra
+const ENEMY_HIT_RADIUS: f32 = 20.0; // ancho del cuerpo de un enemigo de escala 1, en pixels
+const BOLT_RADIUS: f32 = 6.0;  // tamaño del proyectil de plasma
+const RECOIL_TIME: i32 = 6;    // frames que el sprite tarda en volver tras disparar
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WeaponKind {
+    Pistol,
+    Shotgun,
+    MachineGun,
+    Plasma,
+}
+
+/// Fixed stats of a weapon. Times are in frames, distances in pixels.
+pub struct WeaponStats {
+    pub name: &'static str,
+    pub sprite: &'static str,
+    pub damage: i32,      // por bala o perdigón
+    pub pellets: i32,     // balas por disparo
+    pub spread: f32,      // desviación máxima en radianes
+    pub fire_delay: i32,
+    pub magazine: i32,
+    pub max_reserve: i32, // munición de reserva que se puede llevar
+    pub reload_time: i32,
+    pub range: f32,
+    pub automatic: bool,  // dispara mientras se mantiene el gatillo
+    pub projectile_speed: Option<f32>, // `None` = hitscan
+}
+
+impl WeaponKind {
+    pub const ALL: [WeaponKind; 4] = [WeaponKind::Pistol, WeaponKind::Shotgun, WeaponKind::MachineGun, WeaponKind::Plasma];
+
+    pub fn stats(self) -> &'static WeaponStats {
+        match self {
+            Self::Pistol => &WeaponStats {
+                name: "Pistola", sprite: "pistol_view", damage: 34, pellets: 1, spread: 0.01,
+                fire_delay: 18, magazine: 12, max_reserve: 96, reload_time: 60, range: 900.0, automatic: false, projectile_speed: None,
+            },
+            Self::Shotgun => &WeaponStats {
+                name: "Escopeta", sprite: "shotgun_view", damage: 14, pellets: 8, spread: 0.12,
+                fire_delay: 50, magazine: 6, max_reserve: 36, reload_time: 90, range: 500.0, automatic: false, projectile_speed: None,
+            },
+            Self::MachineGun => &WeaponStats {
+                name: "Ametralladora", sprite: "machinegun_view", damage: 18, pellets: 1, spread: 0.04,
+                fire_delay: 6, magazine: 40, max_reserve: 200, reload_time: 100, range: 800.0, automatic: true, projectile_speed: None,
+            },
+            Self::Plasma => &WeaponStats {
+                name: "Plasma", sprite: "plasma_view", damage: 60, pellets: 1, spread: 0.0,
+                fire_delay: 24, magazine: 20, max_reserve: 60, reload_time: 80, range: 1200.0, automatic: true, projectile_speed: Some(9.0),
+            },
+        }
+    }
+
+    /// Name used in saved games.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Pistol => "pistol",
+            Self::Shotgun => "shotgun",
+            Self::MachineGun => "machinegun",
+            Self::Plasma => "plasma",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.name() == name)
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Ammo of one weapon: loaded in the magazine and spare.
+#[derive(Clone, Copy, Debug)]
+pub struct Ammo {
+    pub loaded: i32,
+    pub reserve: i32,
+}
+
+/// A plasma bolt in flight.
+#[derive(Clone, Copy, Debug)]
+pub struct Projectile {
+    pub pos: Vector2,
+    origin: Vector2, // desde dónde se disparó
+    vel: Vector2,
+    damage: i32,
+    travelled: f32,
+    range: f32,
+}
+
+/// What happened when the trigger was pulled.
+pub enum FireResult {
+    Nothing,
+    Fired { hits: i32 },
+    Empty, // sin balas en el cargador ni de reserva
+}
+
+pub struct Weapons {
+    pub current: WeaponKind,
+    pub ammo: [Ammo; 4], // por `WeaponKind::index`
+    pub projectiles: Vec<Projectile>,
+    cooldown: i32,       // frames hasta poder disparar otra vez
+    pub reload: i32,     // frames de recarga restantes
+    pub switch: i32,     // frames restantes del cambio de arma
+    pub recoil: i32,     // frames de retroceso del sprite
+}
+
+impl Weapons {
+    pub fn new() -> Self {
+        Self {
+            current: WeaponKind::Pistol,
+            ammo: [
+                Ammo { loaded: 12, reserve: 48 },
+                Ammo { loaded: 6, reserve: 18 },
+                Ammo { loaded: 40, reserve: 80 },
+                Ammo { loaded: 20, reserve: 20 },
+            ],
+            projectiles: Vec::new(),
+            cooldown: 0,
+            reload: 0,
+            switch: 0,
+            recoil: 0,
+        }
+    }
+
+    pub fn stats(&self) -> &'static WeaponStats {
+        self.current.stats()
+    }
+
+    pub fn current_ammo(&self) -> Ammo {
+        self.ammo[self.current.index()]
+    }
+
+    /// How far the first-person sprite is lowered, as a fraction of its height: the new
+    /// weapon rises after a switch, dips while reloading and kicks after each shot.
+    pub fn view_drop(&self) -> f32 {
+        let switch = self.switch as f32 / SWITCH_TIME as f32;
+        let reload = if self.reload > 0 {
+            let progress = 1.0 - self.reload as f32 / self.stats().reload_time as f32;
+            (progress * std::f32::consts::PI).sin() * 0.35
+        } else {
+            0.0
+        };
+        let recoil = self.recoil as f32 / RECOIL_TIME as f32 * 0.08;
+        switch.max(reload) + recoil
+    }
+
+    /// Switch weapons; cancels a reload in progress.
+    pub fn select(&mut self, kind: WeaponKind) {
+        if kind == self.current { return; }
+        self.current = kind;
+        self.reload = 0;
+        self.switch = SWITCH_TIME;
+    }
+
+    /// Add spare ammo for `kind`, up to its `max_reserve`. Returns `false` if it was full.
+    pub fn add_ammo(&mut self, kind: WeaponKind, amount: i32) -> bool {
+        let ammo = &mut self.ammo[kind.index()];
+        if ammo.reserve >= kind.stats().max_reserve { return false; }
+        ammo.reserve = (ammo.reserve + amount).min(kind.stats().max_reserve);
+        true
+    }
+
+    /// Start reloading if the magazine isn't full and there is spare ammo.
+    pub fn start_reload(&mut self) {
+        let ammo = self.current_ammo();
+        if self.reload > 0 || self.switch > 0 || ammo.reserve == 0 || ammo.loaded == self.stats().magazine { return; }
+        self.reload = self.stats().reload_time;
+    }
+
+    /// Advance timers, finish reloads and move projectiles. Call once per frame.
+    pub fn update(&mut self, enemies: &mut EnemySystem, maze: &Maze) {
+        self.cooldown = (self.cooldown - 1).max(0);
+        self.switch = (self.switch - 1).max(0);
+        self.recoil = (self.recoil - 1).max(0);
+        if self.reload > 0 {
+            self.reload -= 1;
+            if self.reload == 0 {
+                let magazine = self.stats().magazine;
+                let ammo = &mut self.ammo[self.current.index()];
+                let moved = (magazine - ammo.loaded).min(ammo.reserve);
+                ammo.loaded += moved;
+                ammo.reserve -= moved;
+            }
+        }
+        self.projectiles.retain_mut(|bolt| bolt.step(enemies, maze));
+    }
+
+    /// Pull the trigger: `pressed` this frame, or `held` for automatic weapons. Hitscan
+    /// shots are resolved against `zbuffer`, the wall distance per screen column of the
+    /// frame the player is looking at.
+    pub fn fire(&mut self, pressed: bool, held: bool, player: &Player, enemies: &mut EnemySystem, zbuffer: &[f32]) -> FireResult {
+        let stats = self.stats();
+        if !(pressed || (held && stats.automatic)) { return FireResult::Nothing; }
+        if self.cooldown > 0 || self.reload > 0 || self.switch > 0 { return FireResult::Nothing; }
+
+        let ammo = &mut self.ammo[self.current.index()];
+        if ammo.loaded == 0 {
+            if ammo.reserve == 0 {
+                // Clic en vacío: solo avisar al pulsar, no cada frame con el gatillo apretado
+                return if pressed { FireResult::Empty } else { FireResult::Nothing };
+            }
+            self.start_reload();
+            return FireResult::Nothing;
+        }
+        ammo.loaded -= 1;
+        self.cooldown = stats.fire_delay;
+        self.recoil = RECOIL_TIME;
+
+        let mut rng = rand::thread_rng();
+        let mut hits = 0;
+        for _ in 0..stats.pellets {
+            let angle = player.a + if stats.spread > 0.0 { rng.gen_range(-stats.spread..=stats.spread) } else { 0.0 };
+            match stats.projectile_speed {
+                Some(speed) => self.projectiles.push(Projectile {
+                    pos: player.pos,
+                    origin: player.pos,
+                    vel: Vector2::new(angle.cos() * speed, angle.sin() * speed),
+                    damage: stats.damage,
+                    travelled: 0.0,
+                    range: stats.range,
+                }),
+                None => {
+                    let wall = wall_distance(player, angle, zbuffer).min(stats.range);
+                    if hitscan(enemies, player.pos, angle, wall, stats.damage) { hits += 1; }
+                }
+            }
+        }
+        // Recarga automática al vaciar el cargador
+        if self.current_ammo().loaded == 0 { self.start_reload(); }
+        FireResult::Fired { hits }
+    }
+}
+
+impl Default for Weapons {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Distance in pixels to the wall along `angle`, read from the z-buffer column the ray
+/// goes through (the z-buffer stores the DDA hit distance in cells).
+fn wall_distance(player: &Player, angle: f32, zbuffer: &[f32]) -> f32 {
+    let mut diff = angle - player.a;
+    while diff > std::f32::consts::PI { diff -= 2.0 * std::f32::consts::PI; }
+    while diff < -std::f32::consts::PI { diff += 2.0 * std::f32::consts::PI; }
+    let half_w = zbuffer.len() as f32 * 0.5;
+    let column = ((diff / (player.fov * 0.5)) * half_w + half_w).floor();
+    if column < 0.0 || column as usize >= zbuffer.len() { return f32::INFINITY; }
+    zbuffer[column as usize] * block_size() as f32
+}
+
+/// Damage the nearest enemy the ray from `origin` along `angle` passes through before
+/// `max_dist`. Returns whether something was hit.
+fn hitscan(enemies: &mut EnemySystem, origin: Vector2, angle: f32, max_dist: f32, damage: i32) -> bool {
+    let (dir_x, dir_y) = (angle.cos(), angle.sin());
+    let mut nearest: Option<(usize, f32)> = None;
+    for (i, enemy) in enemies.list.iter().enumerate() {
+        if !enemy.alive { continue; }
+        let (dx, dy) = (enemy.pos.x - origin.x, enemy.pos.y - origin.y);
+        let along = dx * dir_x + dy * dir_y;
+        if along <= 0.0 || along > max_dist { continue; }
+        // Distancia del centro del enemigo a la línea del disparo
+        let across = (dx * dir_y - dy * dir_x).abs();
+        if across > ENEMY_HIT_RADIUS * enemies.archetypes.get(enemy.kind).scale { continue; }
+        if nearest.is_none_or(|(_, d)| along < d) { nearest = Some((i, along)); }
+    }
+    match nearest {
+        Some((i, _)) => { enemies.list[i].take_damage(damage, origin); true }
+        None => false,
+    }
+}
+
+impl Projectile {
+    /// Move one frame in small steps; returns `false` once the bolt hits something or
+    /// runs out of range.
+    fn step(&mut self, enemies: &mut EnemySystem, maze: &Maze) -> bool {
+        let b = block_size() as f32;
+        let speed = (self.vel.x * self.vel.x + self.vel.y * self.vel.y).sqrt();
+        let substeps = (speed / 4.0).ceil().max(1.0) as i32;
+        for _ in 0..substeps {
+            self.pos.x += self.vel.x / substeps as f32;
+            self.pos.y += self.vel.y / substeps as f32;
+            self.travelled += speed / substeps as f32;
+            if is_wall(maze, (self.pos.x / b).floor() as isize, (self.pos.y / b).floor() as isize) { return false; }
+            let scale = |kind| enemies.archetypes.get(kind).scale;
+            let target = enemies.list.iter().position(|e| {
+                e.alive && (e.pos.x - self.pos.x).hypot(e.pos.y - self.pos.y) < ENEMY_HIT_RADIUS * scale(e.kind) + BOLT_RADIUS
+            });
+            if let Some(i) = target {
+                enemies.list[i].take_damage(self.damage, self.origin);
+                return false;
+            }
+        }
+        self.travelled < self.range
+    }
+}
@@ -0,0 +1,181 @@
+//! Vistas de depuración del render (AOVs): normales, profundidad, albedo, ID de
+//! material, máscara de sombra, rebotes alcanzados y mapa de calor de costo.
+//!
+//! `trace` llena un `RayStats` por píxel; aquí se traduce a un color según la vista.
+
+use std::io;
+use std::path::Path;
+
+use crate::materials::MaterialParams;
+use crate::math::{Vec3, hash3};
+
+/// Canal de salida de `render`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum RenderView {
+    Beauty,
+    Normals,
+    Depth,
+    Albedo,
+    MaterialId,
+    ShadowMask,
+    ReflectionDepth,
+    /// Pruebas de intersección por píxel (escala logarítmica relativa al máximo del cuadro).
+    Cost,
+}
+
+impl RenderView {
+    pub const ALL: [RenderView; 8] = [
+        RenderView::Beauty,
+        RenderView::Normals,
+        RenderView::Depth,
+        RenderView::Albedo,
+        RenderView::MaterialId,
+        RenderView::ShadowMask,
+        RenderView::ReflectionDepth,
+        RenderView::Cost,
+    ];
+
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|v| *v == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RenderView::Beauty => "final",
+            RenderView::Normals => "normales",
+            RenderView::Depth => "profundidad",
+            RenderView::Albedo => "albedo",
+            RenderView::MaterialId => "material",
+            RenderView::ShadowMask => "sombras",
+            RenderView::ReflectionDepth => "rebotes",
+            RenderView::Cost => "costo",
+        }
+    }
+}
+
+/// Datos del primer impacto del rayo de cámara.
+#[derive(Copy, Clone, Debug)]
+pub struct PrimaryHit {
+    pub t: f32,
+    pub normal: Vec3, // Normal de sombreado
+    pub albedo: Vec3,
+    pub material_id: u32,
+    pub shadow: Shadow,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Shadow {
+    Lit,
+    Occluded,
+    /// La cara no mira al sol (o es de noche): no se lanzó rayo de sombra.
+    Facing,
+}
+
+/// Contadores acumulados mientras se traza un píxel.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct RayStats {
+    pub rays: u32,       // Rayos lanzados (cámara, sombra, AO, reflejo, refracción)
+    pub tests: u32,      // Pruebas de intersección: cajas del BVH y primitivas
+    pub max_bounce: u32, // Rebote más profundo que llegó a trazarse
+    pub primary: Option<PrimaryHit>,
+}
+
+/// Resumen de un cuadro, útil para el HUD y para normalizar el mapa de calor.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct FrameStats {
+    pub rays: u64,
+    pub tests: u64,
+    pub max_tests: u32,
+}
+
+impl FrameStats {
+    pub fn add(&mut self, px: &RayStats) {
+        self.rays += px.rays as u64;
+        self.tests += px.tests as u64;
+        self.max_tests = self.max_tests.max(px.tests);
+    }
+
+    pub fn merge(&mut self, other: &FrameStats) {
+        self.rays += other.rays;
+        self.tests += other.tests;
+        self.max_tests = self.max_tests.max(other.max_tests);
+    }
+}
+
+/// Distancia que corresponde al negro en la vista de profundidad.
+const DEPTH_FAR: f32 = 60.0;
+
+/// Identificador estable de un material a partir de sus parámetros constantes
+/// (el albedo varía por texel, así que no participa).
+pub fn material_id(mat: &MaterialParams) -> u32 {
+    let e = mat.emissive;
+    let a = hash3(mat.specular_strength.to_bits(), mat.shininess.to_bits(), mat.reflectivity.to_bits());
+    let b = hash3(mat.transparency.to_bits(), mat.ior.to_bits(), e.x.to_bits() ^ e.y.to_bits().rotate_left(11));
+    hash3(a, b, e.z.to_bits())
+}
+
+/// Color de un píxel en la vista `view`. El costo se colorea después (ver `cost_color`).
+pub fn view_color(view: RenderView, stats: &RayStats, max_depth: i32) -> Vec3 {
+    let black = Vec3::new(0.0, 0.0, 0.0);
+    match view {
+        RenderView::ReflectionDepth => {
+            if stats.primary.is_none() {
+                return black;
+            }
+            heat(stats.max_bounce as f32 / max_depth.max(1) as f32)
+        }
+        _ => {
+            let Some(hit) = stats.primary else {
+                return black;
+            };
+            match view {
+                RenderView::Normals => hit.normal.mul(0.5).add(Vec3::new(0.5, 0.5, 0.5)),
+                RenderView::Depth => {
+                    let d = 1.0 - (hit.t / DEPTH_FAR).clamp(0.0, 1.0);
+                    Vec3::new(d, d, d)
+                }
+                RenderView::Albedo => hit.albedo,
+                RenderView::MaterialId => {
+                    let id = hit.material_id;
+                    let c = |shift: u32| 0.2 + ((id >> shift) & 0xFF) as f32 / 255.0 * 0.8;
+                    Vec3::new(c(0), c(8), c(16))
+                }
+                RenderView::ShadowMask => match hit.shadow {
+                    Shadow::Lit => Vec3::new(1.0, 1.0, 1.0),
+                    Shadow::Occluded => black,
+                    Shadow::Facing => Vec3::new(0.1, 0.1, 0.3),
+                },
+                _ => black,
+            }
+        }
+    }
+}
+
+/// Mapa de calor logarítmico del número de pruebas relativo al máximo del cuadro.
+pub fn cost_color(tests: u32, max_tests: u32) -> Vec3 {
+    let t = (1.0 + tests as f32).ln() / (1.0 + max_tests.max(1) as f32).ln();
+    heat(t)
+}
+
+/// Rampa negro → azul → verde → amarillo → rojo para t en [0, 1].
+pub fn heat(t: f32) -> Vec3 {
+    const STOPS: [(f32, f32, f32); 5] = [
+        (0.0, 0.0, 0.0),
+        (0.1, 0.2, 0.9),
+        (0.1, 0.8, 0.3),
+        (1.0, 0.9, 0.1),
+        (1.0, 0.1, 0.05),
+    ];
+    let x = t.clamp(0.0, 1.0) * (STOPS.len() - 1) as f32;
+    let i = (x.floor() as usize).min(STOPS.len() - 2);
+    let f = x - i as f32;
+    let (a, b) = (STOPS[i], STOPS[i + 1]);
+    Vec3::new(a.0 + (b.0 - a.0) * f, a.1 + (b.1 - a.1) * f, a.2 + (b.2 - a.2) * f)
+}
+
+/// Guarda un framebuffer RGBA como PNG (exportación sin ventana).
+pub fn save_png(path: &Path, frame: &[u8], w: u32, h: u32) -> io::Result<()> {
+    image::save_buffer(path, frame, w, h, image::ColorType::Rgba8)
+        .map_err(|e| io::Error::other(e.to_string()))
+}
@@ -0,0 +1,234 @@
+//! Cajas envolventes alineadas a los ejes y jerarquía de volúmenes (BVH).
+//!
+//! El BVH solo guarda índices: quien lo construye decide qué primitiva hay detrás
+//! de cada índice (ver `instance::Group`).
+
+use crate::math::Vec3;
+use crate::ray::Ray;
+
+/// Caja envolvente alineada a los ejes.
+#[derive(Copy, Clone, Debug)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    /// Caja vacía: neutra para `union`.
+    pub const EMPTY: Aabb = Aabb {
+        min: Vec3 { x: f32::INFINITY, y: f32::INFINITY, z: f32::INFINITY },
+        max: Vec3 { x: f32::NEG_INFINITY, y: f32::NEG_INFINITY, z: f32::NEG_INFINITY },
+    };
+
+    /// Caja infinita para primitivas sin límites (planos).
+    pub const EVERYTHING: Aabb = Aabb {
+        min: Vec3 { x: f32::NEG_INFINITY, y: f32::NEG_INFINITY, z: f32::NEG_INFINITY },
+        max: Vec3 { x: f32::INFINITY, y: f32::INFINITY, z: f32::INFINITY },
+    };
+
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: Vec3::new(self.min.x.min(other.min.x), self.min.y.min(other.min.y), self.min.z.min(other.min.z)),
+            max: Vec3::new(self.max.x.max(other.max.x), self.max.y.max(other.max.y), self.max.z.max(other.max.z)),
+        }
+    }
+
+    pub fn grow(&self, p: Vec3) -> Aabb {
+        self.union(&Aabb { min: p, max: p })
+    }
+
+    /// Centro de la caja; los ejes infinitos se centran en 0 para poder ordenar.
+    pub fn centroid(&self) -> Vec3 {
+        let mid = |a: f32, b: f32| {
+            let c = (a + b) * 0.5;
+            if c.is_finite() { c } else { 0.0 }
+        };
+        Vec3::new(mid(self.min.x, self.max.x), mid(self.min.y, self.max.y), mid(self.min.z, self.max.z))
+    }
+
+    pub fn corners(&self) -> [Vec3; 8] {
+        let (a, b) = (self.min, self.max);
+        [
+            Vec3::new(a.x, a.y, a.z),
+            Vec3::new(b.x, a.y, a.z),
+            Vec3::new(a.x, b.y, a.z),
+            Vec3::new(b.x, b.y, a.z),
+            Vec3::new(a.x, a.y, b.z),
+            Vec3::new(b.x, a.y, b.z),
+            Vec3::new(a.x, b.y, b.z),
+            Vec3::new(b.x, b.y, b.z),
+        ]
+    }
+
+    /// Distancia de entrada del rayo en [0, `t_max`], o `None` si no la cruza.
+    /// `inv_dir` es `1 / ray.dir` por componente (calculado una vez por rayo).
+    pub fn hit(&self, ray: &Ray, inv_dir: Vec3, t_max: f32) -> Option<f32> {
+        let slab = |min: f32, max: f32, o: f32, inv: f32| {
+            let (t0, t1) = ((min - o) * inv, (max - o) * inv);
+            if t0 < t1 { (t0, t1) } else { (t1, t0) }
+        };
+        let (x0, x1) = slab(self.min.x, self.max.x, ray.orig.x, inv_dir.x);
+        let (y0, y1) = slab(self.min.y, self.max.y, ray.orig.y, inv_dir.y);
+        let (z0, z1) = slab(self.min.z, self.max.z, ray.orig.z, inv_dir.z);
+        // `max`/`min` ignoran los NaN de 0 * inf (rayo paralelo sobre un plano de la caja)
+        let enter = x0.max(y0).max(z0).max(0.0);
+        let exit = x1.min(y1).min(z1).min(t_max);
+        if enter <= exit { Some(enter) } else { None }
+    }
+}
+
+/// Inverso por componente de la dirección del rayo, para `Aabb::hit`.
+pub fn inv_dir(ray: &Ray) -> Vec3 {
+    Vec3::new(1.0 / ray.dir.x, 1.0 / ray.dir.y, 1.0 / ray.dir.z)
+}
+
+/// Primitivas por hoja antes de dejar de partir.
+const LEAF_SIZE: usize = 4;
+
+#[derive(Copy, Clone, Debug)]
+struct Node {
+    bounds: Aabb,
+    /// Hoja: primer índice en `order`. Interno: índice del hijo derecho (el izquierdo es el siguiente).
+    start: u32,
+    /// Primitivas de la hoja; 0 en los nodos internos.
+    count: u32,
+}
+
+/// BVH plano en orden de profundidad, partido por la mediana del eje más largo.
+#[derive(Clone, Debug, Default)]
+pub struct Bvh {
+    nodes: Vec<Node>,
+    order: Vec<u32>,
+}
+
+impl Bvh {
+    /// Construye el árbol sobre las cajas de las primitivas `0..bounds.len()`.
+    pub fn build(bounds: &[Aabb]) -> Self {
+        let mut bvh = Bvh {
+            nodes: Vec::with_capacity(bounds.len().max(1) * 2),
+            order: (0..bounds.len() as u32).collect(),
+        };
+        if !bounds.is_empty() {
+            let centroids: Vec<Vec3> = bounds.iter().map(Aabb::centroid).collect();
+            bvh.split(bounds, &centroids, 0, bounds.len());
+        }
+        bvh
+    }
+
+    fn split(&mut self, bounds: &[Aabb], centroids: &[Vec3], start: usize, end: usize) -> usize {
+        let node = self.nodes.len();
+        let mut bb = Aabb::EMPTY;
+        let mut cb = Aabb::EMPTY;
+        for &i in &self.order[start..end] {
+            bb = bb.union(&bounds[i as usize]);
+            cb = cb.grow(centroids[i as usize]);
+        }
+        self.nodes.push(Node { bounds: bb, start: start as u32, count: (end - start) as u32 });
+        if end - start <= LEAF_SIZE {
+            return node;
+        }
+
+        let ext = cb.max.sub(cb.min);
+        let axis = if ext.x >= ext.y && ext.x >= ext.z {
+            0
+        } else if ext.y >= ext.z {
+            1
+        } else {
+            2
+        };
+        let key = |i: &u32| {
+            let c = centroids[*i as usize];
+            [c.x, c.y, c.z][axis]
+        };
+        let mid = (start + end) / 2;
+        self.order[start..end].select_nth_unstable_by(mid - start, |a, b| key(a).total_cmp(&key(b)));
+
+        self.split(bounds, centroids, start, mid);
+        let right = self.split(bounds, centroids, mid, end);
+        self.nodes[node].start = right as u32;
+        self.nodes[node].count = 0;
+        node
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        self.nodes.first().map_or(Aabb::EMPTY, |n| n.bounds)
+    }
+
+    /// Recorre el árbol llamando a `test(i, t_max)` con cada primitiva cuya caja cruza el rayo.
+    /// `test` devuelve la distancia del impacto (si la hay) y el recorrido se acota con
+    /// el más cercano; con `any` se detiene en el primero. `tests` suma cajas y primitivas probadas.
+    pub fn traverse(
+        &self,
+        ray: &Ray,
+        t_max: f32,
+        any: bool,
+        tests: &mut u32,
+        mut test: impl FnMut(usize, f32) -> Option<f32>,
+    ) -> Option<f32> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+        let inv = inv_dir(ray);
+        let mut best = t_max;
+        let mut found = None;
+        // Pila de (nodo, distancia de entrada); la mediana deja el árbol balanceado
+        let mut stack = [(0u32, 0.0f32); 64];
+        let mut sp = 0;
+        *tests += 1;
+        if let Some(t) = self.nodes[0].bounds.hit(ray, inv, best) {
+            stack[0] = (0, t);
+            sp = 1;
+        }
+        while sp > 0 {
+            sp -= 1;
+            let (idx, t_enter) = stack[sp];
+            if t_enter > best {
+                continue;
+            }
+            let node = self.nodes[idx as usize];
+            if node.count > 0 {
+                let first = node.start as usize;
+                for &i in &self.order[first..first + node.count as usize] {
+                    *tests += 1;
+                    if let Some(t) = test(i as usize, best) {
+                        if t < best {
+                            best = t;
+                            found = Some(t);
+                            if any {
+                                return found;
+                            }
+                        }
+                    }
+                }
+                continue;
+            }
+            let (left, right) = (idx as usize + 1, node.start as usize);
+            *tests += 2;
+            let hl = self.nodes[left].bounds.hit(ray, inv, best);
+            let hr = self.nodes[right].bounds.hit(ray, inv, best);
+            // El hijo más cercano se apila último para visitarlo primero
+            let mut push = |n: usize, t: f32| {
+                stack[sp] = (n as u32, t);
+                sp += 1;
+            };
+            match (hl, hr) {
+                (Some(tl), Some(tr)) if tl <= tr => {
+                    push(right, tr);
+                    push(left, tl);
+                }
+                (Some(tl), Some(tr)) => {
+                    push(left, tl);
+                    push(right, tr);
+                }
+                (Some(tl), None) => push(left, tl),
+                (None, Some(tr)) => push(right, tr),
+                (None, None) => {}
+            }
+        }
+        found
+    }
+}
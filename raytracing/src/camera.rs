@@ -33,6 +33,7 @@ impl Camera {
 }
 
 /// Controlador de cámara orbital simple.
+#[derive(Copy, Clone, Debug)]
 pub struct OrbitCamera {
     pub yaw: f32,
     pub pitch: f32,
@@ -0,0 +1,606 @@
+//! Render distribuido por teselas entre procesos trabajadores conectados por TCP.
+//!
+//! El coordinador parte el cuadro en teselas y manda a cada trabajador una descripción
+//! de la escena (`Job`): mundo, cámara, sol y calidad. Cada trabajador carga sus propias
+//! texturas, construye la escena una vez y devuelve los píxeles de cada tesela pedida.
+//! Si un trabajador se cae, sus teselas vuelven a la cola y se reintenta la conexión.
+//!
+//! Protocolo (líneas de texto terminadas en `\n`, salvo los píxeles):
+//!
+//! ```text
+//! T → C  DIORAMA/1
+//! C → T  TRABAJO <clave=valor ...>      T → C  LISTO | ERROR <motivo>
+//! C → T  TESELA x y w h                 T → C  TESELA x y w h rayos pruebas máx + w*h*4 bytes RGBA
+//! C → T  FIN
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::sync::{Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::aov::{FrameStats, RenderView};
+use crate::camera::{Camera, OrbitCamera};
+use crate::math::Vec3;
+use crate::occlusion::AoMode;
+use crate::raytracer::{self, Assets, RenderSettings, SceneData, Tile, WorldKind};
+use crate::schematic::{RegionWindow, Schematic};
+use crate::terrain::TerrainConfig;
+
+/// Saludo del trabajador; cambia si cambia el protocolo.
+const PROTOCOL: &str = "DIORAMA/1";
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Tiempo máximo de respuesta: cargar texturas o una tesela grande en una máquina lenta.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(300);
+/// Conexiones fallidas seguidas antes de descartar un trabajador.
+const MAX_ATTEMPTS: u32 = 3;
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Mundo a renderizar; cada trabajador lo reconstruye con sus propias texturas.
+#[derive(Clone, Debug)]
+pub enum WorldSpec {
+    Overworld,
+    Nether,
+    Procedural { seed: u64, size: i32 },
+    /// La ruta debe existir en cada trabajador (misma carpeta compartida o copia).
+    Imported { path: PathBuf, window: RegionWindow },
+}
+
+impl WorldSpec {
+    pub fn build<'a>(&self, assets: &Assets<'a>) -> io::Result<SceneData<'a>> {
+        Ok(match self {
+            WorldSpec::Overworld => raytracer::build_scene(assets, WorldKind::Overworld),
+            WorldSpec::Nether => raytracer::build_scene(assets, WorldKind::Nether),
+            WorldSpec::Procedural { seed, size } => {
+                raytracer::build_terrain_scene(assets, &TerrainConfig::with_seed(*seed, *size))
+            }
+            WorldSpec::Imported { path, window } => {
+                raytracer::build_schematic_scene(assets, &Schematic::load(path, window)?)
+            }
+        })
+    }
+}
+
+/// Todo lo que un trabajador necesita para renderizar cualquier tesela del cuadro.
+#[derive(Clone, Debug)]
+pub struct Job {
+    pub world: WorldSpec,
+    pub orbit: OrbitCamera,
+    pub fov: f32,
+    pub sun_angle: f32,
+    pub width: u32,
+    pub height: u32,
+    pub settings: RenderSettings,
+}
+
+impl Job {
+    pub fn camera(&self) -> Camera {
+        self.orbit.to_camera(self.fov)
+    }
+
+    /// Descripción en una línea `clave=valor` separada por espacios.
+    pub fn to_line(&self) -> String {
+        let o = &self.orbit;
+        let mut line = match &self.world {
+            WorldSpec::Overworld => "mundo=overworld".to_string(),
+            WorldSpec::Nether => "mundo=nether".to_string(),
+            WorldSpec::Procedural { seed, size } => format!("mundo=procedural semilla={} tam={}", seed, size),
+            WorldSpec::Imported { path, window } => format!(
+                "mundo=importado archivo={} chunks={},{},{},{} y={},{}",
+                escape(&path.to_string_lossy()),
+                window.chunk_min.0, window.chunk_min.1, window.chunk_max.0, window.chunk_max.1,
+                window.y_min, window.y_max
+            ),
+        };
+        let s = &self.settings;
+        line.push_str(&format!(
+            " yaw={} pitch={} radio={} objetivo={},{},{} fov={} sol={} ancho={} alto={} rebotes={} ao={} muestras_ao={} brillo={} vista={} espectral={} longitudes={} fotones={}",
+            o.yaw, o.pitch, o.radius, o.target.x, o.target.y, o.target.z,
+            self.fov, self.sun_angle, self.width, self.height,
+            s.max_depth, ao_name(s.ao.mode), s.ao.samples, s.gloss_samples, s.view.label(),
+            s.spectral.enabled as u8, s.spectral.samples, s.spectral.caustic_photons
+        ));
+        line
+    }
+
+    pub fn parse(line: &str) -> Result<Job, String> {
+        let map: HashMap<&str, &str> = line.split_whitespace().filter_map(|kv| kv.split_once('=')).collect();
+        let get = |k: &str| map.get(k).copied().ok_or_else(|| format!("falta '{}'", k));
+        fn num<T: std::str::FromStr>(k: &str, v: &str) -> Result<T, String> {
+            v.parse().map_err(|_| format!("valor inválido para '{}': {}", k, v))
+        }
+        let field = |k: &str| get(k).and_then(|v| num::<f32>(k, v));
+        let list = |k: &str| -> Result<Vec<i32>, String> {
+            get(k)?.split(',').map(|n| num(k, n)).collect()
+        };
+
+        let world = match get("mundo")? {
+            "overworld" => WorldSpec::Overworld,
+            "nether" => WorldSpec::Nether,
+            "procedural" => WorldSpec::Procedural {
+                seed: num("semilla", get("semilla")?)?,
+                size: num("tam", get("tam")?)?,
+            },
+            "importado" => {
+                let (c, y) = (list("chunks")?, list("y")?);
+                let (&[x0, z0, x1, z1], &[y0, y1]) = (&c[..], &y[..]) else {
+                    return Err("'chunks' o 'y' con cantidad de valores incorrecta".into());
+                };
+                WorldSpec::Imported {
+                    path: PathBuf::from(unescape(get("archivo")?)),
+                    window: RegionWindow { chunk_min: (x0, z0), chunk_max: (x1, z1), y_min: y0, y_max: y1 },
+                }
+            }
+            other => return Err(format!("mundo desconocido: {}", other)),
+        };
+
+        let target: Vec<f32> = get("objetivo")?.split(',').map(|n| num("objetivo", n)).collect::<Result<_, _>>()?;
+        let [tx, ty, tz] = target[..] else {
+            return Err("'objetivo' necesita x,y,z".into());
+        };
+        let view = get("vista")?;
+        let mut settings = RenderSettings {
+            max_depth: num("rebotes", get("rebotes")?)?,
+            gloss_samples: num("brillo", get("brillo")?)?,
+            view: RenderView::ALL
+                .into_iter()
+                .find(|v| v.label() == view)
+                .ok_or_else(|| format!("vista desconocida: {}", view))?,
+            ..RenderSettings::default()
+        };
+        settings.ao.mode = match get("ao")? {
+            "off" => AoMode::Off,
+            "voxel" => AoMode::Voxel,
+            "rayos" => AoMode::RayTraced,
+            other => return Err(format!("modo de AO desconocido: {}", other)),
+        };
+        settings.ao.samples = num("muestras_ao", get("muestras_ao")?)?;
+        settings.spectral.enabled = match get("espectral")? {
+            "0" => false,
+            "1" => true,
+            other => return Err(format!("valor inválido para 'espectral': {}", other)),
+        };
+        settings.spectral.samples = num("longitudes", get("longitudes")?)?;
+        settings.spectral.caustic_photons = num("fotones", get("fotones")?)?;
+
+        let job = Job {
+            world,
+            orbit: OrbitCamera::new(field("yaw")?, field("pitch")?, field("radio")?, Vec3::new(tx, ty, tz)),
+            fov: field("fov")?,
+            sun_angle: field("sol")?,
+            width: num("ancho", get("ancho")?)?,
+            height: num("alto", get("alto")?)?,
+            settings,
+        };
+        if job.width == 0 || job.height == 0 {
+            return Err("tamaño de cuadro vacío".into());
+        }
+        Ok(job)
+    }
+
+    /// Clave de la escena: dos trabajos con el mismo mundo reutilizan la escena construida.
+    fn world_key(&self) -> String {
+        format!("{:?}", self.world)
+    }
+}
+
+fn ao_name(mode: AoMode) -> &'static str {
+    match mode {
+        AoMode::Off => "off",
+        AoMode::Voxel => "voxel",
+        AoMode::RayTraced => "rayos",
+    }
+}
+
+/// Escapa `%`, espacios y saltos de línea para que una ruta quepa en un solo token.
+fn escape(s: &str) -> String {
+    s.replace('%', "%25").replace(' ', "%20").replace('\n', "%0A")
+}
+
+fn unescape(s: &str) -> String {
+    s.replace("%0A", "\n").replace("%20", " ").replace("%25", "%")
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+// --- Trabajador ---
+
+/// Atiende coordinadores uno a uno para siempre. La última escena construida se conserva
+/// entre conexiones para no reconstruirla en cada reintento.
+pub fn serve<'a>(listener: TcpListener, assets: &Assets<'a>) -> io::Result<()> {
+    let mut cache: Option<(String, SceneData<'a>)> = None;
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(s) => s,
+            Err(e) => {
+                println!("  ✗ conexión rechazada: {}", e);
+                continue;
+            }
+        };
+        let peer = stream.peer_addr().map(|a| a.to_string()).unwrap_or_else(|_| "?".into());
+        println!("Coordinador conectado: {}", peer);
+        match handle(stream, assets, &mut cache) {
+            Ok(tiles) => println!("  {} teselas para {}", tiles, peer),
+            Err(e) => println!("  ✗ {}: {}", peer, e),
+        }
+    }
+    Ok(())
+}
+
+/// Una sesión con un coordinador; devuelve las teselas servidas.
+fn handle<'a>(
+    stream: TcpStream,
+    assets: &Assets<'a>,
+    cache: &mut Option<(String, SceneData<'a>)>,
+) -> io::Result<u32> {
+    stream.set_read_timeout(Some(REPLY_TIMEOUT))?;
+    stream.set_nodelay(true)?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut out = BufWriter::new(stream);
+    writeln!(out, "{}", PROTOCOL)?;
+    out.flush()?;
+
+    let mut job: Option<Job> = None;
+    let mut pixels = Vec::new();
+    let mut served = 0;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(served);
+        }
+        let line = line.trim_end();
+        let (cmd, rest) = line.split_once(' ').unwrap_or((line, ""));
+        match cmd {
+            "TRABAJO" => {
+                let parsed = Job::parse(rest).map_err(invalid).and_then(|j| {
+                    let key = j.world_key();
+                    if cache.as_ref().map(|(k, _)| k) != Some(&key) {
+                        *cache = Some((key, j.world.build(assets)?));
+                    }
+                    Ok(j)
+                });
+                match parsed {
+                    Ok(j) => {
+                        job = Some(j);
+                        writeln!(out, "LISTO")?;
+                    }
+                    Err(e) => writeln!(out, "ERROR {}", e)?,
+                }
+            }
+            "TESELA" => {
+                let (Some(j), Some((_, scene))) = (&job, cache.as_ref()) else {
+                    writeln!(out, "ERROR TESELA antes de TRABAJO")?;
+                    out.flush()?;
+                    continue;
+                };
+                let tile = match parse_tile(rest) {
+                    Some(t) if tile_fits(&t, j.width, j.height) => t,
+                    _ => {
+                        writeln!(out, "ERROR tesela inválida: {}", rest)?;
+                        out.flush()?;
+                        continue;
+                    }
+                };
+                pixels.resize(tile.w as usize * tile.h as usize * 4, 0);
+                let stats = raytracer::render_tile(
+                    &mut pixels,
+                    (j.width, j.height),
+                    tile,
+                    &j.camera(),
+                    j.sun_angle,
+                    scene,
+                    &j.settings,
+                );
+                writeln!(
+                    out,
+                    "TESELA {} {} {} {} {} {} {}",
+                    tile.x, tile.y, tile.w, tile.h, stats.rays, stats.tests, stats.max_tests
+                )?;
+                out.write_all(&pixels)?;
+                served += 1;
+            }
+            "FIN" => return Ok(served),
+            _ => writeln!(out, "ERROR comando desconocido: {}", cmd)?,
+        }
+        out.flush()?;
+    }
+}
+
+fn parse_tile(s: &str) -> Option<Tile> {
+    let mut it = s.split_whitespace().map(|n| n.parse::<u32>().ok());
+    Some(Tile { x: it.next()??, y: it.next()??, w: it.next()??, h: it.next()?? })
+}
+
+/// La tesela no está vacía y cabe en un cuadro de `width` x `height` (sin desbordar u32).
+fn tile_fits(t: &Tile, width: u32, height: u32) -> bool {
+    let inside = |start: u32, len: u32, max: u32| start.checked_add(len).is_some_and(|end| end <= max);
+    t.w > 0 && t.h > 0 && inside(t.x, t.w, width) && inside(t.y, t.h, height)
+}
+
+// --- Coordinador ---
+
+struct Queue {
+    pending: VecDeque<Tile>,
+    in_flight: usize,
+    done: usize,
+}
+
+/// Estado compartido entre los hilos que hablan con cada trabajador.
+struct Shared {
+    queue: Mutex<Queue>,
+    changed: Condvar,
+    frame: Mutex<Vec<u8>>,
+    stats: Mutex<FrameStats>,
+    width: u32,
+    total: usize,
+}
+
+impl Shared {
+    /// Siguiente tesela pendiente. Si no hay pero otras siguen en vuelo, espera: podrían
+    /// volver a la cola si su trabajador falla. `None` cuando ya no queda nada.
+    fn take(&self) -> Option<Tile> {
+        let mut q = self.queue.lock().unwrap();
+        loop {
+            if let Some(tile) = q.pending.pop_front() {
+                q.in_flight += 1;
+                return Some(tile);
+            }
+            if q.in_flight == 0 {
+                return None;
+            }
+            q = self.changed.wait(q).unwrap();
+        }
+    }
+
+    fn give_back(&self, tile: Tile) {
+        let mut q = self.queue.lock().unwrap();
+        q.in_flight -= 1;
+        q.pending.push_back(tile);
+        self.changed.notify_all();
+    }
+
+    fn finish(&self, tile: Tile, pixels: &[u8], stats: &FrameStats) {
+        {
+            let mut frame = self.frame.lock().unwrap();
+            let row = (tile.w * 4) as usize;
+            for (r, src) in pixels.chunks(row).enumerate() {
+                let start = (((tile.y as usize + r) * self.width as usize) + tile.x as usize) * 4;
+                frame[start..start + row].copy_from_slice(src);
+            }
+        }
+        self.stats.lock().unwrap().merge(stats);
+        let mut q = self.queue.lock().unwrap();
+        q.in_flight -= 1;
+        q.done += 1;
+        if q.done.is_multiple_of(16) || q.done == self.total {
+            println!("  {}/{} teselas", q.done, self.total);
+        }
+        self.changed.notify_all();
+    }
+
+    fn is_finished(&self) -> bool {
+        let q = self.queue.lock().unwrap();
+        q.pending.is_empty() && q.in_flight == 0
+    }
+}
+
+/// Renderiza `job` repartiendo teselas de `tile_size` píxeles entre `workers` (`host:puerto`).
+/// Devuelve el cuadro RGBA completo, o un error si todos los trabajadores fallaron antes de terminar.
+pub fn render_distributed(job: &Job, workers: &[String], tile_size: u32) -> io::Result<(Vec<u8>, FrameStats)> {
+    if workers.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "no hay trabajadores"));
+    }
+    let size = tile_size.max(8);
+    let mut pending = VecDeque::new();
+    for y in (0..job.height).step_by(size as usize) {
+        for x in (0..job.width).step_by(size as usize) {
+            pending.push_back(Tile { x, y, w: size.min(job.width - x), h: size.min(job.height - y) });
+        }
+    }
+    let shared = Shared {
+        total: pending.len(),
+        queue: Mutex::new(Queue { pending, in_flight: 0, done: 0 }),
+        changed: Condvar::new(),
+        frame: Mutex::new(vec![0u8; (job.width * job.height * 4) as usize]),
+        stats: Mutex::new(FrameStats::default()),
+        width: job.width,
+    };
+    let line = job.to_line();
+
+    thread::scope(|scope| {
+        for addr in workers {
+            let (shared, line) = (&shared, &line);
+            scope.spawn(move || drive(addr, line, shared));
+        }
+    });
+
+    let left = shared.queue.lock().unwrap().pending.len();
+    if left > 0 {
+        return Err(io::Error::other(format!("quedaron {} teselas sin renderizar: ningún trabajador respondió", left)));
+    }
+    let mut frame = shared.frame.into_inner().unwrap();
+    let stats = shared.stats.into_inner().unwrap();
+    if job.settings.view == RenderView::Cost {
+        raytracer::colorize_cost(&mut frame, stats.max_tests);
+    }
+    Ok((frame, stats))
+}
+
+/// Mantiene la relación con un trabajador: reconecta tras un fallo hasta `MAX_ATTEMPTS`
+/// veces seguidas sin progreso.
+fn drive(addr: &str, job_line: &str, shared: &Shared) {
+    let mut failures = 0;
+    while !shared.is_finished() {
+        match session(addr, job_line, shared) {
+            Ok(_) => return,
+            Err((served, e)) => {
+                if served > 0 {
+                    failures = 0;
+                }
+                failures += 1;
+                println!("  ✗ {} (fallo {}/{}): {}", addr, failures, MAX_ATTEMPTS, e);
+                if failures >= MAX_ATTEMPTS {
+                    println!("  ✗ {} descartado", addr);
+                    return;
+                }
+                thread::sleep(RETRY_DELAY * failures);
+            }
+        }
+    }
+}
+
+/// Una conexión: saludo, trabajo y teselas hasta vaciar la cola. En caso de error
+/// devuelve cuántas teselas alcanzó a entregar.
+fn session(addr: &str, job_line: &str, shared: &Shared) -> Result<u32, (u32, io::Error)> {
+    let mut served = 0;
+    let fail = |served: u32| move |e: io::Error| (served, e);
+
+    let stream = connect(addr).map_err(fail(0))?;
+    let setup = || -> io::Result<(BufReader<TcpStream>, BufWriter<TcpStream>)> {
+        stream.set_read_timeout(Some(REPLY_TIMEOUT))?;
+        stream.set_nodelay(true)?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut out = BufWriter::new(stream.try_clone()?);
+        let greeting = read_line(&mut reader)?;
+        if greeting != PROTOCOL {
+            return Err(invalid(format!("protocolo inesperado: {}", greeting)));
+        }
+        writeln!(out, "TRABAJO {}", job_line)?;
+        out.flush()?;
+        let reply = read_line(&mut reader)?;
+        if reply != "LISTO" {
+            return Err(io::Error::other(reply));
+        }
+        Ok((reader, out))
+    };
+    let (mut reader, mut out) = setup().map_err(fail(0))?;
+
+    let mut pixels = Vec::new();
+    while let Some(tile) = shared.take() {
+        match request(&mut reader, &mut out, tile, &mut pixels) {
+            Ok(stats) => {
+                shared.finish(tile, &pixels, &stats);
+                served += 1;
+            }
+            Err(e) => {
+                shared.give_back(tile);
+                return Err((served, e));
+            }
+        }
+    }
+    // El cuadro ya está completo: un FIN perdido no importa
+    let _ = writeln!(out, "FIN").and_then(|_| out.flush());
+    Ok(served)
+}
+
+fn connect(addr: &str) -> io::Result<TcpStream> {
+    let mut last = io::Error::new(io::ErrorKind::NotFound, format!("no se pudo resolver {}", addr));
+    for a in addr.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&a, CONNECT_TIMEOUT) {
+            Ok(s) => return Ok(s),
+            Err(e) => last = e,
+        }
+    }
+    Err(last)
+}
+
+fn read_line(reader: &mut impl BufRead) -> io::Result<String> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "el trabajador cerró la conexión"));
+    }
+    Ok(line.trim_end().to_string())
+}
+
+/// Pide una tesela y lee su cabecera y sus píxeles en `pixels`.
+fn request(
+    reader: &mut BufReader<TcpStream>,
+    out: &mut impl Write,
+    tile: Tile,
+    pixels: &mut Vec<u8>,
+) -> io::Result<FrameStats> {
+    writeln!(out, "TESELA {} {} {} {}", tile.x, tile.y, tile.w, tile.h)?;
+    out.flush()?;
+    let header = read_line(reader)?;
+    let fields: Vec<&str> = header.split_whitespace().collect();
+    let nums: Vec<u64> = fields.iter().skip(1).filter_map(|n| n.parse().ok()).collect();
+    let expected = [tile.x, tile.y, tile.w, tile.h].map(u64::from);
+    if fields.first() != Some(&"TESELA") || nums.len() != 7 || nums[..4] != expected {
+        return Err(invalid(format!("respuesta inesperada: {}", header)));
+    }
+    pixels.resize((tile.w * tile.h * 4) as usize, 0);
+    reader.read_exact(pixels)?;
+    Ok(FrameStats { rays: nums[4], tests: nums[5], max_tests: nums[6] as u32 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(world: WorldSpec) -> Job {
+        let mut settings = RenderSettings { max_depth: 3, gloss_samples: 4, view: RenderView::ALL[1], ..RenderSettings::default() };
+        settings.ao.mode = AoMode::RayTraced;
+        settings.ao.samples = 5;
+        settings.spectral.enabled = true;
+        settings.spectral.caustic_photons = 12;
+        Job {
+            world,
+            orbit: OrbitCamera::new(0.6, -0.25, 18.5, Vec3::new(1.0, 2.5, -3.0)),
+            fov: 55.0,
+            sun_angle: 1.25,
+            width: 320,
+            height: 200,
+            settings,
+        }
+    }
+
+    #[test]
+    fn job_round_trips_through_its_line() {
+        let window = RegionWindow { chunk_min: (2, 3), chunk_max: (4, 5), y_min: -10, y_max: 90 };
+        for world in [
+            WorldSpec::Overworld,
+            WorldSpec::Nether,
+            WorldSpec::Procedural { seed: u64::MAX, size: 48 },
+            WorldSpec::Imported { path: PathBuf::from("mis mapas/100%\nr.0.0.mca"), window },
+        ] {
+            let original = job(world);
+            let line = original.to_line();
+            assert!(!line.contains('\n'));
+            let parsed = Job::parse(&line).unwrap();
+            assert_eq!(parsed.to_line(), line);
+            assert_eq!(parsed.world_key(), original.world_key());
+            assert_eq!((parsed.width, parsed.height, parsed.fov), (320, 200, 55.0));
+            assert_eq!(parsed.settings.ao.mode, AoMode::RayTraced);
+            assert_eq!(parsed.settings.view, original.settings.view);
+        }
+    }
+
+    #[test]
+    fn broken_jobs_are_errors() {
+        let line = job(WorldSpec::Overworld).to_line();
+        assert!(Job::parse(&line.replace("mundo=overworld", "mundo=end")).is_err());
+        assert!(Job::parse(&line.replace("ancho=320", "ancho=0")).is_err());
+        assert!(Job::parse(&line.replace("ancho=320", "ancho=-1")).is_err());
+        assert!(Job::parse(&line.replace(" fov=55", "")).is_err());
+    }
+
+    #[test]
+    fn tiles_must_fit_the_frame() {
+        let tile = |x, y, w, h| Tile { x, y, w, h };
+        assert!(tile_fits(&tile(0, 0, 320, 200), 320, 200));
+        assert!(tile_fits(&tile(300, 180, 20, 20), 320, 200));
+        assert!(!tile_fits(&tile(300, 180, 21, 20), 320, 200));
+        assert!(!tile_fits(&tile(0, 0, 0, 10), 320, 200));
+        // x + w desborda u32: antes daba la vuelta y pasaba la comprobación
+        assert!(!tile_fits(&tile(u32::MAX, 0, 2, 10), 320, 200));
+        assert!(!tile_fits(&tile(0, 1, 10, u32::MAX), 320, 200));
+        assert_eq!(parse_tile("1 2 3"), None);
+        assert_eq!(parse_tile("1 2 3 -4"), None);
+    }
+}
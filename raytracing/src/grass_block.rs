@@ -1,6 +1,8 @@
 //! Bloque de césped especial con texturas diferentes por cara.
 
 use crate::solid_block::SolidBlock;
+use crate::bvh::Aabb;
+use crate::lighting::Tex;
 use crate::math::Vec3;
 use crate::ray::Ray;
 use crate::materials::{Intersectable, MaterialParams};
@@ -150,6 +152,19 @@ impl<'a> Intersectable for GrassBlock<'a> {
         }
     }
     
+    fn bounds(&self) -> Aabb {
+        self.inner.bounds()
+    }
+
+    fn face_texture(&self, n: Vec3) -> Option<Tex<'_>> {
+        // Igual que `albedo_at`: grass_top arriba, grass_side en el resto
+        Some(if n.y > 0.5 {
+            Tex { pix: self.top_pixels, w: self.top_w, h: self.top_h }
+        } else {
+            Tex { pix: self.side_pixels, w: self.side_w, h: self.side_h }
+        })
+    }
+
     fn center(&self) -> Vec3 {
         Vec3::new(
             (self.inner.min.x + self.inner.max.x) * 0.5,
@@ -166,8 +181,11 @@ impl<'a> Intersectable for GrassBlock<'a> {
             reflectivity: self.reflectivity,
             transparency: self.transparency,
             ior: self.ior,
+            dispersion: self.inner.dispersion,
             emissive: self.emissive,
             opacity: 1.0,
+            roughness: self.inner.roughness,
+            normal: None,
         }
     }
 }
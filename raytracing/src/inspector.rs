@@ -0,0 +1,237 @@
+//! Inspector de materiales del visor: clic sobre un bloque para ver sus
+//! `MaterialParams` y ajustar el preset de su material con deslizadores. Los cambios
+//! se aplican a `MaterialOverrides` al soltar el deslizador y entonces se reconstruye
+//! la escena visible.
+
+use raylib::prelude::*;
+use raytracing_diorama::material_overrides::{FieldValue, MaterialField, MaterialPreset};
+use raytracing_diorama::materials::MaterialParams;
+use raytracing_diorama::math::Vec3;
+use raytracing_diorama::ray::Ray;
+
+/// Deslizador sobre un campo del preset (o un canal, si el campo es un color).
+struct Slider {
+    field: MaterialField,
+    channel: usize,
+    label: &'static str,
+    min: f32,
+    max: f32,
+}
+
+const fn slider(field: MaterialField, channel: usize, label: &'static str, min: f32, max: f32) -> Slider {
+    Slider { field, channel, label, min, max }
+}
+
+const SLIDERS: [Slider; 13] = [
+    slider(MaterialField::Albedo, 0, "albedo R", 0.0, 1.5),
+    slider(MaterialField::Albedo, 1, "albedo G", 0.0, 1.5),
+    slider(MaterialField::Albedo, 2, "albedo B", 0.0, 1.5),
+    slider(MaterialField::Specular, 0, "specular", 0.0, 1.0),
+    slider(MaterialField::Shininess, 0, "shininess", 1.0, 200.0),
+    slider(MaterialField::Reflectivity, 0, "reflectivity", 0.0, 1.0),
+    slider(MaterialField::Transparency, 0, "transparency", 0.0, 1.0),
+    slider(MaterialField::Ior, 0, "ior", 1.0, 2.6),
+    slider(MaterialField::Dispersion, 0, "dispersion", 0.0, 0.03),
+    slider(MaterialField::Roughness, 0, "roughness", 0.0, 1.0),
+    slider(MaterialField::Emissive, 0, "emissive R", 0.0, 2.0),
+    slider(MaterialField::Emissive, 1, "emissive G", 0.0, 2.0),
+    slider(MaterialField::Emissive, 2, "emissive B", 0.0, 2.0),
+];
+
+// Geometría del panel (píxeles de pantalla)
+const PANEL_X: i32 = 10;
+const PANEL_Y: i32 = 92;
+const PANEL_W: i32 = 420;
+const LINE_H: i32 = 20;
+const HEADER_LINES: i32 = 4;
+const BAR_X: i32 = PANEL_X + 120;
+const BAR_W: i32 = 200;
+
+fn channel(value: FieldValue, channel: usize) -> f32 {
+    match value {
+        FieldValue::Scalar(v) => v,
+        FieldValue::Color(r, g, b) => [r, g, b][channel],
+    }
+}
+
+fn with_channel(value: FieldValue, channel: usize, v: f32) -> FieldValue {
+    match value {
+        FieldValue::Scalar(_) => FieldValue::Scalar(v),
+        FieldValue::Color(r, g, b) => {
+            let mut c = [r, g, b];
+            c[channel] = v;
+            FieldValue::Color(c[0], c[1], c[2])
+        }
+    }
+}
+
+fn fmt_vec(v: Vec3) -> String {
+    format!("({:.2}, {:.2}, {:.2})", v.x, v.y, v.z)
+}
+
+/// Bloque elegido: el rayo con que se eligió (para volver a muestrear tras reconstruir
+/// la escena o mover la cámara) y su preset si pertenece a la paleta.
+struct Selection {
+    ray: Ray,
+    block: Option<&'static str>,
+    sample: Option<(Vec3, MaterialParams)>,
+    preset: Option<MaterialPreset>,
+}
+
+pub struct Inspector {
+    pub open: bool,
+    selection: Option<Selection>,
+    dragging: Option<usize>,
+    edited: bool, // Preset modificado y aún no aplicado a la escena
+}
+
+impl Inspector {
+    pub fn new() -> Self {
+        Self { open: false, selection: None, dragging: None, edited: false }
+    }
+
+    fn panel_height(&self) -> i32 {
+        (HEADER_LINES + SLIDERS.len() as i32 + 1) * LINE_H + 10
+    }
+
+    /// `true` si `pos` cae sobre el panel (ahí el clic no elige bloques).
+    pub fn contains(&self, pos: Vector2) -> bool {
+        self.open
+            && pos.x >= PANEL_X as f32
+            && pos.x <= (PANEL_X + PANEL_W) as f32
+            && pos.y >= PANEL_Y as f32
+            && pos.y <= (PANEL_Y + self.panel_height()) as f32
+    }
+
+    /// Elige el bloque bajo `ray`.
+    pub fn select(&mut self, ray: Ray, block: Option<&'static str>, preset: Option<MaterialPreset>) {
+        self.selection = Some(Selection { ray, block, sample: None, preset });
+        self.dragging = None;
+        self.edited = false;
+    }
+
+    /// Rayo del bloque elegido, para volver a muestrearlo.
+    pub fn ray(&self) -> Option<Ray> {
+        self.selection.as_ref().map(|s| s.ray)
+    }
+
+    pub fn block(&self) -> Option<&'static str> {
+        self.selection.as_ref().and_then(|s| s.block)
+    }
+
+    /// Material muestreado en el punto elegido en el cuadro actual.
+    pub fn set_sample(&mut self, sample: Option<(Vec3, MaterialParams)>) {
+        if let Some(sel) = self.selection.as_mut() {
+            sel.sample = sample;
+        }
+    }
+
+    /// Preset vigente del bloque (tras reconstruir o recargar el archivo). Se ignora
+    /// mientras haya un cambio propio sin aplicar.
+    pub fn set_preset(&mut self, preset: Option<MaterialPreset>) {
+        if let Some(sel) = self.selection.as_mut() {
+            if !self.edited {
+                sel.preset = preset;
+            }
+        }
+    }
+
+    pub fn preset(&self) -> Option<(&'static str, MaterialPreset)> {
+        let sel = self.selection.as_ref()?;
+        Some((sel.block?, sel.preset?))
+    }
+
+    /// Arrastre de los deslizadores con el botón izquierdo.
+    pub fn update(&mut self, rl: &RaylibHandle) {
+        if !self.open {
+            self.dragging = None;
+            return;
+        }
+        let mouse = rl.get_mouse_position();
+        if !rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT) {
+            self.dragging = None;
+            return;
+        }
+        if self.dragging.is_none() && rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
+            let row = (mouse.y as i32 - PANEL_Y - HEADER_LINES * LINE_H) / LINE_H;
+            let on_bar = mouse.x >= (BAR_X - 4) as f32 && mouse.x <= (BAR_X + BAR_W + 4) as f32;
+            if on_bar && mouse.y as i32 >= PANEL_Y + HEADER_LINES * LINE_H && (row as usize) < SLIDERS.len() {
+                self.dragging = Some(row as usize);
+            }
+        }
+        let (Some(index), Some(preset)) = (self.dragging, self.selection.as_mut().and_then(|s| s.preset.as_mut()))
+        else {
+            return;
+        };
+        let s = &SLIDERS[index];
+        let t = ((mouse.x - BAR_X as f32) / BAR_W as f32).clamp(0.0, 1.0);
+        let v = s.min + (s.max - s.min) * t;
+        let old = preset.get(s.field);
+        if channel(old, s.channel) != v {
+            preset.set(s.field, with_channel(old, s.channel, v));
+            self.edited = true;
+        }
+    }
+
+    /// Preset editado desde la última llamada, para aplicarlo a la escena. Mientras se
+    /// arrastra un deslizador no hay nada que aplicar.
+    pub fn take_edit(&mut self) -> Option<(&'static str, MaterialPreset)> {
+        if !self.has_edit() {
+            return None;
+        }
+        self.edited = false;
+        self.preset()
+    }
+
+    pub fn has_edit(&self) -> bool {
+        self.edited && self.dragging.is_none()
+    }
+
+    pub fn draw(&self, d: &mut RaylibDrawHandle<'_>) {
+        if !self.open {
+            return;
+        }
+        let h = self.panel_height();
+        d.draw_rectangle(PANEL_X, PANEL_Y, PANEL_W, h, Color::new(0, 0, 0, 190));
+        d.draw_rectangle_lines(PANEL_X, PANEL_Y, PANEL_W, h, Color::GRAY);
+        let text = |d: &mut RaylibDrawHandle<'_>, line: i32, s: &str, c: Color| {
+            d.draw_text(s, PANEL_X + 8, PANEL_Y + 5 + line * LINE_H, 16, c);
+        };
+
+        let Some(sel) = &self.selection else {
+            text(d, 0, "Inspector: clic sobre un bloque", Color::WHITE);
+            return;
+        };
+        let title = match sel.block {
+            Some(b) => format!("Bloque: {}", b),
+            None => "Bloque sin preset (solo lectura)".to_string(),
+        };
+        text(d, 0, &title, Color::YELLOW);
+        match &sel.sample {
+            Some((p, m)) => {
+                text(d, 1, &format!("Punto {}  albedo {}", fmt_vec(*p), fmt_vec(m.albedo)), Color::LIGHTGRAY);
+                text(d, 2, &format!("spec {:.2}  shin {:.0}  refl {:.2}  rough {:.2}",
+                    m.specular_strength, m.shininess, m.reflectivity, m.roughness), Color::LIGHTGRAY);
+                text(d, 3, &format!("transp {:.2}  ior {:.2}  disp {:.4}  emis {}",
+                    m.transparency, m.ior, m.dispersion, fmt_vec(m.emissive)), Color::LIGHTGRAY);
+            }
+            None => text(d, 1, "(el rayo ya no toca ningún bloque)", Color::LIGHTGRAY),
+        }
+
+        let rows = SLIDERS.len() as i32;
+        if let Some(preset) = &sel.preset {
+            for (i, s) in SLIDERS.iter().enumerate() {
+                let y = PANEL_Y + (HEADER_LINES + i as i32) * LINE_H + 5;
+                let v = channel(preset.get(s.field), s.channel);
+                let t = ((v - s.min) / (s.max - s.min)).clamp(0.0, 1.0);
+                let active = self.dragging == Some(i);
+                d.draw_text(s.label, PANEL_X + 8, y, 14, Color::WHITE);
+                d.draw_rectangle(BAR_X, y + 4, BAR_W, 8, Color::DARKGRAY);
+                d.draw_rectangle(BAR_X, y + 4, (BAR_W as f32 * t) as i32, 8, if active { Color::ORANGE } else { Color::SKYBLUE });
+                d.draw_text(&format!("{:.4}", v), BAR_X + BAR_W + 10, y, 14, Color::WHITE);
+            }
+        }
+        let footer = if sel.preset.is_some() { "S: guardar preset | I: cerrar" } else { "I: cerrar" };
+        text(d, HEADER_LINES + rows, footer, Color::YELLOW);
+    }
+}
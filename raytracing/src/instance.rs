@@ -0,0 +1,185 @@
+//! Grupos de objetos con su propio BVH e instancias con transformación afín.
+//!
+//! Una estructura repetida (árbol, portal, construcción importada) se construye una
+//! vez como `Group`, se comparte con `Arc` y se coloca con `Instance` tantas veces
+//! como haga falta: cada copia solo cuesta una transformación y una caja.
+
+use std::sync::Arc;
+
+use crate::bvh::{Aabb, Bvh};
+use crate::materials::{Intersectable, MaterialParams};
+use crate::math::{Transform, Vec3};
+use crate::ray::Ray;
+use crate::raytracer::DynObject;
+
+/// Conjunto de objetos e instancias anidadas con un BVH sobre todos ellos.
+/// Los índices del BVH `0..objects.len()` son objetos; el resto, instancias.
+pub struct Group<'a> {
+    objects: Vec<DynObject<'a>>,
+    instances: Vec<Instance<'a>>,
+    bvh: Bvh,
+}
+
+/// Copia de un `Group` compartido colocada con una transformación.
+pub struct Instance<'a> {
+    group: Arc<Group<'a>>,
+    to_local: Transform,
+    bounds: Aabb, // Caja del grupo llevada al mundo
+}
+
+/// Impacto más cercano: el objeto golpeado y cómo volver a su espacio local.
+pub struct SurfaceHit<'s> {
+    pub t: f32,
+    pub object: &'s dyn Intersectable,
+    pub to_local: Transform, // Mundo → espacio del objeto (identidad fuera de instancias)
+    pub local_ray: Ray,
+}
+
+impl<'s> SurfaceHit<'s> {
+    /// Punto del impacto en el espacio del objeto.
+    pub fn local_point(&self) -> Vec3 {
+        self.local_ray.at(self.t)
+    }
+
+    /// Normal geométrica en el mundo (unitaria).
+    pub fn normal(&self) -> Vec3 {
+        let n = self.object.normal_at(self.local_point());
+        self.to_local.normal_from_inverse(n).norm()
+    }
+
+    /// Material en el punto, con la normal de sombreado llevada al mundo.
+    pub fn material(&self) -> MaterialParams {
+        let mut mat = self.object.material_at(self.local_point());
+        mat.normal = mat.normal.map(|n| self.to_local.normal_from_inverse(n).norm());
+        mat
+    }
+}
+
+impl<'a> Group<'a> {
+    pub fn new(objects: Vec<DynObject<'a>>, instances: Vec<Instance<'a>>) -> Self {
+        let bounds: Vec<Aabb> = objects
+            .iter()
+            .map(|o| o.bounds())
+            .chain(instances.iter().map(|i| i.bounds))
+            .collect();
+        let bvh = Bvh::build(&bounds);
+        Self { objects, instances, bvh }
+    }
+
+    /// Objetos e instancias del grupo, para armar otro sin alguno de ellos.
+    pub fn into_parts(self) -> (Vec<DynObject<'a>>, Vec<Instance<'a>>) {
+        (self.objects, self.instances)
+    }
+
+    pub fn objects(&self) -> &[DynObject<'a>] {
+        &self.objects
+    }
+
+    pub fn instances(&self) -> &[Instance<'a>] {
+        &self.instances
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        self.bvh.bounds()
+    }
+
+    /// Objetos que se ven en pantalla, contando cada copia de las instancias.
+    pub fn object_count(&self) -> usize {
+        self.objects.len() + self.instances.iter().map(|i| i.group.object_count()).sum::<usize>()
+    }
+
+    /// Impacto más cercano con `0 < t < t_max`.
+    pub fn closest(&self, ray: &Ray, t_max: f32, tests: &mut u32) -> Option<SurfaceHit<'_>> {
+        let mut best: Option<SurfaceHit<'_>> = None;
+        let n = self.objects.len();
+        // `traverse` necesita el contador mientras la clausura prueba instancias anidadas
+        let mut inner_tests = 0;
+        self.bvh.traverse(ray, t_max, false, tests, |i, bound| {
+            if i < n {
+                let o = self.objects[i].as_ref();
+                let t = o.intersect(ray).filter(|&t| t > 0.0 && t < bound)?;
+                best = Some(SurfaceHit { t, object: o, to_local: Transform::IDENTITY, local_ray: *ray });
+                Some(t)
+            } else {
+                let hit = self.instances[i - n].closest(ray, bound, &mut inner_tests)?;
+                let t = hit.t;
+                best = Some(hit);
+                Some(t)
+            }
+        });
+        *tests += inner_tests;
+        best
+    }
+
+    /// `true` si algo corta el rayo con `0 < t < t_max` (rayos de sombra y AO).
+    pub fn occluded(&self, ray: &Ray, t_max: f32, tests: &mut u32) -> bool {
+        let n = self.objects.len();
+        let mut inner_tests = 0;
+        let hit = self.bvh.traverse(ray, t_max, true, tests, |i, bound| {
+            if i < n {
+                self.objects[i].intersect(ray).filter(|&t| t > 0.0 && t < bound)
+            } else {
+                let inst = &self.instances[i - n];
+                let local = inst.local_ray(ray);
+                inst.group.occluded(&local, bound, &mut inner_tests).then_some(0.0)
+            }
+        });
+        *tests += inner_tests;
+        hit.is_some()
+    }
+
+    /// Visita cada objeto con la transformación mundo → local que le corresponde.
+    pub fn for_each_object(&self, to_local: &Transform, f: &mut dyn FnMut(&dyn Intersectable, &Transform)) {
+        for o in &self.objects {
+            f(o.as_ref(), to_local);
+        }
+        for inst in &self.instances {
+            inst.group.for_each_object(&to_local.then(&inst.to_local), f);
+        }
+    }
+}
+
+impl<'a> Instance<'a> {
+    /// Coloca `group` con la transformación local → mundo `to_world`.
+    /// `None` si no es invertible (escala 0 en algún eje): la instancia quedaría
+    /// aplanada, sin volumen que dibujar.
+    pub fn new(group: Arc<Group<'a>>, to_world: Transform) -> Option<Self> {
+        let to_local = to_world.inverse()?;
+        let local = group.bounds();
+        let bounds = if local.min.x > local.max.x {
+            Aabb::EMPTY // Grupo vacío
+        } else {
+            local.corners().iter().fold(Aabb::EMPTY, |b, &c| b.grow(to_world.point(c)))
+        };
+        Some(Self { group, to_local, bounds })
+    }
+
+    pub fn group(&self) -> &Arc<Group<'a>> {
+        &self.group
+    }
+
+    /// El rayo en el espacio del grupo. La dirección no se normaliza,
+    /// así que `t` es la misma en ambos espacios.
+    fn local_ray(&self, ray: &Ray) -> Ray {
+        Ray::new(self.to_local.point(ray.orig), self.to_local.vector(ray.dir))
+    }
+
+    fn closest(&self, ray: &Ray, t_max: f32, tests: &mut u32) -> Option<SurfaceHit<'_>> {
+        let local = self.local_ray(ray);
+        let mut hit = self.group.closest(&local, t_max, tests)?;
+        hit.to_local = self.to_local.then(&hit.to_local);
+        Some(hit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flattened_instance_is_skipped() {
+        let group = Arc::new(Group::new(Vec::new(), Vec::new()));
+        assert!(Instance::new(group.clone(), Transform::scale(Vec3::new(1.0, 0.0, 1.0))).is_none());
+        assert!(Instance::new(group, Transform::translate(Vec3::new(2.0, 0.0, 0.0))).is_some());
+    }
+}
@@ -0,0 +1,29 @@
+//! Núcleo del raytracer en CPU, sin dependencias de ventana.
+//!
+//! `main.rs` lo usa para el diorama interactivo y `tests/` para renderizar
+//! escenas fijas sin abrir una ventana.
+
+pub mod camera;
+pub mod math;
+pub mod materials;
+pub mod solid_block;
+pub mod textured_block;
+pub mod grass_block;
+pub mod lighting;
+pub mod spectral;
+pub mod raytracer;
+pub mod bvh;
+pub mod instance;
+pub mod textured_plane;
+pub mod texture_loader;
+pub mod ray;
+pub mod terrain;
+pub mod occlusion;
+pub mod nbt;
+pub mod schematic;
+pub mod aov;
+pub mod distributed;
+pub mod mesh_export;
+pub mod material_overrides;
+pub mod watch;
+pub mod physics;
@@ -1,6 +1,6 @@
 //! Utilidades de iluminación: cielo procedural, reflejos y muestreo de skybox.
 
-use crate::math::Vec3;
+use crate::math::{Vec3, orthonormal_basis};
 
 pub fn to_rgba(c: Vec3) -> [u8; 4] {
     let g = c.clamp01();
@@ -51,6 +51,31 @@ pub fn refract(i: Vec3, n: Vec3, eta: f32) -> Option<Vec3> {
     Some(i.mul(eta).add(n.mul(eta * cosi - cost)).norm())
 }
 
+/// Dirección de reflexión difuminada según la rugosidad (lóbulo tipo Phong alrededor de `r`).
+/// `u1`, `u2` son números uniformes en [0,1).
+pub fn glossy_reflect(r: Vec3, n: Vec3, roughness: f32, u1: f32, u2: f32) -> Vec3 {
+    if roughness <= 0.0 {
+        return r;
+    }
+    // Rugosidad 1 ≈ exponente 1 (casi difuso); rugosidad baja = lóbulo estrecho
+    let exponent = 2.0 / (roughness * roughness).max(1e-4) - 2.0;
+    let cos_theta = u1.powf(1.0 / (exponent.max(0.0) + 1.0));
+    let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+    let phi = 2.0 * std::f32::consts::PI * u2;
+    let (t, b) = orthonormal_basis(r);
+    let dir = t
+        .mul(sin_theta * phi.cos())
+        .add(b.mul(sin_theta * phi.sin()))
+        .add(r.mul(cos_theta))
+        .norm();
+    // No atravesar la superficie: reflejar de vuelta al hemisferio de la normal
+    if dir.dot(n) < 0.0 {
+        dir.sub(n.mul(2.0 * dir.dot(n))).norm()
+    } else {
+        dir
+    }
+}
+
 // Especular Phong
 pub fn specular_phong(r: Vec3, v: Vec3, k_s: f32, shininess: f32) -> f32 {
     let rv = r.dot(v).max(0.0);
@@ -64,6 +89,25 @@ pub struct Tex<'a> {
     pub h: u32,
 }
 
+impl<'a> Tex<'a> {
+    /// Muestreo sin filtrar (pixel art) con UV en [0,1] y v hacia arriba.
+    pub fn sample_nearest(&self, u: f32, v: f32) -> Vec3 {
+        let uu = u.clamp(0.0, 1.0 - f32::EPSILON);
+        let vv = v.clamp(0.0, 1.0 - f32::EPSILON);
+        let px = ((uu * self.w as f32).floor() as u32).min(self.w.saturating_sub(1));
+        let py = (((1.0 - vv) * self.h as f32).floor() as u32).min(self.h.saturating_sub(1));
+        let idx = ((py * self.w + px) * 4) as usize;
+        if idx + 3 >= self.pix.len() {
+            return Vec3::new(0.5, 0.5, 1.0);
+        }
+        Vec3::new(
+            self.pix[idx] as f32 / 255.0,
+            self.pix[idx + 1] as f32 / 255.0,
+            self.pix[idx + 2] as f32 / 255.0,
+        )
+    }
+}
+
 #[derive(Copy, Clone)]
 pub struct Skybox<'a> {
     pub px: Tex<'a>,
@@ -1,41 +1,417 @@
 //! Diorama interactivo inspirado en Minecraft renderizado íntegramente en CPU.
 
-mod camera;
-mod math;
-mod materials;
-mod solid_block;
-mod textured_block;
-mod grass_block;
-mod lighting;
-mod raytracer;
-mod textured_plane;
-mod texture_loader;
 mod framebuffer;
-mod ray;
+mod inspector;
 
-use camera::OrbitCamera;
-use math::Vec3;
+use raytracing_diorama::camera::{Camera, OrbitCamera};
+use raytracing_diorama::math::Vec3;
 use raylib::prelude::*;
 use std::f32::consts::PI;
-use texture_loader::TextureStorage;
-use raytracer::{Assets, SceneData, WorldKind, render};
-use lighting::Skybox;
+use raytracing_diorama::texture_loader::{AssetManifest, AssetRoots, TextureStorage};
+use raytracing_diorama::raytracer::{self, Assets, RenderSettings, SceneData, WorldKind, render};
+use raytracing_diorama::terrain::TerrainConfig;
+use raytracing_diorama::schematic::{RegionWindow, Schematic};
+use raytracing_diorama::aov::{self, RenderView};
+use raytracing_diorama::distributed::{self, Job, WorldSpec};
+use raytracing_diorama::mesh_export::MeshExport;
+use raytracing_diorama::material_overrides::MaterialOverrides;
+use raytracing_diorama::watch::{FileWatcher, WatchId};
+use raytracing_diorama::physics::{BlockPhysics, Cell, LiveScene, SOURCE};
+use raytracing_diorama::ray::Ray;
+use inspector::Inspector;
+use std::collections::HashSet;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// Segundos entre pasos de la física de bloques.
+const PHYSICS_TICK: f32 = 0.15;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum WorldType {
     Overworld,
     Nether,
+    Terrain,
+    Imported,
 }
 
 impl WorldType {
     fn toggle(self) -> Self {
         match self {
             WorldType::Overworld => WorldType::Nether,
-            WorldType::Nether => WorldType::Overworld,
+            WorldType::Nether => WorldType::Terrain,
+            WorldType::Terrain => WorldType::Imported,
+            WorldType::Imported => WorldType::Overworld,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            WorldType::Overworld => "OVERWORLD",
+            WorldType::Nether => "NETHER",
+            WorldType::Terrain => "PROCEDURAL",
+            WorldType::Imported => "IMPORTADO",
+        }
+    }
+}
+
+/// Lee `--seed <n>` y `--size <n>` de la línea de comandos para el mundo procedural.
+fn terrain_config_from_args() -> TerrainConfig {
+    let mut config = TerrainConfig::default();
+    let args: Vec<String> = std::env::args().collect();
+    for pair in args.windows(2) {
+        match pair[0].as_str() {
+            "--seed" => {
+                if let Ok(seed) = pair[1].parse() {
+                    config.seed = seed;
+                }
+            }
+            "--size" => {
+                if let Ok(size) = pair[1].parse::<i32>() {
+                    config = TerrainConfig::with_seed(config.seed, size);
+                }
+            }
+            _ => {}
+        }
+    }
+    config
+}
+
+/// Lee `--import <archivo>` (`.schem`, `.schematic` o `.mca`) y, para regiones,
+/// `--chunks x0,z0,x1,z1` y `--y y0,y1` para acotar la ventana importada.
+fn import_args() -> Option<(PathBuf, RegionWindow)> {
+    let args: Vec<String> = std::env::args().collect();
+    let mut path = None;
+    let mut window = RegionWindow::default();
+    let numbers = |s: &str| -> Vec<i32> { s.split(',').filter_map(|n| n.trim().parse().ok()).collect() };
+    for pair in args.windows(2) {
+        match pair[0].as_str() {
+            "--import" => path = Some(PathBuf::from(&pair[1])),
+            "--chunks" => {
+                if let [x0, z0, x1, z1] = numbers(&pair[1])[..] {
+                    window.chunk_min = (x0, z0);
+                    window.chunk_max = (x1, z1);
+                }
+            }
+            "--y" => {
+                if let [y0, y1] = numbers(&pair[1])[..] {
+                    window.y_min = y0;
+                    window.y_max = y1;
+                }
+            }
+            _ => {}
+        }
+    }
+    path.map(|p| (p, window))
+}
+
+/// Valor de `--<name> <valor>` en la línea de comandos.
+fn arg_value(name: &str) -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    args.windows(2).find(|p| p[0] == name).map(|p| p[1].clone())
+}
+
+/// Texturas y ajustes de materiales, buscados primero en `--assets <dir>` y luego en
+/// las carpetas por defecto.
+struct AssetSet {
+    roots: AssetRoots,
+    textures: TextureStorage,
+    materials_path: PathBuf, // `--materials <archivo>` o `materials.txt` junto a las texturas
+    materials: MaterialOverrides,
+}
+
+impl AssetSet {
+    /// Con `--strict-assets` el programa termina si alguna textura falló.
+    fn load() -> Self {
+        let roots = arg_value("--assets").map_or_else(AssetRoots::default, AssetRoots::with_dir);
+        let textures = TextureStorage::load_from(&roots, &AssetManifest::default());
+        if textures.check().is_err() && std::env::args().any(|a| a == "--strict-assets") {
+            println!("Abortando por --strict-assets");
+            std::process::exit(1);
+        }
+        let materials_path = arg_value("--materials")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| roots.find("materials.txt").unwrap_or_else(|tried| tried[0].clone()));
+        let mut set = Self { roots, textures, materials_path, materials: MaterialOverrides::default() };
+        set.reload_materials();
+        set
+    }
+
+    /// Opciones de `load` tal como llegaron, para reenviarlas a los trabajadores locales.
+    fn forwarded_args() -> Vec<String> {
+        let mut args = Vec::new();
+        for name in ["--assets", "--materials"] {
+            if let Some(value) = arg_value(name) {
+                args.extend([name.to_string(), value]);
+            }
+        }
+        if std::env::args().any(|a| a == "--strict-assets") {
+            args.push("--strict-assets".to_string());
+        }
+        args
+    }
+
+    fn assets(&self) -> Assets<'_> {
+        let mut assets = self.textures.assets();
+        assets.materials = Some(&self.materials);
+        assets
+    }
+
+    fn reload_textures(&mut self) {
+        self.textures = TextureStorage::load_from(&self.roots, &AssetManifest::default());
+    }
+
+    /// Si el archivo tiene errores se conservan los ajustes anteriores.
+    fn reload_materials(&mut self) {
+        match MaterialOverrides::load(&self.materials_path) {
+            Ok(materials) => {
+                if !materials.is_empty() {
+                    println!("Materiales ajustados desde {}", self.materials_path.display());
+                }
+                self.materials = materials;
+            }
+            Err(e) => println!("Materiales sin cambios: {}", e),
+        }
+    }
+}
+
+/// Lee la construcción de `--import`, o `None` si no se pidió o no se pudo leer.
+fn load_import(import: Option<&(PathBuf, RegionWindow)>) -> Option<Schematic> {
+    let (path, window) = import?;
+    match Schematic::load(path, window) {
+        Ok(build) => {
+            println!("Importado {} ({}x{}x{})", path.display(), build.width, build.height, build.length);
+            build.report_unknown();
+            Some(build)
+        }
+        Err(e) => {
+            println!("No se pudo importar {}: {}", path.display(), e);
+            None
         }
     }
 }
 
+/// Escena estática de `world`. Sin construcción importada se ve el mundo procedural.
+fn build_world<'a>(
+    assets: &Assets<'a>,
+    world: WorldType,
+    terrain: &TerrainConfig,
+    imported: Option<&Schematic>,
+) -> SceneData<'a> {
+    match (world, imported) {
+        (WorldType::Overworld, _) => raytracer::build_scene(assets, WorldKind::Overworld),
+        (WorldType::Nether, _) => raytracer::build_scene(assets, WorldKind::Nether),
+        (WorldType::Imported, Some(build)) => raytracer::build_schematic_scene(assets, build),
+        (WorldType::Terrain | WorldType::Imported, _) => raytracer::build_terrain_scene(assets, terrain),
+    }
+}
+
+/// Escenas de cada mundo, construidas la primera vez que se muestran. Un cambio
+/// invalida solo los mundos que dependen de él; se rehacen al volver a verlos.
+#[derive(Default)]
+struct WorldScenes<'a> {
+    scenes: [Option<SceneData<'a>>; 4],
+}
+
+impl<'a> WorldScenes<'a> {
+    fn invalidate(&mut self, world: WorldType) {
+        self.scenes[world as usize] = None;
+    }
+
+    fn get(
+        &mut self,
+        assets: &Assets<'a>,
+        world: WorldType,
+        terrain: &TerrainConfig,
+        imported: Option<&Schematic>,
+    ) -> &SceneData<'a> {
+        self.scenes[world as usize].get_or_insert_with(|| {
+            let scene = build_world(assets, world, terrain, imported);
+            println!("{} (raytracing): {} bloques visibles", world.label(), scene.root.object_count());
+            scene
+        })
+    }
+}
+
+/// Rayo de la cámara que pasa por el puntero.
+fn mouse_ray(rl: &RaylibHandle, camera: &Camera, aspect: f32) -> Ray {
+    let mouse = rl.get_mouse_position();
+    let u = mouse.x / rl.get_screen_width() as f32;
+    let v = mouse.y / rl.get_screen_height() as f32;
+    camera.make_ray(u, v, aspect)
+}
+
+/// Bloque dinámico que suelta la tecla pulsada (1-4).
+fn spawn_key(rl: &RaylibHandle) -> Option<Cell> {
+    [
+        (KeyboardKey::KEY_ONE, Cell::Sand),
+        (KeyboardKey::KEY_TWO, Cell::Gravel),
+        (KeyboardKey::KEY_THREE, Cell::Water(SOURCE)),
+        (KeyboardKey::KEY_FOUR, Cell::Lava(SOURCE)),
+    ]
+    .into_iter()
+    .find(|&(key, _)| rl.is_key_pressed(key))
+    .map(|(_, cell)| cell)
+}
+
+/// Opciones de render de los modos sin ventana: `--spectral` activa la dispersión.
+fn render_settings_from_args() -> RenderSettings {
+    let mut settings = RenderSettings::default();
+    settings.spectral.enabled = std::env::args().any(|a| a == "--spectral");
+    settings
+}
+
+/// `--export-size WxH` (640x360 por defecto).
+fn export_size() -> (u32, u32) {
+    arg_value("--export-size")
+        .and_then(|s| {
+            let (a, b) = s.split_once('x')?;
+            Some((a.parse().ok()?, b.parse().ok()?))
+        })
+        .unwrap_or((640, 360))
+}
+
+/// Mundo elegido con `--world nether|procedural|importado` para los modos sin ventana.
+fn world_spec_from_args() -> WorldSpec {
+    let config = terrain_config_from_args();
+    match arg_value("--world").as_deref() {
+        Some("nether") => WorldSpec::Nether,
+        Some("procedural") => WorldSpec::Procedural { seed: config.seed, size: config.size_x },
+        Some("importado") => match import_args() {
+            Some((path, window)) => WorldSpec::Imported { path, window },
+            None => WorldSpec::Overworld,
+        },
+        _ => WorldSpec::Overworld,
+    }
+}
+
+/// Renderiza sin ventana todas las vistas de depuración del mundo elegido con
+/// `--world` y las guarda como `<dir>/<vista>.png`.
+fn export_views(dir: &Path) {
+    let (w, h) = export_size();
+    let asset_set = AssetSet::load();
+    let assets = asset_set.assets();
+    let scene = world_spec_from_args().build(&assets).unwrap_or_else(|e| {
+        println!("No se pudo construir el mundo ({}), se usa el Overworld", e);
+        raytracer::build_scene(&assets, WorldKind::Overworld)
+    });
+    let camera = OrbitCamera::new(0.6, 0.25, 25.0, Vec3::new(0.0, 2.0, 0.0)).to_camera(60.0);
+    if let Err(e) = std::fs::create_dir_all(dir) {
+        println!("No se pudo crear {}: {}", dir.display(), e);
+        return;
+    }
+
+    let mut frame = vec![0u8; (w * h * 4) as usize];
+    for view in RenderView::ALL {
+        let settings = RenderSettings { view, ..render_settings_from_args() };
+        let stats = render(&mut frame, w as i32, h as i32, &camera, 0.6, &scene, &settings);
+        let path = dir.join(format!("{}.png", view.label()));
+        match aov::save_png(&path, &frame, w, h) {
+            Ok(()) => println!("  {} ({} rayos, {} pruebas, máx {} por píxel)",
+                path.display(), stats.rays, stats.tests, stats.max_tests),
+            Err(e) => println!("  ✗ {}: {}", path.display(), e),
+        }
+    }
+}
+
+/// `--export-mesh <ruta.gltf|ruta.obj>`: guarda el mundo elegido con `--world` como malla.
+fn export_mesh(path: &Path) {
+    let asset_set = AssetSet::load();
+    let assets = asset_set.assets();
+    let scene = match world_spec_from_args().build(&assets) {
+        Ok(scene) => scene,
+        Err(e) => {
+            println!("No se pudo construir el mundo: {}", e);
+            return;
+        }
+    };
+    let mesh = MeshExport::from_group(&scene.root);
+    if mesh.skipped_objects > 0 {
+        println!("  {} objetos sin caja finita no se exportan", mesh.skipped_objects);
+    }
+    match mesh.write(path) {
+        Ok(()) => println!("  {} ({} triángulos, {} materiales, {} texturas, {} caras ocultas descartadas)",
+            path.display(), mesh.triangle_count(), mesh.primitives.len(), mesh.textures.len(), mesh.culled_faces),
+        Err(e) => println!("  ✗ {}: {}", path.display(), e),
+    }
+}
+
+/// `--worker <dirección>`: atiende teselas de coordinadores hasta que se mate el proceso.
+fn run_worker(addr: &str) {
+    // Escuchar antes de cargar texturas: los coordinadores esperan en la cola del socket
+    let listener = match TcpListener::bind(addr) {
+        Ok(l) => l,
+        Err(e) => {
+            println!("No se pudo escuchar en {}: {}", addr, e);
+            return;
+        }
+    };
+    println!("Trabajador escuchando en {}", addr);
+    let asset_set = AssetSet::load();
+    if let Err(e) = distributed::serve(listener, &asset_set.assets()) {
+        println!("Trabajador detenido: {}", e);
+    }
+}
+
+/// `--render-distributed <salida.png>`: reparte el cuadro entre `--workers host:puerto,...`
+/// y/o `--local-workers N` procesos lanzados aquí mismo (puertos desde `--port`, 7878).
+fn render_distributed(out: &Path) {
+    let (width, height) = export_size();
+    let mut workers: Vec<String> = arg_value("--workers")
+        .map(|w| w.split(',').map(|a| a.trim().to_string()).filter(|a| !a.is_empty()).collect())
+        .unwrap_or_default();
+    let local: usize = arg_value("--local-workers").and_then(|n| n.parse().ok()).unwrap_or(0);
+    let base_port: u16 = arg_value("--port").and_then(|p| p.parse().ok()).unwrap_or(7878);
+
+    let mut children = Vec::new();
+    if local > 0 {
+        match std::env::current_exe() {
+            Ok(exe) => {
+                let asset_args = AssetSet::forwarded_args();
+                for i in 0..local {
+                    let addr = format!("127.0.0.1:{}", base_port as usize + i);
+                    match Command::new(&exe).args(["--worker", &addr]).args(&asset_args).spawn() {
+                        Ok(child) => {
+                            children.push(child);
+                            workers.push(addr);
+                        }
+                        Err(e) => println!("No se pudo lanzar el trabajador {}: {}", addr, e),
+                    }
+                }
+            }
+            Err(e) => println!("No se encontró el ejecutable para lanzar trabajadores: {}", e),
+        }
+    }
+
+    let job = Job {
+        world: world_spec_from_args(),
+        orbit: OrbitCamera::new(0.6, 0.25, 25.0, Vec3::new(0.0, 2.0, 0.0)),
+        fov: 60.0,
+        sun_angle: 0.6,
+        width,
+        height,
+        settings: render_settings_from_args(),
+    };
+    let tile = arg_value("--tile").and_then(|t| t.parse().ok()).unwrap_or(64);
+    println!("Render distribuido {}x{} en teselas de {} px con {} trabajadores",
+        width, height, tile, workers.len());
+    let start = Instant::now();
+    match distributed::render_distributed(&job, &workers, tile) {
+        Ok((frame, stats)) => match aov::save_png(out, &frame, width, height) {
+            Ok(()) => println!("  {} en {:.1} s ({} rayos, {} pruebas)",
+                out.display(), start.elapsed().as_secs_f32(), stats.rays, stats.tests),
+            Err(e) => println!("  ✗ {}: {}", out.display(), e),
+        },
+        Err(e) => println!("  ✗ {}", e),
+    }
+
+    for mut child in children {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
 fn main() {
     // Configurar threads
     let num_threads = num_cpus::get();
@@ -47,6 +423,28 @@ fn main() {
     println!("=== RAYTRACER CPU - MINECRAFT DIORAMA ===");
     println!("Threads: {}", num_threads);
     
+    // Exportación sin ventana de las vistas de depuración
+    if let Some(dir) = arg_value("--export-views") {
+        export_views(Path::new(&dir));
+        return;
+    }
+    
+    // Exportación del mundo a glTF/OBJ
+    if let Some(path) = arg_value("--export-mesh") {
+        export_mesh(Path::new(&path));
+        return;
+    }
+    
+    // Render distribuido por teselas: trabajador o coordinador
+    if let Some(addr) = arg_value("--worker") {
+        run_worker(&addr);
+        return;
+    }
+    if let Some(out) = arg_value("--render-distributed") {
+        render_distributed(Path::new(&out));
+        return;
+    }
+    
     // Configuración de ventana FULLSCREEN
     let (mut rl, thread) = raylib::init()
         .title("Raytracer CPU - Minecraft Diorama")
@@ -74,49 +472,20 @@ fn main() {
     let mut tex = rl.load_texture_from_image(&thread, &img).expect("texture");
     let mut frame = vec![0u8; (fb_width * fb_height * 4) as usize];
     
-    // Cargar texturas PNG para raytracing optimizado
-    let textures = TextureStorage::load();
-    
-    // Crear skybox usando clouds.png para todas las caras
-    let clouds_tex = textures.get_clouds();
-    let skybox_overworld = Skybox {
-        px: clouds_tex,  // +X (derecha)
-        nx: clouds_tex,  // -X (izquierda)
-        py: clouds_tex,  // +Y (arriba)
-        ny: clouds_tex,  // -Y (abajo)
-        pz: clouds_tex,  // +Z (frente)
-        nz: clouds_tex,  // -Z (atrás)
-        tint: Vec3::new(0.8, 0.9, 1.0), // Tinte azul cielo
-    };
-    
-    // Construir assets para raytracing con texturas y skybox
-    let assets = Assets {
-        grass_cover: Some(textures.get_grass_cover()),
-        grass_side: Some(textures.get_grass_side()),
-        dirt: Some(textures.get_dirt()),
-        stone: Some(textures.get_stone()),
-        wood: Some(textures.get_wood()),
-        leaves: Some(textures.get_leaves()),
-        water: Some(textures.get_water()),
-        lava: Some(textures.get_lava()),
-        obsidian: Some(textures.get_obsidian()),
-        glowstone: Some(textures.get_glowstone()),
-        diamond: Some(textures.get_diamond()),
-        iron: Some(textures.get_iron()),
-        chest: Some(textures.get_chest()),
-        ice: Some(textures.get_ice()),
-        portal: Some(textures.get_portal()),
-        torch: Some(textures.get_torch()),
-        skybox_overworld: Some(skybox_overworld),
-        skybox_nether: None,  // Nether sin skybox (cielo procedural rojo)
-    };
-    
-    // Construir escenas de raytracing con texturas
-    let overworld_rt = raytracer::build_scene(&assets, WorldKind::Overworld);
-    let nether_rt = raytracer::build_scene(&assets, WorldKind::Nether);
+    // Cargar texturas PNG y ajustes de materiales para raytracing optimizado
+    let mut asset_set = AssetSet::load();
+    let mut terrain_config = terrain_config_from_args();
+    let import = import_args();
+    let mut imported = load_import(import.as_ref());
     
-    println!("Overworld (raytracing): {} bloques texturizados", overworld_rt.objects.len());
-    println!("Nether (raytracing): {} bloques texturizados", nether_rt.objects.len());
+    // Recarga en caliente: carpetas de texturas, archivo de materiales y construcción importada
+    let mut watcher = FileWatcher::new(Duration::from_millis(300));
+    let texture_watches: Vec<WatchId> = asset_set.roots.dirs.iter()
+        .filter(|d| d.is_dir())
+        .map(|d| watcher.watch(d))
+        .collect();
+    let materials_watch = watcher.watch(&asset_set.materials_path);
+    let import_watch = import.as_ref().map(|(path, _)| watcher.watch(path));
     
     let mut current_world = WorldType::Overworld;
     
@@ -132,86 +501,283 @@ fn main() {
     let mut sun_angle: f32 = 0.6; // Ángulo del sol
     let mut animate_sun = false; // ESPACIO para activar
     
+    // Calidad de render y oclusión ambiental (O para cambiar de modo)
+    let mut settings = render_settings_from_args();
+    
+    // Inspector de materiales (I)
+    let mut inspector = Inspector::new();
+    
+    // Física de bloques de un mundo (1-4 sueltan bloques, F pausa, C limpia); el estado
+    // sobrevive a las recargas y se vuelve a montar sobre las escenas nuevas
+    let mut physics: Option<(WorldType, BlockPhysics)> = None;
+    let mut simulate = true;
+    let mut physics_clock = 0.0f32;
+    
     println!("\n=== CONTROLES ===");
     println!("Flechas: Orbitar cámara");
     println!("Q/E: Zoom in/out");
-    println!("M: Cambiar mundo (Overworld/Nether)");
+    println!("M: Cambiar mundo (Overworld/Nether/Procedural/Importado si hay --import)");
+    println!("G: Nueva semilla para el mundo procedural");
     println!("ESPACIO: Ciclo solar día/noche");
-    println!("ESC: Salir\n");
+    println!("O: Oclusión ambiental (off/voxel/rayos)");
+    println!("V: Vista de depuración (normales, profundidad, albedo, material, sombras, rebotes, costo)");
+    println!("I: Inspector de materiales (clic: elegir bloque, S: guardar preset)");
+    println!("1-4: Soltar arena/grava/agua/lava bajo el puntero | F: Pausar física | C: Quitar bloques sueltos");
+    println!("ESC: Salir");
+    println!("Recarga en caliente: {} y {}\n",
+        texture_watches.iter().map(|&id| watcher.path(id).display().to_string()).collect::<Vec<_>>().join(", "),
+        asset_set.materials_path.display());
     
-    while !rl.window_should_close() {
-        let dt = rl.get_frame_time();
-        let speed = 1.6;
+    loop {
+        // Las escenas toman prestadas las texturas: se descartan en cada vuelta y se
+        // construye solo la del mundo que se está viendo
+        let changed = {
+            let assets = asset_set.assets();
+            let mut scenes = WorldScenes::default();
+            
+            inspector.set_preset(inspector.block().and_then(|b| raytracer::block_preset(&assets, b)));
+            let mut live = physics.as_ref().map(|(world, sim)| {
+                LiveScene::new(build_world(&assets, *world, &terrain_config, imported.as_ref()), &assets, sim)
+            });
+            
+            loop {
+                if rl.window_should_close() {
+                    return;
+                }
+                
+                // Archivos cambiados: la construcción importada solo afecta a su mundo y se
+                // rehace aquí; texturas y materiales los usan todos los mundos
+                let changed = watcher.poll();
+                if import_watch.is_some_and(|id| changed.contains(&id)) {
+                    imported = load_import(import.as_ref());
+                    scenes.invalidate(WorldType::Imported);
+                    if imported.is_none() && current_world == WorldType::Imported {
+                        current_world = WorldType::Terrain;
+                    }
+                    if physics.as_ref().is_some_and(|(world, _)| *world == WorldType::Imported) {
+                        physics = None;
+                        live = None;
+                    }
+                }
+                if inspector.has_edit() || changed.iter().any(|id| texture_watches.contains(id) || *id == materials_watch) {
+                    break changed;
+                }
+                
+                let dt = rl.get_frame_time();
+                let speed = 1.6;
+                
+                // Actualizar ciclo solar
+                if animate_sun {
+                    sun_angle += dt * 0.3;
+                }
+                
+                // Control de cámara
+                if rl.is_key_down(KeyboardKey::KEY_LEFT) { orbit.yaw -= speed * dt; }
+                if rl.is_key_down(KeyboardKey::KEY_RIGHT) { orbit.yaw += speed * dt; }
+                if rl.is_key_down(KeyboardKey::KEY_UP) { orbit.pitch -= speed * dt; }
+                if rl.is_key_down(KeyboardKey::KEY_DOWN) { orbit.pitch += speed * dt; }
+                if rl.is_key_down(KeyboardKey::KEY_Q) { orbit.radius = (orbit.radius - 2.0 * dt).max(3.0); }
+                if rl.is_key_down(KeyboardKey::KEY_E) { orbit.radius = (orbit.radius + 2.0 * dt).min(50.0); }
+                
+                orbit.pitch = orbit.pitch.clamp(-PI * 0.48, PI * 0.48);
+                
+                // Cambiar mundo (M)
+                if rl.is_key_pressed(KeyboardKey::KEY_M) {
+                    current_world = current_world.toggle();
+                    if current_world == WorldType::Imported && imported.is_none() {
+                        current_world = current_world.toggle();
+                    }
+                    println!("Mundo: {}", current_world.label());
+                }
+                
+                // Regenerar mundo procedural con la siguiente semilla (G)
+                if rl.is_key_pressed(KeyboardKey::KEY_G) {
+                    terrain_config.seed = terrain_config.seed.wrapping_add(1);
+                    scenes.invalidate(WorldType::Terrain);
+                    println!("Semilla procedural: {}", terrain_config.seed);
+                    if physics.as_ref().is_some_and(|(world, _)| *world == WorldType::Terrain) {
+                        physics = None;
+                        live = None;
+                    }
+                }
+                
+                // Ciclo solar (ESPACIO)
+                if rl.is_key_pressed(KeyboardKey::KEY_SPACE) {
+                    animate_sun = !animate_sun;
+                    println!("Ciclo solar: {}", if animate_sun { "ACTIVO" } else { "PAUSADO" });
+                }
+                
+                // Modo de oclusión ambiental (O)
+                if rl.is_key_pressed(KeyboardKey::KEY_O) {
+                    settings.ao.mode = settings.ao.mode.next();
+                    println!("{}", settings.ao.mode.label());
+                }
+                
+                // Dispersión espectral y cáusticas (D)
+                if rl.is_key_pressed(KeyboardKey::KEY_D) {
+                    settings.spectral.enabled = !settings.spectral.enabled;
+                    println!("Render espectral: {}", if settings.spectral.enabled { "ACTIVO" } else { "APAGADO" });
+                }
+                
+                // Vista de depuración (V)
+                if rl.is_key_pressed(KeyboardKey::KEY_V) {
+                    settings.view = settings.view.next();
+                    println!("Vista: {}", settings.view.label());
+                }
+                
+                // Inspector de materiales (I)
+                if rl.is_key_pressed(KeyboardKey::KEY_I) {
+                    inspector.open = !inspector.open;
+                    println!("Inspector de materiales: {}", if inspector.open { "ABIERTO" } else { "CERRADO" });
+                }
+                inspector.update(&rl);
+                
+                let camera = orbit.to_camera(60.0);
+                let aspect = fb_width as f32 / fb_height as f32;
+                
+                // Pausar la física (F) o quitar todos los bloques sueltos (C)
+                if rl.is_key_pressed(KeyboardKey::KEY_F) {
+                    simulate = !simulate;
+                    println!("Física de bloques: {}", if simulate { "ACTIVA" } else { "PAUSADA" });
+                }
+                if rl.is_key_pressed(KeyboardKey::KEY_C) && physics.is_some() {
+                    physics = None;
+                    live = None;
+                    println!("Bloques sueltos eliminados");
+                }
+                
+                // Soltar un bloque frente a la cara bajo el puntero (1-4). Solo un mundo
+                // simula a la vez: soltar en otro empieza una simulación nueva
+                if let Some(cell) = spawn_key(&rl) {
+                    if physics.as_ref().map(|(world, _)| *world) != Some(current_world) {
+                        let base = build_world(&assets, current_world, &terrain_config, imported.as_ref());
+                        let (scene, sim) = LiveScene::start(base, &assets);
+                        live = Some(scene);
+                        physics = Some((current_world, sim));
+                    }
+                    if let (Some((_, sim)), Some(live)) = (physics.as_mut(), live.as_mut()) {
+                        let ray = mouse_ray(&rl, &camera, aspect);
+                        if let Some((point, _)) = raytracer::pick(live.scene(), &ray) {
+                            // Retroceder un poco por el rayo deja el punto en la celda vecina a la cara
+                            let p = point.sub(ray.dir.mul(0.01));
+                            let pos = (p.x.round() as i32, p.y.round() as i32, p.z.round() as i32);
+                            if sim.spawn(pos, cell) {
+                                live.update(&assets, sim, &HashSet::from([pos]));
+                            }
+                        }
+                    }
+                }
+                
+                // Un paso de simulación cada PHYSICS_TICK, como mucho uno por cuadro
+                if let (Some((world, sim)), Some(live)) = (physics.as_mut(), live.as_mut()) {
+                    if simulate && *world == current_world {
+                        physics_clock += dt;
+                        if physics_clock >= PHYSICS_TICK {
+                            physics_clock = 0.0;
+                            let changed = sim.step();
+                            if !changed.is_empty() {
+                                live.update(&assets, sim, &changed);
+                            }
+                        }
+                    }
+                }
+                
+                // Seleccionar escena actual (raytracing con texturas)
+                let scene_rt = match (&live, &physics) {
+                    (Some(live), Some((world, _))) if *world == current_world => live.scene(),
+                    _ => scenes.get(&assets, current_world, &terrain_config, imported.as_ref()),
+                };
+                
+                // Elegir bloque con clic fuera del panel y guardar su preset (S)
+                if inspector.open {
+                    let mouse = rl.get_mouse_position();
+                    if rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) && !inspector.contains(mouse) {
+                        let ray = mouse_ray(&rl, &camera, aspect);
+                        let block = raytracer::pick(scene_rt, &ray)
+                            .and_then(|(_, mat)| raytracer::identify_block(&assets, &mat));
+                        inspector.select(ray, block, block.and_then(|b| raytracer::block_preset(&assets, b)));
+                    }
+                    if let Some(ray) = inspector.ray() {
+                        inspector.set_sample(raytracer::pick(scene_rt, &ray));
+                    }
+                    if rl.is_key_pressed(KeyboardKey::KEY_S) {
+                        if let Some((block, preset)) = inspector.preset() {
+                            let mut materials = asset_set.materials.clone();
+                            materials.set_preset(block, &preset);
+                            match materials.save(&asset_set.materials_path) {
+                                Ok(()) => {
+                                    watcher.refresh(materials_watch);
+                                    println!("Preset de {} guardado en {}", block, asset_set.materials_path.display());
+                                }
+                                Err(e) => println!("No se pudo guardar {}: {}", asset_set.materials_path.display(), e),
+                            }
+                        }
+                    }
+                }
+                
+                // === RENDERIZADO RAYTRACING ===
+                
+                // Raytracing optimizado en CPU con texturas PNG y ciclo solar
+                let frame_stats = render(
+                    &mut frame,
+                    fb_width as i32,
+                    fb_height as i32,
+                    &camera,
+                    sun_angle, // Pasar el ángulo del sol para iluminación dinámica
+                    scene_rt,
+                    &settings,
+                );
+                let _ = tex.update_texture(&frame);
+
+                
+                let mut d = rl.begin_drawing(&thread);
+                d.clear_background(Color::BLACK);
+                
+                // Escalar textura a pantalla completa con filtrado
+                let src = Rectangle::new(0.0, 0.0, fb_width as f32, fb_height as f32);
+                let dst = Rectangle::new(0.0, 0.0, d.get_screen_width() as f32, d.get_screen_height() as f32);
+                d.draw_texture_pro(&tex, src, dst, Vector2::zero(), 0.0, Color::WHITE);
+                
+                let physics_label = match &physics {
+                    Some((world, sim)) if *world == current_world => format!(" | Física: {} bloques{}",
+                        sim.len(), if simulate { "" } else { " (pausa)" }),
+                    _ => String::new(),
+                };
+                d.draw_text(&format!("FPS: {} | RAYTRACING | {} | Sol: {} | {}{}{}", 
+                    d.get_fps(),
+                    current_world.label(),
+                    if animate_sun { "Animado" } else { "Pausado" },
+                    settings.ao.mode.label(),
+                    if settings.spectral.enabled { " | Espectral" } else { "" },
+                    physics_label
+                ), 10, 10, 24, Color::LIME);
+                d.draw_text("M: Cambiar mundo | G: Nueva semilla | SPACE: Ciclo solar | O: AO | D: Espectral | V: Vista | I: Inspector | 1-4: Bloques | F: Física", 10, 40, 18, Color::YELLOW);
+                if settings.view != RenderView::Beauty {
+                    let pixels = (fb_width * fb_height).max(1) as u64;
+                    d.draw_text(&format!("Vista: {} | {} rayos/px | {} pruebas/px (máx {})",
+                        settings.view.label(),
+                        frame_stats.rays / pixels,
+                        frame_stats.tests / pixels,
+                        frame_stats.max_tests
+                    ), 10, 64, 18, Color::SKYBLUE);
+                }
+                inspector.draw(&mut d);
+            }
+        };
         
-        // Actualizar ciclo solar
-        if animate_sun {
-            sun_angle += dt * 0.3;
+        // Aplicar el preset editado en el inspector a los ajustes en memoria
+        if let Some((block, preset)) = inspector.take_edit() {
+            asset_set.materials.set_preset(block, &preset);
         }
         
-        // Control de cámara
-        if rl.is_key_down(KeyboardKey::KEY_LEFT) { orbit.yaw -= speed * dt; }
-        if rl.is_key_down(KeyboardKey::KEY_RIGHT) { orbit.yaw += speed * dt; }
-        if rl.is_key_down(KeyboardKey::KEY_UP) { orbit.pitch -= speed * dt; }
-        if rl.is_key_down(KeyboardKey::KEY_DOWN) { orbit.pitch += speed * dt; }
-        if rl.is_key_down(KeyboardKey::KEY_Q) { orbit.radius = (orbit.radius - 2.0 * dt).max(3.0); }
-        if rl.is_key_down(KeyboardKey::KEY_E) { orbit.radius = (orbit.radius + 2.0 * dt).min(50.0); }
-        
-        orbit.pitch = orbit.pitch.clamp(-PI * 0.48, PI * 0.48);
-        
-        // Cambiar mundo (M)
-        if rl.is_key_pressed(KeyboardKey::KEY_M) {
-            current_world = current_world.toggle();
-            println!("Mundo: {}", match current_world {
-                WorldType::Overworld => "OVERWORLD",
-                WorldType::Nether => "NETHER",
-            });
+        // Recargar lo que cambió; cámara, sol y mundo actual se conservan
+        if changed.iter().any(|id| texture_watches.contains(id)) {
+            println!("Texturas modificadas, recargando...");
+            asset_set.reload_textures();
         }
-        
-        // Ciclo solar (ESPACIO)
-        if rl.is_key_pressed(KeyboardKey::KEY_SPACE) {
-            animate_sun = !animate_sun;
-            println!("Ciclo solar: {}", if animate_sun { "ACTIVO" } else { "PAUSADO" });
+        if changed.contains(&materials_watch) {
+            println!("Materiales modificados, recargando...");
+            asset_set.reload_materials();
         }
-        
-        // Seleccionar escena actual (raytracing con texturas)
-        let scene_rt = match current_world {
-            WorldType::Overworld => &overworld_rt,
-            WorldType::Nether => &nether_rt,
-        };
-        
-        let camera = orbit.to_camera(60.0);
-        
-        // === RENDERIZADO RAYTRACING ===
-        
-        // Raytracing optimizado en CPU con texturas PNG y ciclo solar
-        render(
-            &mut frame,
-            fb_width as i32,
-            fb_height as i32,
-            &camera,
-            sun_angle, // Pasar el ángulo del sol para iluminación dinámica
-            scene_rt,
-            1, // max depth REDUCIDO de 2 a 1 para MUCHO mejor FPS (menos reflejos recursivos)
-        );
-        let _ = tex.update_texture(&frame);
-
-        
-        let mut d = rl.begin_drawing(&thread);
-        d.clear_background(Color::BLACK);
-        
-        // Escalar textura a pantalla completa con filtrado
-        let src = Rectangle::new(0.0, 0.0, fb_width as f32, fb_height as f32);
-        let dst = Rectangle::new(0.0, 0.0, d.get_screen_width() as f32, d.get_screen_height() as f32);
-        d.draw_texture_pro(&tex, src, dst, Vector2::zero(), 0.0, Color::WHITE);
-        
-        d.draw_text(&format!("FPS: {} | RAYTRACING | {} | Sol: {}", 
-            d.get_fps(),
-            match current_world {
-                WorldType::Overworld => "OVERWORLD",
-                WorldType::Nether => "NETHER",
-            },
-            if animate_sun { "Animado" } else { "Pausado" }
-        ), 10, 10, 24, Color::LIME);
-        d.draw_text("M: Cambiar mundo | SPACE: Ciclo solar", 10, 40, 18, Color::YELLOW);
     }
 }
@@ -0,0 +1,371 @@
+//! Ajustes de materiales de bloque leídos de un archivo de texto (`materials.txt`).
+//!
+//! Permite retocar los parámetros de la paleta sin recompilar; el visor vuelve a leer
+//! el archivo cuando cambia. Formato por secciones, una por bloque:
+//!
+//! ```text
+//! # Comentario
+//! [water]
+//! transparency = 0.6
+//! albedo = 0.5 0.7 1.0
+//! ```
+//!
+//! Los campos que no aparecen conservan el valor de la paleta. El inspector de
+//! materiales del visor escribe aquí los presets ajustados (`MaterialOverrides::save`).
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::math::Vec3;
+
+/// Bloques de la paleta que se pueden ajustar.
+pub const BLOCKS: [&str; 17] = [
+    "grass", "dirt", "wood", "leaves", "obsidian", "portal", "stone",
+    "water", "iron", "diamond", "lava", "glowstone", "ice", "glass", "torch",
+    "sand", "gravel",
+];
+
+/// Campo de `BlockMaterial` que se puede sobrescribir.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum MaterialField {
+    Albedo,
+    Specular,
+    Shininess,
+    Reflectivity,
+    Transparency,
+    Ior,
+    Dispersion,
+    Emissive,
+    Roughness,
+}
+
+impl MaterialField {
+    pub const ALL: [MaterialField; 9] = [
+        Self::Albedo,
+        Self::Specular,
+        Self::Shininess,
+        Self::Reflectivity,
+        Self::Transparency,
+        Self::Ior,
+        Self::Dispersion,
+        Self::Emissive,
+        Self::Roughness,
+    ];
+
+    /// Nombre del campo en el archivo.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Albedo => "albedo",
+            Self::Specular => "specular",
+            Self::Shininess => "shininess",
+            Self::Reflectivity => "reflectivity",
+            Self::Transparency => "transparency",
+            Self::Ior => "ior",
+            Self::Dispersion => "dispersion",
+            Self::Emissive => "emissive",
+            Self::Roughness => "roughness",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|f| f.name() == name)
+    }
+
+    /// Cantidad de números que espera el campo (colores: 3).
+    fn arity(self) -> usize {
+        match self {
+            Self::Albedo | Self::Emissive => 3,
+            _ => 1,
+        }
+    }
+
+    /// Rango admitido para cada número del campo (los colores no tienen tope).
+    fn range(self) -> (f32, f32) {
+        match self {
+            Self::Albedo | Self::Emissive | Self::Dispersion => (0.0, f32::INFINITY),
+            Self::Specular | Self::Reflectivity | Self::Transparency | Self::Roughness => (0.0, 1.0),
+            Self::Shininess | Self::Ior => (1.0, f32::INFINITY),
+        }
+    }
+
+    /// Mensaje de error si `v` no es finito o cae fuera de `range`.
+    fn check(self, v: f32) -> Result<(), String> {
+        let (min, max) = self.range();
+        if v.is_finite() && (min..=max).contains(&v) {
+            Ok(())
+        } else if max.is_finite() {
+            Err(format!("'{}' debe estar entre {} y {} (vale {})", self.name(), min, max, v))
+        } else {
+            Err(format!("'{}' debe ser un número finito >= {} (vale {})", self.name(), min, v))
+        }
+    }
+}
+
+/// Valor de un campo: un escalar o un color RGB.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FieldValue {
+    Scalar(f32),
+    Color(f32, f32, f32),
+}
+
+impl fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Scalar(v) => write!(f, "{}", v),
+            Self::Color(r, g, b) => write!(f, "{} {} {}", r, g, b),
+        }
+    }
+}
+
+/// Parámetros editables de un bloque de la paleta (lo que el archivo puede ajustar).
+#[derive(Copy, Clone, Debug)]
+pub struct MaterialPreset {
+    pub albedo: Vec3,
+    pub specular: f32,
+    pub shininess: f32,
+    pub reflectivity: f32,
+    pub transparency: f32,
+    pub ior: f32,
+    pub dispersion: f32,
+    pub emissive: Vec3,
+    pub roughness: f32,
+}
+
+impl MaterialPreset {
+    pub fn get(&self, field: MaterialField) -> FieldValue {
+        let color = |c: Vec3| FieldValue::Color(c.x, c.y, c.z);
+        match field {
+            MaterialField::Albedo => color(self.albedo),
+            MaterialField::Emissive => color(self.emissive),
+            MaterialField::Specular => FieldValue::Scalar(self.specular),
+            MaterialField::Shininess => FieldValue::Scalar(self.shininess),
+            MaterialField::Reflectivity => FieldValue::Scalar(self.reflectivity),
+            MaterialField::Transparency => FieldValue::Scalar(self.transparency),
+            MaterialField::Ior => FieldValue::Scalar(self.ior),
+            MaterialField::Dispersion => FieldValue::Scalar(self.dispersion),
+            MaterialField::Roughness => FieldValue::Scalar(self.roughness),
+        }
+    }
+
+    /// Asigna `value` si su forma (escalar o color) corresponde al campo.
+    pub fn set(&mut self, field: MaterialField, value: FieldValue) {
+        match (field, value) {
+            (MaterialField::Albedo, FieldValue::Color(r, g, b)) => self.albedo = Vec3::new(r, g, b),
+            (MaterialField::Emissive, FieldValue::Color(r, g, b)) => self.emissive = Vec3::new(r, g, b),
+            (MaterialField::Specular, FieldValue::Scalar(v)) => self.specular = v,
+            (MaterialField::Shininess, FieldValue::Scalar(v)) => self.shininess = v,
+            (MaterialField::Reflectivity, FieldValue::Scalar(v)) => self.reflectivity = v,
+            (MaterialField::Transparency, FieldValue::Scalar(v)) => self.transparency = v,
+            (MaterialField::Ior, FieldValue::Scalar(v)) => self.ior = v,
+            (MaterialField::Dispersion, FieldValue::Scalar(v)) => self.dispersion = v,
+            (MaterialField::Roughness, FieldValue::Scalar(v)) => self.roughness = v,
+            _ => {}
+        }
+    }
+
+    /// Aplica los campos que ajusta el archivo, en orden.
+    pub fn apply(&mut self, fields: &[(MaterialField, FieldValue)]) {
+        for &(field, value) in fields {
+            self.set(field, value);
+        }
+    }
+}
+
+/// Línea del archivo que no se pudo interpretar.
+#[derive(Clone, Debug)]
+pub struct OverrideError {
+    pub path: PathBuf,
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for OverrideError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.path.display(), self.line, self.message)
+    }
+}
+
+impl std::error::Error for OverrideError {}
+
+/// Campos sobrescritos por bloque.
+#[derive(Clone, Debug, Default)]
+pub struct MaterialOverrides {
+    blocks: HashMap<String, Vec<(MaterialField, FieldValue)>>,
+}
+
+impl MaterialOverrides {
+    /// Lee y valida el archivo. Un archivo inexistente equivale a no tener ajustes.
+    pub fn load(path: &Path) -> Result<Self, OverrideError> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text).map_err(|(line, message)| OverrideError {
+                path: path.to_path_buf(),
+                line,
+                message,
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(OverrideError { path: path.to_path_buf(), line: 0, message: e.to_string() }),
+        }
+    }
+
+    /// Interpreta el texto; el error trae el número de línea (desde 1).
+    pub fn parse(text: &str) -> Result<Self, (usize, String)> {
+        let mut overrides = Self::default();
+        let mut block: Option<&str> = None;
+        for (i, raw) in text.lines().enumerate() {
+            let line_no = i + 1;
+            let line = raw.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let name = name.trim();
+                if !BLOCKS.contains(&name) {
+                    return Err((line_no, format!("bloque desconocido '{}'", name)));
+                }
+                block = Some(name);
+                continue;
+            }
+            let Some(current) = block else {
+                return Err((line_no, "campo fuera de una sección [bloque]".to_string()));
+            };
+            let Some((key, value)) = line.split_once('=') else {
+                return Err((line_no, format!("se esperaba 'campo = valor', no '{}'", line)));
+            };
+            let key = key.trim();
+            let field = MaterialField::parse(key).ok_or_else(|| (line_no, format!("campo desconocido '{}'", key)))?;
+            let numbers: Vec<f32> = value
+                .split_whitespace()
+                .map(|n| n.parse::<f32>().map_err(|_| (line_no, format!("'{}' no es un número", n))))
+                .collect::<Result<_, _>>()?;
+            let value = match (field.arity(), numbers.as_slice()) {
+                (1, &[v]) => FieldValue::Scalar(v),
+                (3, &[r, g, b]) => FieldValue::Color(r, g, b),
+                (n, _) => return Err((line_no, format!("'{}' espera {} número(s)", key, n))),
+            };
+            if let Some(e) = numbers.iter().find_map(|&v| field.check(v).err()) {
+                return Err((line_no, e));
+            }
+            let fields = overrides.blocks.entry(current.to_string()).or_default();
+            fields.retain(|(f, _)| *f != field);
+            fields.push((field, value));
+        }
+        Ok(overrides)
+    }
+
+    /// Campos ajustados de `block`, en el orden del archivo.
+    pub fn fields(&self, block: &str) -> &[(MaterialField, FieldValue)] {
+        self.blocks.get(block).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// Reemplaza los ajustes de `block` por todos los campos de `preset`.
+    pub fn set_preset(&mut self, block: &str, preset: &MaterialPreset) {
+        let fields = MaterialField::ALL.iter().map(|&f| (f, preset.get(f))).collect();
+        self.blocks.insert(block.to_string(), fields);
+    }
+
+    /// Texto en el formato de `parse`, con los bloques en el orden de `BLOCKS`.
+    pub fn to_text(&self) -> String {
+        let mut out = String::from("# Ajustes de materiales de bloque (ver `material_overrides.rs`).\n");
+        for block in BLOCKS {
+            let fields = self.fields(block);
+            if fields.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("\n[{}]\n", block));
+            for (field, value) in fields {
+                out.push_str(&format!("{} = {}\n", field.name(), value));
+            }
+        }
+        out
+    }
+
+    /// Guarda los ajustes en `path` (reemplaza el archivo, comentarios incluidos).
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::write(path, self.to_text())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sections_and_fields() {
+        let text = "# ajustes\n[water]\ntransparency = 0.6  # más clara\nalbedo = 0.5 0.7 1.0\n\n[diamond]\nior=2.4\ntransparency = 0.2\n";
+        let o = MaterialOverrides::parse(text).unwrap();
+        assert_eq!(o.fields("water"), [
+            (MaterialField::Transparency, FieldValue::Scalar(0.6)),
+            (MaterialField::Albedo, FieldValue::Color(0.5, 0.7, 1.0)),
+        ]);
+        assert_eq!(o.fields("diamond").len(), 2);
+        assert!(o.fields("stone").is_empty());
+    }
+
+    #[test]
+    fn repeated_field_keeps_the_last_value() {
+        let o = MaterialOverrides::parse("[ice]\nior = 1.2\nior = 1.31\n").unwrap();
+        assert_eq!(o.fields("ice"), [(MaterialField::Ior, FieldValue::Scalar(1.31))]);
+    }
+
+    #[test]
+    fn errors_carry_the_line_number() {
+        let cases = [
+            ("[lodo]\n", 1),
+            ("ior = 1.5\n", 1),
+            ("[water]\n\ntransparency 0.5\n", 3),
+            ("[water]\nbrillo = 1\n", 2),
+            ("[water]\nalbedo = 1 2\n", 2),
+            ("[water]\nior = uno\n", 2),
+        ];
+        for (text, line) in cases {
+            let (got, message) = MaterialOverrides::parse(text).unwrap_err();
+            assert_eq!(got, line, "{:?}: {}", text, message);
+        }
+    }
+
+    #[test]
+    fn out_of_range_values_are_errors() {
+        for line in [
+            "ior = 0",
+            "ior = 0.5",
+            "transparency = -0.1",
+            "reflectivity = 1.5",
+            "roughness = NaN",
+            "shininess = 0",
+            "dispersion = -0.01",
+            "albedo = 0.5 inf 0.5",
+            "emissive = -1 0 0",
+        ] {
+            let text = format!("[glass]\n# comentario\n{}\n", line);
+            assert_eq!(MaterialOverrides::parse(&text).map(|_| ()).unwrap_err().0, 3, "{}", line);
+        }
+        // Los extremos del rango son válidos
+        assert!(MaterialOverrides::parse("[glass]\ntransparency = 1\nior = 1\nemissive = 4 4 4\n").is_ok());
+    }
+
+    #[test]
+    fn to_text_round_trips() {
+        let preset = MaterialPreset {
+            albedo: Vec3::new(0.9, 1.0, 1.0),
+            specular: 0.7,
+            shininess: 120.0,
+            reflectivity: 0.15,
+            transparency: 0.4,
+            ior: 2.42,
+            dispersion: 0.023,
+            emissive: Vec3::new(0.0, 0.0, 0.0),
+            roughness: 0.05,
+        };
+        let mut o = MaterialOverrides::parse("[water]\ntransparency = 0.6\n").unwrap();
+        o.set_preset("diamond", &preset);
+        let back = MaterialOverrides::parse(&o.to_text()).unwrap();
+        for block in BLOCKS {
+            assert_eq!(back.fields(block), o.fields(block), "{}", block);
+        }
+    }
+}
@@ -1,5 +1,7 @@
 //! Definición de materiales y la interfaz `Intersectable` usada por las primitivas.
 
+use crate::bvh::Aabb;
+use crate::lighting::Tex;
 use crate::math::Vec3;
 use crate::ray::Ray;
 
@@ -12,8 +14,50 @@ pub struct MaterialParams {
     pub reflectivity: f32,      // [0..1]
     pub transparency: f32,      // [0..1]
     pub ior: f32,               // índice de refracción (1=aire)
+    pub dispersion: f32,        // coeficiente B de Cauchy en µm² (0 = sin dispersión)
     pub emissive: Vec3,         // luz propia
     pub opacity: f32,   
+    pub roughness: f32,         // [0..1] 0 = espejo perfecto, 1 = reflejo muy difuso
+    pub normal: Option<Vec3>,   // normal de sombreado (mapa de normales); None = geométrica
+}
+
+/// Mapas opcionales por texel que acompañan al albedo de un bloque.
+/// Todos usan las mismas UV que la textura base de cada cara.
+#[derive(Copy, Clone, Default)]
+pub struct MaterialMaps<'a> {
+    pub normal: Option<Tex<'a>>,    // Normal en espacio tangente (RGB = XYZ * 0.5 + 0.5)
+    pub roughness: Option<Tex<'a>>, // Canal R = rugosidad
+    pub specular: Option<Tex<'a>>,  // Luminancia = multiplicador de specular_strength
+    pub emissive: Option<Tex<'a>>,  // RGB sumado a la emisión del material
+}
+
+impl<'a> MaterialMaps<'a> {
+    pub fn is_empty(&self) -> bool {
+        self.normal.is_none() && self.roughness.is_none() && self.specular.is_none() && self.emissive.is_none()
+    }
+
+    /// Aplica los mapas sobre `params` en la coordenada `(u, v)` de una cara con
+    /// base tangente `t`, `b` y normal geométrica `n`.
+    pub fn apply(&self, params: &mut MaterialParams, u: f32, v: f32, t: Vec3, b: Vec3, n: Vec3) {
+        if let Some(tex) = self.normal {
+            let c = tex.sample_nearest(u, v);
+            let ts = Vec3::new(c.x * 2.0 - 1.0, c.y * 2.0 - 1.0, c.z * 2.0 - 1.0);
+            params.normal = Some(t.mul(ts.x).add(b.mul(ts.y)).add(n.mul(ts.z.max(0.05))).norm());
+        }
+        if let Some(tex) = self.roughness {
+            params.roughness = tex.sample_nearest(u, v).x;
+            // Superficies rugosas: brillo especular más ancho
+            let gloss = (1.0 - params.roughness).max(0.05);
+            params.shininess = (params.shininess * gloss * gloss).max(1.0);
+        }
+        if let Some(tex) = self.specular {
+            let c = tex.sample_nearest(u, v);
+            params.specular_strength *= (c.x + c.y + c.z) / 3.0;
+        }
+        if let Some(tex) = self.emissive {
+            params.emissive = params.emissive.add(tex.sample_nearest(u, v));
+        }
+    }
 }
 
 /// Interfaz común para cualquier objeto intersectable por un rayo.
@@ -30,6 +74,23 @@ pub trait Intersectable: Send + Sync {
     // NUEVO: centro del objeto (para fuentes de luz)
     fn center(&self) -> Vec3;
 
+    /// Caja envolvente para el BVH; por defecto infinita (se prueba siempre).
+    fn bounds(&self) -> Aabb {
+        Aabb::EVERYTHING
+    }
+
+    /// Textura de la cara con normal local `n` (±X, ±Y o ±Z) para exportar el mundo como
+    /// malla; las UV siguen `textured_block::face_uv`. `None` = cara de color plano.
+    fn face_texture(&self, _n: Vec3) -> Option<Tex<'_>> {
+        None
+    }
+
+    /// `true` si `intersect` descarta los texeles transparentes (hojas, vidrio, antorchas).
+    /// Estos objetos no cuentan como celdas llenas para el AO por vóxel.
+    fn is_cutout(&self) -> bool {
+        false
+    }
+
     // NUEVO: material paramétrico por punto
     fn material_at(&self, p: Vec3) -> MaterialParams {
         MaterialParams {
@@ -39,8 +100,11 @@ pub trait Intersectable: Send + Sync {
             reflectivity: 0.0,
             transparency: 0.0,
             ior: 1.0,
+            dispersion: 0.0,
             emissive: Vec3::new(0.0, 0.0, 0.0),
             opacity: 1.0, 
+            roughness: 0.0,
+            normal: None,
         }
     }
 }
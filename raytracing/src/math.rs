@@ -70,6 +70,141 @@ impl Vec3 {
     }
 }
 
+/// Transformación afín: `p' = m * p + t` (matriz 3×3 por filas más traslación).
+#[derive(Debug, Clone, Copy)]
+pub struct Transform {
+    pub m: [[f32; 3]; 3],
+    pub t: Vec3,
+}
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform {
+        m: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+        t: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+    };
+
+    pub fn translate(v: Vec3) -> Self {
+        Self { t: v, ..Self::IDENTITY }
+    }
+
+    pub fn scale(s: Vec3) -> Self {
+        Self {
+            m: [[s.x, 0.0, 0.0], [0.0, s.y, 0.0], [0.0, 0.0, s.z]],
+            ..Self::IDENTITY
+        }
+    }
+
+    /// Rotación alrededor de Y (radianes, regla de la mano derecha).
+    pub fn rotate_y(angle: f32) -> Self {
+        let (s, c) = angle.sin_cos();
+        Self {
+            m: [[c, 0.0, s], [0.0, 1.0, 0.0], [-s, 0.0, c]],
+            ..Self::IDENTITY
+        }
+    }
+
+    /// Composición: primero `self`, después `after`.
+    pub fn then(&self, after: &Transform) -> Transform {
+        let mut m = [[0.0; 3]; 3];
+        for (r, row) in m.iter_mut().enumerate() {
+            for (c, v) in row.iter_mut().enumerate() {
+                *v = (0..3).map(|k| after.m[r][k] * self.m[k][c]).sum();
+            }
+        }
+        Transform { m, t: after.point(self.t) }
+    }
+
+    pub fn point(&self, p: Vec3) -> Vec3 {
+        self.vector(p).add(self.t)
+    }
+
+    pub fn vector(&self, v: Vec3) -> Vec3 {
+        let m = &self.m;
+        Vec3::new(
+            m[0][0] * v.x + m[0][1] * v.y + m[0][2] * v.z,
+            m[1][0] * v.x + m[1][1] * v.y + m[1][2] * v.z,
+            m[2][0] * v.x + m[2][1] * v.y + m[2][2] * v.z,
+        )
+    }
+
+    /// Transforma una normal con la inversa transpuesta de `m` (`self` debe ser la
+    /// transformación inversa de la que se aplicó a los puntos). No normaliza.
+    pub fn normal_from_inverse(&self, n: Vec3) -> Vec3 {
+        let m = &self.m;
+        Vec3::new(
+            m[0][0] * n.x + m[1][0] * n.y + m[2][0] * n.z,
+            m[0][1] * n.x + m[1][1] * n.y + m[2][1] * n.z,
+            m[0][2] * n.x + m[1][2] * n.y + m[2][2] * n.z,
+        )
+    }
+
+    /// Inversa, o `None` si la matriz es singular (escala 0).
+    pub fn inverse(&self) -> Option<Transform> {
+        let m = &self.m;
+        let cof = |r0: usize, r1: usize, c0: usize, c1: usize| m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0];
+        let det = m[0][0] * cof(1, 2, 1, 2) - m[0][1] * cof(1, 2, 0, 2) + m[0][2] * cof(1, 2, 0, 1);
+        if det.abs() < 1e-12 {
+            return None;
+        }
+        let d = 1.0 / det;
+        let inv = [
+            [cof(1, 2, 1, 2) * d, -cof(0, 2, 1, 2) * d, cof(0, 1, 1, 2) * d],
+            [-cof(1, 2, 0, 2) * d, cof(0, 2, 0, 2) * d, -cof(0, 1, 0, 2) * d],
+            [cof(1, 2, 0, 1) * d, -cof(0, 2, 0, 1) * d, cof(0, 1, 0, 1) * d],
+        ];
+        let lin = Transform { m: inv, t: Vec3::zero() };
+        Some(Transform { t: -lin.vector(self.t), ..lin })
+    }
+
+    /// `true` si lleva la rejilla de bloques sobre sí misma: giros de 90°, sin escala
+    /// y traslación entera. Solo así los bloques instanciados cuentan para el AO por vóxel.
+    pub fn is_grid_aligned(&self) -> bool {
+        let near = |v: f32, target: f32| (v - target).abs() < 1e-4;
+        let axes = self.m.iter().all(|row| {
+            row.iter().filter(|v| near(v.abs(), 1.0)).count() == 1 && row.iter().filter(|v| near(**v, 0.0)).count() == 2
+        });
+        let t = self.t;
+        axes && near(t.x, t.x.round()) && near(t.y, t.y.round()) && near(t.z, t.z.round())
+    }
+}
+
+/// Base ortonormal (tangente, bitangente) alrededor de `n`.
+pub fn orthonormal_basis(n: Vec3) -> (Vec3, Vec3) {
+    let helper = if n.x.abs() > 0.9 {
+        Vec3::new(0.0, 1.0, 0.0)
+    } else {
+        Vec3::new(1.0, 0.0, 0.0)
+    };
+    let t = helper.cross(n).norm();
+    let b = n.cross(t);
+    (t, b)
+}
+
+/// Hash entero para sembrar muestreos deterministas a partir de una posición.
+pub fn hash3(x: u32, y: u32, z: u32) -> u32 {
+    let mut h = x.wrapping_mul(0x8da6_b343) ^ y.wrapping_mul(0xd816_3841) ^ z.wrapping_mul(0xcb1a_b31f);
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x7feb_352d);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x846c_a68b);
+    h ^ (h >> 16)
+}
+
+/// Semilla estable para un punto de la escena.
+pub fn hash_point(p: Vec3) -> u32 {
+    hash3(p.x.to_bits(), p.y.to_bits(), p.z.to_bits())
+}
+
+/// Avanza el estado (xorshift) y devuelve un número en [0, 1).
+pub fn next_unit(state: &mut u32) -> f32 {
+    let mut x = *state | 1;
+    x ^= x << 13;
+    x ^= x >> 17;
+    x ^= x << 5;
+    *state = x;
+    (x >> 8) as f32 / (1u32 << 24) as f32
+}
+
 // Implementar operador - unario para negación
 impl std::ops::Neg for Vec3 {
     type Output = Vec3;
@@ -0,0 +1,424 @@
+//! Exporta el mundo de bloques a glTF 2.0 y OBJ+MTL para abrirlo en Blender o en
+//! otros renderizadores (p. ej. el rasterizador de `solar_system`).
+//!
+//! Cada caja de la escena (incluidas las copias de las instancias) se convierte en seis
+//! quads con las mismas UV que usa el trazador. Las caras pegadas a un bloque opaco, o a
+//! otro bloque transparente del mismo material (agua con agua), se descartan. Todo se
+//! junta en una sola malla con una primitiva por material; las texturas se escriben
+//! como PNG junto al archivo.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::aov::{material_id, save_png};
+use crate::instance::Group;
+use crate::materials::{Intersectable, MaterialParams};
+use crate::math::{Transform, Vec3};
+use crate::textured_block::face_uv;
+
+/// Material de una primitiva exportada.
+pub struct ExportMaterial {
+    pub name: String,
+    pub color: Vec3,             // Multiplica la textura (blanco si hay textura)
+    pub texture: Option<usize>,  // Índice en `MeshExport::textures`
+    pub specular: f32,
+    pub shininess: f32,
+    pub roughness: f32,
+    pub transparency: f32,
+    pub ior: f32,
+    pub emissive: Vec3,
+    pub cutout: bool,            // Recorte por alfa de la textura
+}
+
+/// Triángulos de un mismo material.
+pub struct Primitive {
+    pub material: ExportMaterial,
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub uvs: Vec<[f32; 2]>, // v hacia arriba, como en el trazador
+    pub indices: Vec<u32>,
+}
+
+/// Textura RGBA8 copiada de los assets.
+pub struct ExportTexture {
+    pub w: u32,
+    pub h: u32,
+    pub rgba: Vec<u8>,
+}
+
+/// Malla del mundo lista para escribir.
+pub struct MeshExport {
+    pub primitives: Vec<Primitive>,
+    pub textures: Vec<ExportTexture>,
+    pub culled_faces: usize,
+    pub skipped_objects: usize, // Objetos sin caja finita (planos infinitos)
+}
+
+/// Contenido de una celda de la rejilla, para descartar caras ocultas.
+#[derive(Copy, Clone)]
+struct CellFill {
+    opaque: bool,
+    material: u32,
+}
+
+/// Identifica un material exportado: textura, parámetros constantes y color plano.
+type MaterialKey = (usize, u32, [u32; 3], bool);
+
+/// Celda de la rejilla que ocupa el objeto si es un bloque unitario alineado.
+fn unit_cell(o: &dyn Intersectable, to_world: &Transform) -> Option<(i32, i32, i32)> {
+    if !to_world.is_grid_aligned() {
+        return None;
+    }
+    let b = o.bounds();
+    let size = b.max.sub(b.min);
+    let unit = |s: f32| (s - 1.0).abs() < 0.01;
+    if !(unit(size.x) && unit(size.y) && unit(size.z)) {
+        return None;
+    }
+    let c = to_world.point(b.centroid());
+    let (rx, ry, rz) = (c.x.round(), c.y.round(), c.z.round());
+    let on_grid = (c.x - rx).abs() < 0.01 && (c.y - ry).abs() < 0.01 && (c.z - rz).abs() < 0.01;
+    on_grid.then_some((rx as i32, ry as i32, rz as i32))
+}
+
+fn is_see_through(o: &dyn Intersectable, mat: &MaterialParams) -> bool {
+    o.is_cutout() || mat.transparency > 0.0
+}
+
+fn axis(v: Vec3, i: usize) -> f32 {
+    [v.x, v.y, v.z][i]
+}
+
+fn with_axes(i: usize, a: f32, j: usize, b: f32, k: usize, c: f32) -> Vec3 {
+    let mut v = [0.0; 3];
+    v[i] = a;
+    v[j] = b;
+    v[k] = c;
+    Vec3::new(v[0], v[1], v[2])
+}
+
+impl MeshExport {
+    /// Recorre todos los objetos de `scene` (con sus instancias) y arma la malla.
+    pub fn from_group(scene: &Group<'_>) -> Self {
+        let mut cells: HashMap<(i32, i32, i32), CellFill> = HashMap::new();
+        scene.for_each_object(&Transform::IDENTITY, &mut |o, to_local| {
+            let Some(to_world) = to_local.inverse() else { return };
+            if let Some(cell) = unit_cell(o, &to_world) {
+                let mat = o.material_at(o.center());
+                cells.insert(cell, CellFill { opaque: !is_see_through(o, &mat), material: material_id(&mat) });
+            }
+        });
+
+        let mut out = MeshExport { primitives: Vec::new(), textures: Vec::new(), culled_faces: 0, skipped_objects: 0 };
+        let mut by_material: HashMap<MaterialKey, usize> = HashMap::new();
+        let mut by_texture: HashMap<usize, usize> = HashMap::new();
+        scene.for_each_object(&Transform::IDENTITY, &mut |o, to_local| {
+            let b = o.bounds();
+            let finite = [b.min.x, b.min.y, b.min.z, b.max.x, b.max.y, b.max.z].iter().all(|v| v.is_finite());
+            let Some(to_world) = to_local.inverse().filter(|_| finite && b.min.x <= b.max.x) else {
+                out.skipped_objects += 1;
+                return;
+            };
+            let cell = unit_cell(o, &to_world);
+            for i in 0..3 {
+                let (j, k) = ((i + 1) % 3, (i + 2) % 3);
+                for sign in [1.0f32, -1.0] {
+                    let n = with_axes(i, sign, j, 0.0, k, 0.0);
+                    let world_n = to_local.normal_from_inverse(n).norm();
+                    let plane = if sign > 0.0 { axis(b.max, i) } else { axis(b.min, i) };
+                    let center = with_axes(
+                        i, plane,
+                        j, (axis(b.min, j) + axis(b.max, j)) * 0.5,
+                        k, (axis(b.min, k) + axis(b.max, k)) * 0.5,
+                    );
+                    let mat = o.material_at(center);
+                    let id = material_id(&mat);
+
+                    if let Some((x, y, z)) = cell {
+                        let d = (world_n.x.round() as i32, world_n.y.round() as i32, world_n.z.round() as i32);
+                        if let Some(next) = cells.get(&(x + d.0, y + d.1, z + d.2)) {
+                            let same_glass = !o.is_cutout() && mat.transparency > 0.0 && next.material == id;
+                            if next.opaque || same_glass {
+                                out.culled_faces += 1;
+                                continue;
+                            }
+                        }
+                    }
+
+                    // Esquinas en sentido antihorario vistas desde fuera
+                    let (j0, j1, k0, k1) = (axis(b.min, j), axis(b.max, j), axis(b.min, k), axis(b.max, k));
+                    let mut corners = [(j0, k0), (j1, k0), (j1, k1), (j0, k1)].map(|(a, c)| with_axes(i, plane, j, a, k, c));
+                    if sign < 0.0 {
+                        corners.reverse();
+                    }
+                    let mut world = corners.map(|c| to_world.point(c));
+                    // Una transformación con espejo invierte el sentido de giro
+                    if world[1].sub(world[0]).cross(world[2].sub(world[0])).dot(world_n) < 0.0 {
+                        corners.reverse();
+                        world.reverse();
+                    }
+
+                    let tex = o.face_texture(n);
+                    let tex_key = tex.map_or(0, |t| t.pix.as_ptr() as usize);
+                    let flat = if tex.is_some() { [0; 3] } else { [mat.albedo.x.to_bits(), mat.albedo.y.to_bits(), mat.albedo.z.to_bits()] };
+                    let key = (tex_key, id, flat, o.is_cutout());
+                    let slot = *by_material.entry(key).or_insert_with(|| {
+                        let texture = tex.map(|t| {
+                            let next = by_texture.len();
+                            *by_texture.entry(tex_key).or_insert_with(|| {
+                                let len = ((t.w * t.h * 4) as usize).min(t.pix.len());
+                                out.textures.push(ExportTexture { w: t.w, h: t.h, rgba: t.pix[..len].to_vec() });
+                                next
+                            })
+                        });
+                        out.primitives.push(Primitive {
+                            material: ExportMaterial {
+                                name: format!("material_{}", out.primitives.len()),
+                                color: if texture.is_some() { Vec3::new(1.0, 1.0, 1.0) } else { mat.albedo },
+                                texture,
+                                specular: mat.specular_strength,
+                                shininess: mat.shininess,
+                                roughness: mat.roughness,
+                                transparency: mat.transparency,
+                                ior: mat.ior,
+                                emissive: mat.emissive,
+                                cutout: o.is_cutout(),
+                            },
+                            positions: Vec::new(),
+                            normals: Vec::new(),
+                            uvs: Vec::new(),
+                            indices: Vec::new(),
+                        });
+                        out.primitives.len() - 1
+                    });
+
+                    let prim = &mut out.primitives[slot];
+                    let base = prim.positions.len() as u32;
+                    for (c, w) in corners.iter().zip(world.iter()) {
+                        let (u, v) = face_uv(b.min, b.max, n, *c);
+                        prim.positions.push([w.x, w.y, w.z]);
+                        prim.normals.push([world_n.x, world_n.y, world_n.z]);
+                        prim.uvs.push([u, v]);
+                    }
+                    prim.indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+                }
+            }
+        });
+        out
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.primitives.iter().map(|p| p.indices.len() / 3).sum()
+    }
+
+    /// Escribe según la extensión de `path`: `.gltf` u `.obj`.
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        if self.primitives.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "la escena no tiene bloques que exportar"));
+        }
+        match path.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase).as_deref() {
+            Some("gltf") => self.write_gltf(path),
+            Some("obj") => self.write_obj(path),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidInput, "extensión no soportada (usa .gltf u .obj)")),
+        }
+    }
+
+    /// Guarda las texturas como `<nombre>_tex<i>.png` junto a `path` y devuelve sus nombres.
+    fn write_textures(&self, path: &Path) -> io::Result<Vec<String>> {
+        let stem = file_stem(path);
+        let mut names = Vec::with_capacity(self.textures.len());
+        for (i, t) in self.textures.iter().enumerate() {
+            let name = format!("{}_tex{}.png", stem, i);
+            save_png(&sibling(path, &name), &t.rgba, t.w, t.h)?;
+            names.push(name);
+        }
+        Ok(names)
+    }
+
+    /// OBJ con triángulos y su `.mtl` (mismo nombre).
+    pub fn write_obj(&self, path: &Path) -> io::Result<()> {
+        let textures = self.write_textures(path)?;
+        let mtl_name = format!("{}.mtl", file_stem(path));
+
+        let mut mtl = io::BufWriter::new(fs::File::create(sibling(path, &mtl_name))?);
+        for p in &self.primitives {
+            let m = &p.material;
+            writeln!(mtl, "newmtl {}", m.name)?;
+            writeln!(mtl, "Kd {} {} {}", m.color.x, m.color.y, m.color.z)?;
+            writeln!(mtl, "Ks {0} {0} {0}", m.specular)?;
+            writeln!(mtl, "Ns {}", m.shininess)?;
+            writeln!(mtl, "Ni {}", m.ior)?;
+            writeln!(mtl, "d {}", 1.0 - m.transparency)?;
+            writeln!(mtl, "Ke {} {} {}", m.emissive.x, m.emissive.y, m.emissive.z)?;
+            writeln!(mtl, "illum 2")?;
+            if let Some(t) = m.texture {
+                writeln!(mtl, "map_Kd {}", textures[t])?;
+                if m.cutout {
+                    writeln!(mtl, "map_d {}", textures[t])?;
+                }
+            }
+            writeln!(mtl)?;
+        }
+        mtl.flush()?;
+
+        let mut obj = io::BufWriter::new(fs::File::create(path)?);
+        writeln!(obj, "# Diorama exportado por raytracing_diorama")?;
+        writeln!(obj, "mtllib {}", mtl_name)?;
+        writeln!(obj, "o diorama")?;
+        for p in &self.primitives {
+            for v in &p.positions {
+                writeln!(obj, "v {} {} {}", v[0], v[1], v[2])?;
+            }
+        }
+        for p in &self.primitives {
+            for t in &p.uvs {
+                writeln!(obj, "vt {} {}", t[0], t[1])?;
+            }
+        }
+        for p in &self.primitives {
+            for n in &p.normals {
+                writeln!(obj, "vn {} {} {}", n[0], n[1], n[2])?;
+            }
+        }
+        // Posición, UV y normal comparten índice (base 1)
+        let mut offset = 1;
+        for p in &self.primitives {
+            writeln!(obj, "usemtl {}", p.material.name)?;
+            for tri in p.indices.chunks(3) {
+                let [a, b, c] = [tri[0], tri[1], tri[2]].map(|i| i as usize + offset);
+                writeln!(obj, "f {0}/{0}/{0} {1}/{1}/{1} {2}/{2}/{2}", a, b, c)?;
+            }
+            offset += p.positions.len();
+        }
+        obj.flush()
+    }
+
+    /// glTF 2.0 con el búfer en `<nombre>.bin` y las texturas en PNG aparte.
+    pub fn write_gltf(&self, path: &Path) -> io::Result<()> {
+        let textures = self.write_textures(path)?;
+        let bin_name = format!("{}.bin", file_stem(path));
+
+        let mut bin: Vec<u8> = Vec::new();
+        let mut views: Vec<String> = Vec::new();
+        let mut accessors: Vec<String> = Vec::new();
+        let mut push_view = |bin: &mut Vec<u8>, data: &[u8], target: u32| -> usize {
+            let offset = bin.len();
+            bin.extend_from_slice(data);
+            views.push(format!(
+                "{{\"buffer\":0,\"byteOffset\":{},\"byteLength\":{},\"target\":{}}}",
+                offset, data.len(), target
+            ));
+            views.len() - 1
+        };
+        fn floats<const N: usize>(v: &[[f32; N]]) -> Vec<u8> {
+            v.iter().flatten().flat_map(|f| f.to_le_bytes()).collect()
+        }
+
+        let mut primitives = Vec::new();
+        for (m, p) in self.primitives.iter().enumerate() {
+            let (mut lo, mut hi) = ([f32::INFINITY; 3], [f32::NEG_INFINITY; 3]);
+            for v in &p.positions {
+                for i in 0..3 {
+                    lo[i] = lo[i].min(v[i]);
+                    hi[i] = hi[i].max(v[i]);
+                }
+            }
+            // glTF pone el origen de las UV arriba a la izquierda
+            let uvs: Vec<[f32; 2]> = p.uvs.iter().map(|t| [t[0], 1.0 - t[1]]).collect();
+            let count = p.positions.len();
+            let pos = push_view(&mut bin, &floats(&p.positions), 34962);
+            let nrm = push_view(&mut bin, &floats(&p.normals), 34962);
+            let tex = push_view(&mut bin, &floats(&uvs), 34962);
+            let idx_bytes: Vec<u8> = p.indices.iter().flat_map(|i| i.to_le_bytes()).collect();
+            let idx = push_view(&mut bin, &idx_bytes, 34963);
+
+            let a = accessors.len();
+            accessors.push(format!(
+                "{{\"bufferView\":{},\"componentType\":5126,\"count\":{},\"type\":\"VEC3\",\"min\":[{},{},{}],\"max\":[{},{},{}]}}",
+                pos, count, lo[0], lo[1], lo[2], hi[0], hi[1], hi[2]
+            ));
+            accessors.push(format!("{{\"bufferView\":{},\"componentType\":5126,\"count\":{},\"type\":\"VEC3\"}}", nrm, count));
+            accessors.push(format!("{{\"bufferView\":{},\"componentType\":5126,\"count\":{},\"type\":\"VEC2\"}}", tex, count));
+            accessors.push(format!(
+                "{{\"bufferView\":{},\"componentType\":5125,\"count\":{},\"type\":\"SCALAR\"}}",
+                idx, p.indices.len()
+            ));
+            primitives.push(format!(
+                "{{\"attributes\":{{\"POSITION\":{},\"NORMAL\":{},\"TEXCOORD_0\":{}}},\"indices\":{},\"material\":{}}}",
+                a, a + 1, a + 2, a + 3, m
+            ));
+        }
+
+        let materials: Vec<String> = self.primitives.iter().map(|p| gltf_material(&p.material)).collect();
+        let images: Vec<String> = textures.iter().map(|t| format!("{{\"uri\":{}}}", json_str(t))).collect();
+        let texture_refs: Vec<String> = (0..textures.len()).map(|i| format!("{{\"sampler\":0,\"source\":{}}}", i)).collect();
+
+        let mut json = String::new();
+        json.push_str("{\"asset\":{\"version\":\"2.0\",\"generator\":\"raytracing_diorama\"},");
+        json.push_str("\"scene\":0,\"scenes\":[{\"nodes\":[0]}],\"nodes\":[{\"name\":\"diorama\",\"mesh\":0}],");
+        json.push_str(&format!("\"meshes\":[{{\"name\":\"diorama\",\"primitives\":[{}]}}],", primitives.join(",")));
+        json.push_str(&format!("\"materials\":[{}],", materials.join(",")));
+        if !textures.is_empty() {
+            // Pixel art: sin filtrado y repitiendo
+            json.push_str("\"samplers\":[{\"magFilter\":9728,\"minFilter\":9728,\"wrapS\":10497,\"wrapT\":10497}],");
+            json.push_str(&format!("\"images\":[{}],\"textures\":[{}],", images.join(","), texture_refs.join(",")));
+        }
+        json.push_str(&format!(
+            "\"buffers\":[{{\"uri\":{},\"byteLength\":{}}}],",
+            json_str(&bin_name),
+            bin.len()
+        ));
+        json.push_str(&format!("\"bufferViews\":[{}],", views.join(",")));
+        json.push_str(&format!("\"accessors\":[{}]}}", accessors.join(",")));
+
+        fs::write(sibling(path, &bin_name), &bin)?;
+        fs::write(path, json)
+    }
+}
+
+/// Material PBR aproximado: sin metal, rugosidad del bloque y transparencia como alfa.
+fn gltf_material(m: &ExportMaterial) -> String {
+    let c = m.color.clamp01();
+    let e = m.emissive.clamp01(); // glTF base limita la emisión a [0,1]
+    let mut s = format!(
+        "{{\"name\":{},\"pbrMetallicRoughness\":{{\"baseColorFactor\":[{},{},{},{}],",
+        json_str(&m.name), c.x, c.y, c.z, 1.0 - m.transparency
+    );
+    if let Some(t) = m.texture {
+        s.push_str(&format!("\"baseColorTexture\":{{\"index\":{}}},", t));
+    }
+    s.push_str(&format!("\"metallicFactor\":0,\"roughnessFactor\":{}}},", m.roughness.clamp(0.0, 1.0)));
+    s.push_str(&format!("\"emissiveFactor\":[{},{},{}]", e.x, e.y, e.z));
+    if m.cutout {
+        s.push_str(",\"alphaMode\":\"MASK\",\"alphaCutoff\":0.5,\"doubleSided\":true");
+    } else if m.transparency > 0.0 {
+        s.push_str(",\"alphaMode\":\"BLEND\"");
+    }
+    s.push('}');
+    s
+}
+
+fn json_str(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_else(|| "diorama".into())
+}
+
+fn sibling(path: &Path, name: &str) -> PathBuf {
+    path.with_file_name(name)
+}
@@ -0,0 +1,326 @@
+//! Lector mínimo del formato NBT de Minecraft (big-endian, comprimido o no).
+
+use std::collections::HashMap;
+use std::io::{self, Read};
+
+use flate2::read::{GzDecoder, ZlibDecoder};
+
+/// Valor NBT ya decodificado. Los nombres de las etiquetas viven en los compuestos.
+#[derive(Debug, Clone)]
+pub enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<u8>),
+    String(String),
+    List(Vec<Tag>),
+    Compound(HashMap<String, Tag>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl Tag {
+    pub fn get(&self, key: &str) -> Option<&Tag> {
+        match self {
+            Tag::Compound(map) => map.get(key),
+            _ => None,
+        }
+    }
+
+    /// Cualquier entero como `i64` (los formatos mezclan Byte/Short/Int).
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Tag::Byte(v) => Some(v as i64),
+            Tag::Short(v) => Some(v as i64),
+            Tag::Int(v) => Some(v as i64),
+            Tag::Long(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Tag::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Tag::ByteArray(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_longs(&self) -> Option<&[i64]> {
+        match self {
+            Tag::LongArray(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[Tag]> {
+        match self {
+            Tag::List(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_compound(&self) -> Option<&HashMap<String, Tag>> {
+        match self {
+            Tag::Compound(map) => Some(map),
+            _ => None,
+        }
+    }
+}
+
+/// Descomprime si hace falta (gzip o zlib) y lee el compuesto raíz.
+pub fn read_compressed(bytes: &[u8]) -> io::Result<Tag> {
+    let mut raw = Vec::new();
+    match bytes {
+        [0x1f, 0x8b, ..] => {
+            GzDecoder::new(bytes).read_to_end(&mut raw)?;
+        }
+        [0x78, ..] => {
+            ZlibDecoder::new(bytes).read_to_end(&mut raw)?;
+        }
+        _ => raw.extend_from_slice(bytes),
+    }
+    read_root(&raw)
+}
+
+/// Lee el compuesto raíz de un NBT sin comprimir (se descarta su nombre).
+pub fn read_root(bytes: &[u8]) -> io::Result<Tag> {
+    let mut r = Reader { bytes, pos: 0 };
+    let id = r.u8()?;
+    if id != 10 {
+        return Err(invalid(format!("la raíz NBT no es un compuesto (tipo {})", id)));
+    }
+    r.string()?;
+    r.payload(id, 0)
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Límite de anidamiento para no desbordar la pila con archivos corruptos.
+const MAX_DEPTH: usize = 512;
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        let end = self.pos.checked_add(n).filter(|&e| e <= self.bytes.len());
+        match end {
+            Some(end) => {
+                let s = &self.bytes[self.pos..end];
+                self.pos = end;
+                Ok(s)
+            }
+            None => Err(invalid("NBT truncado".into())),
+        }
+    }
+
+    fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn i16(&mut self) -> io::Result<i16> {
+        let b = self.take(2)?;
+        Ok(i16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn i32(&mut self) -> io::Result<i32> {
+        let b = self.take(4)?;
+        Ok(i32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn i64(&mut self) -> io::Result<i64> {
+        let b = self.take(8)?;
+        let mut a = [0u8; 8];
+        a.copy_from_slice(b);
+        Ok(i64::from_be_bytes(a))
+    }
+
+    fn len(&mut self) -> io::Result<usize> {
+        let n = self.i32()?;
+        if n < 0 {
+            return Err(invalid(format!("longitud NBT negativa ({})", n)));
+        }
+        Ok(n as usize)
+    }
+
+    fn string(&mut self) -> io::Result<String> {
+        let n = self.i16()? as u16 as usize;
+        // Java usa "UTF-8 modificado"; para nombres de bloque basta con lossy
+        Ok(String::from_utf8_lossy(self.take(n)?).into_owned())
+    }
+
+    fn payload(&mut self, id: u8, depth: usize) -> io::Result<Tag> {
+        if depth > MAX_DEPTH {
+            return Err(invalid("NBT demasiado anidado".into()));
+        }
+        Ok(match id {
+            1 => Tag::Byte(self.u8()? as i8),
+            2 => Tag::Short(self.i16()?),
+            3 => Tag::Int(self.i32()?),
+            4 => Tag::Long(self.i64()?),
+            5 => Tag::Float(f32::from_bits(self.i32()? as u32)),
+            6 => Tag::Double(f64::from_bits(self.i64()? as u64)),
+            7 => {
+                let n = self.len()?;
+                Tag::ByteArray(self.take(n)?.to_vec())
+            }
+            8 => Tag::String(self.string()?),
+            9 => {
+                let item = self.u8()?;
+                let n = self.len()?;
+                let mut items = Vec::with_capacity(n.min(4096));
+                for _ in 0..n {
+                    items.push(self.payload(item, depth + 1)?);
+                }
+                Tag::List(items)
+            }
+            10 => {
+                let mut map = HashMap::new();
+                loop {
+                    let child = self.u8()?;
+                    if child == 0 {
+                        break;
+                    }
+                    let name = self.string()?;
+                    map.insert(name, self.payload(child, depth + 1)?);
+                }
+                Tag::Compound(map)
+            }
+            11 => {
+                let n = self.len()?;
+                let mut v = Vec::with_capacity(n.min(1 << 20));
+                for _ in 0..n {
+                    v.push(self.i32()?);
+                }
+                Tag::IntArray(v)
+            }
+            12 => {
+                let n = self.len()?;
+                let mut v = Vec::with_capacity(n.min(1 << 20));
+                for _ in 0..n {
+                    v.push(self.i64()?);
+                }
+                Tag::LongArray(v)
+            }
+            _ => return Err(invalid(format!("tipo de etiqueta NBT desconocido {}", id))),
+        })
+    }
+}
+
+/// Constructores de NBT en memoria para las pruebas de este módulo y de `schematic`.
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    /// Etiqueta con nombre: tipo, nombre y carga útil.
+    pub(crate) fn named(id: u8, name: &str, payload: &[u8]) -> Vec<u8> {
+        let mut out = vec![id];
+        out.extend_from_slice(&(name.len() as u16).to_be_bytes());
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(payload);
+        out
+    }
+
+    pub(crate) fn short(name: &str, v: i16) -> Vec<u8> {
+        named(2, name, &v.to_be_bytes())
+    }
+
+    pub(crate) fn byte_array(name: &str, data: &[u8]) -> Vec<u8> {
+        let mut payload = (data.len() as i32).to_be_bytes().to_vec();
+        payload.extend_from_slice(data);
+        named(7, name, &payload)
+    }
+
+    pub(crate) fn gzip(bytes: &[u8]) -> Vec<u8> {
+        let mut gz = GzEncoder::new(Vec::new(), Compression::default());
+        gz.write_all(bytes).unwrap();
+        gz.finish().unwrap()
+    }
+
+    /// Compuesto raíz con las etiquetas dadas, comprimido con gzip como en disco.
+    pub(crate) fn gzip_root(tags: &[Vec<u8>]) -> Vec<u8> {
+        let mut body: Vec<u8> = tags.concat();
+        body.push(0);
+        gzip(&named(10, "", &body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::fixtures::{gzip, named, short};
+
+    /// `{Width: 3s, Name: "hola", Nums: [1, 2], Inner: {B: 7b}}` como raíz sin comprimir.
+    fn sample() -> Vec<u8> {
+        let mut list = vec![3];
+        list.extend_from_slice(&2i32.to_be_bytes());
+        list.extend_from_slice(&1i32.to_be_bytes());
+        list.extend_from_slice(&2i32.to_be_bytes());
+        let mut inner = named(1, "B", &[7]);
+        inner.push(0);
+
+        let mut body = short("Width", 3);
+        let mut text = 4u16.to_be_bytes().to_vec();
+        text.extend_from_slice(b"hola");
+        body.extend(named(8, "Name", &text));
+        body.extend(named(9, "Nums", &list));
+        body.extend(named(10, "Inner", &inner));
+        body.push(0);
+        named(10, "", &body)
+    }
+
+    #[test]
+    fn reads_plain_and_gzip() {
+        let raw = sample();
+        for bytes in [raw.clone(), gzip(&raw)] {
+            let root = read_compressed(&bytes).unwrap();
+            assert_eq!(root.get("Width").and_then(Tag::as_i64), Some(3));
+            assert_eq!(root.get("Name").and_then(Tag::as_str), Some("hola"));
+            let nums: Vec<i64> = root.get("Nums").and_then(Tag::as_list).unwrap().iter().filter_map(Tag::as_i64).collect();
+            assert_eq!(nums, [1, 2]);
+            assert_eq!(root.get("Inner").and_then(|t| t.get("B")).and_then(Tag::as_i64), Some(7));
+        }
+    }
+
+    #[test]
+    fn truncated_input_is_an_error() {
+        let raw = sample();
+        for len in 0..raw.len() {
+            assert!(read_root(&raw[..len]).is_err(), "prefijo de {} bytes", len);
+        }
+        let gz = gzip(&raw);
+        assert!(read_compressed(&gz[..gz.len() / 2]).is_err());
+    }
+
+    #[test]
+    fn unknown_tag_is_an_error() {
+        let mut body = named(13, "Raro", &[0; 4]);
+        body.push(0);
+        let err = read_root(&named(10, "", &body)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(read_root(&named(3, "", &[0; 4])).is_err()); // raíz que no es un compuesto
+    }
+
+    #[test]
+    fn negative_length_is_an_error() {
+        let mut body = named(7, "Bytes", &(-1i32).to_be_bytes());
+        body.push(0);
+        assert!(read_root(&named(10, "", &body)).is_err());
+    }
+}
@@ -0,0 +1,226 @@
+//! Oclusión ambiental: muestreo de hemisferio por rayos y AO por esquinas de vóxel.
+//!
+//! El factor calculado aquí solo atenúa el término ambiente de `trace`;
+//! la luz directa del sol sigue dependiendo de las sombras duras.
+
+use std::collections::HashSet;
+
+use crate::instance::Group;
+use crate::materials::Intersectable;
+use crate::math::{Transform, Vec3, hash_point, next_unit, orthonormal_basis};
+use crate::ray::Ray;
+
+/// Modo de oclusión ambiental seleccionable en tiempo de ejecución.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AoMode {
+    Off,
+    /// Rayos en el hemisferio de la normal (más preciso, más costoso).
+    RayTraced,
+    /// Ocupación de los vecinos de la rejilla, estilo Minecraft (casi gratis).
+    Voxel,
+}
+
+impl AoMode {
+    pub fn next(self) -> Self {
+        match self {
+            AoMode::Off => AoMode::Voxel,
+            AoMode::Voxel => AoMode::RayTraced,
+            AoMode::RayTraced => AoMode::Off,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AoMode::Off => "AO: off",
+            AoMode::RayTraced => "AO: rayos",
+            AoMode::Voxel => "AO: voxel",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct AoSettings {
+    pub mode: AoMode,
+    pub samples: u32,      // Rayos por punto en modo RayTraced
+    pub max_distance: f32, // Oclusores más lejanos no cuentan
+    pub strength: f32,     // [0..1] cuánto oscurece la oclusión total
+    pub ambient: f32,      // Intensidad del ambiente cuando AO está activo
+}
+
+impl Default for AoSettings {
+    fn default() -> Self {
+        Self {
+            mode: AoMode::Voxel,
+            samples: 6,
+            max_distance: 1.5,
+            strength: 0.85,
+            ambient: 0.25,
+        }
+    }
+}
+
+/// Coordenadas enteras de una celda de la rejilla.
+pub type Voxel = (i32, i32, i32);
+
+/// Celdas ocupadas por bloques opacos de tamaño unitario.
+#[derive(Clone, Default)]
+pub struct VoxelOccupancy {
+    cells: HashSet<Voxel>,
+}
+
+impl VoxelOccupancy {
+    /// Registra los objetos que son cubos unitarios centrados en coordenadas enteras,
+    /// incluidos los de instancias que respetan la rejilla (giros de 90°, traslación entera).
+    /// Los bloques muy transparentes (agua, portal) y los recortes por alfa no ocluyen.
+    pub fn from_group(scene: &Group<'_>) -> Self {
+        Self::collect(scene, |o, local| !o.is_cutout() && o.material_at(local).transparency < 0.5)
+    }
+
+    /// Todas las celdas con un bloque, también agua, vidrio y hojas: los obstáculos
+    /// para la simulación de `physics`.
+    pub fn blocks(scene: &Group<'_>) -> Self {
+        Self::collect(scene, |_, _| true)
+    }
+
+    fn collect(scene: &Group<'_>, keep: impl Fn(&dyn Intersectable, Vec3) -> bool) -> Self {
+        let mut cells = HashSet::new();
+        scene.for_each_object(&Transform::IDENTITY, &mut |o, to_local| {
+            let Some(to_world) = to_local.inverse().filter(Transform::is_grid_aligned) else {
+                return;
+            };
+            let local = o.center();
+            let c = to_world.point(local);
+            let (rx, ry, rz) = (c.x.round(), c.y.round(), c.z.round());
+            let on_grid = (c.x - rx).abs() < 0.01 && (c.y - ry).abs() < 0.01 && (c.z - rz).abs() < 0.01;
+            if on_grid && keep(o, local) {
+                cells.insert((rx as i32, ry as i32, rz as i32));
+            }
+        });
+        Self { cells }
+    }
+
+    pub fn is_solid(&self, x: i32, y: i32, z: i32) -> bool {
+        self.cells.contains(&(x, y, z))
+    }
+
+    /// Marca una celda como sólida (bloques añadidos después de construir la escena).
+    pub fn insert(&mut self, x: i32, y: i32, z: i32) {
+        self.cells.insert((x, y, z));
+    }
+
+    /// Libera una celda (un bloque dinámico que se movió).
+    pub fn remove(&mut self, x: i32, y: i32, z: i32) {
+        self.cells.remove(&(x, y, z));
+    }
+
+    /// Caja de celdas ocupadas (mínimo y máximo inclusivos), o `None` si no hay ninguna.
+    pub fn bounds(&self) -> Option<(Voxel, Voxel)> {
+        let mut cells = self.cells.iter();
+        let first = *cells.next()?;
+        Some(cells.fold((first, first), |(lo, hi), &(x, y, z)| {
+            ((lo.0.min(x), lo.1.min(y), lo.2.min(z)), (hi.0.max(x), hi.1.max(y), hi.2.max(z)))
+        }))
+    }
+}
+
+/// Visibilidad ambiental [0..1] en `point` con normal `n` (1 = sin oclusión).
+pub fn ambient_visibility(
+    settings: &AoSettings,
+    point: Vec3,
+    n: Vec3,
+    scene: &Group<'_>,
+    occupancy: &VoxelOccupancy,
+    tests: &mut u32,
+) -> f32 {
+    let occlusion = match settings.mode {
+        AoMode::Off => return 1.0,
+        AoMode::RayTraced => 1.0 - ray_traced(point, n, scene, settings, tests),
+        AoMode::Voxel => 1.0 - voxel_corner(point, n, occupancy),
+    };
+    1.0 - occlusion * settings.strength.clamp(0.0, 1.0)
+}
+
+/// Fracción de rayos del hemisferio (distribución coseno) que escapan sin chocar
+/// antes de `max_distance`. El patrón se siembra con la posición para que sea estable entre cuadros.
+fn ray_traced(point: Vec3, n: Vec3, scene: &Group<'_>, settings: &AoSettings, tests: &mut u32) -> f32 {
+    let samples = settings.samples.max(1);
+    let (t, b) = orthonormal_basis(n);
+    let origin = point.add(n.mul(1e-3));
+    let mut seed = hash_point(point);
+    let mut open = 0u32;
+
+    for i in 0..samples {
+        // Estratificado en el ángulo polar, aleatorio en el azimut
+        let r1 = (i as f32 + next_unit(&mut seed)) / samples as f32;
+        let r2 = next_unit(&mut seed);
+        let r = r1.sqrt();
+        let phi = 2.0 * std::f32::consts::PI * r2;
+        let local = Vec3::new(r * phi.cos(), r * phi.sin(), (1.0 - r1).max(0.0).sqrt());
+        let dir = t.mul(local.x).add(b.mul(local.y)).add(n.mul(local.z)).norm();
+        let ray = Ray::new(origin, dir);
+
+        if !scene.occluded(&ray, settings.max_distance, tests) {
+            open += 1;
+        }
+    }
+    open as f32 / samples as f32
+}
+
+/// AO clásico de vóxeles: por cada esquina de la cara se cuentan los dos vecinos laterales
+/// y el diagonal en la capa frente a la cara, y se interpola bilinealmente.
+fn voxel_corner(point: Vec3, n: Vec3, occupancy: &VoxelOccupancy) -> f32 {
+    // Eje dominante de la normal (los bloques son alineados a los ejes)
+    let (axis, sign) = if n.x.abs() > 0.5 {
+        (0, n.x.signum() as i32)
+    } else if n.y.abs() > 0.5 {
+        (1, n.y.signum() as i32)
+    } else {
+        (2, n.z.signum() as i32)
+    };
+    let (ua, va) = match axis {
+        0 => (1, 2),
+        1 => (0, 2),
+        _ => (0, 1),
+    };
+
+    let p = [point.x, point.y, point.z];
+    // Celda del bloque golpeado: medio bloque hacia dentro de la cara
+    let cell: [i32; 3] = std::array::from_fn(|k| {
+        if k == axis {
+            (p[k] - sign as f32 * 0.5).round() as i32
+        } else {
+            p[k].round() as i32
+        }
+    });
+    let mut layer = cell;
+    layer[axis] += sign;
+
+    let solid = |du: i32, dv: i32| -> u8 {
+        let mut c = layer;
+        c[ua] += du;
+        c[va] += dv;
+        occupancy.is_solid(c[0], c[1], c[2]) as u8
+    };
+    let corner_ao = |du: i32, dv: i32| -> f32 {
+        let s1 = solid(du, 0);
+        let s2 = solid(0, dv);
+        let c = solid(du, dv);
+        if s1 == 1 && s2 == 1 {
+            0.0
+        } else {
+            (3 - s1 - s2 - c) as f32 / 3.0
+        }
+    };
+
+    // Coordenadas [0..1] del punto dentro de la cara
+    let fu = (p[ua] - (cell[ua] as f32 - 0.5)).clamp(0.0, 1.0);
+    let fv = (p[va] - (cell[va] as f32 - 0.5)).clamp(0.0, 1.0);
+
+    let a00 = corner_ao(-1, -1);
+    let a10 = corner_ao(1, -1);
+    let a01 = corner_ao(-1, 1);
+    let a11 = corner_ao(1, 1);
+    let bottom = a00 + (a10 - a00) * fu;
+    let top = a01 + (a11 - a01) * fu;
+    bottom + (top - bottom) * fv
+}
@@ -0,0 +1,440 @@
+//! Física celular de bloques sueltos: arena y grava que caen, agua y lava que fluyen
+//! por niveles y obsidiana donde ambas se tocan.
+//!
+//! La simulación es una capa de bloques dinámicos sobre cualquier mundo: la arena, grava,
+//! agua y lava del mundo pasan a esa capa al empezar (`raytracer::split_dynamic_blocks`) y
+//! el resto de la escena estática solo hace de obstáculo (`VoxelOccupancy::blocks`).
+//! `LiveScene` reparte los bloques dinámicos en grupos de `CHUNK`×`CHUNK` columnas, cada
+//! uno con su BVH; tras un paso solo se reconstruyen los grupos con celdas cambiadas y el
+//! BVH raíz, que apenas tiene la instancia del mundo y las de esos grupos.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::instance::{Group, Instance};
+use crate::lighting::Skybox;
+use crate::math::Transform;
+use crate::occlusion::{Voxel, VoxelOccupancy};
+use crate::raytracer::{self, Assets, SceneData};
+
+/// Celda de la rejilla de bloques.
+pub type Pos = Voxel;
+
+/// Nivel de un bloque fuente de agua o lava: no se agota. Los que fluyen bajan de nivel
+/// al alejarse de la fuente hasta desaparecer.
+pub const SOURCE: u8 = 8;
+/// Columnas por lado de cada grupo de bloques dinámicos en `LiveScene`.
+pub const CHUNK: i32 = 8;
+/// La lava avanza solo en uno de cada `LAVA_PERIOD` pasos.
+const LAVA_PERIOD: u64 = 3;
+/// Celdas alrededor del mundo por donde aún pueden moverse los bloques; más allá caen al vacío.
+const MARGIN: i32 = 8;
+
+/// Bloque dinámico.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Cell {
+    Sand,
+    Gravel,
+    Obsidian,
+    Water(u8), // Nivel 1..=SOURCE
+    Lava(u8),
+}
+
+impl Cell {
+    pub fn label(self) -> &'static str {
+        match self {
+            Cell::Sand => "arena",
+            Cell::Gravel => "grava",
+            Cell::Obsidian => "obsidiana",
+            Cell::Water(_) => "agua",
+            Cell::Lava(_) => "lava",
+        }
+    }
+
+    /// Bloque que ocupa la celda entera y frena a los demás.
+    fn is_solid(self) -> bool {
+        matches!(self, Cell::Sand | Cell::Gravel | Cell::Obsidian)
+    }
+
+    /// Altura visible del bloque: los fluidos se dibujan según su nivel.
+    pub fn height(self) -> f32 {
+        match self {
+            Cell::Water(level) | Cell::Lava(level) => level as f32 / SOURCE as f32,
+            _ => 1.0,
+        }
+    }
+
+    /// Cuenta como vecino opaco para el AO por vóxel.
+    fn occludes(self) -> bool {
+        self.is_solid() || self == Cell::Lava(SOURCE)
+    }
+
+    fn water(self) -> Option<u8> {
+        match self {
+            Cell::Water(level) => Some(level),
+            _ => None,
+        }
+    }
+
+    fn lava(self) -> Option<u8> {
+        match self {
+            Cell::Lava(level) => Some(level),
+            _ => None,
+        }
+    }
+}
+
+fn below(p: Pos) -> Pos {
+    (p.0, p.1 - 1, p.2)
+}
+
+fn above(p: Pos) -> Pos {
+    (p.0, p.1 + 1, p.2)
+}
+
+fn horizontal(p: Pos) -> [Pos; 4] {
+    [(p.0 - 1, p.1, p.2), (p.0 + 1, p.1, p.2), (p.0, p.1, p.2 - 1), (p.0, p.1, p.2 + 1)]
+}
+
+fn neighbours(p: Pos) -> [Pos; 6] {
+    let [a, b, c, d] = horizontal(p);
+    [a, b, c, d, below(p), above(p)]
+}
+
+/// Estado de la simulación de un mundo.
+pub struct BlockPhysics {
+    cells: HashMap<Pos, Cell>,
+    obstacles: VoxelOccupancy, // Bloques de la escena estática
+    min: Pos,                  // Región simulada (inclusiva)
+    max: Pos,
+    steps: u64,
+}
+
+impl BlockPhysics {
+    /// Simulación sobre los bloques de `world` que empieza con las celdas `seeds`.
+    pub fn new(world: &Group<'_>, seeds: Vec<(Pos, Cell)>) -> Self {
+        let obstacles = VoxelOccupancy::blocks(world);
+        let (lo, hi) = seeds.iter().fold(obstacles.bounds().unwrap_or(((0, 0, 0), (0, 0, 0))), |(lo, hi), &(p, _)| {
+            ((lo.0.min(p.0), lo.1.min(p.1), lo.2.min(p.2)), (hi.0.max(p.0), hi.1.max(p.1), hi.2.max(p.2)))
+        });
+        Self {
+            cells: seeds.into_iter().collect(),
+            obstacles,
+            min: (lo.0 - MARGIN, lo.1 - MARGIN, lo.2 - MARGIN),
+            max: (hi.0 + MARGIN, hi.1 + MARGIN, hi.2 + MARGIN),
+            steps: 0,
+        }
+    }
+
+    pub fn get(&self, p: Pos) -> Option<Cell> {
+        self.cells.get(&p).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = (Pos, Cell)> + '_ {
+        self.cells.iter().map(|(&p, &c)| (p, c))
+    }
+
+    fn inside(&self, p: Pos) -> bool {
+        (self.min.0..=self.max.0).contains(&p.0)
+            && (self.min.1..=self.max.1).contains(&p.1)
+            && (self.min.2..=self.max.2).contains(&p.2)
+    }
+
+    /// Celda donde no puede entrar un fluido: fuera de la región, un bloque estático o
+    /// un bloque dinámico sólido. Un fluido distinto no bloquea: ahí se forma obsidiana.
+    fn blocked(&self, p: Pos) -> bool {
+        !self.inside(p)
+            || self.obstacles.is_solid(p.0, p.1, p.2)
+            || self.cells.get(&p).is_some_and(|c| c.is_solid())
+    }
+
+    /// Suelta `cell` en `p` si la celda está libre. El agua y la lava entran como fuente.
+    pub fn spawn(&mut self, p: Pos, cell: Cell) -> bool {
+        if self.blocked(p) || self.cells.contains_key(&p) {
+            return false;
+        }
+        let cell = match cell {
+            Cell::Water(_) => Cell::Water(SOURCE),
+            Cell::Lava(_) => Cell::Lava(SOURCE),
+            other => other,
+        };
+        self.cells.insert(p, cell);
+        true
+    }
+
+    /// Avanza un paso y devuelve las celdas que cambiaron.
+    pub fn step(&mut self) -> HashSet<Pos> {
+        let before = self.cells.clone();
+        self.steps += 1;
+        self.fall();
+
+        let water = self.flow(Cell::water, 1);
+        let lava = self.steps.is_multiple_of(LAVA_PERIOD).then(|| self.flow(Cell::lava, 2));
+        let lava_moved = lava.is_some();
+        self.cells.retain(|_, c| match c {
+            Cell::Water(_) => false,
+            Cell::Lava(_) => !lava_moved,
+            _ => true,
+        });
+        // Donde el agua y la lava se encuentran queda obsidiana
+        for (p, level) in water {
+            let cell = if self.get(p).is_some_and(|c| c.lava().is_some()) { Cell::Obsidian } else { Cell::Water(level) };
+            self.cells.insert(p, cell);
+        }
+        for (p, level) in lava.unwrap_or_default() {
+            let cell = if self.get(p).is_some_and(|c| c.water().is_some()) { Cell::Obsidian } else { Cell::Lava(level) };
+            self.cells.insert(p, cell);
+        }
+        self.harden();
+
+        before
+            .keys()
+            .chain(self.cells.keys())
+            .filter(|p| before.get(p) != self.cells.get(p))
+            .copied()
+            .collect()
+    }
+
+    /// Arena y grava bajan una celda si debajo no hay nada sólido; desplazan al fluido
+    /// que encuentren y, fuera de la región, caen al vacío.
+    fn fall(&mut self) {
+        let mut grains: Vec<(Pos, Cell)> = self
+            .cells()
+            .filter(|(_, c)| matches!(c, Cell::Sand | Cell::Gravel))
+            .collect();
+        // De abajo arriba, para que una columna caiga entera en el mismo paso
+        grains.sort_by_key(|&((x, y, z), _)| (y, z, x));
+        for (p, cell) in grains {
+            let to = below(p);
+            if !self.inside(to) {
+                self.cells.remove(&p);
+            } else if !self.blocked(to) {
+                self.cells.remove(&p);
+                self.cells.insert(to, cell);
+            }
+        }
+    }
+
+    /// Niveles del fluido tras un paso, calculados todos a partir del estado actual.
+    /// Las fuentes se mantienen; cada celda toma el nivel de la que tiene encima (cayendo)
+    /// o el del vecino lateral menos `decay`, si ese vecino no puede seguir cayendo.
+    fn flow(&self, level: fn(Cell) -> Option<u8>, decay: u8) -> HashMap<Pos, u8> {
+        let level_at = |p: Pos| self.get(p).and_then(level).unwrap_or(0);
+        let mut candidates = HashSet::new();
+        for (p, c) in self.cells() {
+            if level(c).is_some() {
+                candidates.insert(p);
+                candidates.extend(neighbours(p));
+            }
+        }
+
+        let mut levels = HashMap::new();
+        for p in candidates {
+            if self.blocked(p) {
+                continue;
+            }
+            let current = level_at(p);
+            let new = if current == SOURCE {
+                SOURCE
+            } else {
+                let falling = if level_at(above(p)) > 0 { SOURCE - 1 } else { 0 };
+                horizontal(p)
+                    .into_iter()
+                    .filter(|&q| {
+                        // Solo se extiende de lado sobre algo firme o sobre una fuente
+                        let support = below(q);
+                        self.inside(support) && (self.blocked(support) || level_at(support) == SOURCE)
+                    })
+                    .map(|q| level_at(q).saturating_sub(decay))
+                    .fold(falling, u8::max)
+            };
+            if new > 0 {
+                levels.insert(p, new);
+            }
+        }
+        levels
+    }
+
+    /// La lava que toca agua se vuelve obsidiana.
+    fn harden(&mut self) {
+        let touched: Vec<Pos> = self
+            .cells()
+            .filter(|&(p, c)| {
+                c.lava().is_some() && neighbours(p).into_iter().any(|n| self.get(n).is_some_and(|c| c.water().is_some()))
+            })
+            .map(|(p, _)| p)
+            .collect();
+        for p in touched {
+            self.cells.insert(p, Cell::Obsidian);
+        }
+    }
+}
+
+fn chunk_of(p: Pos) -> (i32, i32) {
+    (p.0.div_euclid(CHUNK), p.2.div_euclid(CHUNK))
+}
+
+/// Escena de un mundo con los bloques dinámicos de una `BlockPhysics` encima.
+pub struct LiveScene<'a> {
+    world: Arc<Group<'a>>, // Escena estática, compartida por todas las versiones de la raíz
+    skybox: Option<Skybox<'a>>,
+    is_nether: bool,
+    occupancy: VoxelOccupancy, // Ocupación de la escena estática; la de `scene` suma los bloques dinámicos
+    chunks: HashMap<(i32, i32), Arc<Group<'a>>>,
+    scene: SceneData<'a>,
+}
+
+impl<'a> LiveScene<'a> {
+    /// Empieza a simular `world`: su arena, grava, agua y lava son las primeras celdas.
+    pub fn start(world: SceneData<'a>, assets: &Assets<'a>) -> (Self, BlockPhysics) {
+        let (mut live, seeds) = Self::split(world, assets);
+        let physics = BlockPhysics::new(&live.world, seeds);
+        let all: HashSet<Pos> = physics.cells().map(|(p, _)| p).collect();
+        live.update(assets, &physics, &all);
+        (live, physics)
+    }
+
+    /// Vuelve a montar una simulación en curso sobre `world` (p. ej. tras recargar texturas).
+    pub fn new(world: SceneData<'a>, assets: &Assets<'a>, physics: &BlockPhysics) -> Self {
+        let (mut live, _) = Self::split(world, assets);
+        let all: HashSet<Pos> = physics.cells().map(|(p, _)| p).collect();
+        live.update(assets, physics, &all);
+        live
+    }
+
+    /// Escena sin bloques dinámicos y las celdas que se le quitaron.
+    fn split(world: SceneData<'a>, assets: &Assets<'a>) -> (Self, Vec<(Pos, Cell)>) {
+        let (root, skybox, is_nether, mut occupancy) = world.into_parts();
+        let (root, seeds) = raytracer::split_dynamic_blocks(assets, root);
+        for &(p, _) in &seeds {
+            occupancy.remove(p.0, p.1, p.2);
+        }
+        let scene = SceneData::from_parts(Group::new(Vec::new(), Vec::new()), skybox, is_nether, occupancy.clone());
+        let live = Self { world: Arc::new(root), skybox, is_nether, occupancy, chunks: HashMap::new(), scene };
+        (live, seeds)
+    }
+
+    pub fn scene(&self) -> &SceneData<'a> {
+        &self.scene
+    }
+
+    /// Rehace los grupos que contienen alguna celda de `changed` y la raíz de la escena.
+    /// La escena nueva trae su propia caché de cáusticas, así que se recalculan solas.
+    pub fn update(&mut self, assets: &Assets<'a>, physics: &BlockPhysics, changed: &HashSet<Pos>) {
+        let dirty: HashSet<(i32, i32)> = changed.iter().map(|&p| chunk_of(p)).collect();
+        let mut cells: HashMap<(i32, i32), Vec<(Pos, Cell)>> = HashMap::new();
+        for (p, c) in physics.cells() {
+            if dirty.contains(&chunk_of(p)) {
+                cells.entry(chunk_of(p)).or_default().push((p, c));
+            }
+        }
+        for key in dirty {
+            match cells.remove(&key) {
+                Some(mut chunk) => {
+                    chunk.sort_by_key(|&(p, _)| p);
+                    self.chunks.insert(key, Arc::new(raytracer::build_dynamic_group(assets, &chunk)));
+                }
+                None => {
+                    self.chunks.remove(&key);
+                }
+            }
+        }
+
+        let mut keys: Vec<_> = self.chunks.keys().copied().collect();
+        keys.sort();
+        let instances = std::iter::once(&self.world)
+            .chain(keys.iter().map(|k| &self.chunks[k]))
+            .filter_map(|g| Instance::new(Arc::clone(g), Transform::IDENTITY))
+            .collect();
+        // La ocupación pasa de una escena a la siguiente y solo se tocan las celdas cambiadas
+        let mut occupancy = std::mem::take(&mut self.scene.occupancy);
+        for &(x, y, z) in changed {
+            if self.occupancy.is_solid(x, y, z) || physics.get((x, y, z)).is_some_and(Cell::occludes) {
+                occupancy.insert(x, y, z);
+            } else {
+                occupancy.remove(x, y, z);
+            }
+        }
+        self.scene = SceneData::from_parts(Group::new(Vec::new(), instances), self.skybox, self.is_nether, occupancy);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::Vec3;
+    use crate::raytracer::DynObject;
+    use crate::solid_block::SolidBlock;
+
+    /// Piso de piedra de 7×7 en y = 0.
+    fn floor() -> Group<'static> {
+        let mut objects: Vec<DynObject<'static>> = Vec::new();
+        for x in -3..=3 {
+            for z in -3..=3 {
+                let c = Vec3::new(x as f32, 0.0, z as f32);
+                let half = Vec3::new(0.5, 0.5, 0.5);
+                objects.push(Box::new(SolidBlock { min: c.sub(half), max: c.add(half), ..SolidBlock::unit() }));
+            }
+        }
+        Group::new(objects, Vec::new())
+    }
+
+    #[test]
+    fn sand_falls_until_it_lands() {
+        let mut sim = BlockPhysics::new(&floor(), Vec::new());
+        assert!(sim.spawn((0, 4, 0), Cell::Sand));
+        let changed = sim.step();
+        assert_eq!(changed, HashSet::from([(0, 4, 0), (0, 3, 0)]));
+        assert_eq!(sim.get((0, 3, 0)), Some(Cell::Sand));
+        for _ in 0..4 {
+            sim.step();
+        }
+        assert_eq!(sim.get((0, 1, 0)), Some(Cell::Sand));
+        assert!(sim.step().is_empty());
+    }
+
+    #[test]
+    fn water_spreads_and_loses_level() {
+        let mut sim = BlockPhysics::new(&floor(), Vec::new());
+        assert!(sim.spawn((0, 1, 0), Cell::Water(3)));
+        assert_eq!(sim.get((0, 1, 0)), Some(Cell::Water(SOURCE)));
+        sim.step();
+        assert_eq!(sim.get((1, 1, 0)), Some(Cell::Water(SOURCE - 1)));
+        assert_eq!(sim.get((2, 1, 0)), None);
+        sim.step();
+        assert_eq!(sim.get((2, 1, 0)), Some(Cell::Water(SOURCE - 2)));
+        assert_eq!(sim.get((0, 1, 0)), Some(Cell::Water(SOURCE)));
+    }
+
+    #[test]
+    fn lava_next_to_world_water_becomes_obsidian() {
+        // El agua entra como celda inicial, igual que la de un mundo
+        let mut sim = BlockPhysics::new(&floor(), vec![((0, 1, 0), Cell::Water(SOURCE))]);
+        assert!(sim.spawn((2, 1, 0), Cell::Lava(SOURCE)));
+        let changed = sim.step();
+        assert!(changed.contains(&(2, 1, 0)));
+        assert_eq!(sim.get((2, 1, 0)), Some(Cell::Obsidian));
+        assert_eq!(sim.get((0, 1, 0)), Some(Cell::Water(SOURCE)));
+    }
+
+    #[test]
+    fn sand_displaces_fluid_and_leaves_the_region() {
+        let mut sim = BlockPhysics::new(&floor(), vec![((5, 1, 0), Cell::Water(SOURCE))]);
+        assert!(sim.spawn((5, 2, 0), Cell::Gravel));
+        sim.step();
+        assert_eq!(sim.get((5, 1, 0)), Some(Cell::Gravel));
+        // Fuera del piso no hay nada debajo: la grava cae hasta salir de la región
+        for _ in 0..32 {
+            sim.step();
+        }
+        assert!(!sim.cells().any(|(_, c)| c == Cell::Gravel));
+    }
+}
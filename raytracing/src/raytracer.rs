@@ -1,18 +1,27 @@
 //! Construye la escena de bloques y ejecuta el trazador de rayos en CPU.
 
 use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
 use std::thread;
 
-use crate::lighting::{Skybox, Tex, reflect, refract, sample_skybox, sky, specular_phong, to_rgba};
+use crate::lighting::{Skybox, Tex, glossy_reflect, reflect, refract, sample_skybox, sky, specular_phong, to_rgba};
 use crate::camera::Camera;
 use crate::solid_block::SolidBlock;
 use crate::textured_block::TexturedBlock;
 use crate::grass_block::GrassBlock;
-use crate::math::Vec3;
+use crate::math::{Transform, Vec3, hash_point, next_unit};
 use crate::ray::Ray;
-use crate::materials::Intersectable;
+use crate::materials::{Intersectable, MaterialMaps, MaterialParams};
+use crate::instance::{Group, Instance};
+use crate::terrain::{self, Block, TerrainConfig};
+use crate::schematic::{BlockKind, Schematic};
+use crate::occlusion::{AoMode, AoSettings, VoxelOccupancy, ambient_visibility};
+use crate::spectral::{CausticMap, SpectralSettings, cauchy_ior, sample_wavelengths, wavelength_rgb};
+use crate::aov::{FrameStats, PrimaryHit, RayStats, RenderView, Shadow, cost_color, material_id, view_color};
+use crate::material_overrides::{BLOCKS, MaterialOverrides, MaterialPreset};
+use crate::physics::{Cell, Pos, SOURCE};
 
-type DynObject<'a> = Box<dyn Intersectable + Send + Sync + 'a>;
+pub(crate) type DynObject<'a> = Box<dyn Intersectable + Send + Sync + 'a>;
 
 #[derive(Copy, Clone, Eq, PartialEq)]
 pub enum WorldKind {
@@ -29,13 +38,6 @@ impl WorldKind {
     }
 }
 
-struct Hit<'a> {
-    t: f32,
-    point: Vec3,
-    normal: Vec3,
-    object: &'a dyn Intersectable,
-}
-
 #[derive(Copy, Clone)]
 pub struct Assets<'a> {
     pub grass_cover: Option<Tex<'a>>,
@@ -54,14 +56,108 @@ pub struct Assets<'a> {
     pub ice: Option<Tex<'a>>,
     pub portal: Option<Tex<'a>>,
     pub torch: Option<Tex<'a>>,
+    pub glass: Option<Tex<'a>>,
+    pub sand: Option<Tex<'a>>,
+    pub gravel: Option<Tex<'a>>,
+    // Mapas de normales/rugosidad/especular/emisión (opcionales)
+    pub stone_maps: MaterialMaps<'a>,
+    pub obsidian_maps: MaterialMaps<'a>,
+    pub iron_maps: MaterialMaps<'a>,
+    pub diamond_maps: MaterialMaps<'a>,
     pub skybox_overworld: Option<Skybox<'a>>,
     pub skybox_nether: Option<Skybox<'a>>,
+    pub materials: Option<&'a MaterialOverrides>, // Ajustes de `materials.txt` sobre la paleta
 }
 
 pub struct SceneData<'a> {
-    pub objects: Vec<DynObject<'a>>,
+    pub root: Group<'a>, // Bloques sueltos e instancias, con su BVH
     pub skybox: Option<Skybox<'a>>,
     pub is_nether: bool,  // Indica si es el mundo Nether
+    pub occupancy: VoxelOccupancy, // Celdas sólidas para el AO por vóxel
+    caustics: Mutex<Option<(CausticKey, Arc<CausticMap>)>>, // Último mapa de cáusticas pedido
+}
+
+/// Posición del sol (bits) y fotones por bloque con que se construyó un mapa de cáusticas.
+type CausticKey = (u32, u32, u32, u32);
+
+impl<'a> SceneData<'a> {
+    pub fn new(objects: Vec<DynObject<'a>>, skybox: Option<Skybox<'a>>, is_nether: bool) -> Self {
+        Self::with_instances(objects, Vec::new(), skybox, is_nether)
+    }
+
+    pub fn with_instances(
+        objects: Vec<DynObject<'a>>,
+        instances: Vec<Instance<'a>>,
+        skybox: Option<Skybox<'a>>,
+        is_nether: bool,
+    ) -> Self {
+        let root = Group::new(objects, instances);
+        let occupancy = VoxelOccupancy::from_group(&root);
+        Self { root, skybox, is_nether, occupancy, caustics: Mutex::new(None) }
+    }
+
+    /// Escena con una ocupación ya calculada (p. ej. la de `physics::LiveScene`, que
+    /// suma los bloques dinámicos a la del mundo sin recorrerlo otra vez).
+    pub fn from_parts(root: Group<'a>, skybox: Option<Skybox<'a>>, is_nether: bool, occupancy: VoxelOccupancy) -> Self {
+        Self { root, skybox, is_nether, occupancy, caustics: Mutex::new(None) }
+    }
+
+    /// Inverso de `from_parts`: raíz, cielo, Nether y ocupación.
+    pub fn into_parts(self) -> (Group<'a>, Option<Skybox<'a>>, bool, VoxelOccupancy) {
+        (self.root, self.skybox, self.is_nether, self.occupancy)
+    }
+
+    /// Mapa de cáusticas para esta posición del sol. Se reutiliza mientras el sol no se
+    /// mueva, así que todas las teselas de un cuadro comparten el mismo.
+    pub fn caustics(&self, light_pos: Vec3, sun_brightness: f32, photons: u32) -> Arc<CausticMap> {
+        let key = (light_pos.x.to_bits(), light_pos.y.to_bits(), light_pos.z.to_bits(), photons);
+        let mut cache = self.caustics.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((k, map)) = cache.as_ref() {
+            if *k == key {
+                return Arc::clone(map);
+            }
+        }
+        let map = Arc::new(CausticMap::build(&self.root, light_pos, sun_brightness, photons));
+        *cache = Some((key, Arc::clone(&map)));
+        map
+    }
+}
+
+/// Opciones de calidad del render que se pueden cambiar en tiempo de ejecución.
+#[derive(Copy, Clone, Debug)]
+pub struct RenderSettings {
+    pub max_depth: i32, // Rebotes de reflexión/refracción
+    pub ao: AoSettings,
+    pub gloss_samples: u32, // Rayos por reflejo en materiales rugosos (0 = espejo)
+    pub view: RenderView,   // Canal de salida (final o vista de depuración)
+    pub spectral: SpectralSettings, // Dispersión por longitud de onda y cáusticas
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self {
+            max_depth: 1, // REDUCIDO de 2 a 1 para MUCHO mejor FPS (menos reflejos recursivos)
+            ao: AoSettings::default(),
+            gloss_samples: 2,
+            view: RenderView::Beauty,
+            spectral: SpectralSettings::default(),
+        }
+    }
+}
+
+/// Parámetros constantes durante un cuadro, compartidos por todos los rayos.
+struct TraceCtx<'s, 'a> {
+    scene: &'s Group<'a>,
+    light_pos: Vec3,
+    sun_brightness: f32, // Intensidad del sol (0.1 a 1.0)
+    skybox: Option<&'s Skybox<'a>>,
+    is_nether: bool,     // Flag para indicar si es el mundo Nether
+    ao: AoSettings,
+    gloss_samples: u32,
+    max_depth: i32,
+    occupancy: &'s VoxelOccupancy,
+    spectral: SpectralSettings,
+    caustics: Option<&'s CausticMap>,
 }
 
 fn push_block<'a>(objects: &mut Vec<DynObject<'a>>, min: Vec3, max: Vec3, mat: BlockMaterial<'a>) {
@@ -74,7 +170,9 @@ fn push_block<'a>(objects: &mut Vec<DynObject<'a>>, min: Vec3, max: Vec3, mat: B
         reflectivity: mat.reflectivity,
         transparency: mat.transparency,
         ior: mat.ior,
+        dispersion: mat.dispersion,
         emissive: mat.emissive,
+        roughness: mat.roughness,
     };
 
     if let Some(t) = mat.tex {
@@ -89,7 +187,7 @@ fn push_block<'a>(objects: &mut Vec<DynObject<'a>>, min: Vec3, max: Vec3, mat: B
             mat.transparency,
             mat.ior,
             mat.emissive,
-        )));
+        ).with_maps(mat.maps, mat.roughness).with_alpha_test(mat.cutout)));
     } else {
         objects.push(Box::new(inner));
     }
@@ -104,7 +202,11 @@ struct BlockMaterial<'a> {
     reflectivity: f32,
     transparency: f32,
     ior: f32,
+    dispersion: f32,         // Coeficiente B de Cauchy (µm²): separa colores en modo espectral
     emissive: Vec3,
+    roughness: f32,          // Difumina los reflejos (0 = espejo)
+    maps: MaterialMaps<'a>,
+    cutout: bool,            // Recorte por alfa de la textura (hojas, vidrio, antorchas)
 }
 
 impl<'a> BlockMaterial<'a> {
@@ -127,6 +229,26 @@ impl<'a> BlockMaterial<'a> {
         let max = Vec3::new(x as f32 + 0.5, top, z as f32 + 0.5);
         push_block(objects, min, max, *self);
     }
+
+    /// Bloque apoyado en el suelo de la celda con altura `height` (fluidos por nivel).
+    fn place_level(&self, objects: &mut Vec<DynObject<'a>>, (x, y, z): Pos, height: f32) {
+        let bottom = y as f32 - 0.5;
+        let min = Vec3::new(x as f32 - 0.5, bottom, z as f32 - 0.5);
+        let max = Vec3::new(x as f32 + 0.5, bottom + height, z as f32 + 0.5);
+        push_block(objects, min, max, *self);
+    }
+
+    /// `true` si `p` es este material sin retoques de mapas (el albedo no cuenta).
+    fn matches(&self, p: &MaterialParams) -> bool {
+        self.specular == p.specular_strength
+            && self.shininess == p.shininess
+            && self.reflectivity == p.reflectivity
+            && self.transparency == p.transparency
+            && self.ior == p.ior
+            && self.dispersion == p.dispersion
+            && self.roughness == p.roughness
+            && [self.emissive.x, self.emissive.y, self.emissive.z] == [p.emissive.x, p.emissive.y, p.emissive.z]
+    }
 }
 
 fn place_with_tag<'a>(
@@ -154,12 +276,12 @@ fn place_block<'a>(
     place_with_tag(objects, used, mat, x, y, z, 0);
 }
 
-/// Coloca un bloque de césped con grass_top arriba y grass_side en los lados
+/// Coloca un bloque de césped con grass_top arriba y grass_side en los lados;
+/// el resto del material sale de `mats.grass`.
 fn place_grass_block<'a>(
     objects: &mut Vec<DynObject<'a>>,
     used: &mut HashSet<(i32, i32, i32, u8)>,
-    top_mat: BlockMaterial<'a>,
-    side_mat: BlockMaterial<'a>,
+    mats: &Palette<'a>,
     x: i32,
     y: i32,
     z: i32,
@@ -174,21 +296,24 @@ fn place_grass_block<'a>(
     let min = Vec3::new(x as f32 - 0.5, y as f32 - 0.5, z as f32 - 0.5);
     let max = Vec3::new(x as f32 + 0.5, y as f32 + 0.5, z as f32 + 0.5);
 
+    let mat = mats.grass;
     let solid = SolidBlock {
         min,
         max,
-        albedo_color: Vec3::new(0.9, 0.95, 0.85),
-        specular_strength: 0.08,
-        shininess: 20.0,
-        reflectivity: 0.01,
-        transparency: 0.0,
-        ior: 1.0,
-        emissive: Vec3::new(0.0, 0.0, 0.0),
+        albedo_color: mat.albedo,
+        specular_strength: mat.specular,
+        shininess: mat.shininess,
+        reflectivity: mat.reflectivity,
+        transparency: mat.transparency,
+        ior: mat.ior,
+        dispersion: mat.dispersion,
+        emissive: mat.emissive,
+        roughness: mat.roughness,
     };
 
     // Obtener las texturas
-    let top_tex = top_mat.tex.unwrap();
-    let side_tex = side_mat.tex.unwrap();
+    let top_tex = mats.grass_top.tex.unwrap();
+    let side_tex = mats.grass_side.tex.unwrap();
 
     let grass = GrassBlock::new(
         solid,
@@ -198,43 +323,24 @@ fn place_grass_block<'a>(
         side_tex.pix,
         side_tex.w,
         side_tex.h,
-        0.08,  // specular_strength
-        20.0,  // shininess
-        0.01,  // reflectivity
-        0.0,   // transparency
-        1.0,   // ior
-        Vec3::new(0.0, 0.0, 0.0), // emissive
+        mat.specular,
+        mat.shininess,
+        mat.reflectivity,
+        mat.transparency,
+        mat.ior,
+        mat.emissive,
     );
 
     objects.push(Box::new(grass));
 }
 
-fn trace<'a>(
-    ray: &Ray,
-    objects: &'a [DynObject<'a>],
-    light_pos: Vec3,
-    sun_brightness: f32, // Intensidad del sol (0.1 a 1.0)
-    depth: i32,
-    skybox: Option<&Skybox<'a>>,
-    is_nether: bool, // Flag para indicar si es el mundo Nether
-) -> Vec3 {
-    let mut closest: Option<Hit> = None;
-    for o in objects.iter() {
-        if let Some(t) = o.intersect(ray) {
-            if t > 0.0 && (closest.is_none() || t < closest.as_ref().unwrap().t) {
-                let p = ray.orig.add(ray.dir.mul(t));
-                let n = o.normal_at(p);
-                closest = Some(Hit {
-                    t,
-                    point: p,
-                    normal: n,
-                    object: o.as_ref(),
-                });
-            }
-        }
-    }
-
-    if closest.is_none() {
+/// `lambda` es la longitud de onda (µm) del rayo tras una refracción dispersiva;
+/// `None` mientras el rayo siga siendo luz blanca.
+fn trace(ray: &Ray, ctx: &TraceCtx, depth: i32, stats: &mut RayStats, lambda: Option<f32>) -> Vec3 {
+    let TraceCtx { scene, light_pos, sun_brightness, skybox, is_nether, .. } = *ctx;
+    stats.rays += 1;
+    stats.max_bounce = stats.max_bounce.max((ctx.max_depth - depth).max(0) as u32);
+    let Some(hit) = scene.closest(ray, f32::INFINITY, &mut stats.tests) else {
         // Color del cielo modulado por el brillo del sol
         let sky_color = if let Some(sb) = skybox {
             sample_skybox(ray.dir, sb)
@@ -248,42 +354,49 @@ fn trace<'a>(
         } else {
             return sky_color.mul(sun_brightness * 0.8 + 0.2); // Entre 20% y 100%
         }
-    }
+    };
 
-    let hit = closest.unwrap();
-    let mat = hit.object.material_at(hit.point);
+    let point = ray.at(hit.t);
+    let mat = hit.material();
 
     // Constante para evitar auto-intersección
     let bias = 1e-3;
     
     // Iluminación - calcular normal y dirección de luz primero
-    let n = hit.normal.norm();
-    let ldir = light_pos.sub(hit.point).norm();
+    let mut geo_n = hit.normal();
+    // Cara trasera vista a través de un recorte: sombrear el lado que mira al rayo
+    if hit.object.is_cutout() && geo_n.dot(ray.dir) > 0.0 {
+        geo_n = -geo_n;
+    }
+    let n = mat.normal.unwrap_or(geo_n); // Normal de sombreado (mapa de normales si existe)
+    let ldir = light_pos.sub(point).norm();
     let ndotl = n.dot(ldir).max(0.0);
     
     // OPTIMIZACIÓN: Solo calcular sombras si el objeto está orientado hacia la luz
     // y hay suficiente brillo solar (evita cálculos costosos en la noche o caras traseras)
     let mut in_shadow = false;
     if ndotl > 0.01 && sun_brightness > 0.15 {
-        let shadow_origin = hit.point.add(hit.normal.mul(bias));
-        let light_distance = light_pos.sub(hit.point).len();
+        let shadow_origin = point.add(geo_n.mul(bias));
+        let light_distance = light_pos.sub(point).len();
         let sray = Ray {
             orig: shadow_origin,
             dir: ldir,
         };
         
-        for o in objects.iter() {
-            if let Some(t) = o.intersect(&sray) {
-                if t < light_distance {
-                    in_shadow = true;
-                    break;
-                }
-            }
-        }
+        stats.rays += 1;
+        in_shadow = scene.occluded(&sray, light_distance, &mut stats.tests);
     }
 
     // Iluminación modulada por el ciclo solar
-    let ambient = 0.05 * sun_brightness; // Ambiente varía con el sol
+    let mut ambient = 0.05 * sun_brightness; // Ambiente varía con el sol
+    if ctx.ao.mode != AoMode::Off {
+        // Con AO el ambiente puede ser más fuerte: las grietas lo atenúan
+        let visibility = ambient_visibility(&ctx.ao, point, n, scene, ctx.occupancy, &mut stats.tests);
+        ambient = ctx.ao.ambient * sun_brightness * visibility;
+        if ctx.ao.mode == AoMode::RayTraced {
+            stats.rays += ctx.ao.samples.max(1);
+        }
+    }
     let v = (-ray.dir).norm();
     let mut local = mat
         .albedo
@@ -295,9 +408,31 @@ fn trace<'a>(
         local = local.add(Vec3::new(spec, spec, spec));
     }
 
+    // Luz del sol concentrada por bloques dispersivos (modo espectral)
+    if let Some(caustics) = ctx.caustics {
+        local = local.add(mat.albedo.hadamard(caustics.irradiance(point, geo_n)));
+    }
+
     // Añadir emisión propia del material (para portal)
     local = local.add(mat.emissive);
 
+    // El primer impacto registrado siempre es el del rayo de cámara
+    if stats.primary.is_none() {
+        stats.primary = Some(PrimaryHit {
+            t: hit.t,
+            normal: n,
+            albedo: mat.albedo,
+            material_id: material_id(&mat),
+            shadow: if in_shadow {
+                Shadow::Occluded
+            } else if ndotl > 0.01 && sun_brightness > 0.15 {
+                Shadow::Lit
+            } else {
+                Shadow::Facing
+            },
+        });
+    }
+
     if depth <= 0 {
         return local;
     }
@@ -306,32 +441,75 @@ fn trace<'a>(
     let mut weight = 1.0;
 
     if mat.transparency > 0.0 {
-        let mut n_out = n;
+        let mut n_out = geo_n;
         let mut eta = 1.0 / mat.ior;
-        if ray.dir.dot(n) > 0.0 {
-            n_out = -n;
+        if ray.dir.dot(geo_n) > 0.0 {
+            n_out = -geo_n;
             eta = mat.ior;
         }
-        if let Some(tdir) = refract(ray.dir, n_out, eta) {
-            let ro = hit.point.add(tdir.mul(bias));
-            let rr = Ray {
-                orig: ro,
-                dir: tdir,
-            };
-            let refr_col = trace(&rr, objects, light_pos, sun_brightness, depth - 1, skybox, is_nether);
-            accum = accum.add(refr_col.mul(mat.transparency));
-            weight -= mat.transparency;
+        let dispersive = ctx.spectral.enabled && mat.dispersion > 0.0;
+        if dispersive && lambda.is_none() {
+            // Luz blanca: dividir en longitudes de onda, cada una con su IOR (Cauchy)
+            let mut seed = hash_point(point);
+            let mut sum = Vec3::new(0.0, 0.0, 0.0);
+            let mut white = Vec3::new(0.0, 0.0, 0.0);
+            let mut refracted = false;
+            for l in sample_wavelengths(ctx.spectral.samples, &mut seed) {
+                let w = wavelength_rgb(l);
+                white = white.add(w);
+                let ior = cauchy_ior(mat.ior, mat.dispersion, l);
+                let eta = if ray.dir.dot(geo_n) > 0.0 { ior } else { 1.0 / ior };
+                // Las longitudes de onda con reflexión total interna no aportan
+                if let Some(tdir) = refract(ray.dir, n_out, eta) {
+                    let rr = Ray { orig: point.add(tdir.mul(bias)), dir: tdir };
+                    sum = sum.add(trace(&rr, ctx, depth - 1, stats, Some(l)).hadamard(w));
+                    refracted = true;
+                }
+            }
+            if refracted {
+                let refr_col = Vec3::new(sum.x / white.x.max(1e-6), sum.y / white.y.max(1e-6), sum.z / white.z.max(1e-6));
+                accum = accum.add(refr_col.mul(mat.transparency));
+                weight -= mat.transparency;
+            }
+        } else {
+            // Un rayo ya monocromático conserva su longitud de onda en cada interfaz
+            if let Some(l) = lambda.filter(|_| dispersive) {
+                let ior = cauchy_ior(mat.ior, mat.dispersion, l);
+                eta = if ray.dir.dot(geo_n) > 0.0 { ior } else { 1.0 / ior };
+            }
+            if let Some(tdir) = refract(ray.dir, n_out, eta) {
+                let ro = point.add(tdir.mul(bias));
+                let rr = Ray {
+                    orig: ro,
+                    dir: tdir,
+                };
+                let refr_col = trace(&rr, ctx, depth - 1, stats, lambda);
+                accum = accum.add(refr_col.mul(mat.transparency));
+                weight -= mat.transparency;
+            }
         }
     }
 
     if mat.reflectivity > 0.0 && weight > 0.0 {
         let rdir = reflect(ray.dir, n).norm();
-        let ro = hit.point.add(n.mul(bias));
-        let rr = Ray {
-            orig: ro,
-            dir: rdir,
+        let ro = point.add(geo_n.mul(bias));
+        let refl_col = if mat.roughness > 0.02 && ctx.gloss_samples > 0 {
+            // Reflejo difuso: promedio de rayos dentro del lóbulo de rugosidad
+            let mut seed = hash_point(point);
+            let mut sum = Vec3::new(0.0, 0.0, 0.0);
+            for _ in 0..ctx.gloss_samples {
+                let (u1, u2) = (next_unit(&mut seed), next_unit(&mut seed));
+                let dir = glossy_reflect(rdir, geo_n, mat.roughness, u1, u2);
+                sum = sum.add(trace(&Ray { orig: ro, dir }, ctx, depth - 1, stats, lambda));
+            }
+            sum.mul(1.0 / ctx.gloss_samples as f32)
+        } else {
+            let rr = Ray {
+                orig: ro,
+                dir: rdir,
+            };
+            trace(&rr, ctx, depth - 1, stats, lambda)
         };
-        let refl_col = trace(&rr, objects, light_pos, sun_brightness, depth - 1, skybox, is_nether);
         accum = accum.add(refl_col.mul(mat.reflectivity));
         weight -= mat.reflectivity;
     }
@@ -339,87 +517,528 @@ fn trace<'a>(
     local.mul(weight.max(0.0)).add(accum)
 }
 
+/// Materiales de bloque compartidos por todos los mundos.
+#[derive(Copy, Clone)]
+struct Palette<'a> {
+    grass: BlockMaterial<'a>, // Bloque de césped completo (texturas de grass_top y grass_side)
+    grass_top: BlockMaterial<'a>,
+    grass_side: BlockMaterial<'a>,
+    dirt: BlockMaterial<'a>,
+    wood: BlockMaterial<'a>,
+    leaves: BlockMaterial<'a>,
+    obsidian: BlockMaterial<'a>,
+    portal: BlockMaterial<'a>,
+    stone: BlockMaterial<'a>,
+    water: BlockMaterial<'a>,
+    iron: BlockMaterial<'a>,
+    diamond: BlockMaterial<'a>,
+    lava: BlockMaterial<'a>,
+    glowstone: BlockMaterial<'a>,
+    ice: BlockMaterial<'a>,
+    glass: BlockMaterial<'a>,
+    torch: BlockMaterial<'a>,
+    sand: BlockMaterial<'a>,   // Bloques dinámicos de `physics`
+    gravel: BlockMaterial<'a>,
+}
+
+impl BlockMaterial<'_> {
+    fn preset(&self) -> MaterialPreset {
+        MaterialPreset {
+            albedo: self.albedo,
+            specular: self.specular,
+            shininess: self.shininess,
+            reflectivity: self.reflectivity,
+            transparency: self.transparency,
+            ior: self.ior,
+            dispersion: self.dispersion,
+            emissive: self.emissive,
+            roughness: self.roughness,
+        }
+    }
+
+    fn set_preset(&mut self, p: &MaterialPreset) {
+        self.albedo = p.albedo;
+        self.specular = p.specular;
+        self.shininess = p.shininess;
+        self.reflectivity = p.reflectivity;
+        self.transparency = p.transparency;
+        self.ior = p.ior;
+        self.dispersion = p.dispersion;
+        self.emissive = p.emissive;
+        self.roughness = p.roughness;
+    }
+}
+
+impl<'a> Palette<'a> {
+    /// Materiales en el orden de `material_overrides::BLOCKS`.
+    fn slots(&mut self) -> [&mut BlockMaterial<'a>; 17] {
+        let Palette {
+            grass, dirt, wood, leaves, obsidian, portal, stone,
+            water, iron, diamond, lava, glowstone, ice, glass, torch, sand, gravel, ..
+        } = self;
+        [
+            grass, dirt, wood, leaves, obsidian, portal, stone,
+            water, iron, diamond, lava, glowstone, ice, glass, torch, sand, gravel,
+        ]
+    }
+}
+
+/// Paleta base con los ajustes de `assets.materials` aplicados.
+fn palette<'a>(assets: &Assets<'a>) -> Palette<'a> {
+    let mut p = base_palette(assets);
+    if let Some(overrides) = assets.materials {
+        for (name, mat) in BLOCKS.iter().zip(p.slots()) {
+            let mut preset = mat.preset();
+            preset.apply(overrides.fields(name));
+            mat.set_preset(&preset);
+        }
+    }
+    p
+}
+
+/// Parámetros editables del bloque `block` de la paleta, con los ajustes ya aplicados.
+pub fn block_preset(assets: &Assets<'_>, block: &str) -> Option<MaterialPreset> {
+    let mut p = palette(assets);
+    let index = BLOCKS.iter().position(|name| *name == block)?;
+    Some(p.slots()[index].preset())
+}
+
+/// Bloque de la paleta al que pertenece un material muestreado. Los mapas pueden
+/// cambiar brillo, rugosidad y emisión por texel, así que deben coincidir exactamente
+/// solo reflectividad, transparencia, IOR y dispersión; el resto elige al más parecido.
+pub fn identify_block(assets: &Assets<'_>, mat: &MaterialParams) -> Option<&'static str> {
+    let mut p = palette(assets);
+    let distance = |m: &BlockMaterial| {
+        (m.specular - mat.specular_strength).abs()
+            + (m.shininess - mat.shininess).abs() / 100.0
+            + (m.roughness - mat.roughness).abs()
+            + m.emissive.sub(mat.emissive).len()
+    };
+    BLOCKS
+        .iter()
+        .zip(p.slots())
+        .filter(|(_, m)| {
+            m.reflectivity == mat.reflectivity
+                && m.transparency == mat.transparency
+                && m.ior == mat.ior
+                && m.dispersion == mat.dispersion
+        })
+        .min_by(|a, b| distance(a.1).total_cmp(&distance(b.1)))
+        .map(|(name, _)| *name)
+}
+
+/// Primer impacto de `ray` en la escena: punto y material (para el inspector).
+pub fn pick(scene: &SceneData<'_>, ray: &Ray) -> Option<(Vec3, MaterialParams)> {
+    let mut tests = 0;
+    let hit = scene.root.closest(ray, f32::INFINITY, &mut tests)?;
+    Some((ray.at(hit.t), hit.material()))
+}
+
+/// Grupo con los bloques dinámicos de `physics` (arena, grava, agua, lava y obsidiana).
+pub fn build_dynamic_group<'a>(assets: &Assets<'a>, cells: &[(Pos, Cell)]) -> Group<'a> {
+    let mats = palette(assets);
+    let mut objects: Vec<DynObject<'a>> = Vec::new();
+    for &(p, cell) in cells {
+        let mat = match cell {
+            Cell::Sand => mats.sand,
+            Cell::Gravel => mats.gravel,
+            Cell::Obsidian => mats.obsidian,
+            Cell::Water(_) => mats.water,
+            Cell::Lava(_) => mats.lava,
+        };
+        mat.place_level(&mut objects, p, cell.height());
+    }
+    Group::new(objects, Vec::new())
+}
+
+/// Separa de `root` los bloques sueltos de arena, grava, agua y lava para que los simule
+/// `physics`; el agua y la lava del mundo entran como fuente. Se reconocen por tener
+/// exactamente el material de la paleta; los bloques dentro de instancias se quedan.
+pub fn split_dynamic_blocks<'a>(assets: &Assets<'a>, root: Group<'a>) -> (Group<'a>, Vec<(Pos, Cell)>) {
+    let mats = palette(assets);
+    let kinds = [
+        (mats.sand, Cell::Sand),
+        (mats.gravel, Cell::Gravel),
+        (mats.water, Cell::Water(SOURCE)),
+        (mats.lava, Cell::Lava(SOURCE)),
+    ];
+    let (objects, instances) = root.into_parts();
+    let mut kept = Vec::with_capacity(objects.len());
+    let mut cells = Vec::new();
+    for object in objects {
+        let b = object.bounds();
+        let c = b.centroid();
+        let (rx, ry, rz) = (c.x.round(), c.y.round(), c.z.round());
+        let on_grid = (c.x - rx).abs() < 0.01 && (c.y - ry).abs() < 0.01 && (c.z - rz).abs() < 0.01;
+        let mat = object.material_at(Vec3::new(c.x, b.max.y, c.z));
+        match kinds.iter().find(|(m, _)| on_grid && m.matches(&mat)) {
+            Some(&(_, cell)) => cells.push(((rx as i32, ry as i32, rz as i32), cell)),
+            None => kept.push(object),
+        }
+    }
+    (Group::new(kept, instances), cells)
+}
+
+/// Define los materiales con RAYTRACING mejorado a partir de las texturas cargadas.
+fn base_palette<'a>(assets: &Assets<'a>) -> Palette<'a> {
+    Palette {
+        grass: BlockMaterial {
+            tex: None,
+            albedo: Vec3::new(0.9, 0.95, 0.85),
+            specular: 0.08,
+            shininess: 20.0,
+            reflectivity: 0.01,
+            transparency: 0.0,
+            ior: 1.0,
+            dispersion: 0.0,
+            emissive: Vec3::new(0.0, 0.0, 0.0),
+            roughness: 0.6,
+            maps: MaterialMaps::default(),
+            cutout: false,
+        },
+
+        grass_top: BlockMaterial {
+            tex: assets.grass_cover,
+            albedo: Vec3::new(0.95, 1.0, 0.95),
+            specular: 0.15,        // Aumentado de 0.08 para reflejos especulares visibles
+            shininess: 35.0,       // Aumentado de 20.0 para reflejos más definidos
+            reflectivity: 0.08,    // Aumentado de 0.01 para reflejos ambientales
+            transparency: 0.0,
+            ior: 1.0,
+            dispersion: 0.0,
+            emissive: Vec3::new(0.0, 0.0, 0.0),
+            roughness: 0.6,
+            maps: MaterialMaps::default(),
+            cutout: false,
+        },
+
+        grass_side: BlockMaterial {
+            tex: assets.grass_side,
+            albedo: Vec3::new(0.90, 0.95, 0.85),
+            specular: 0.12,        // Aumentado de 0.06
+            shininess: 28.0,       // Aumentado de 18.0
+            reflectivity: 0.06,    // Aumentado de 0.01
+            transparency: 0.0,
+            ior: 1.0,
+            dispersion: 0.0,
+            emissive: Vec3::new(0.0, 0.0, 0.0),
+            roughness: 0.6,
+            maps: MaterialMaps::default(),
+            cutout: false,
+        },
+
+        dirt: BlockMaterial {
+            tex: assets.dirt,
+            albedo: Vec3::new(0.85, 0.76, 0.6),
+            specular: 0.02,
+            shininess: 10.0,
+            reflectivity: 0.0,
+            transparency: 0.0,
+            ior: 1.0,
+            dispersion: 0.0,
+            emissive: Vec3::new(0.0, 0.0, 0.0),
+            roughness: 0.9,
+            maps: MaterialMaps::default(),
+            cutout: false,
+        },
+
+        wood: BlockMaterial {
+            tex: assets.wood,
+            albedo: Vec3::new(1.0, 0.98, 0.92),
+            specular: 0.18,        // Aumentado de 0.04 - madera con barniz reflectante
+            shininess: 40.0,       // Aumentado de 18.0 para reflejos más brillantes
+            reflectivity: 0.12,    // Aumentado de 0.01 para reflejos visibles
+            transparency: 0.0,
+            ior: 1.0,
+            dispersion: 0.0,
+            emissive: Vec3::new(0.0, 0.0, 0.0),
+            roughness: 0.45,
+            maps: MaterialMaps::default(),
+            cutout: false,
+        },
+
+        leaves: BlockMaterial {
+            tex: assets.leaves,
+            albedo: Vec3::new(0.6, 1.0, 0.6),
+            specular: 0.20,        // Aumentado de 0.05 - hojas brillantes/húmedas
+            shininess: 25.0,       // Aumentado de 15.0
+            reflectivity: 0.10,    // Aumentado de 0.02 para reflejos de luz
+            transparency: 0.0,     // La luz pasa por los huecos del recorte, no por el bloque
+            ior: 1.0,
+            dispersion: 0.0,
+            emissive: Vec3::new(0.0, 0.0, 0.0),
+            roughness: 0.5,
+            maps: MaterialMaps::default(),
+            cutout: true,
+        },
+
+        obsidian: BlockMaterial {
+            tex: assets.obsidian,
+            albedo: Vec3::new(0.6, 0.65, 0.8),
+            specular: 0.18,
+            shininess: 70.0,
+            reflectivity: 0.08,
+            transparency: 0.0,
+            ior: 1.46,
+            dispersion: 0.0,
+            emissive: Vec3::new(0.0, 0.0, 0.0),
+            roughness: 0.08,
+            maps: assets.obsidian_maps,
+            cutout: false,
+        },
+
+        portal: BlockMaterial {
+            tex: assets.portal,
+            albedo: Vec3::new(1.0, 0.4, 1.2),
+            specular: 0.6,
+            shininess: 60.0,
+            reflectivity: 0.12,
+            transparency: 0.55,
+            ior: 1.6,
+            dispersion: 0.0,
+            emissive: Vec3::new(1.5, 0.3, 1.8),
+            roughness: 0.0,
+            maps: MaterialMaps::default(),
+            cutout: false,
+        },
+
+        stone: BlockMaterial {
+            tex: assets.stone,
+            albedo: Vec3::new(0.9, 0.9, 0.92),
+            specular: 0.08,
+            shininess: 24.0,
+            reflectivity: 0.02,
+            transparency: 0.0,
+            ior: 1.0,
+            dispersion: 0.0,
+            emissive: Vec3::new(0.0, 0.0, 0.0),
+            roughness: 0.7,
+            maps: assets.stone_maps,
+            cutout: false,
+        },
+
+        water: BlockMaterial {
+            tex: assets.water,
+            albedo: Vec3::new(0.55, 0.7, 1.0),
+            specular: 0.5,
+            shininess: 90.0,
+            reflectivity: 0.2,
+            transparency: 0.5,     // Agua translúcida: se ve el fondo
+            ior: 1.33,
+            dispersion: 0.0031,
+            emissive: Vec3::new(0.0, 0.0, 0.0),
+            roughness: 0.03,
+            maps: MaterialMaps::default(),
+            cutout: false,
+        },
+
+        iron: BlockMaterial {
+            tex: assets.iron,
+            albedo: Vec3::new(1.0, 0.95, 0.9),
+            specular: 0.35,
+            shininess: 60.0,
+            reflectivity: 0.06,
+            transparency: 0.0,
+            ior: 1.0,
+            dispersion: 0.0,
+            emissive: Vec3::new(0.0, 0.0, 0.0),
+            roughness: 0.3,
+            maps: assets.iron_maps,
+            cutout: false,
+        },
+
+        diamond: BlockMaterial {
+            tex: assets.diamond,
+            albedo: Vec3::new(0.9, 1.0, 1.0),
+            specular: 0.7,
+            shininess: 120.0,
+            reflectivity: 0.15,
+            transparency: 0.0,
+            ior: 2.42,
+            dispersion: 0.0,
+            emissive: Vec3::new(0.0, 0.0, 0.0),
+            roughness: 0.05,
+            maps: assets.diamond_maps,
+            cutout: false,
+        },
+
+        lava: BlockMaterial {
+            tex: assets.lava,
+            albedo: Vec3::new(1.0, 0.6, 0.3),
+            specular: 0.1,
+            shininess: 20.0,
+            reflectivity: 0.0,
+            transparency: 0.0,
+            ior: 1.0,
+            dispersion: 0.0,
+            emissive: Vec3::new(1.2, 0.45, 0.05), // Lava brillante
+            roughness: 0.8,
+            maps: MaterialMaps::default(),
+            cutout: false,
+        },
+
+        glowstone: BlockMaterial {
+            tex: assets.glowstone,
+            albedo: Vec3::new(1.0, 0.9, 0.7),
+            specular: 0.1,
+            shininess: 20.0,
+            reflectivity: 0.0,
+            transparency: 0.0,
+            ior: 1.0,
+            dispersion: 0.0,
+            emissive: Vec3::new(1.0, 0.85, 0.5),
+            roughness: 0.8,
+            maps: MaterialMaps::default(),
+            cutout: false,
+        },
+
+        ice: BlockMaterial {
+            tex: assets.ice,
+            albedo: Vec3::new(0.85, 0.92, 1.0),
+            specular: 0.6,
+            shininess: 100.0,
+            reflectivity: 0.15,
+            transparency: 0.35,
+            ior: 1.31,
+            dispersion: 0.0029,
+            emissive: Vec3::new(0.0, 0.0, 0.0),
+            roughness: 0.1,
+            maps: MaterialMaps::default(),
+            cutout: false,
+        },
+
+        glass: BlockMaterial {
+            tex: assets.glass,
+            albedo: Vec3::new(0.95, 1.0, 1.0),
+            specular: 0.6,
+            shininess: 120.0,
+            reflectivity: 0.1,
+            transparency: 0.0,     // Solo el marco y los brillos son opacos
+            ior: 1.0,
+            dispersion: 0.0042,    // Vidrio crown; los texels claros del recorte son aire
+            emissive: Vec3::new(0.0, 0.0, 0.0),
+            roughness: 0.0,
+            maps: MaterialMaps::default(),
+            cutout: true,
+        },
+
+        torch: BlockMaterial {
+            tex: assets.torch,
+            albedo: Vec3::new(1.0, 1.0, 1.0),
+            specular: 0.05,
+            shininess: 10.0,
+            reflectivity: 0.0,
+            transparency: 0.0,
+            ior: 1.0,
+            dispersion: 0.0,
+            emissive: Vec3::new(0.6, 0.45, 0.2), // Brillo cálido de la llama
+            roughness: 0.9,
+            maps: MaterialMaps::default(),
+            cutout: true,
+        },
+
+        sand: BlockMaterial {
+            tex: assets.sand,
+            albedo: Vec3::new(1.0, 0.97, 0.9),
+            specular: 0.04,
+            shininess: 12.0,
+            reflectivity: 0.0,
+            transparency: 0.0,
+            ior: 1.0,
+            dispersion: 0.0,
+            emissive: Vec3::new(0.0, 0.0, 0.0),
+            roughness: 0.95,
+            maps: MaterialMaps::default(),
+            cutout: false,
+        },
+
+        gravel: BlockMaterial {
+            tex: assets.gravel,
+            albedo: Vec3::new(0.95, 0.95, 0.95),
+            specular: 0.06,
+            shininess: 14.0,
+            reflectivity: 0.0,
+            transparency: 0.0,
+            ior: 1.0,
+            dispersion: 0.0,
+            emissive: Vec3::new(0.0, 0.0, 0.0),
+            roughness: 0.85,
+            maps: MaterialMaps::default(),
+            cutout: false,
+        },
+    }
+}
+
+/// Árbol con tronco de 3 bloques y copa de hojas, con la base del tronco en el origen.
+/// OPTIMIZACIÓN: Árboles más pequeños (altura 5 en vez de 6) para mejor FPS
+fn tree_group<'a>(mats: &Palette<'a>) -> Arc<Group<'a>> {
+    let mut objects: Vec<DynObject<'a>> = Vec::new();
+    let mut used: HashSet<(i32, i32, i32, u8)> = HashSet::new();
+    // Tronco de 3 bloques (reducido de 4)
+    for y in 1..=3 {
+        place_block(&mut objects, &mut used, mats.wood, 0, y, 0);
+    }
+    // Hojas capa inferior (y=3) y media (y=4)
+    for y in 3..=4 {
+        for dx in -1..=1 {
+            for dz in -1..=1 {
+                place_block(&mut objects, &mut used, mats.leaves, dx, y, dz);
+            }
+        }
+    }
+    // Hojas capa superior (y=5) - solo 5 bloques en cruz
+    for (dx, dz) in [(0, 0), (-1, 0), (1, 0), (0, -1), (0, 1)] {
+        place_block(&mut objects, &mut used, mats.leaves, dx, 5, dz);
+    }
+    Arc::new(Group::new(objects, Vec::new()))
+}
+
+/// Mismo árbol que `tree_group`, bloque a bloque en (`x`, `z`) y sin repetir celdas ya usadas.
+fn place_tree<'a>(objects: &mut Vec<DynObject<'a>>, used: &mut HashSet<(i32, i32, i32, u8)>, mats: &Palette<'a>, x: i32, z: i32) {
+    // Tronco de 3 bloques
+    for y in 1..=3 {
+        place_with_tag(objects, used, mats.wood, x, y, z, 1);
+    }
+    // Hojas capa inferior (y=3, sin el tronco) y media (y=4)
+    for y in 3..=4 {
+        for dx in -1..=1 {
+            for dz in -1..=1 {
+                if y == 4 || dx != 0 || dz != 0 {
+                    place_with_tag(objects, used, mats.leaves, x + dx, y, z + dz, 1);
+                }
+            }
+        }
+    }
+    // Hojas capa superior (y=5) - solo 5 bloques en cruz
+    for (dx, dz) in [(0, 0), (-1, 0), (1, 0), (0, -1), (0, 1)] {
+        place_with_tag(objects, used, mats.leaves, x + dx, 5, z + dz, 1);
+    }
+}
+
+/// Portal de obsidiana centrado en x = 0 con `inner` bloques de portal apilados desde y = 1.
+fn portal_group<'a>(mats: &Palette<'a>, inner: i32) -> Arc<Group<'a>> {
+    let mut objects: Vec<DynObject<'a>> = Vec::new();
+    let mut used: HashSet<(i32, i32, i32, u8)> = HashSet::new();
+    // Pilares
+    for y in 0..=inner + 1 {
+        place_block(&mut objects, &mut used, mats.obsidian, -1, y, 0);
+        place_block(&mut objects, &mut used, mats.obsidian, 1, y, 0);
+    }
+    // Base y techo
+    place_block(&mut objects, &mut used, mats.obsidian, 0, 0, 0);
+    place_block(&mut objects, &mut used, mats.obsidian, 0, inner + 1, 0);
+    // Interior
+    for y in 1..=inner {
+        place_block(&mut objects, &mut used, mats.portal, 0, y, 0);
+    }
+    Arc::new(Group::new(objects, Vec::new()))
+}
+
 pub fn build_scene<'a>(assets: &Assets<'a>, world: WorldKind) -> SceneData<'a> {
     let mut objects: Vec<DynObject<'a>> = Vec::new();
+    let mut instances: Vec<Instance<'a>> = Vec::new();
     let mut used: HashSet<(i32, i32, i32, u8)> = HashSet::new();
-    
-    // Definir materiales con RAYTRACING mejorado
-    let grass_top_mat = BlockMaterial {
-        tex: assets.grass_cover,
-        albedo: Vec3::new(0.95, 1.0, 0.95),
-        specular: 0.15,        // Aumentado de 0.08 para reflejos especulares visibles
-        shininess: 35.0,       // Aumentado de 20.0 para reflejos más definidos
-        reflectivity: 0.08,    // Aumentado de 0.01 para reflejos ambientales
-        transparency: 0.0,
-        ior: 1.0,
-        emissive: Vec3::new(0.0, 0.0, 0.0),
-    };
-    
-    let grass_side_mat = BlockMaterial {
-        tex: assets.grass_side,
-        albedo: Vec3::new(0.90, 0.95, 0.85),
-        specular: 0.12,        // Aumentado de 0.06
-        shininess: 28.0,       // Aumentado de 18.0
-        reflectivity: 0.06,    // Aumentado de 0.01
-        transparency: 0.0,
-        ior: 1.0,
-        emissive: Vec3::new(0.0, 0.0, 0.0),
-    };
-    
-    let dirt_mat = BlockMaterial {
-        tex: assets.dirt,
-        albedo: Vec3::new(0.85, 0.76, 0.6),
-        specular: 0.02,
-        shininess: 10.0,
-        reflectivity: 0.0,
-        transparency: 0.0,
-        ior: 1.0,
-        emissive: Vec3::new(0.0, 0.0, 0.0),
-    };
-    
-    let wood_mat = BlockMaterial {
-        tex: assets.wood,
-        albedo: Vec3::new(1.0, 0.98, 0.92),
-        specular: 0.18,        // Aumentado de 0.04 - madera con barniz reflectante
-        shininess: 40.0,       // Aumentado de 18.0 para reflejos más brillantes
-        reflectivity: 0.12,    // Aumentado de 0.01 para reflejos visibles
-        transparency: 0.0,
-        ior: 1.0,
-        emissive: Vec3::new(0.0, 0.0, 0.0),
-    };
-    
-    let leaves_mat = BlockMaterial {
-        tex: assets.leaves,
-        albedo: Vec3::new(0.6, 1.0, 0.6),
-        specular: 0.20,        // Aumentado de 0.05 - hojas brillantes/húmedas
-        shininess: 25.0,       // Aumentado de 15.0
-        reflectivity: 0.10,    // Aumentado de 0.02 para reflejos de luz
-        transparency: 0.25,    // Aumentado de 0.15 para más translucidez
-        ior: 1.08,             // Aumentado de 1.05 para refracción más visible
-        emissive: Vec3::new(0.0, 0.0, 0.0),
-    };
-    
-    let obsidian_mat = BlockMaterial {
-        tex: assets.obsidian,
-        albedo: Vec3::new(0.6, 0.65, 0.8),
-        specular: 0.18,
-        shininess: 70.0,
-        reflectivity: 0.08,
-        transparency: 0.0,
-        ior: 1.46,
-        emissive: Vec3::new(0.0, 0.0, 0.0),
-    };
-    
-    let portal_mat = BlockMaterial {
-        tex: assets.portal,
-        albedo: Vec3::new(1.0, 0.4, 1.2),
-        specular: 0.6,
-        shininess: 60.0,
-        reflectivity: 0.12,
-        transparency: 0.55,
-        ior: 1.6,
-        emissive: Vec3::new(1.5, 0.3, 1.8),
-    };
+    let mats = palette(assets);
     
     // Construir mundo simple
     match world {
@@ -438,73 +1057,31 @@ pub fn build_scene<'a>(assets: &Assets<'a>, world: WorldKind) -> SceneData<'a> {
             // Piso de pasto completo 11x8
             for x in -5..=5 {
                 for z in -3..=4 {
-                    place_grass_block(&mut objects, &mut used, grass_top_mat, grass_side_mat, x, 0, z);
+                    place_grass_block(&mut objects, &mut used, &mats, x, 0, z);
                 }
             }
             
-            // Helper para crear un árbol completo en una posición
-            // OPTIMIZACIÓN: Árboles más pequeños (altura 5 en vez de 6) para mejor FPS
-            let place_tree = |objects: &mut Vec<Box<dyn Intersectable + Send + Sync>>, used: &mut HashSet<(i32, i32, i32, u8)>, x: i32, z: i32| {
-                // Tronco de 3 bloques (reducido de 4)
-                for y in 1..=3 {
-                    place_with_tag(objects, used, wood_mat, x, y, z, 1);
-                }
-                // Hojas capa inferior (y=3)
-                for dx in -1..=1 {
-                    for dz in -1..=1 {
-                        if dx != 0 || dz != 0 {
-                            place_with_tag(objects, used, leaves_mat, x + dx, 3, z + dz, 1);
-                        }
-                    }
-                }
-                // Hojas capa media (y=4)
-                for dx in -1..=1 {
-                    for dz in -1..=1 {
-                        place_with_tag(objects, used, leaves_mat, x + dx, 4, z + dz, 1);
-                    }
-                }
-                // Hojas capa superior (y=5) - solo 5 bloques en cruz
-                place_with_tag(objects, used, leaves_mat, x, 5, z, 1);
-                place_with_tag(objects, used, leaves_mat, x - 1, 5, z, 1);
-                place_with_tag(objects, used, leaves_mat, x + 1, 5, z, 1);
-                place_with_tag(objects, used, leaves_mat, x, 5, z - 1, 1);
-                place_with_tag(objects, used, leaves_mat, x, 5, z + 1, 1);
-            };
-            
             // ÁRBOLES distribuidos al azar - 7 árboles optimizados para FPS
-            // Distribución en 9x7: mismas posiciones pero con más espacio
-            
-            // Fila 2 (z=-2): 3 árboles 🌲🟩🟩🌲🟩🟩🟩🟩🌲
-            place_tree(&mut objects, &mut used, -4, -2);
-            place_tree(&mut objects, &mut used, -1, -2);
-            place_tree(&mut objects, &mut used, 4, -2);
-            
-            // Fila 3 (z=-1): 1 árbol 🟩🟩🟩🟩🟩🟩🌲🟩🟩
-            place_tree(&mut objects, &mut used, 3, -1);
-            
-            // Fila 5 (z=1): 1 árbol 🟩🟩🌲🟩🟩🟩🟩🟩🟩
-            place_tree(&mut objects, &mut used, -3, 1);
-            
-            // Fila 6 (z=2): 2 árboles 🌲🟩🟩🟩🟩🟩🟩🟩🌲
-            place_tree(&mut objects, &mut used, -4, 2);
-            place_tree(&mut objects, &mut used, 4, 2);
-            
-            // PORTAL (⬛️🟪⬛️) en z=0, x=0,1,2 (centro fila 3)
-            // Pilar izquierdo
-            for y in 0..=4 {
-                place_with_tag(&mut objects, &mut used, obsidian_mat, 0, y, 0, 1);
-            }
-            // Pilar derecho
-            for y in 0..=4 {
-                place_with_tag(&mut objects, &mut used, obsidian_mat, 2, y, 0, 1);
+            let trees = [(-4, -2), (-1, -2), (4, -2), (3, -1), (-3, 1), (-4, 2), (4, 2)];
+            // Las instancias no deduplican bloques: un árbol cuya copa toca la de otro se
+            // coloca bloque a bloque (la hoja que ya ocupa una celda gana), el resto se instancia
+            let overlaps = |x: i32, z: i32| {
+                trees.iter().any(|&(ox, oz)| (ox, oz) != (x, z) && (ox - x).abs() <= 2 && (oz - z).abs() <= 2)
+            };
+            let tree = tree_group(&mats);
+            for (x, z) in trees {
+                if overlaps(x, z) {
+                    place_tree(&mut objects, &mut used, &mats, x, z);
+                } else {
+                    instances.extend(Instance::new(tree.clone(), Transform::translate(Vec3::new(x as f32, 0.0, z as f32))));
+                }
             }
-            // Base y techo
-            place_with_tag(&mut objects, &mut used, obsidian_mat, 1, 0, 0, 1);
-            place_with_tag(&mut objects, &mut used, obsidian_mat, 1, 4, 0, 1);
-            // Interior portal
-            place_with_tag(&mut objects, &mut used, portal_mat, 1, 1, 0, 1);
-            place_with_tag(&mut objects, &mut used, portal_mat, 1, 2, 0, 1);
-            place_with_tag(&mut objects, &mut used, portal_mat, 1, 3, 0, 1);
+            
+            // PORTAL (⬛️🟪⬛️) en z=0, x=0,1,2 (centro fila 4)
+            instances.extend(Instance::new(portal_group(&mats, 3), Transform::translate(Vec3::new(1.0, 0.0, 0.0))));
+            // Antorchas a ambos lados del portal
+            place_with_tag(&mut objects, &mut used, mats.torch, -1, 1, 0, 1);
+            place_with_tag(&mut objects, &mut used, mats.torch, 3, 1, 0, 1);
         }
         WorldKind::Nether => {
             // Piso de obsidiana 9x5
@@ -517,7 +1094,7 @@ pub fn build_scene<'a>(assets: &Assets<'a>, world: WorldKind) -> SceneData<'a> {
             // Piso completo de obsidiana (9x5)
             for x in -4..=4 {  // 9 bloques de ancho
                 for z in -2..=2 { // 5 bloques de profundidad
-                    place_block(&mut objects, &mut used, obsidian_mat, x, 0, z);
+                    place_block(&mut objects, &mut used, mats.obsidian, x, 0, z);
                 }
             }
             
@@ -526,40 +1103,29 @@ pub fn build_scene<'a>(assets: &Assets<'a>, world: WorldKind) -> SceneData<'a> {
             
             // Fila 1: Esquina izquierda (-4, -2) y derecha (4, -2)
             for y in 1..=pillar_height {
-                place_with_tag(&mut objects, &mut used, obsidian_mat, -4, y, -2, 2);
-                place_with_tag(&mut objects, &mut used, obsidian_mat, 4, y, -2, 2);
+                place_with_tag(&mut objects, &mut used, mats.obsidian, -4, y, -2, 2);
+                place_with_tag(&mut objects, &mut used, mats.obsidian, 4, y, -2, 2);
             }
             
             // Fila 2: Posición (2, -1) - pilar
             for y in 1..=pillar_height {
-                place_with_tag(&mut objects, &mut used, obsidian_mat, 2, y, -1, 2);
+                place_with_tag(&mut objects, &mut used, mats.obsidian, 2, y, -1, 2);
             }
             
             // Fila 4: Posición (1, 1) - pilar
             for y in 1..=pillar_height {
-                place_with_tag(&mut objects, &mut used, obsidian_mat, 1, y, 1, 2);
+                place_with_tag(&mut objects, &mut used, mats.obsidian, 1, y, 1, 2);
             }
             
             // Fila 5: Esquina izquierda (-4, 2) y derecha (4, 2)
             for y in 1..=pillar_height {
-                place_with_tag(&mut objects, &mut used, obsidian_mat, -4, y, 2, 2);
-                place_with_tag(&mut objects, &mut used, obsidian_mat, 4, y, 2, 2);
+                place_with_tag(&mut objects, &mut used, mats.obsidian, -4, y, 2, 2);
+                place_with_tag(&mut objects, &mut used, mats.obsidian, 4, y, 2, 2);
             }
             
             // Portal en la tercera fila (🟪) - posición (-2, 0)
-            // Marco del portal (obsidiana) - altura reducida a 3 para optimización
-            for y in 0..=3 {
-                place_with_tag(&mut objects, &mut used, obsidian_mat, -3, y, 0, 2); // Pilar izquierdo
-                place_with_tag(&mut objects, &mut used, obsidian_mat, -1, y, 0, 2); // Pilar derecho
-            }
-            
-            // Base y techo del portal
-            place_with_tag(&mut objects, &mut used, obsidian_mat, -2, 0, 0, 2);
-            place_with_tag(&mut objects, &mut used, obsidian_mat, -2, 3, 0, 2);
-            
-            // Interior del portal (🟪) - bloques morados emisivos
-            place_with_tag(&mut objects, &mut used, portal_mat, -2, 1, 0, 2);
-            place_with_tag(&mut objects, &mut used, portal_mat, -2, 2, 0, 2);
+            // Marco de obsidiana de altura 4 (interior de 2) para optimización
+            instances.extend(Instance::new(portal_group(&mats, 2), Transform::translate(Vec3::new(-2.0, 0.0, 0.0))));
         }
     }
     
@@ -570,7 +1136,162 @@ pub fn build_scene<'a>(assets: &Assets<'a>, world: WorldKind) -> SceneData<'a> {
     
     let is_nether = matches!(world, WorldKind::Nether);
 
-    SceneData { objects, skybox, is_nether }
+    SceneData::with_instances(objects, instances, skybox, is_nether)
+}
+
+/// Construye una escena a partir de terreno procedural (ver `terrain::generate`).
+/// Solo se emiten los bloques con alguna cara visible para no saturar el trazador.
+pub fn build_terrain_scene<'a>(assets: &Assets<'a>, config: &TerrainConfig) -> SceneData<'a> {
+    let mut objects: Vec<DynObject<'a>> = Vec::new();
+    let mut used: HashSet<(i32, i32, i32, u8)> = HashSet::new();
+    let mats = palette(assets);
+    let grid = terrain::generate(config);
+
+    for (x, y, z, block) in grid.iter_solid() {
+        if !grid.is_exposed(x, y, z) {
+            continue;
+        }
+        match block {
+            Block::Grass => place_grass_block(&mut objects, &mut used, &mats, x, y, z),
+            Block::Dirt => place_block(&mut objects, &mut used, mats.dirt, x, y, z),
+            Block::Stone => place_block(&mut objects, &mut used, mats.stone, x, y, z),
+            Block::Water => place_block(&mut objects, &mut used, mats.water, x, y, z),
+            Block::Wood => place_block(&mut objects, &mut used, mats.wood, x, y, z),
+            Block::Leaves => place_block(&mut objects, &mut used, mats.leaves, x, y, z),
+            Block::IronOre => place_block(&mut objects, &mut used, mats.iron, x, y, z),
+            Block::DiamondOre => place_block(&mut objects, &mut used, mats.diamond, x, y, z),
+            Block::Air => {}
+        }
+    }
+
+    SceneData::new(objects, assets.skybox_overworld, false)
+}
+
+/// Construye una escena con una construcción importada (ver `schematic`), centrada en X/Z
+/// y apoyada en y = 0. Igual que el terreno, solo se emiten los bloques con caras visibles.
+/// La construcción queda en su propio grupo y se coloca con una instancia.
+pub fn build_schematic_scene<'a>(assets: &Assets<'a>, build: &Schematic) -> SceneData<'a> {
+    let mut objects: Vec<DynObject<'a>> = Vec::new();
+    let mut used: HashSet<(i32, i32, i32, u8)> = HashSet::new();
+    let mats = palette(assets);
+
+    let opaque: HashSet<(i32, i32, i32)> = build
+        .blocks
+        .iter()
+        .filter(|b| !b.3.is_see_through())
+        .map(|&(x, y, z, _)| (x, y, z))
+        .collect();
+    let hidden = |x: i32, y: i32, z: i32| {
+        [(1, 0, 0), (-1, 0, 0), (0, 1, 0), (0, -1, 0), (0, 0, 1), (0, 0, -1)]
+            .iter()
+            .all(|(dx, dy, dz)| opaque.contains(&(x + dx, y + dy, z + dz)))
+    };
+
+    for &(x, y, z, kind) in &build.blocks {
+        if hidden(x, y, z) {
+            continue;
+        }
+        let mat = match kind {
+            BlockKind::Grass => {
+                place_grass_block(&mut objects, &mut used, &mats, x, y, z);
+                continue;
+            }
+            BlockKind::Dirt => mats.dirt,
+            BlockKind::Stone => mats.stone,
+            BlockKind::Water => mats.water,
+            BlockKind::Wood => mats.wood,
+            BlockKind::Leaves => mats.leaves,
+            BlockKind::Obsidian => mats.obsidian,
+            BlockKind::Portal => mats.portal,
+            BlockKind::Iron => mats.iron,
+            BlockKind::Diamond => mats.diamond,
+            BlockKind::Lava => mats.lava,
+            BlockKind::Glowstone => mats.glowstone,
+            BlockKind::Ice => mats.ice,
+            BlockKind::Glass => mats.glass,
+            BlockKind::Torch => mats.torch,
+            // Sin material propio: cubo sólido del color estimado
+            BlockKind::Unknown(i) => BlockMaterial {
+                tex: None,
+                albedo: build.unknown[i as usize].color,
+                ..mats.stone
+            },
+        };
+        place_block(&mut objects, &mut used, mat, x, y, z);
+    }
+
+    let model = Arc::new(Group::new(objects, Vec::new()));
+    let (ox, oz) = (build.width / 2, build.length / 2);
+    let placed = Instance::new(model, Transform::translate(Vec3::new(-ox as f32, 0.0, -oz as f32)));
+    SceneData::with_instances(Vec::new(), placed.into_iter().collect(), assets.skybox_overworld, false)
+}
+
+/// Tablero de materiales: una fila de cada bloque de la paleta sobre un piso de piedra.
+/// Pensado para pruebas de regresión de iluminación (ver `tests/golden.rs`).
+pub fn build_material_board<'a>(assets: &Assets<'a>) -> SceneData<'a> {
+    let mats = palette(assets);
+    let row = [
+        mats.dirt,
+        mats.wood,
+        mats.leaves,
+        mats.stone,
+        mats.obsidian,
+        mats.water,
+        mats.iron,
+        mats.diamond,
+        mats.portal,
+    ];
+    material_row(assets, &mats, &row)
+}
+
+/// Variante del tablero para el render espectral: el diamante se vuelve translúcido
+/// (con la dispersión de un diamante real) y se agrega hielo al final de la fila.
+pub fn build_spectral_board<'a>(assets: &Assets<'a>) -> SceneData<'a> {
+    let mats = palette(assets);
+    let diamond = BlockMaterial { transparency: 0.4, dispersion: 0.023, ..mats.diamond };
+    let row = [
+        mats.dirt,
+        mats.wood,
+        mats.leaves,
+        mats.stone,
+        mats.obsidian,
+        mats.water,
+        mats.iron,
+        diamond,
+        mats.portal,
+        mats.ice,
+    ];
+    material_row(assets, &mats, &row)
+}
+
+fn material_row<'a>(assets: &Assets<'a>, mats: &Palette<'a>, row: &[BlockMaterial<'a>]) -> SceneData<'a> {
+    let mut objects: Vec<DynObject<'a>> = Vec::new();
+    let mut used: HashSet<(i32, i32, i32, u8)> = HashSet::new();
+
+    for x in -5..=5 {
+        for z in -1..=1 {
+            place_block(&mut objects, &mut used, mats.stone, x, 0, z);
+        }
+    }
+
+    place_grass_block(&mut objects, &mut used, mats, -5, 1, 0);
+    for (i, mat) in row.iter().enumerate() {
+        place_block(&mut objects, &mut used, *mat, i as i32 - 4, 1, 0);
+    }
+    // Recortes por alfa apoyados sobre la fila: vidrio sobre la piedra, antorcha sobre el hierro
+    place_block(&mut objects, &mut used, mats.glass, -1, 2, 0);
+    place_block(&mut objects, &mut used, mats.torch, 2, 2, 0);
+
+    SceneData::new(objects, assets.skybox_overworld, false)
+}
+
+/// Rectángulo de píxeles dentro del cuadro completo (render por teselas).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Tile {
+    pub x: u32,
+    pub y: u32,
+    pub w: u32,
+    pub h: u32,
 }
 
 pub fn render<'a>(
@@ -580,11 +1301,41 @@ pub fn render<'a>(
     cam: &Camera,
     sun_angle: f32, // Ángulo del sol para ciclo día/noche
     scene: &SceneData<'a>,
-    max_depth: i32,
-) {
+    settings: &RenderSettings,
+) -> FrameStats {
+    let size = (w as u32, h as u32);
+    let full = Tile { x: 0, y: 0, w: size.0, h: size.1 };
+    let frame_stats = render_tile(frame, size, full, cam, sun_angle, scene, settings);
+    if settings.view == RenderView::Cost {
+        colorize_cost(frame, frame_stats.max_tests);
+    }
+    frame_stats
+}
+
+/// Colorea un cuadro con conteos crudos de pruebas (vista de costo) relativo a `max_tests`.
+pub fn colorize_cost(frame: &mut [u8], max_tests: u32) {
+    for px in frame.chunks_mut(4) {
+        let tests = u32::from_le_bytes([px[0], px[1], px[2], px[3]]);
+        px.copy_from_slice(&to_rgba(cost_color(tests, max_tests)));
+    }
+}
+
+/// Renderiza solo `tile` de un cuadro de `size` (ancho, alto) en `frame`, que mide
+/// `tile.w`×`tile.h`. En la vista de costo deja el conteo crudo de pruebas por píxel
+/// (u32 LE): el máximo depende del cuadro completo, se colorea con `colorize_cost`.
+pub fn render_tile<'a>(
+    frame: &mut [u8],
+    size: (u32, u32),
+    tile: Tile,
+    cam: &Camera,
+    sun_angle: f32,
+    scene: &SceneData<'a>,
+    settings: &RenderSettings,
+) -> FrameStats {
+    let (w, h) = size;
     let aspect = w as f32 / h as f32;
-    let width = w as usize;
-    let height = h as usize;
+    let width = tile.w as usize;
+    let height = tile.h as usize;
     
     // Calcular posición del sol basada en el ángulo
     let sun_distance = 20.0;
@@ -603,13 +1354,30 @@ pub fn render<'a>(
         .min(height.max(1));
     let rows_per_chunk = (height + threads - 1) / threads;
     let pixels_per_row = width * 4;
-    let objects_ref: &[DynObject<'a>] = &scene.objects;
-    let skybox = scene.skybox.as_ref();
-    let is_nether = scene.is_nether;
+    let caustics = (settings.spectral.enabled && settings.spectral.caustic_photons > 0)
+        .then(|| scene.caustics(light_pos, sun_brightness, settings.spectral.caustic_photons));
+    let ctx = TraceCtx {
+        scene: &scene.root,
+        light_pos,
+        sun_brightness,
+        skybox: scene.skybox.as_ref(),
+        is_nether: scene.is_nether,
+        ao: settings.ao,
+        gloss_samples: settings.gloss_samples,
+        max_depth: settings.max_depth,
+        occupancy: &scene.occupancy,
+        spectral: settings.spectral,
+        caustics: caustics.as_deref(),
+    };
+    let ctx_ref = &ctx;
+    let max_depth = settings.max_depth;
+    let view = settings.view;
 
+    let mut frame_stats = FrameStats::default();
     thread::scope(|scope| {
         let mut start_row = 0usize;
-        let mut remaining: &mut [u8] = frame;
+        let mut remaining: &mut [u8] = &mut frame[..width * height * 4];
+        let mut workers = Vec::with_capacity(threads);
         for _ in 0..threads {
             if start_row >= height {
                 break;
@@ -621,20 +1389,35 @@ pub fn render<'a>(
             let chunk_start = start_row;
             remaining = rest;
             let cam_ref = cam;
-            scope.spawn(move || {
+            workers.push(scope.spawn(move || {
+                let mut chunk_stats = FrameStats::default();
                 for (row_offset, row) in chunk.chunks_mut(pixels_per_row).enumerate() {
-                    let y = (chunk_start + row_offset) as i32;
+                    let y = tile.y as usize + chunk_start + row_offset;
                     let v = (y as f32 + 0.5) / h as f32;
                     for x in 0..width {
-                        let u = (x as f32 + 0.5) / w as f32;
+                        let u = ((tile.x as usize + x) as f32 + 0.5) / w as f32;
                         let ray = cam_ref.make_ray(u, v, aspect);
-                        let color = trace(&ray, objects_ref, light_pos, sun_brightness, max_depth, skybox, is_nether);
+                        let mut stats = RayStats::default();
+                        let color = trace(&ray, ctx_ref, max_depth, &mut stats, None);
+                        chunk_stats.add(&stats);
                         let idx = x * 4;
-                        row[idx..idx + 4].copy_from_slice(&to_rgba(color));
+                        let px = match view {
+                            RenderView::Beauty => to_rgba(color),
+                            // El máximo del cuadro aún no se conoce: guardar el conteo crudo
+                            RenderView::Cost => stats.tests.to_le_bytes(),
+                            _ => to_rgba(view_color(view, &stats, max_depth)),
+                        };
+                        row[idx..idx + 4].copy_from_slice(&px);
                     }
                 }
-            });
+                chunk_stats
+            }));
             start_row += rows_here;
         }
+        for worker in workers {
+            frame_stats.merge(&worker.join().expect("hilo de render"));
+        }
     });
+
+    frame_stats
 }
@@ -0,0 +1,583 @@
+//! Importación de construcciones reales de Minecraft: esquemas Sponge (`.schem`),
+//! esquemas legacy de MCEdit (`.schematic`) y trozos de regiones Anvil (`.mca`).
+//!
+//! Todos los formatos se reducen a una lista de bloques con coordenadas locales
+//! (esquina mínima en el origen). Los bloques sin material equivalente en la paleta
+//! del diorama se conservan como `BlockKind::Unknown` con un color aproximado.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::math::Vec3;
+use crate::nbt::{self, Tag};
+
+/// Bloque importado, ya traducido a los materiales del diorama.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum BlockKind {
+    Grass,
+    Dirt,
+    Stone,
+    Water,
+    Wood,
+    Leaves,
+    Obsidian,
+    Portal,
+    Iron,
+    Diamond,
+    Lava,
+    Glowstone,
+    Ice,
+    Glass,
+    Torch,
+    /// Índice en `Schematic::unknown`.
+    Unknown(u16),
+}
+
+impl BlockKind {
+    /// Bloques que dejan ver a sus vecinos (no ocultan caras).
+    pub fn is_see_through(self) -> bool {
+        matches!(
+            self,
+            BlockKind::Water
+                | BlockKind::Leaves
+                | BlockKind::Portal
+                | BlockKind::Ice
+                | BlockKind::Glass
+                | BlockKind::Torch
+        )
+    }
+}
+
+/// Bloque sin equivalente en la paleta; se dibuja como un cubo de color.
+#[derive(Clone, Debug)]
+pub struct UnknownBlock {
+    pub name: String,
+    pub color: Vec3,
+    pub count: usize,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Schematic {
+    pub width: i32,  // X
+    pub height: i32, // Y
+    pub length: i32, // Z
+    /// Bloques no vacíos en coordenadas locales `[0, width) x [0, height) x [0, length)`.
+    pub blocks: Vec<(i32, i32, i32, BlockKind)>,
+    pub unknown: Vec<UnknownBlock>,
+}
+
+/// Ventana de una región Anvil a importar: una región completa tiene 32x32 chunks
+/// y cientos de bloques de alto, demasiado para el trazador.
+#[derive(Copy, Clone, Debug)]
+pub struct RegionWindow {
+    pub chunk_min: (i32, i32), // Chunks locales a la región [0, 31], inclusivos
+    pub chunk_max: (i32, i32),
+    pub y_min: i32,
+    pub y_max: i32,
+}
+
+impl Default for RegionWindow {
+    fn default() -> Self {
+        Self {
+            chunk_min: (0, 0),
+            chunk_max: (1, 1),
+            y_min: 56,
+            y_max: 100,
+        }
+    }
+}
+
+impl Schematic {
+    /// Cuenta cuántas veces aparece cada bloque desconocido (para el reporte).
+    fn count_unknown(mut self) -> Self {
+        for &(_, _, _, kind) in &self.blocks {
+            if let BlockKind::Unknown(i) = kind {
+                self.unknown[i as usize].count += 1;
+            }
+        }
+        self
+    }
+
+    /// Carga un `.schem`, `.schematic` o `.mca` (este último con `window`).
+    pub fn load(path: &Path, window: &RegionWindow) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("mca") => Self::from_region(&bytes, window),
+            _ => Self::from_schematic(&nbt::read_compressed(&bytes)?),
+        }
+    }
+
+    /// Esquemas Sponge (v1-v3) o legacy de MCEdit, según las etiquetas presentes.
+    pub fn from_schematic(root: &Tag) -> io::Result<Self> {
+        // Sponge v3 envuelve todo en un compuesto "Schematic"
+        let root = root.get("Schematic").unwrap_or(root);
+        let dim = |key: &str| -> io::Result<i32> {
+            root.get(key)
+                .and_then(Tag::as_i64)
+                .map(|v| v as u16 as i32) // Los tamaños son shorts sin signo
+                .ok_or_else(|| invalid(format!("falta la etiqueta {}", key)))
+        };
+        let (width, height, length) = (dim("Width")?, dim("Height")?, dim("Length")?);
+        let mut out = Schematic { width, height, length, ..Default::default() };
+        let mut names = NameCache::default();
+
+        if let Some(ids) = root.get("Blocks").and_then(Tag::as_bytes) {
+            // Legacy: IDs numéricos + metadatos, con "AddBlocks" para IDs > 255
+            let add = root.get("AddBlocks").and_then(Tag::as_bytes);
+            for (i, &low) in ids.iter().enumerate() {
+                let high = add
+                    .and_then(|a| a.get(i / 2))
+                    .map(|&b| if i % 2 == 0 { (b >> 4) & 0xF } else { b & 0xF })
+                    .unwrap_or(0) as u16;
+                let id = (high << 8) | low as u16;
+                let (x, y, z) = unflatten(i, width, length);
+                if let Some(kind) = names.legacy(id, &mut out.unknown) {
+                    out.blocks.push((x, y, z, kind));
+                }
+            }
+            return Ok(out.count_unknown());
+        }
+
+        // Sponge: paleta nombre -> índice y datos en varints
+        let container = root.get("Blocks").unwrap_or(root);
+        let palette = container
+            .get("Palette")
+            .and_then(Tag::as_compound)
+            .ok_or_else(|| invalid("falta la paleta del esquema".into()))?;
+        let data = container
+            .get("Data")
+            .or_else(|| container.get("BlockData"))
+            .and_then(Tag::as_bytes)
+            .ok_or_else(|| invalid("faltan los datos de bloques".into()))?;
+
+        let mut by_index: HashMap<i64, Option<BlockKind>> = HashMap::new();
+        for (name, idx) in palette {
+            if let Some(idx) = idx.as_i64() {
+                by_index.insert(idx, names.modern(name, &mut out.unknown));
+            }
+        }
+
+        let mut pos = 0usize;
+        let mut i = 0usize;
+        while pos < data.len() {
+            let (value, used) = read_varint(&data[pos..])?;
+            pos += used;
+            if let Some(Some(kind)) = by_index.get(&value) {
+                let (x, y, z) = unflatten(i, width, length);
+                out.blocks.push((x, y, z, *kind));
+            }
+            i += 1;
+        }
+        Ok(out.count_unknown())
+    }
+
+    /// Importa una ventana de chunks de un archivo de región Anvil.
+    pub fn from_region(bytes: &[u8], window: &RegionWindow) -> io::Result<Self> {
+        if bytes.len() < 8192 {
+            return Err(invalid("archivo de región demasiado corto".into()));
+        }
+        let (cx0, cz0) = window.chunk_min;
+        let (cx1, cz1) = window.chunk_max;
+        let (cx0, cx1) = (cx0.clamp(0, 31), cx1.clamp(0, 31));
+        let (cz0, cz1) = (cz0.clamp(0, 31), cz1.clamp(0, 31));
+        let mut out = Schematic {
+            width: (cx1 - cx0 + 1).max(0) * 16,
+            height: (window.y_max - window.y_min + 1).max(0),
+            length: (cz1 - cz0 + 1).max(0) * 16,
+            ..Default::default()
+        };
+        let mut names = NameCache::default();
+
+        for cz in cz0..=cz1 {
+            for cx in cx0..=cx1 {
+                let Some(chunk) = read_chunk(bytes, cx, cz)? else {
+                    continue; // Chunk aún no generado
+                };
+                let ox = (cx - cx0) * 16;
+                let oz = (cz - cz0) * 16;
+                // 1.18+ guarda las secciones en la raíz; antes, dentro de "Level"
+                let level = chunk.get("Level").unwrap_or(&chunk);
+                let sections = level
+                    .get("sections")
+                    .or_else(|| level.get("Sections"))
+                    .and_then(Tag::as_list)
+                    .unwrap_or(&[]);
+                for section in sections {
+                    let Some(sy) = section.get("Y").and_then(Tag::as_i64) else {
+                        continue;
+                    };
+                    let base_y = sy as i32 * 16;
+                    if base_y + 15 < window.y_min || base_y > window.y_max {
+                        continue;
+                    }
+                    let mut emit = |i: usize, kind: BlockKind| {
+                        let (x, y, z) = (i as i32 & 15, (i as i32 >> 8) + base_y, (i as i32 >> 4) & 15);
+                        if y >= window.y_min && y <= window.y_max {
+                            out.blocks.push((ox + x, y - window.y_min, oz + z, kind));
+                        }
+                    };
+                    decode_section(section, &mut names, &mut out.unknown, &mut emit);
+                }
+            }
+        }
+        Ok(out.count_unknown())
+    }
+
+    /// Imprime los bloques que no tienen material propio, de más a menos frecuentes.
+    pub fn report_unknown(&self) {
+        if self.unknown.is_empty() {
+            return;
+        }
+        let mut list: Vec<&UnknownBlock> = self.unknown.iter().collect();
+        list.sort_by(|a, b| b.count.cmp(&a.count).then(a.name.cmp(&b.name)));
+        println!("  Bloques sin material (se usan cubos de color):");
+        for u in list {
+            println!("    {} x{}", u.name, u.count);
+        }
+    }
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Índice lineal YZX (orden de Sponge y MCEdit) a coordenadas locales.
+fn unflatten(i: usize, width: i32, length: i32) -> (i32, i32, i32) {
+    let i = i as i32;
+    let x = i % width.max(1);
+    let z = (i / width.max(1)) % length.max(1);
+    let y = i / (width.max(1) * length.max(1));
+    (x, y, z)
+}
+
+fn read_varint(bytes: &[u8]) -> io::Result<(i64, usize)> {
+    let mut value = 0i64;
+    for (i, &b) in bytes.iter().enumerate().take(5) {
+        value |= ((b & 0x7F) as i64) << (7 * i);
+        if b & 0x80 == 0 {
+            return Ok((value, i + 1));
+        }
+    }
+    Err(invalid("varint inválido en los datos del esquema".into()))
+}
+
+/// Lee el NBT del chunk `(cx, cz)` de la región, o `None` si no existe.
+fn read_chunk(bytes: &[u8], cx: i32, cz: i32) -> io::Result<Option<Tag>> {
+    let entry = ((cx & 31) + (cz & 31) * 32) as usize * 4;
+    let loc = &bytes[entry..entry + 4];
+    let offset = (((loc[0] as usize) << 16) | ((loc[1] as usize) << 8) | loc[2] as usize) * 4096;
+    if offset == 0 || loc[3] == 0 {
+        return Ok(None);
+    }
+    let header = bytes
+        .get(offset..offset + 5)
+        .ok_or_else(|| invalid(format!("chunk ({}, {}) fuera del archivo", cx, cz)))?;
+    let len = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+    let data = bytes
+        .get(offset + 5..offset + 4 + len)
+        .ok_or_else(|| invalid(format!("chunk ({}, {}) truncado", cx, cz)))?;
+    match header[4] {
+        // 1 = gzip, 2 = zlib, 3 = sin comprimir; read_compressed detecta cada caso
+        1..=3 => nbt::read_compressed(data).map(Some),
+        other => Err(invalid(format!("compresión de chunk no soportada ({})", other))),
+    }
+}
+
+/// Decodifica los 4096 bloques de una sección de chunk (formato moderno o legacy).
+fn decode_section(
+    section: &Tag,
+    names: &mut NameCache,
+    unknown: &mut Vec<UnknownBlock>,
+    emit: &mut dyn FnMut(usize, BlockKind),
+) {
+    // Pre-1.13: IDs numéricos en "Blocks"
+    if let Some(ids) = section.get("Blocks").and_then(Tag::as_bytes) {
+        for (i, &id) in ids.iter().enumerate().take(4096) {
+            if let Some(kind) = names.legacy(id as u16, unknown) {
+                emit(i, kind);
+            }
+        }
+        return;
+    }
+
+    // 1.18+: "block_states" { palette, data }; 1.13-1.17: "Palette" + "BlockStates"
+    let (palette, data) = match section.get("block_states") {
+        Some(states) => (states.get("palette"), states.get("data")),
+        None => (section.get("Palette"), section.get("BlockStates")),
+    };
+    let Some(palette) = palette.and_then(Tag::as_list) else {
+        return;
+    };
+    let kinds: Vec<Option<BlockKind>> = palette
+        .iter()
+        .map(|entry| {
+            let name = entry.get("Name").and_then(Tag::as_str).unwrap_or("minecraft:air");
+            names.modern(name, unknown)
+        })
+        .collect();
+
+    let Some(longs) = data.and_then(Tag::as_longs) else {
+        // Sección uniforme: sin datos, todo es la primera entrada de la paleta
+        if let Some(Some(kind)) = kinds.first() {
+            for i in 0..4096 {
+                emit(i, *kind);
+            }
+        }
+        return;
+    };
+
+    let bits = (usize::BITS - (kinds.len().max(2) - 1).leading_zeros()).max(4) as usize;
+    // Desde 1.16 los índices no cruzan de un long al siguiente
+    let spanning = longs.len() * 64 == 4096 * bits;
+    let per_long = 64 / bits;
+    let mask = (1u64 << bits) - 1;
+    for i in 0..4096 {
+        let index = if spanning {
+            let bit = i * bits;
+            let (word, shift) = (bit / 64, bit % 64);
+            let mut v = (*longs.get(word).unwrap_or(&0) as u64) >> shift;
+            if shift + bits > 64 {
+                v |= (*longs.get(word + 1).unwrap_or(&0) as u64) << (64 - shift);
+            }
+            v & mask
+        } else {
+            let word = *longs.get(i / per_long).unwrap_or(&0) as u64;
+            (word >> ((i % per_long) * bits)) & mask
+        };
+        if let Some(Some(kind)) = kinds.get(index as usize) {
+            emit(i, *kind);
+        }
+    }
+}
+
+/// Memoriza la traducción nombre -> bloque y cuenta los desconocidos.
+#[derive(Default)]
+struct NameCache {
+    modern: HashMap<String, Option<BlockKind>>,
+}
+
+impl NameCache {
+    fn modern(&mut self, full_name: &str, unknown: &mut Vec<UnknownBlock>) -> Option<BlockKind> {
+        // "minecraft:oak_log[axis=y]" -> "oak_log"
+        let name = full_name.split('[').next().unwrap_or(full_name);
+        let name = name.rsplit(':').next().unwrap_or(name);
+        if let Some(kind) = self.modern.get(name) {
+            return *kind;
+        }
+        let kind = match map_name(name) {
+            Mapped::Air => None,
+            Mapped::Known(kind) => Some(kind),
+            Mapped::Unknown => {
+                unknown.push(UnknownBlock { name: name.to_string(), color: guess_color(name), count: 0 });
+                Some(BlockKind::Unknown((unknown.len() - 1) as u16))
+            }
+        };
+        self.modern.insert(name.to_string(), kind);
+        kind
+    }
+
+    fn legacy(&mut self, id: u16, unknown: &mut Vec<UnknownBlock>) -> Option<BlockKind> {
+        let name = legacy_name(id).map(str::to_string).unwrap_or_else(|| format!("legacy_id_{}", id));
+        self.modern(&name, unknown)
+    }
+}
+
+enum Mapped {
+    Air,
+    Known(BlockKind),
+    Unknown,
+}
+
+/// Traduce un identificador moderno (sin espacio de nombres) a la paleta del diorama.
+fn map_name(name: &str) -> Mapped {
+    use BlockKind::*;
+    let kind = match name {
+        "air" | "cave_air" | "void_air" | "structure_void" | "light" | "barrier" => return Mapped::Air,
+        "grass_block" => Grass,
+        "dirt" | "coarse_dirt" | "rooted_dirt" | "podzol" | "mycelium" | "farmland" | "dirt_path"
+        | "grass_path" | "mud" => Dirt,
+        "water" | "bubble_column" => Water,
+        "lava" => Lava,
+        "obsidian" | "crying_obsidian" => Obsidian,
+        "nether_portal" => Portal,
+        "glowstone" | "sea_lantern" | "shroomlight" => Glowstone,
+        "ice" | "packed_ice" | "blue_ice" | "frosted_ice" => Ice,
+        "glass" | "tinted_glass" => Glass,
+        "torch" | "wall_torch" | "soul_torch" | "soul_wall_torch" => Torch,
+        "iron_ore" | "deepslate_iron_ore" | "iron_block" | "raw_iron_block" => Iron,
+        "diamond_ore" | "deepslate_diamond_ore" | "diamond_block" => Diamond,
+        n if n.ends_with("_leaves") => Leaves,
+        n if n.ends_with("_stained_glass") => Glass,
+        n if n.ends_with("_log") || n.ends_with("_wood") || n.ends_with("_planks") || n.ends_with("_stem")
+            || n.ends_with("_hyphae") => Wood,
+        n if n.contains("sandstone") || n.contains("redstone") || n.contains("end_stone") => return Mapped::Unknown,
+        n if n == "stone" || n == "cobblestone" || n == "mossy_cobblestone" || n == "smooth_stone"
+            || n == "andesite" || n == "diorite" || n == "granite" || n == "tuff" || n == "deepslate"
+            || n == "cobbled_deepslate" || (n.starts_with("polished_") && !n.contains("blackstone"))
+            || n.ends_with("stone_bricks") || n == "bedrock" => Stone,
+        _ => return Mapped::Unknown,
+    };
+    Mapped::Known(kind)
+}
+
+/// IDs numéricos anteriores a 1.13 (solo los relevantes para el diorama y los más comunes).
+fn legacy_name(id: u16) -> Option<&'static str> {
+    Some(match id {
+        0 => "air",
+        1 => "stone",
+        2 => "grass_block",
+        3 => "dirt",
+        4 => "cobblestone",
+        5 => "oak_planks",
+        7 => "bedrock",
+        8 | 9 => "water",
+        10 | 11 => "lava",
+        12 => "sand",
+        13 => "gravel",
+        14 => "gold_ore",
+        15 => "iron_ore",
+        16 => "coal_ore",
+        17 => "oak_log",
+        18 => "oak_leaves",
+        20 => "glass",
+        24 => "sandstone",
+        35 => "white_wool",
+        41 => "gold_block",
+        42 => "iron_block",
+        45 => "bricks",
+        47 => "bookshelf",
+        48 => "mossy_cobblestone",
+        49 => "obsidian",
+        50 => "torch",
+        54 => "chest",
+        56 => "diamond_ore",
+        57 => "diamond_block",
+        79 => "ice",
+        80 => "snow_block",
+        82 => "clay",
+        87 => "netherrack",
+        89 => "glowstone",
+        90 => "nether_portal",
+        98 => "stone_bricks",
+        112 => "nether_bricks",
+        155 => "quartz_block",
+        159 => "white_terracotta",
+        162 => "acacia_log",
+        161 => "acacia_leaves",
+        172 => "terracotta",
+        174 => "packed_ice",
+        _ => return None,
+    })
+}
+
+/// Color aproximado para bloques sin material: por palabra clave o, si no, por hash del nombre.
+fn guess_color(name: &str) -> Vec3 {
+    const DYES: [(&str, (f32, f32, f32)); 16] = [
+        ("light_blue", (0.23, 0.67, 0.85)),
+        ("light_gray", (0.62, 0.62, 0.59)),
+        ("white", (0.92, 0.93, 0.93)),
+        ("orange", (0.94, 0.46, 0.08)),
+        ("magenta", (0.74, 0.27, 0.71)),
+        ("yellow", (0.97, 0.78, 0.16)),
+        ("lime", (0.44, 0.73, 0.10)),
+        ("pink", (0.93, 0.55, 0.67)),
+        ("gray", (0.24, 0.26, 0.28)),
+        ("cyan", (0.08, 0.54, 0.57)),
+        ("purple", (0.47, 0.16, 0.67)),
+        ("blue", (0.21, 0.22, 0.62)),
+        ("brown", (0.45, 0.28, 0.16)),
+        ("green", (0.33, 0.43, 0.11)),
+        ("red", (0.63, 0.15, 0.13)),
+        ("black", (0.08, 0.08, 0.10)),
+    ];
+    const MATERIALS: [(&str, (f32, f32, f32)); 12] = [
+        ("sand", (0.86, 0.81, 0.62)),
+        ("gravel", (0.52, 0.50, 0.49)),
+        ("clay", (0.63, 0.65, 0.71)),
+        ("brick", (0.59, 0.38, 0.32)),
+        ("snow", (0.95, 0.98, 0.98)),
+        ("netherrack", (0.44, 0.21, 0.21)),
+        ("quartz", (0.92, 0.90, 0.86)),
+        ("gold", (0.98, 0.85, 0.30)),
+        ("coal", (0.35, 0.35, 0.35)),
+        ("glass", (0.75, 0.88, 0.92)),
+        ("terracotta", (0.60, 0.37, 0.26)),
+        ("blackstone", (0.17, 0.15, 0.18)),
+    ];
+    for (key, (r, g, b)) in DYES.iter().chain(MATERIALS.iter()) {
+        if name.contains(key) {
+            return Vec3::new(*r, *g, *b);
+        }
+    }
+    // FNV-1a: el mismo nombre siempre recibe el mismo color
+    let h = name.bytes().fold(0x811c_9dc5u32, |h, b| (h ^ b as u32).wrapping_mul(0x0100_0193));
+    let channel = |shift: u32| 0.3 + ((h >> shift) & 0xFF) as f32 / 255.0 * 0.6;
+    Vec3::new(channel(0), channel(8), channel(16))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nbt::fixtures::{byte_array, gzip_root, named, short};
+
+    fn sponge(data: &[u8]) -> Vec<u8> {
+        let mut palette = named(3, "minecraft:stone", &0i32.to_be_bytes());
+        palette.extend(named(3, "minecraft:air", &1i32.to_be_bytes()));
+        palette.extend(named(3, "minecraft:purple_wool", &2i32.to_be_bytes()));
+        palette.push(0);
+        gzip_root(&[
+            short("Width", 3),
+            short("Height", 1),
+            short("Length", 1),
+            named(10, "Palette", &palette),
+            byte_array("BlockData", data),
+        ])
+    }
+
+    #[test]
+    fn decodes_sponge_schematic() {
+        let root = nbt::read_compressed(&sponge(&[0, 1, 2])).unwrap();
+        let s = Schematic::from_schematic(&root).unwrap();
+        assert_eq!((s.width, s.height, s.length), (3, 1, 1));
+        assert_eq!(s.blocks, [(0, 0, 0, BlockKind::Stone), (2, 0, 0, BlockKind::Unknown(0))]);
+        assert_eq!(s.unknown[0].name, "purple_wool");
+        assert_eq!(s.unknown[0].count, 1);
+    }
+
+    #[test]
+    fn decodes_legacy_schematic() {
+        let bytes = gzip_root(&[short("Width", 2), short("Height", 2), short("Length", 1), byte_array("Blocks", &[1, 0, 0, 3])]);
+        let s = Schematic::from_schematic(&nbt::read_compressed(&bytes).unwrap()).unwrap();
+        assert_eq!(s.blocks, [(0, 0, 0, BlockKind::Stone), (1, 1, 0, BlockKind::Dirt)]);
+    }
+
+    #[test]
+    fn broken_schematics_are_errors() {
+        let bytes = sponge(&[0, 1, 2]);
+        assert!(nbt::read_compressed(&bytes[..bytes.len() - 8]).is_err());
+
+        let no_size = gzip_root(&[short("Height", 1), byte_array("Blocks", &[1])]);
+        assert!(Schematic::from_schematic(&nbt::read_compressed(&no_size).unwrap()).is_err());
+
+        // Un varint que nunca termina
+        let root = nbt::read_compressed(&sponge(&[0xFF; 6])).unwrap();
+        assert!(Schematic::from_schematic(&root).is_err());
+    }
+
+    #[test]
+    fn broken_regions_are_errors() {
+        let window = RegionWindow::default();
+        assert!(Schematic::from_region(&[0; 100], &window).is_err());
+
+        // Chunk (0, 0) apunta al sector 2, que no existe
+        let mut region = vec![0u8; 8192];
+        region[..4].copy_from_slice(&[0, 0, 2, 1]);
+        assert!(Schematic::from_region(&region, &window).is_err());
+
+        // Región vacía: ningún chunk generado
+        let empty = Schematic::from_region(&[0; 8192], &window).unwrap();
+        assert!(empty.blocks.is_empty());
+    }
+}
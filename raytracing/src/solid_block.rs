@@ -1,5 +1,6 @@
 //! Cubo alineado a los ejes usado para construir bloques estilo Minecraft.
 
+use crate::bvh::Aabb;
 use crate::math::Vec3;
 use crate::ray::Ray;
 use crate::materials::{Intersectable, MaterialParams};
@@ -17,7 +18,9 @@ pub struct SolidBlock {
     pub reflectivity: f32,
     pub transparency: f32,
     pub ior: f32,
+    pub dispersion: f32, // Coeficiente B de Cauchy (µm²), solo con render espectral
     pub emissive: Vec3,
+    pub roughness: f32,
 }
 
 impl SolidBlock {
@@ -33,11 +36,20 @@ impl SolidBlock {
             reflectivity: 0.0,
             transparency: 0.0,
             ior: 1.0,
+            dispersion: 0.0,
             emissive: Vec3::new(0.0, 0.0, 0.0),
+            roughness: 0.0,
         }
     }
 
     fn intersect_impl(&self, ray: &Ray) -> Option<f32> {
+        self.intersect_range(ray)
+            .map(|(tmin, tmax)| if tmin >= 0.0 { tmin } else { tmax })
+    }
+
+    /// Distancias de entrada y salida del rayo por la caja (método de slabs).
+    /// `None` si no la cruza o si queda completamente detrás del origen.
+    pub fn intersect_range(&self, ray: &Ray) -> Option<(f32, f32)> {
         let inv = |d: f32| if d != 0.0 { 1.0 / d } else { f32::INFINITY };
         let (ix, iy, iz) = (inv(ray.dir.x), inv(ray.dir.y), inv(ray.dir.z));
 
@@ -85,7 +97,7 @@ impl SolidBlock {
         if tmax < 0.0 {
             return None;
         }
-        Some(if tmin >= 0.0 { tmin } else { tmax })
+        Some((tmin, tmax))
     }
 
     fn normal_impl(&self, p: Vec3) -> Vec3 {
@@ -122,6 +134,10 @@ impl Intersectable for SolidBlock {
     fn albedo(&self) -> Vec3 {
         self.albedo_color
     }
+    fn bounds(&self) -> Aabb {
+        Aabb::new(self.min, self.max)
+    }
+
     fn center(&self) -> Vec3 {
         Vec3::new(
             (self.min.x + self.max.x) * 0.5,
@@ -137,8 +153,11 @@ impl Intersectable for SolidBlock {
             reflectivity: self.reflectivity,
             transparency: self.transparency,
             ior: self.ior,
+            dispersion: self.dispersion,
             emissive: self.emissive,
             opacity: 1.0, 
+            roughness: self.roughness,
+            normal: None,
         }
     }
 }
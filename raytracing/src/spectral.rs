@@ -0,0 +1,235 @@
+//! Render espectral opcional: IOR dependiente de la longitud de onda (Cauchy) y
+//! conversión de longitudes de onda a RGB.
+//!
+//! El trazador sigue siendo RGB. Al refractar en un material con dispersión, el rayo
+//! se divide en varias longitudes de onda; cada rama conserva la suya en los rebotes
+//! siguientes y su color se pondera con la respuesta RGB de esa longitud de onda.
+//!
+//! Las cáusticas (la luz del sol concentrada y separada en colores al atravesar hielo,
+//! agua o diamante) salen de un mapa de fotones: se lanzan fotones de una longitud de
+//! onda cada uno hacia los bloques dispersivos y se guardan donde caen en una
+//! superficie opaca. El trazador suma la densidad local de fotones como luz directa.
+
+use std::collections::HashMap;
+
+use crate::instance::Group;
+use crate::lighting::{reflect, refract};
+use crate::math::{Transform, Vec3, hash3, next_unit, orthonormal_basis};
+use crate::ray::Ray;
+
+/// Línea D del sodio (µm): `MaterialParams::ior` es el índice a esta longitud de onda.
+pub const LAMBDA_D: f32 = 0.5893;
+/// Rango visible muestreado (µm).
+pub const LAMBDA_MIN: f32 = 0.38;
+pub const LAMBDA_MAX: f32 = 0.72;
+
+#[derive(Copy, Clone, Debug)]
+pub struct SpectralSettings {
+    pub enabled: bool,
+    pub samples: u32,         // Longitudes de onda por refracción dispersiva
+    pub caustic_photons: u32, // Fotones por bloque dispersivo (0 = sin cáusticas)
+}
+
+impl Default for SpectralSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            samples: 6,
+            caustic_photons: 20000,
+        }
+    }
+}
+
+/// IOR a `lambda` (µm) según Cauchy `n(λ) = A + B / λ²`, con `A` elegido para que
+/// `n(λ_D) = ior_d`. `b` es el coeficiente de dispersión en µm².
+pub fn cauchy_ior(ior_d: f32, b: f32, lambda: f32) -> f32 {
+    ior_d + b * (1.0 / (lambda * lambda) - 1.0 / (LAMBDA_D * LAMBDA_D))
+}
+
+/// Lóbulo gaussiano asimétrico del ajuste de Wyman et al. (2013) a las curvas CIE 1931.
+fn lobe(nm: f32, mu: f32, s1: f32, s2: f32) -> f32 {
+    let t = (nm - mu) / if nm < mu { s1 } else { s2 };
+    (-0.5 * t * t).exp()
+}
+
+/// Color RGB lineal (sRGB, sin negativos) de luz monocromática de longitud de onda `lambda` (µm).
+pub fn wavelength_rgb(lambda: f32) -> Vec3 {
+    let nm = lambda * 1000.0;
+    let x = 1.056 * lobe(nm, 599.8, 37.9, 31.0) + 0.362 * lobe(nm, 442.0, 16.0, 26.7)
+        - 0.065 * lobe(nm, 501.1, 20.4, 26.2);
+    let y = 0.821 * lobe(nm, 568.8, 46.9, 40.5) + 0.286 * lobe(nm, 530.9, 16.3, 31.1);
+    let z = 1.217 * lobe(nm, 437.0, 11.8, 36.0) + 0.681 * lobe(nm, 459.0, 26.0, 13.8);
+    Vec3::new(
+        3.2406 * x - 1.5372 * y - 0.4986 * z,
+        -0.9689 * x + 1.8758 * y + 0.0415 * z,
+        0.0557 * x - 0.2040 * y + 1.0570 * z,
+    )
+    .clamp(0.0, f32::INFINITY)
+}
+
+/// `n` longitudes de onda estratificadas en el rango visible, desplazadas con `seed`.
+pub fn sample_wavelengths(n: u32, seed: &mut u32) -> impl Iterator<Item = f32> + '_ {
+    let n = n.max(1);
+    (0..n).map(move |i| {
+        let u = (i as f32 + next_unit(seed)) / n as f32;
+        LAMBDA_MIN + (LAMBDA_MAX - LAMBDA_MIN) * u
+    })
+}
+
+/// Respuesta RGB media del rango visible: dividir por ella hace que un espectro plano sea blanco.
+pub fn white_point() -> Vec3 {
+    const STEPS: u32 = 64;
+    let mut sum = Vec3::zero();
+    for i in 0..STEPS {
+        let l = LAMBDA_MIN + (LAMBDA_MAX - LAMBDA_MIN) * (i as f32 + 0.5) / STEPS as f32;
+        sum = sum.add(wavelength_rgb(l));
+    }
+    sum.mul(1.0 / STEPS as f32)
+}
+
+/// Tope de fotones por mapa: en mundos con mucha agua se reparten entre todos los bloques.
+const MAX_PHOTONS: u32 = 200_000;
+/// Interfaces que atraviesa un fotón antes de abandonarlo.
+const MAX_BOUNCES: u32 = 8;
+/// Radio (en bloques) en el que se promedian los fotones alrededor de un punto.
+const GATHER_RADIUS: f32 = 0.12;
+
+#[derive(Copy, Clone)]
+struct Photon {
+    pos: Vec3,
+    normal: Vec3, // Cara sobre la que cayó, para no iluminar caras vecinas en ángulo
+    power: Vec3,
+}
+
+/// Fotones depositados tras atravesar materiales dispersivos, en una rejilla hash.
+pub struct CausticMap {
+    cells: HashMap<(i32, i32, i32), Vec<Photon>>,
+    photons: usize,
+}
+
+fn cell_of(p: Vec3) -> (i32, i32, i32) {
+    let s = 1.0 / GATHER_RADIUS;
+    ((p.x * s).floor() as i32, (p.y * s).floor() as i32, (p.z * s).floor() as i32)
+}
+
+impl CausticMap {
+    /// Lanza fotones desde `light_pos` hacia cada bloque transparente con dispersión.
+    /// Es determinista: la misma escena y sol producen siempre el mismo mapa.
+    pub fn build(scene: &Group, light_pos: Vec3, sun_brightness: f32, photons_per_block: u32) -> Self {
+        let mut map = Self { cells: HashMap::new(), photons: 0 };
+        // Con poco sol el trazador tampoco calcula luz directa
+        if sun_brightness <= 0.15 || photons_per_block == 0 {
+            return map;
+        }
+
+        // Esferas que envuelven a cada bloque dispersivo, en coordenadas de mundo
+        let mut targets: Vec<(Vec3, f32)> = Vec::new();
+        scene.for_each_object(&Transform::IDENTITY, &mut |object, to_local| {
+            let local = object.bounds();
+            if local.min.x > local.max.x || !local.max.x.is_finite() {
+                return;
+            }
+            let c = local.centroid();
+            let mat = object.material_at(Vec3::new(c.x, local.max.y, c.z));
+            if mat.transparency <= 0.0 || mat.dispersion <= 0.0 {
+                return;
+            }
+            let Some(to_world) = to_local.inverse() else { return };
+            let center = to_world.point(c);
+            let radius = local.corners().iter().map(|&p| to_world.point(p).sub(center).len()).fold(0.0, f32::max);
+            targets.push((center, radius));
+        });
+        if targets.is_empty() {
+            return map;
+        }
+
+        let per_block = photons_per_block.min(MAX_PHOTONS / targets.len() as u32).max(1);
+        let white = white_point();
+        for (k, &(center, radius)) in targets.iter().enumerate() {
+            let axis = center.sub(light_pos).norm();
+            let (t, b) = orthonormal_basis(axis);
+            // Flujo que cruza el disco de la esfera, repartido entre los fotones
+            let flux = sun_brightness * std::f32::consts::PI * radius * radius / per_block as f32;
+            for i in 0..per_block {
+                let mut seed = hash3(k as u32, i, 0x5eed);
+                // Punto uniforme en el disco y longitud de onda estratificada
+                let r = radius * next_unit(&mut seed).sqrt();
+                let phi = 2.0 * std::f32::consts::PI * next_unit(&mut seed);
+                let aim = center.add(t.mul(r * phi.cos())).add(b.mul(r * phi.sin()));
+                let u = (i as f32 + next_unit(&mut seed)) / per_block as f32;
+                let lambda = LAMBDA_MIN + (LAMBDA_MAX - LAMBDA_MIN) * u;
+                let color = wavelength_rgb(lambda);
+                let power = Vec3::new(color.x / white.x, color.y / white.y, color.z / white.z).mul(flux);
+                let ray = Ray::new(light_pos, aim.sub(light_pos).norm());
+                map.trace_photon(scene, ray, lambda, power);
+            }
+        }
+        map
+    }
+
+    /// Sigue un fotón a través de las interfaces transparentes y lo guarda en la
+    /// primera superficie opaca, solo si antes atravesó alguna.
+    fn trace_photon(&mut self, scene: &Group, mut ray: Ray, lambda: f32, mut power: Vec3) {
+        let bias = 1e-3;
+        let mut refracted = false;
+        let mut tests = 0;
+        for _ in 0..MAX_BOUNCES {
+            let Some(hit) = scene.closest(&ray, f32::INFINITY, &mut tests) else { return };
+            let point = ray.at(hit.t);
+            let mat = hit.material();
+            let mut n = hit.normal();
+            if n.dot(ray.dir) > 0.0 {
+                n = -n;
+            }
+            if mat.transparency <= 0.0 {
+                if refracted {
+                    self.cells.entry(cell_of(point)).or_default().push(Photon { pos: point, normal: n, power });
+                    self.photons += 1;
+                }
+                return;
+            }
+            let ior = cauchy_ior(mat.ior, mat.dispersion, lambda);
+            // Entrando: aire → material; saliendo: material → aire
+            let eta = if hit.normal().dot(ray.dir) < 0.0 { 1.0 / ior } else { ior };
+            let dir = match refract(ray.dir, n, eta) {
+                Some(d) => {
+                    power = power.mul(mat.transparency);
+                    refracted = true;
+                    d
+                }
+                None => reflect(ray.dir, n), // Reflexión total interna
+            };
+            ray = Ray::new(point.add(dir.mul(bias)), dir);
+        }
+    }
+
+    /// Irradiancia cáustica en `p` sobre una cara con normal `n`.
+    pub fn irradiance(&self, p: Vec3, n: Vec3) -> Vec3 {
+        let (cx, cy, cz) = cell_of(p);
+        let r2 = GATHER_RADIUS * GATHER_RADIUS;
+        let mut sum = Vec3::zero();
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    let Some(cell) = self.cells.get(&(cx + dx, cy + dy, cz + dz)) else { continue };
+                    for ph in cell {
+                        let d = ph.pos.sub(p);
+                        if d.dot(d) < r2 && ph.normal.dot(n) > 0.7 {
+                            sum = sum.add(ph.power);
+                        }
+                    }
+                }
+            }
+        }
+        sum.mul(1.0 / (std::f32::consts::PI * r2))
+    }
+
+    /// Fotones guardados (para la consola).
+    pub fn len(&self) -> usize {
+        self.photons
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.photons == 0
+    }
+}
@@ -0,0 +1,333 @@
+//! Generación procedural de terreno por vóxeles a partir de una semilla.
+//!
+//! El resultado es una rejilla de bloques (`VoxelGrid`) que luego
+//! `raytracer::build_terrain_scene` convierte en objetos intersectables.
+//! Todo el proceso es determinista: la misma `TerrainConfig` produce
+//! exactamente el mismo mundo, lo que permite comparar renders entre versiones.
+
+/// Tipos de bloque que puede contener el terreno generado.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Block {
+    Air,
+    Grass,
+    Dirt,
+    Stone,
+    Water,
+    Wood,
+    Leaves,
+    IronOre,
+    DiamondOre,
+}
+
+impl Block {
+    /// Bloques que no tapan a sus vecinos (el rayo puede atravesarlos).
+    pub fn is_see_through(self) -> bool {
+        matches!(self, Block::Air | Block::Water | Block::Leaves)
+    }
+}
+
+/// Parámetros del generador. `Default` produce un mundo mediano tipo diorama.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TerrainConfig {
+    pub seed: u64,
+    pub size_x: i32,         // Ancho en bloques (eje X)
+    pub size_z: i32,         // Profundidad en bloques (eje Z)
+    pub min_y: i32,          // Capa más baja del mundo
+    pub sea_level: i32,      // Columnas por debajo se llenan de agua
+    pub base_height: i32,    // Altura media de la superficie
+    pub height_amplitude: f32, // Variación máxima de altura sobre/bajo la media
+    pub noise_scale: f32,    // Tamaño de las colinas (bloques por periodo)
+    pub dirt_depth: i32,     // Bloques de tierra bajo el césped
+    pub cave_threshold: f32, // [0..1] mayor = menos cuevas
+    pub tree_density: f32,   // Probabilidad de árbol por columna de césped
+    pub ore_density: f32,    // Probabilidad de mineral por bloque de piedra
+}
+
+impl Default for TerrainConfig {
+    fn default() -> Self {
+        Self {
+            seed: 7,
+            size_x: 16,
+            size_z: 16,
+            min_y: -3,
+            sea_level: 1,
+            base_height: 2,
+            height_amplitude: 4.0,
+            noise_scale: 10.0,
+            dirt_depth: 2,
+            cave_threshold: 0.72,
+            tree_density: 0.025,
+            ore_density: 0.04,
+        }
+    }
+}
+
+impl TerrainConfig {
+    /// Mundo cuadrado de `size` bloques por lado con la semilla indicada.
+    pub fn with_seed(seed: u64, size: i32) -> Self {
+        Self {
+            seed,
+            size_x: size.max(4),
+            size_z: size.max(4),
+            ..Self::default()
+        }
+    }
+
+    fn max_y(&self) -> i32 {
+        // Espacio suficiente para la colina más alta más un árbol completo
+        self.base_height + self.height_amplitude.ceil() as i32 + 6
+    }
+}
+
+/// Rejilla densa de bloques centrada en el origen en X/Z.
+pub struct VoxelGrid {
+    pub min: (i32, i32, i32),
+    pub size: (i32, i32, i32),
+    blocks: Vec<Block>,
+}
+
+impl VoxelGrid {
+    fn new(min: (i32, i32, i32), size: (i32, i32, i32)) -> Self {
+        let len = (size.0 * size.1 * size.2).max(0) as usize;
+        Self {
+            min,
+            size,
+            blocks: vec![Block::Air; len],
+        }
+    }
+
+    fn index(&self, x: i32, y: i32, z: i32) -> Option<usize> {
+        let (lx, ly, lz) = (x - self.min.0, y - self.min.1, z - self.min.2);
+        if lx < 0 || ly < 0 || lz < 0 || lx >= self.size.0 || ly >= self.size.1 || lz >= self.size.2 {
+            return None;
+        }
+        Some(((ly * self.size.2 + lz) * self.size.0 + lx) as usize)
+    }
+
+    /// Bloque en coordenadas de mundo; fuera de la rejilla se considera aire.
+    pub fn get(&self, x: i32, y: i32, z: i32) -> Block {
+        self.index(x, y, z).map_or(Block::Air, |i| self.blocks[i])
+    }
+
+    pub fn set(&mut self, x: i32, y: i32, z: i32, block: Block) {
+        if let Some(i) = self.index(x, y, z) {
+            self.blocks[i] = block;
+        }
+    }
+
+    /// Un bloque es visible si alguno de sus seis vecinos deja pasar la luz.
+    pub fn is_exposed(&self, x: i32, y: i32, z: i32) -> bool {
+        const NEIGHBORS: [(i32, i32, i32); 6] = [
+            (1, 0, 0), (-1, 0, 0), (0, 1, 0), (0, -1, 0), (0, 0, 1), (0, 0, -1),
+        ];
+        NEIGHBORS
+            .iter()
+            .any(|&(dx, dy, dz)| self.get(x + dx, y + dy, z + dz).is_see_through())
+    }
+
+    /// Recorre todos los bloques no vacíos en orden determinista (y, z, x).
+    pub fn iter_solid(&self) -> impl Iterator<Item = (i32, i32, i32, Block)> + '_ {
+        let (sx, sy, sz) = self.size;
+        let min = self.min;
+        (0..sy).flat_map(move |ly| {
+            (0..sz).flat_map(move |lz| {
+                (0..sx).filter_map(move |lx| {
+                    let (x, y, z) = (lx + min.0, ly + min.1, lz + min.2);
+                    match self.get(x, y, z) {
+                        Block::Air => None,
+                        b => Some((x, y, z, b)),
+                    }
+                })
+            })
+        })
+    }
+}
+
+/// Genera el mundo completo: relieve, capas, agua, cuevas, minerales y árboles.
+pub fn generate(config: &TerrainConfig) -> VoxelGrid {
+    let min_x = -config.size_x / 2;
+    let min_z = -config.size_z / 2;
+    let height = config.max_y() - config.min_y + 1;
+    let mut grid = VoxelGrid::new(
+        (min_x, config.min_y, min_z),
+        (config.size_x, height, config.size_z),
+    );
+
+    let seed = config.seed;
+    let mut rng = SplitMix64::new(seed ^ 0x5eed_7e22_a1a1_u64);
+    let mut surfaces = Vec::with_capacity((config.size_x * config.size_z) as usize);
+
+    // 1) Mapa de alturas y capas de césped / tierra / piedra
+    for z in min_z..min_z + config.size_z {
+        for x in min_x..min_x + config.size_x {
+            let n = fbm2(seed, x as f32 / config.noise_scale, z as f32 / config.noise_scale, 4);
+            // El fbm se concentra alrededor de 0.5: se expande para aprovechar la amplitud
+            let relief = ((n - 0.5) * 3.0).clamp(-1.0, 1.0);
+            let surface = (config.base_height as f32 + relief * config.height_amplitude).round() as i32;
+            let surface = surface.clamp(config.min_y + 1, config.max_y() - 6);
+            surfaces.push((x, z, surface));
+
+            for y in config.min_y..=surface {
+                let block = if y == surface {
+                    if surface >= config.sea_level { Block::Grass } else { Block::Dirt }
+                } else if y >= surface - config.dirt_depth {
+                    Block::Dirt
+                } else {
+                    Block::Stone
+                };
+                grid.set(x, y, z, block);
+            }
+
+            // 2) Agua hasta el nivel del mar
+            for y in surface + 1..=config.sea_level {
+                grid.set(x, y, z, Block::Water);
+            }
+        }
+    }
+
+    // 3) Cuevas: ruido 3D por debajo de la superficie (sin perforar la capa base)
+    for &(x, z, surface) in &surfaces {
+        for y in config.min_y + 1..surface - 1 {
+            let c = value_noise3(seed.wrapping_add(101), x as f32 / 6.0, y as f32 / 4.0, z as f32 / 6.0);
+            if c > config.cave_threshold {
+                grid.set(x, y, z, Block::Air);
+            }
+        }
+    }
+
+    // 4) Minerales: diamante solo en las capas profundas
+    for &(x, z, surface) in &surfaces {
+        for y in config.min_y..surface {
+            if grid.get(x, y, z) != Block::Stone {
+                continue;
+            }
+            let roll = rng.next_f32();
+            if roll < config.ore_density * 0.25 && y <= config.min_y + 2 {
+                grid.set(x, y, z, Block::DiamondOre);
+            } else if roll < config.ore_density {
+                grid.set(x, y, z, Block::IronOre);
+            }
+        }
+    }
+
+    // 5) Árboles sobre césped, separados al menos 3 bloques entre sí
+    let mut trees: Vec<(i32, i32)> = Vec::new();
+    for &(x, z, surface) in &surfaces {
+        let roll = rng.next_f32();
+        if grid.get(x, surface, z) != Block::Grass || roll >= config.tree_density {
+            continue;
+        }
+        // Evitar copas cortadas por el borde del mundo
+        if x <= min_x || z <= min_z || x >= min_x + config.size_x - 1 || z >= min_z + config.size_z - 1 {
+            continue;
+        }
+        if trees.iter().any(|&(tx, tz)| (tx - x).abs() < 3 && (tz - z).abs() < 3) {
+            continue;
+        }
+        trees.push((x, z));
+        place_tree(&mut grid, x, surface + 1, z);
+    }
+
+    grid
+}
+
+/// Misma forma que el árbol del diorama: tronco de 3 y copa de 3 capas.
+fn place_tree(grid: &mut VoxelGrid, x: i32, base_y: i32, z: i32) {
+    let leaf = |grid: &mut VoxelGrid, lx: i32, ly: i32, lz: i32| {
+        if grid.get(lx, ly, lz) == Block::Air {
+            grid.set(lx, ly, lz, Block::Leaves);
+        }
+    };
+    for dy in 0..3 {
+        grid.set(x, base_y + dy, z, Block::Wood);
+    }
+    for dx in -1..=1 {
+        for dz in -1..=1 {
+            if dx != 0 || dz != 0 {
+                leaf(grid, x + dx, base_y + 2, z + dz);
+            }
+            leaf(grid, x + dx, base_y + 3, z + dz);
+        }
+    }
+    leaf(grid, x, base_y + 4, z);
+    leaf(grid, x - 1, base_y + 4, z);
+    leaf(grid, x + 1, base_y + 4, z);
+    leaf(grid, x, base_y + 4, z - 1);
+    leaf(grid, x, base_y + 4, z + 1);
+}
+
+/// Generador pseudoaleatorio pequeño y reproducible (SplitMix64).
+pub struct SplitMix64(u64);
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        mix64(self.0)
+    }
+
+    /// Número uniforme en [0, 1).
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+fn mix64(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Valor pseudoaleatorio en [0, 1) asociado a un punto entero de la rejilla.
+fn lattice(seed: u64, x: i32, y: i32, z: i32) -> f32 {
+    let h = mix64(
+        seed ^ (x as u64).wrapping_mul(0x8da6_b343)
+            ^ (y as u64).wrapping_mul(0xd816_3841)
+            ^ (z as u64).wrapping_mul(0xcb1a_b31f),
+    );
+    (h >> 40) as f32 / (1u64 << 24) as f32
+}
+
+fn smooth(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// Ruido de valor 2D interpolado suavemente, en [0, 1).
+fn value_noise2(seed: u64, x: f32, z: f32) -> f32 {
+    value_noise3(seed, x, 0.0, z)
+}
+
+/// Ruido de valor 3D interpolado suavemente, en [0, 1).
+fn value_noise3(seed: u64, x: f32, y: f32, z: f32) -> f32 {
+    let (x0, y0, z0) = (x.floor() as i32, y.floor() as i32, z.floor() as i32);
+    let (tx, ty, tz) = (smooth(x - x0 as f32), smooth(y - y0 as f32), smooth(z - z0 as f32));
+    let c = |dx: i32, dy: i32, dz: i32| lattice(seed, x0 + dx, y0 + dy, z0 + dz);
+
+    let x00 = lerp(c(0, 0, 0), c(1, 0, 0), tx);
+    let x10 = lerp(c(0, 1, 0), c(1, 1, 0), tx);
+    let x01 = lerp(c(0, 0, 1), c(1, 0, 1), tx);
+    let x11 = lerp(c(0, 1, 1), c(1, 1, 1), tx);
+    lerp(lerp(x00, x10, ty), lerp(x01, x11, ty), tz)
+}
+
+/// Suma de octavas de ruido 2D normalizada a [0, 1).
+fn fbm2(seed: u64, x: f32, z: f32, octaves: u32) -> f32 {
+    let mut sum = 0.0;
+    let mut amp = 1.0;
+    let mut freq = 1.0;
+    let mut norm = 0.0;
+    for o in 0..octaves {
+        sum += value_noise2(seed.wrapping_add(o as u64 * 7919), x * freq, z * freq) * amp;
+        norm += amp;
+        amp *= 0.5;
+        freq *= 2.0;
+    }
+    sum / norm
+}
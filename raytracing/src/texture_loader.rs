@@ -1,298 +1,538 @@
 //! Sistema de carga de texturas para el raytracer optimizado.
+//!
+//! Las texturas del diorama se describen en un `AssetManifest` (archivo, qué hacer si
+//! falta, recorte por alfa, mapas de material) y se buscan en una lista de carpetas
+//! (`AssetRoots`). Una textura obligatoria que falta o no se puede decodificar se
+//! reemplaza por un damero magenta/negro, bien visible, y queda registrada en
+//! `TextureStorage::failures` para reportarla con `AssetError`.
 
-use crate::lighting::Tex;
-use image::GenericImageView;
+use std::fmt;
+use std::path::{Path, PathBuf};
 
-/// Estructura que almacena los buffers de todas las texturas cargadas.
-pub struct TextureStorage {
-    pub grass_cover_buf: Vec<u8>,
-    pub grass_cover_wh: (u32, u32),
-    
-    pub grass_side_buf: Vec<u8>,
-    pub grass_side_wh: (u32, u32),
-    
-    pub dirt_buf: Vec<u8>,
-    pub dirt_wh: (u32, u32),
-    
-    pub stone_buf: Vec<u8>,
-    pub stone_wh: (u32, u32),
-    
-    pub wood_buf: Vec<u8>,
-    pub wood_wh: (u32, u32),
-    
-    pub leaves_buf: Vec<u8>,
-    pub leaves_wh: (u32, u32),
-    
-    pub water_buf: Vec<u8>,
-    pub water_wh: (u32, u32),
-    
-    pub lava_buf: Vec<u8>,
-    pub lava_wh: (u32, u32),
-    
-    pub obsidian_buf: Vec<u8>,
-    pub obsidian_wh: (u32, u32),
-    
-    pub glowstone_buf: Vec<u8>,
-    pub glowstone_wh: (u32, u32),
-    
-    pub diamond_buf: Vec<u8>,
-    pub diamond_wh: (u32, u32),
-    
-    pub iron_buf: Vec<u8>,
-    pub iron_wh: (u32, u32),
-    
-    pub chest_buf: Vec<u8>,
-    pub chest_wh: (u32, u32),
-    
-    pub ice_buf: Vec<u8>,
-    pub ice_wh: (u32, u32),
-    
-    pub portal_buf: Vec<u8>,
-    pub portal_wh: (u32, u32),
-    
-    pub torch_buf: Vec<u8>,
-    pub torch_wh: (u32, u32),
-    
-    pub clouds_buf: Vec<u8>,
-    pub clouds_wh: (u32, u32),
+use crate::lighting::{Skybox, Tex};
+use crate::materials::MaterialMaps;
+use crate::math::Vec3;
+use crate::raytracer::Assets;
+
+type Buffer = (Vec<u8>, (u32, u32));
+
+/// Texturas que usa el diorama.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum AssetId {
+    GrassTop,
+    GrassSide,
+    Dirt,
+    Stone,
+    Wood,
+    Leaves,
+    Water,
+    Lava,
+    Obsidian,
+    Glowstone,
+    Diamond,
+    Iron,
+    Chest,
+    Ice,
+    Portal,
+    Torch,
+    Glass,
+    Sand,
+    Gravel,
+    Clouds,
 }
 
-impl TextureStorage {
-    /// Carga todas las texturas desde el directorio assets/
-    pub fn load() -> Self {
-        println!("Cargando texturas PNG para raytracing...");
-        
-        let grass_cover = load_or_default("assets/grass_top_16x16.png", (128, 128, 70));
-        let grass_side = load_or_default("assets/grass_side_16x16.png", (128, 128, 70));
-        let dirt = load_or_default("assets/dirt_16x16.png", (134, 96, 67));
-        let stone = load_or_default("assets/stone_16x16.png", (128, 128, 128));
-        let wood = load_or_default("assets/wood_16x16.png", (139, 90, 43));
-        let leaves = load_or_default("assets/leaves_16x16.png", (80, 160, 80));
-        let water = load_or_default("assets/water.png", (50, 100, 200));
-        let lava = load_or_default("assets/lava.png", (255, 100, 0));
-        let obsidian = load_or_default("assets/obsidian_16x16.png", (20, 10, 30));
-        let glowstone = load_or_default("assets/glowstone.png", (255, 230, 180));
-        let diamond = load_or_default("assets/diamond.png", (150, 230, 255));
-        let iron = load_or_default("assets/iron.png", (180, 180, 180));
-        let chest = load_or_default("assets/chest.png", (160, 100, 50));
-        let ice = load_or_default("assets/ice_16x16.png", (180, 200, 255));
-        let portal = load_or_default("assets/portal.png", (150, 50, 255));
-        let torch = load_or_default("assets/torch_16x16.png", (255, 180, 80));
-        let clouds = load_or_default("assets/clouds.png", (200, 220, 255));
-        
-        Self {
-            grass_cover_buf: grass_cover.0,
-            grass_cover_wh: grass_cover.1,
-            
-            grass_side_buf: grass_side.0,
-            grass_side_wh: grass_side.1,
-            
-            dirt_buf: dirt.0,
-            dirt_wh: dirt.1,
-            
-            stone_buf: stone.0,
-            stone_wh: stone.1,
-            
-            wood_buf: wood.0,
-            wood_wh: wood.1,
-            
-            leaves_buf: leaves.0,
-            leaves_wh: leaves.1,
-            
-            water_buf: water.0,
-            water_wh: water.1,
-            
-            lava_buf: lava.0,
-            lava_wh: lava.1,
-            
-            obsidian_buf: obsidian.0,
-            obsidian_wh: obsidian.1,
-            
-            glowstone_buf: glowstone.0,
-            glowstone_wh: glowstone.1,
-            
-            diamond_buf: diamond.0,
-            diamond_wh: diamond.1,
-            
-            iron_buf: iron.0,
-            iron_wh: iron.1,
-            
-            chest_buf: chest.0,
-            chest_wh: chest.1,
-            
-            ice_buf: ice.0,
-            ice_wh: ice.1,
-            
-            portal_buf: portal.0,
-            portal_wh: portal.1,
-            
-            torch_buf: torch.0,
-            torch_wh: torch.1,
-            
-            clouds_buf: clouds.0,
-            clouds_wh: clouds.1,
+impl AssetId {
+    pub const ALL: [AssetId; 20] = [
+        AssetId::GrassTop,
+        AssetId::GrassSide,
+        AssetId::Dirt,
+        AssetId::Stone,
+        AssetId::Wood,
+        AssetId::Leaves,
+        AssetId::Water,
+        AssetId::Lava,
+        AssetId::Obsidian,
+        AssetId::Glowstone,
+        AssetId::Diamond,
+        AssetId::Iron,
+        AssetId::Chest,
+        AssetId::Ice,
+        AssetId::Portal,
+        AssetId::Torch,
+        AssetId::Glass,
+        AssetId::Sand,
+        AssetId::Gravel,
+        AssetId::Clouds,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AssetId::GrassTop => "césped (arriba)",
+            AssetId::GrassSide => "césped (lado)",
+            AssetId::Dirt => "tierra",
+            AssetId::Stone => "piedra",
+            AssetId::Wood => "madera",
+            AssetId::Leaves => "hojas",
+            AssetId::Water => "agua",
+            AssetId::Lava => "lava",
+            AssetId::Obsidian => "obsidiana",
+            AssetId::Glowstone => "piedra luminosa",
+            AssetId::Diamond => "diamante",
+            AssetId::Iron => "hierro",
+            AssetId::Chest => "cofre",
+            AssetId::Ice => "hielo",
+            AssetId::Portal => "portal",
+            AssetId::Torch => "antorcha",
+            AssetId::Glass => "vidrio",
+            AssetId::Sand => "arena",
+            AssetId::Gravel => "grava",
+            AssetId::Clouds => "nubes (skybox)",
         }
     }
-    
-    /// Obtiene la textura de grass_cover como Tex
-    pub fn get_grass_cover(&self) -> Tex {
-        Tex {
-            pix: &self.grass_cover_buf,
-            w: self.grass_cover_wh.0,
-            h: self.grass_cover_wh.1,
+}
+
+/// Qué usar cuando el archivo de una textura no está.
+#[derive(Copy, Clone, Debug)]
+pub enum Fallback {
+    /// Obligatoria: se reporta como error y se dibuja el damero de "falta textura".
+    Required,
+    /// Opcional: bloque de color sólido, sin error.
+    Solid(u8, u8, u8),
+}
+
+/// Cómo generar el canal alfa de un recorte cuando el PNG no lo incluye.
+#[derive(Copy, Clone, Debug)]
+pub enum CutoutMask {
+    /// Textura opaca, sin recorte.
+    Opaque,
+    /// Usar el alfa del PNG tal cual.
+    None,
+    /// Los texeles más oscuros son huecos entre las hojas.
+    Leaves,
+    /// Solo el marco y los reflejos claros son opacos.
+    Glass,
+}
+
+/// Una entrada del manifiesto.
+#[derive(Clone, Debug)]
+pub struct AssetEntry {
+    pub id: AssetId,
+    pub file: String, // Relativo a cada carpeta de `AssetRoots`
+    pub fallback: Fallback,
+    pub cutout: CutoutMask,
+    pub maps: bool, // Buscar `<base>_normal.png`, `_roughness.png`, ...
+}
+
+/// Lista tipada de las texturas a cargar.
+#[derive(Clone, Debug)]
+pub struct AssetManifest {
+    pub entries: Vec<AssetEntry>,
+}
+
+impl Default for AssetManifest {
+    /// Las texturas que trae `assets/` son obligatorias; las que el repositorio no
+    /// incluye (agua, lava, minerales...) se dibujan lisas si faltan.
+    fn default() -> Self {
+        use AssetId::*;
+        use CutoutMask::Opaque;
+        use Fallback::{Required, Solid};
+        let entry = |id, file: &str, fallback, cutout, maps| AssetEntry { id, file: file.to_string(), fallback, cutout, maps };
+        Self {
+            entries: vec![
+                entry(GrassTop, "grass_top_16x16.png", Required, Opaque, false),
+                entry(GrassSide, "grass_side_16x16.png", Required, Opaque, false),
+                entry(Dirt, "dirt_16x16.png", Required, Opaque, false),
+                entry(Stone, "stone_16x16.png", Required, Opaque, true),
+                entry(Wood, "wood_16x16.png", Required, Opaque, false),
+                entry(Leaves, "leaves_16x16.png", Required, CutoutMask::Leaves, false),
+                entry(Water, "water.png", Solid(50, 100, 200), Opaque, false),
+                entry(Lava, "lava.png", Solid(255, 100, 0), Opaque, false),
+                entry(Obsidian, "obsidian_16x16.png", Required, Opaque, true),
+                entry(Glowstone, "glowstone.png", Solid(255, 230, 180), Opaque, false),
+                entry(Diamond, "diamond.png", Solid(150, 230, 255), Opaque, true),
+                entry(Iron, "iron.png", Solid(180, 180, 180), Opaque, true),
+                entry(Chest, "chest.png", Solid(160, 100, 50), Opaque, false),
+                entry(Ice, "ice_16x16.png", Solid(180, 200, 255), Opaque, false),
+                entry(Portal, "portal.png", Solid(150, 50, 255), Opaque, false),
+                entry(Torch, "torch_16x16.png", Required, CutoutMask::None, false),
+                entry(Glass, "glass_16x16.png", Required, CutoutMask::Glass, false),
+                entry(Sand, "sand_16x16.png", Required, Opaque, false),
+                entry(Gravel, "gravel_16x16.png", Solid(130, 124, 120), Opaque, false),
+                entry(Clouds, "clouds.png", Required, Opaque, false),
+            ],
         }
     }
-    
-    pub fn get_grass_side(&self) -> Tex {
-        Tex {
-            pix: &self.grass_side_buf,
-            w: self.grass_side_wh.0,
-            h: self.grass_side_wh.1,
+}
+
+/// Carpetas donde buscar las texturas, en orden de prioridad.
+#[derive(Clone, Debug)]
+pub struct AssetRoots {
+    pub dirs: Vec<PathBuf>,
+}
+
+impl Default for AssetRoots {
+    /// `$DIORAMA_ASSETS`, luego `assets/` del directorio actual y por último el
+    /// `assets/` del crate (para ejecutar pruebas o el binario desde otra carpeta).
+    fn default() -> Self {
+        let mut dirs = Vec::new();
+        if let Some(dir) = std::env::var_os("DIORAMA_ASSETS") {
+            dirs.push(PathBuf::from(dir));
         }
-    }
-    
-    pub fn get_dirt(&self) -> Tex {
-        Tex {
-            pix: &self.dirt_buf,
-            w: self.dirt_wh.0,
-            h: self.dirt_wh.1,
+        dirs.push(PathBuf::from("assets"));
+        let crate_assets = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets");
+        if !dirs.contains(&crate_assets) {
+            dirs.push(crate_assets);
         }
+        Self { dirs }
     }
-    
-    pub fn get_stone(&self) -> Tex {
-        Tex {
-            pix: &self.stone_buf,
-            w: self.stone_wh.0,
-            h: self.stone_wh.1,
-        }
+}
+
+impl AssetRoots {
+    /// Antepone `dir` a las carpetas por defecto (`--assets <dir>`).
+    pub fn with_dir(dir: impl Into<PathBuf>) -> Self {
+        let mut roots = Self::default();
+        roots.dirs.insert(0, dir.into());
+        roots
     }
-    
-    pub fn get_wood(&self) -> Tex {
-        Tex {
-            pix: &self.wood_buf,
-            w: self.wood_wh.0,
-            h: self.wood_wh.1,
-        }
+
+    /// Primera ruta existente para `file`, o todas las que se probaron.
+    pub fn find(&self, file: &str) -> Result<PathBuf, Vec<PathBuf>> {
+        let tried: Vec<PathBuf> = self.dirs.iter().map(|d| d.join(file)).collect();
+        tried.iter().find(|p| p.is_file()).cloned().ok_or(tried)
     }
-    
-    pub fn get_leaves(&self) -> Tex {
-        Tex {
-            pix: &self.leaves_buf,
-            w: self.leaves_wh.0,
-            h: self.leaves_wh.1,
+}
+
+/// Por qué falló una textura.
+#[derive(Clone, Debug)]
+pub enum AssetProblem {
+    NotFound { tried: Vec<PathBuf> },
+    Decode { path: PathBuf, message: String },
+    NotInManifest,
+}
+
+/// Un archivo que no se pudo usar.
+#[derive(Clone, Debug)]
+pub struct AssetFailure {
+    pub id: AssetId,
+    pub file: String,
+    pub problem: AssetProblem,
+}
+
+impl fmt::Display for AssetFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}): ", self.file, self.id.label())?;
+        match &self.problem {
+            AssetProblem::NotFound { tried } => {
+                let tried: Vec<String> = tried.iter().map(|p| p.display().to_string()).collect();
+                write!(f, "no encontrado (se buscó en {})", tried.join(", "))
+            }
+            AssetProblem::Decode { path, message } => write!(f, "{} no se pudo leer: {}", path.display(), message),
+            AssetProblem::NotInManifest => write!(f, "no está en el manifiesto"),
         }
     }
-    
-    pub fn get_water(&self) -> Tex {
-        Tex {
-            pix: &self.water_buf,
-            w: self.water_wh.0,
-            h: self.water_wh.1,
+}
+
+/// Todas las texturas que fallaron al cargar.
+#[derive(Debug)]
+pub struct AssetError {
+    pub failures: Vec<AssetFailure>,
+}
+
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} textura(s) con problemas:", self.failures.len())?;
+        for failure in &self.failures {
+            write!(f, "\n  - {}", failure)?;
         }
+        Ok(())
     }
-    
-    pub fn get_lava(&self) -> Tex {
-        Tex {
-            pix: &self.lava_buf,
-            w: self.lava_wh.0,
-            h: self.lava_wh.1,
+}
+
+impl std::error::Error for AssetError {}
+
+/// Mapas opcionales de un material (`<base>_normal.png`, `_roughness.png`, ...).
+#[derive(Default)]
+pub struct MapSet {
+    pub normal: Option<Buffer>,
+    pub roughness: Option<Buffer>,
+    pub specular: Option<Buffer>,
+    pub emissive: Option<Buffer>,
+}
+
+impl MapSet {
+    /// Busca los mapas junto a `albedo_path`. Si no hay mapa de normales se
+    /// deriva uno del albedo usando su luminancia como altura. Un mapa que existe
+    /// pero no se puede leer se reporta en `failures`.
+    fn load(id: AssetId, albedo_path: &Path, albedo: &Buffer, failures: &mut Vec<AssetFailure>) -> Self {
+        let mut load = |suffix: &str| {
+            let stem = albedo_path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+            let path = albedo_path.with_file_name(format!("{}_{}.png", stem, suffix));
+            if !path.is_file() {
+                return None;
+            }
+            match decode(&path) {
+                Ok((buf, _)) => {
+                    println!("  ✓ Mapa: {} ({}x{})", path.display(), buf.1 .0, buf.1 .1);
+                    Some(buf)
+                }
+                Err(message) => {
+                    let file = path.file_name().map(|f| f.to_string_lossy().into_owned()).unwrap_or_default();
+                    failures.push(AssetFailure { id, file, problem: AssetProblem::Decode { path, message } });
+                    None
+                }
+            }
+        };
+        let normal = load("normal").or_else(|| Some(normal_from_albedo(albedo, 2.0)));
+        Self {
+            normal,
+            roughness: load("roughness"),
+            specular: load("specular"),
+            emissive: load("emissive"),
         }
     }
-    
-    pub fn get_obsidian(&self) -> Tex {
-        Tex {
-            pix: &self.obsidian_buf,
-            w: self.obsidian_wh.0,
-            h: self.obsidian_wh.1,
+
+    pub fn as_maps(&self) -> MaterialMaps<'_> {
+        MaterialMaps {
+            normal: as_tex(&self.normal),
+            roughness: as_tex(&self.roughness),
+            specular: as_tex(&self.specular),
+            emissive: as_tex(&self.emissive),
         }
     }
-    
-    pub fn get_glowstone(&self) -> Tex {
-        Tex {
-            pix: &self.glowstone_buf,
-            w: self.glowstone_wh.0,
-            h: self.glowstone_wh.1,
-        }
+}
+
+/// Estructura que almacena los buffers de todas las texturas cargadas.
+pub struct TextureStorage {
+    textures: Vec<Buffer>, // Indexado por `AssetId as usize`
+    maps: Vec<MapSet>,
+    placeholders: Vec<AssetId>,   // Dibujadas con el damero de "falta textura"
+    pub failures: Vec<AssetFailure>,
+}
+
+impl TextureStorage {
+    /// Carga el manifiesto por defecto desde las carpetas por defecto. Los errores se
+    /// imprimen y quedan en `failures`; nunca falla.
+    pub fn load() -> Self {
+        Self::load_from(&AssetRoots::default(), &AssetManifest::default())
     }
-    
-    pub fn get_diamond(&self) -> Tex {
-        Tex {
-            pix: &self.diamond_buf,
-            w: self.diamond_wh.0,
-            h: self.diamond_wh.1,
-        }
+
+    /// Como `load`, pero devuelve `AssetError` si alguna textura obligatoria falló.
+    pub fn try_load(roots: &AssetRoots, manifest: &AssetManifest) -> Result<Self, AssetError> {
+        let storage = Self::load_from(roots, manifest);
+        storage.check()?;
+        Ok(storage)
     }
-    
-    pub fn get_iron(&self) -> Tex {
-        Tex {
-            pix: &self.iron_buf,
-            w: self.iron_wh.0,
-            h: self.iron_wh.1,
+
+    pub fn load_from(roots: &AssetRoots, manifest: &AssetManifest) -> Self {
+        println!("Cargando texturas PNG para raytracing...");
+        let mut textures: Vec<Buffer> = AssetId::ALL.iter().map(|_| checkerboard()).collect();
+        let mut maps: Vec<MapSet> = AssetId::ALL.iter().map(|_| MapSet::default()).collect();
+        let mut placeholders = Vec::new();
+        let mut failures = Vec::new();
+
+        for id in AssetId::ALL {
+            let Some(entry) = manifest.entries.iter().find(|e| e.id == id) else {
+                placeholders.push(id);
+                failures.push(AssetFailure { id, file: String::new(), problem: AssetProblem::NotInManifest });
+                continue;
+            };
+            let found = roots.find(&entry.file);
+            let loaded = match &found {
+                Ok(path) => decode(path).map_err(|message| AssetProblem::Decode { path: path.clone(), message }),
+                Err(tried) => Err(AssetProblem::NotFound { tried: tried.clone() }),
+            };
+            let buf = match (loaded, entry.fallback) {
+                (Ok((buf, has_alpha)), _) => {
+                    if let Ok(path) = &found {
+                        println!("  ✓ Cargada: {} ({}x{})", path.display(), buf.1 .0, buf.1 .1);
+                    }
+                    if has_alpha { buf } else { apply_mask(buf, entry.cutout) }
+                }
+                (Err(AssetProblem::NotFound { .. }), Fallback::Solid(r, g, b)) => {
+                    println!("  · Sin textura: {} (usando color sólido)", entry.file);
+                    apply_mask(solid((r, g, b)), entry.cutout)
+                }
+                (Err(problem), _) => {
+                    println!("  ✗ {}: usando damero de textura faltante", entry.file);
+                    placeholders.push(id);
+                    failures.push(AssetFailure { id, file: entry.file.clone(), problem });
+                    checkerboard()
+                }
+            };
+            if entry.maps && !placeholders.contains(&id) {
+                // Los mapas se buscan junto al albedo (o donde estaría en la primera carpeta)
+                let base = found.unwrap_or_else(|tried| tried.into_iter().next().unwrap_or_else(|| PathBuf::from(&entry.file)));
+                maps[id as usize] = MapSet::load(id, &base, &buf, &mut failures);
+            }
+            textures[id as usize] = buf;
         }
-    }
-    
-    pub fn get_chest(&self) -> Tex {
-        Tex {
-            pix: &self.chest_buf,
-            w: self.chest_wh.0,
-            h: self.chest_wh.1,
+
+        let storage = Self { textures, maps, placeholders, failures };
+        if let Err(e) = storage.check() {
+            println!("{}", e);
         }
+        storage
     }
-    
-    pub fn get_ice(&self) -> Tex {
-        Tex {
-            pix: &self.ice_buf,
-            w: self.ice_wh.0,
-            h: self.ice_wh.1,
+
+    /// `Err` con la lista de archivos que fallaron, si hubo alguno.
+    pub fn check(&self) -> Result<(), AssetError> {
+        if self.failures.is_empty() {
+            return Ok(());
         }
+        Err(AssetError { failures: self.failures.clone() })
     }
-    
-    pub fn get_portal(&self) -> Tex {
-        Tex {
-            pix: &self.portal_buf,
-            w: self.portal_wh.0,
-            h: self.portal_wh.1,
-        }
+
+    /// `true` si `id` se dibuja con el damero de textura faltante.
+    pub fn is_placeholder(&self, id: AssetId) -> bool {
+        self.placeholders.contains(&id)
+    }
+
+    pub fn get(&self, id: AssetId) -> Tex<'_> {
+        let (pix, (w, h)) = &self.textures[id as usize];
+        Tex { pix, w: *w, h: *h }
+    }
+
+    pub fn maps(&self, id: AssetId) -> MaterialMaps<'_> {
+        self.maps[id as usize].as_maps()
     }
-    
-    pub fn get_torch(&self) -> Tex {
-        Tex {
-            pix: &self.torch_buf,
-            w: self.torch_wh.0,
-            h: self.torch_wh.1,
+
+    /// Construye los assets del raytracer con todas las texturas y el skybox.
+    /// Las texturas que faltan están presentes como damero a propósito: se notan en pantalla.
+    pub fn assets(&self) -> Assets<'_> {
+        // Crear skybox usando clouds.png para todas las caras
+        let clouds_tex = self.get(AssetId::Clouds);
+        let skybox_overworld = Skybox {
+            px: clouds_tex,  // +X (derecha)
+            nx: clouds_tex,  // -X (izquierda)
+            py: clouds_tex,  // +Y (arriba)
+            ny: clouds_tex,  // -Y (abajo)
+            pz: clouds_tex,  // +Z (frente)
+            nz: clouds_tex,  // -Z (atrás)
+            tint: Vec3::new(0.8, 0.9, 1.0), // Tinte azul cielo
+        };
+
+        Assets {
+            grass_cover: Some(self.get(AssetId::GrassTop)),
+            grass_side: Some(self.get(AssetId::GrassSide)),
+            dirt: Some(self.get(AssetId::Dirt)),
+            stone: Some(self.get(AssetId::Stone)),
+            wood: Some(self.get(AssetId::Wood)),
+            leaves: Some(self.get(AssetId::Leaves)),
+            water: Some(self.get(AssetId::Water)),
+            lava: Some(self.get(AssetId::Lava)),
+            obsidian: Some(self.get(AssetId::Obsidian)),
+            glowstone: Some(self.get(AssetId::Glowstone)),
+            diamond: Some(self.get(AssetId::Diamond)),
+            iron: Some(self.get(AssetId::Iron)),
+            chest: Some(self.get(AssetId::Chest)),
+            ice: Some(self.get(AssetId::Ice)),
+            portal: Some(self.get(AssetId::Portal)),
+            torch: Some(self.get(AssetId::Torch)),
+            glass: Some(self.get(AssetId::Glass)),
+            sand: Some(self.get(AssetId::Sand)),
+            gravel: Some(self.get(AssetId::Gravel)),
+            skybox_overworld: Some(skybox_overworld),
+            skybox_nether: None,  // Nether sin skybox (cielo procedural rojo)
+            stone_maps: self.maps(AssetId::Stone),
+            obsidian_maps: self.maps(AssetId::Obsidian),
+            iron_maps: self.maps(AssetId::Iron),
+            diamond_maps: self.maps(AssetId::Diamond),
+            materials: None,
         }
     }
-    
-    pub fn get_clouds(&self) -> Tex {
-        Tex {
-            pix: &self.clouds_buf,
-            w: self.clouds_wh.0,
-            h: self.clouds_wh.1,
+}
+
+/// Decodifica un PNG a RGBA8. Devuelve también si el archivo traía canal alfa.
+fn decode(path: &Path) -> Result<(Buffer, bool), String> {
+    let img = image::open(path).map_err(|e| e.to_string())?;
+    let has_alpha = img.color().has_alpha();
+    let rgba = img.to_rgba8();
+    let (w, h) = rgba.dimensions();
+    if w == 0 || h == 0 {
+        return Err("imagen vacía".into());
+    }
+    Ok(((rgba.into_raw(), (w, h)), has_alpha))
+}
+
+/// Textura 16×16 de un solo color.
+fn solid(rgb: (u8, u8, u8)) -> Buffer {
+    let (w, h) = (16u32, 16u32);
+    let buf = [rgb.0, rgb.1, rgb.2, 255].repeat((w * h) as usize);
+    (buf, (w, h))
+}
+
+/// Damero magenta/negro 16×16 (celdas de 4 px): la textura "falta" de siempre.
+fn checkerboard() -> Buffer {
+    let (w, h) = (16u32, 16u32);
+    let mut buf = Vec::with_capacity((w * h * 4) as usize);
+    for y in 0..h {
+        for x in 0..w {
+            let on = (x / 4 + y / 4) % 2 == 0;
+            buf.extend_from_slice(if on { &[255, 0, 255, 255] } else { &[0, 0, 0, 255] });
         }
     }
+    (buf, (w, h))
+}
+
+fn as_tex(b: &Option<Buffer>) -> Option<Tex<'_>> {
+    b.as_ref().map(|(pix, (w, h))| Tex { pix, w: *w, h: *h })
 }
 
-/// Carga una textura o genera una de color sólido si falla.
-fn load_or_default(path: &str, fallback_rgb: (u8, u8, u8)) -> (Vec<u8>, (u32, u32)) {
-    match image::open(path) {
-        Ok(img) => {
-            let rgba = img.to_rgba8();
-            let (w, h) = rgba.dimensions();
-            println!("  ✓ Cargada: {} ({}x{})", path, w, h);
-            (rgba.into_raw(), (w, h))
+/// Genera el canal alfa de un recorte a partir de la luminancia según `mask`.
+fn apply_mask(buf: Buffer, mask: CutoutMask) -> Buffer {
+    let (mut buf, (w, h)) = buf;
+    let lum = |px: &[u8]| 0.299 * px[0] as f32 + 0.587 * px[1] as f32 + 0.114 * px[2] as f32;
+    match mask {
+        CutoutMask::Opaque | CutoutMask::None => {}
+        CutoutMask::Leaves => {
+            // Umbral en el primer cuartil de luminancia: ~25% de huecos
+            let mut sorted: Vec<f32> = buf.chunks(4).map(lum).collect();
+            sorted.sort_by(|a, b| a.total_cmp(b));
+            let cutoff = sorted[sorted.len() / 4];
+            for px in buf.chunks_mut(4) {
+                if lum(px) <= cutoff {
+                    px[3] = 0;
+                }
+            }
         }
-        Err(_) => {
-            println!("  ✗ No encontrada: {} (usando color sólido)", path);
-            let w = 16u32;
-            let h = 16u32;
-            let mut buf = Vec::with_capacity((w * h * 4) as usize);
-            for _ in 0..(w * h) {
-                buf.push(fallback_rgb.0);
-                buf.push(fallback_rgb.1);
-                buf.push(fallback_rgb.2);
-                buf.push(255);
+        CutoutMask::Glass => {
+            let border = (w / 16).max(1);
+            for (i, px) in buf.chunks_mut(4).enumerate() {
+                let (x, y) = (i as u32 % w, i as u32 / w);
+                let frame = x < border || y < border || x >= w - border || y >= h - border;
+                if !frame && lum(px) < 230.0 {
+                    px[3] = 0;
+                }
             }
-            (buf, (w, h))
         }
     }
+    (buf, (w, h))
+}
+
+/// Genera un mapa de normales en espacio tangente a partir de la luminancia
+/// del albedo (filtro Sobel con bordes repetidos, como la textura).
+fn normal_from_albedo(albedo: &Buffer, strength: f32) -> Buffer {
+    let (buf, (w, h)) = albedo;
+    let (w, h) = (*w as i32, *h as i32);
+    let height = |x: i32, y: i32| -> f32 {
+        let idx = ((y.rem_euclid(h) * w + x.rem_euclid(w)) * 4) as usize;
+        (0.299 * buf[idx] as f32 + 0.587 * buf[idx + 1] as f32 + 0.114 * buf[idx + 2] as f32) / 255.0
+    };
+    let mut out = Vec::with_capacity((w * h * 4) as usize);
+    for y in 0..h {
+        for x in 0..w {
+            let dx = (height(x + 1, y - 1) + 2.0 * height(x + 1, y) + height(x + 1, y + 1))
+                - (height(x - 1, y - 1) + 2.0 * height(x - 1, y) + height(x - 1, y + 1));
+            // Filas de imagen crecen hacia abajo; v de la textura crece hacia arriba
+            let dy = (height(x - 1, y - 1) + 2.0 * height(x, y - 1) + height(x + 1, y - 1))
+                - (height(x - 1, y + 1) + 2.0 * height(x, y + 1) + height(x + 1, y + 1));
+            let (nx, ny, nz) = (-dx * strength, -dy * strength, 1.0);
+            let len = (nx * nx + ny * ny + nz * nz).sqrt();
+            out.push(((nx / len * 0.5 + 0.5) * 255.0) as u8);
+            out.push(((ny / len * 0.5 + 0.5) * 255.0) as u8);
+            out.push(((nz / len * 0.5 + 0.5) * 255.0) as u8);
+            out.push(255);
+        }
+    }
+    (out, (w as u32, h as u32))
 }
@@ -1,9 +1,11 @@
 //! Variante texturizada que envuelve un `SolidBlock` con un atlas.
 
 use crate::solid_block::SolidBlock;
+use crate::bvh::Aabb;
+use crate::lighting::Tex;
 use crate::math::Vec3;
 use crate::ray::Ray;
-use crate::materials::{Intersectable, MaterialParams};
+use crate::materials::{Intersectable, MaterialMaps, MaterialParams};
 
 /// Cubo que muestrea un atlas de textura por cara.
 pub struct TexturedBlock<'a> {
@@ -19,8 +21,18 @@ pub struct TexturedBlock<'a> {
     pub transparency: f32,
     pub ior: f32,
     pub emissive: Vec3,
+
+    // Mapas por texel (normal, rugosidad, especular, emisión)
+    pub maps: MaterialMaps<'a>,
+    pub roughness: f32,
+
+    // Recorte por alfa: los rayos atraviesan los texeles con alfa < ALPHA_CUTOFF
+    pub alpha_test: bool,
 }
 
+/// Umbral del recorte por alfa.
+pub const ALPHA_CUTOFF: f32 = 0.5;
+
 impl<'a> TexturedBlock<'a> {
     pub fn from_raw(
         inner: SolidBlock,
@@ -45,9 +57,30 @@ impl<'a> TexturedBlock<'a> {
             transparency,
             ior,
             emissive,
+            maps: MaterialMaps::default(),
+            roughness: inner.roughness,
+            alpha_test: false,
         }
     }
 
+    /// Añade mapas de material y la rugosidad base usada cuando no hay mapa de rugosidad.
+    pub fn with_maps(mut self, maps: MaterialMaps<'a>, roughness: f32) -> Self {
+        self.maps = maps;
+        self.roughness = roughness;
+        self
+    }
+
+    /// Activa el recorte por alfa de la textura.
+    pub fn with_alpha_test(mut self, alpha_test: bool) -> Self {
+        self.alpha_test = alpha_test;
+        self
+    }
+
+    fn is_opaque_at(&self, p: Vec3) -> bool {
+        let (u, v) = self.uv_from_point(p);
+        self.sample_rgba_a(u, v).1 >= ALPHA_CUTOFF
+    }
+
     fn sample_rgba_a(&self, u: f32, v: f32) -> (Vec3, f32) {
         let uu = u.clamp(0.0, 1.0 - f32::EPSILON);
         let vv = v.clamp(0.0, 1.0 - f32::EPSILON);
@@ -70,45 +103,72 @@ impl<'a> TexturedBlock<'a> {
         self.sample_rgba_a(u, v).0
     }
 
-    fn uv_from_point(&self, p: Vec3) -> (f32, f32) {
-        let n = self.inner.normal_at(p);
-        let min = self.inner.min;
-        let max = self.inner.max;
-        let dx = max.x - min.x;
-        let dy = max.y - min.y;
-        let dz = max.z - min.z;
-
+    /// Base tangente (T, B) de la cara que contiene `p`, alineada con `uv_from_point`:
+    /// T apunta hacia +u y B hacia +v.
+    fn tangent_frame(&self, n: Vec3) -> (Vec3, Vec3) {
         if n.x > 0.5 {
-            let u = (p.z - min.z) / dz;
-            let v = (p.y - min.y) / dy;
-            (u, v)
+            (Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 1.0, 0.0))
         } else if n.x < -0.5 {
-            let u = (max.z - p.z) / dz;
-            let v = (p.y - min.y) / dy;
-            (u, v)
+            (Vec3::new(0.0, 0.0, -1.0), Vec3::new(0.0, 1.0, 0.0))
         } else if n.y > 0.5 {
-            let u = (p.x - min.x) / dx;
-            let v = (max.z - p.z) / dz;
-            (u, v)
+            (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0))
         } else if n.y < -0.5 {
-            let u = (p.x - min.x) / dx;
-            let v = (p.z - min.z) / dz;
-            (u, v)
+            (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0))
         } else if n.z > 0.5 {
-            let u = (max.x - p.x) / dx;
-            let v = (p.y - min.y) / dy;
-            (u, v)
+            (Vec3::new(-1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0))
         } else {
-            let u = (p.x - min.x) / dx;
-            let v = (p.y - min.y) / dy;
-            (u, v)
+            (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0))
         }
     }
+
+    fn uv_from_point(&self, p: Vec3) -> (f32, f32) {
+        face_uv(self.inner.min, self.inner.max, self.inner.normal_at(p), p)
+    }
+}
+
+/// UV en [0,1] del punto `p` sobre la cara con normal `n` de la caja `min..max`
+/// (v hacia arriba). Una textura completa por cara, sin repetir.
+pub fn face_uv(min: Vec3, max: Vec3, n: Vec3, p: Vec3) -> (f32, f32) {
+    let dx = max.x - min.x;
+    let dy = max.y - min.y;
+    let dz = max.z - min.z;
+
+    if n.x > 0.5 {
+        ((p.z - min.z) / dz, (p.y - min.y) / dy)
+    } else if n.x < -0.5 {
+        ((max.z - p.z) / dz, (p.y - min.y) / dy)
+    } else if n.y > 0.5 {
+        ((p.x - min.x) / dx, (max.z - p.z) / dz)
+    } else if n.y < -0.5 {
+        ((p.x - min.x) / dx, (p.z - min.z) / dz)
+    } else if n.z > 0.5 {
+        ((max.x - p.x) / dx, (p.y - min.y) / dy)
+    } else {
+        ((p.x - min.x) / dx, (p.y - min.y) / dy)
+    }
 }
 
 impl<'a> Intersectable for TexturedBlock<'a> {
     fn intersect(&self, ray: &Ray) -> Option<f32> {
-        self.inner.intersect(ray)
+        if !self.alpha_test {
+            return self.inner.intersect(ray);
+        }
+        // Cara de entrada; si su texel es transparente, probar la cara de salida
+        let (tmin, tmax) = self.inner.intersect_range(ray)?;
+        let at = |t: f32| ray.orig.add(ray.dir.mul(t));
+        if tmin >= 0.0 && self.is_opaque_at(at(tmin)) {
+            return Some(tmin);
+        }
+        if tmax > 1e-4 && self.is_opaque_at(at(tmax)) {
+            return Some(tmax);
+        }
+        None
+    }
+    fn is_cutout(&self) -> bool {
+        self.alpha_test
+    }
+    fn face_texture(&self, _n: Vec3) -> Option<Tex<'_>> {
+        Some(Tex { pix: self.pixels, w: self.w, h: self.h })
     }
     fn normal_at(&self, point: Vec3) -> Vec3 {
         self.inner.normal_at(point)
@@ -120,6 +180,10 @@ impl<'a> Intersectable for TexturedBlock<'a> {
         let (u, v) = self.uv_from_point(point);
         self.sample_rgba(u, v)
     }
+    fn bounds(&self) -> Aabb {
+        self.inner.bounds()
+    }
+
     fn center(&self) -> Vec3 {
         Vec3::new(
             (self.inner.min.x + self.inner.max.x) * 0.5,
@@ -130,15 +194,24 @@ impl<'a> Intersectable for TexturedBlock<'a> {
     fn material_at(&self, p: Vec3) -> MaterialParams {
         let (u, v) = self.uv_from_point(p);
         let (rgb, a) = self.sample_rgba_a(u, v);
-        MaterialParams {
+        let mut params = MaterialParams {
             albedo: rgb,
             specular_strength: self.specular_strength,
             shininess: self.shininess,
             reflectivity: self.reflectivity,
             transparency: self.transparency,
             ior: self.ior,
+            dispersion: self.inner.dispersion,
             emissive: self.emissive,
             opacity: a,   // <-- usa alfa del PNG
+            roughness: self.roughness,
+            normal: None,
+        };
+        if !self.maps.is_empty() {
+            let n = self.inner.normal_at(p);
+            let (t, b) = self.tangent_frame(n);
+            self.maps.apply(&mut params, u, v, t, b, n);
         }
+        params
     }
 }
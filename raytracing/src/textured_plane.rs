@@ -95,8 +95,11 @@ impl<'a> Intersectable for TexturedPlane<'a> {
             reflectivity: self.reflectivity,
             transparency: self.transparency,
             ior: self.ior,
+            dispersion: 0.0,
             emissive: self.emissive,
             opacity: 1.0,
+            roughness: 0.0,
+            normal: None,
         }
     }
 }
@@ -0,0 +1,98 @@
+//! Vigilancia de archivos por sondeo para la recarga en caliente del visor.
+//!
+//! Cada cierto intervalo se compara la fecha de modificación y el tamaño de los
+//! archivos vigilados (o de los archivos dentro de una carpeta). Un cambio se reporta
+//! cuando se mantiene igual en dos sondeos seguidos, para no leer un PNG a medio
+//! guardar por el editor.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Identificador de un objetivo registrado con `FileWatcher::watch`.
+pub type WatchId = usize;
+
+/// Huella de un archivo o carpeta: (ruta, fecha de modificación, tamaño) de cada archivo.
+type Stamp = Vec<(PathBuf, Option<SystemTime>, u64)>;
+
+struct Target {
+    path: PathBuf,
+    seen: Stamp,
+    pending: Option<Stamp>, // Cambio visto en el último sondeo, aún sin confirmar
+}
+
+pub struct FileWatcher {
+    targets: Vec<Target>,
+    interval: Duration,
+    next_poll: Instant,
+}
+
+fn stamp(path: &Path) -> Stamp {
+    let file_stamp = |p: &Path| {
+        let meta = std::fs::metadata(p).ok();
+        let modified = meta.as_ref().and_then(|m| m.modified().ok());
+        (p.to_path_buf(), modified, meta.map_or(0, |m| m.len()))
+    };
+    if path.is_dir() {
+        let mut files: Vec<PathBuf> = std::fs::read_dir(path)
+            .map(|dir| dir.filter_map(|e| e.ok()).map(|e| e.path()).filter(|p| p.is_file()).collect())
+            .unwrap_or_default();
+        files.sort();
+        files.iter().map(|p| file_stamp(p)).collect()
+    } else {
+        // Un archivo que aún no existe también se vigila: crearlo cuenta como cambio
+        vec![file_stamp(path)]
+    }
+}
+
+impl FileWatcher {
+    /// Vigilante que sondea cada `interval`.
+    pub fn new(interval: Duration) -> Self {
+        Self { targets: Vec::new(), interval, next_poll: Instant::now() + interval }
+    }
+
+    /// Empieza a vigilar un archivo o, si es carpeta, los archivos que contiene.
+    pub fn watch(&mut self, path: impl Into<PathBuf>) -> WatchId {
+        let path = path.into();
+        let seen = stamp(&path);
+        self.targets.push(Target { path, seen, pending: None });
+        self.targets.len() - 1
+    }
+
+    /// Ruta registrada para `id`.
+    pub fn path(&self, id: WatchId) -> &Path {
+        &self.targets[id].path
+    }
+
+    /// Toma el estado actual de `id` como ya visto, p. ej. tras escribir el archivo
+    /// desde el propio visor.
+    pub fn refresh(&mut self, id: WatchId) {
+        let target = &mut self.targets[id];
+        target.seen = stamp(&target.path);
+        target.pending = None;
+    }
+
+    /// Objetivos cuyo cambio ya se asentó. Entre sondeos devuelve una lista vacía
+    /// sin tocar el disco, así que se puede llamar en cada cuadro.
+    pub fn poll(&mut self) -> Vec<WatchId> {
+        let now = Instant::now();
+        if now < self.next_poll {
+            return Vec::new();
+        }
+        self.next_poll = now + self.interval;
+
+        let mut changed = Vec::new();
+        for (id, target) in self.targets.iter_mut().enumerate() {
+            let current = stamp(&target.path);
+            if current == target.seen {
+                target.pending = None;
+            } else if target.pending.as_ref() == Some(&current) {
+                target.seen = current;
+                target.pending = None;
+                changed.push(id);
+            } else {
+                target.pending = Some(current);
+            }
+        }
+        changed
+    }
+}
@@ -0,0 +1,199 @@
+//! Pruebas de regresión por imagen de referencia ("golden images").
+//!
+//! Cada prueba renderiza una escena fija a baja resolución con cámara y sol
+//! fijos y la compara con `tests/golden/<nombre>.png`. Si la diferencia supera
+//! la tolerancia se escriben `<nombre>_actual.png` y `<nombre>_diff.png` en el
+//! directorio temporal de cargo y la prueba falla.
+//!
+//! Para regenerar las referencias tras un cambio intencional:
+//! `UPDATE_GOLDEN=1 cargo test --test golden`
+
+use std::path::{Path, PathBuf};
+
+use raytracing_diorama::aov::RenderView;
+use raytracing_diorama::camera::{Camera, OrbitCamera};
+use raytracing_diorama::math::Vec3;
+use raytracing_diorama::raytracer::{self, RenderSettings, SceneData, WorldKind};
+use raytracing_diorama::terrain::TerrainConfig;
+use raytracing_diorama::texture_loader::TextureStorage;
+
+const WIDTH: u32 = 96;
+const HEIGHT: u32 = 64;
+const SUN_ANGLE: f32 = 0.6;
+
+/// Diferencia perceptual (ΔE en CIELAB) a partir de la cual un píxel cuenta como distinto.
+const MAX_DELTA_E: f32 = 6.0;
+/// Fracción de píxeles distintos tolerada antes de fallar.
+const MAX_BAD_FRACTION: f32 = 0.005;
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden")
+}
+
+fn output_dir() -> PathBuf {
+    Path::new(env!("CARGO_TARGET_TMPDIR")).join("golden")
+}
+
+fn camera() -> Camera {
+    OrbitCamera::new(0.6, 0.25, 18.0, Vec3::new(0.0, 2.0, 0.0)).to_camera(60.0)
+}
+
+fn render_scene(scene: &SceneData, cam: &Camera) -> Vec<u8> {
+    render_view(scene, cam, RenderView::Beauty)
+}
+
+fn render_view(scene: &SceneData, cam: &Camera, view: RenderView) -> Vec<u8> {
+    render_with(scene, cam, &RenderSettings { view, ..RenderSettings::default() })
+}
+
+fn render_with(scene: &SceneData, cam: &Camera, settings: &RenderSettings) -> Vec<u8> {
+    let mut frame = vec![0u8; (WIDTH * HEIGHT * 4) as usize];
+    raytracer::render(
+        &mut frame,
+        WIDTH as i32,
+        HEIGHT as i32,
+        cam,
+        SUN_ANGLE,
+        scene,
+        settings,
+    );
+    frame
+}
+
+/// sRGB (0..255) a CIELAB con iluminante D65.
+fn to_lab(px: &[u8]) -> [f32; 3] {
+    let lin = |c: u8| {
+        let c = c as f32 / 255.0;
+        if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+    };
+    let (r, g, b) = (lin(px[0]), lin(px[1]), lin(px[2]));
+    let x = (0.4124 * r + 0.3576 * g + 0.1805 * b) / 0.95047;
+    let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    let z = (0.0193 * r + 0.1192 * g + 0.9505 * b) / 1.08883;
+    let f = |t: f32| if t > 0.008856 { t.cbrt() } else { 7.787 * t + 16.0 / 116.0 };
+    let (fx, fy, fz) = (f(x), f(y), f(z));
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+fn delta_e(a: &[u8], b: &[u8]) -> f32 {
+    let (la, lb) = (to_lab(a), to_lab(b));
+    ((la[0] - lb[0]).powi(2) + (la[1] - lb[1]).powi(2) + (la[2] - lb[2]).powi(2)).sqrt()
+}
+
+/// Compara contra la referencia; devuelve la imagen de diferencias si falla.
+fn compare(actual: &[u8], expected: &[u8]) -> Result<(), (usize, Vec<u8>)> {
+    let mut diff = Vec::with_capacity(actual.len());
+    let mut bad = 0usize;
+    for (a, e) in actual.chunks(4).zip(expected.chunks(4)) {
+        if delta_e(a, e) > MAX_DELTA_E {
+            bad += 1;
+            diff.extend_from_slice(&[255, 0, 255, 255]);
+        } else {
+            // Referencia atenuada en gris para ubicar las zonas con errores
+            let l = ((e[0] as u32 + e[1] as u32 + e[2] as u32) / 9) as u8;
+            diff.extend_from_slice(&[l, l, l, 255]);
+        }
+    }
+    if bad as f32 > MAX_BAD_FRACTION * (WIDTH * HEIGHT) as f32 {
+        Err((bad, diff))
+    } else {
+        Ok(())
+    }
+}
+
+fn save_png(path: &Path, rgba: &[u8]) {
+    image::save_buffer(path, rgba, WIDTH, HEIGHT, image::ColorType::Rgba8)
+        .unwrap_or_else(|e| panic!("no se pudo escribir {}: {}", path.display(), e));
+}
+
+fn check_golden(name: &str, actual: &[u8]) {
+    let reference = golden_dir().join(format!("{}.png", name));
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::create_dir_all(golden_dir()).unwrap();
+        save_png(&reference, actual);
+        println!("referencia actualizada: {}", reference.display());
+        return;
+    }
+
+    let expected = image::open(&reference)
+        .unwrap_or_else(|e| {
+            panic!(
+                "falta la referencia {} ({}); ejecute con UPDATE_GOLDEN=1 para crearla",
+                reference.display(),
+                e
+            )
+        })
+        .to_rgba8();
+    assert_eq!(
+        expected.dimensions(),
+        (WIDTH, HEIGHT),
+        "la referencia {} tiene otro tamaño",
+        reference.display()
+    );
+
+    if let Err((bad, diff)) = compare(actual, expected.as_raw()) {
+        let out = output_dir();
+        std::fs::create_dir_all(&out).unwrap();
+        let actual_path = out.join(format!("{}_actual.png", name));
+        let diff_path = out.join(format!("{}_diff.png", name));
+        save_png(&actual_path, actual);
+        save_png(&diff_path, &diff);
+        panic!(
+            "{}: {} de {} píxeles difieren (ΔE > {}). Ver {} y {}",
+            name,
+            bad,
+            WIDTH * HEIGHT,
+            MAX_DELTA_E,
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+}
+
+#[test]
+fn overworld() {
+    let textures = TextureStorage::load();
+    let scene = raytracer::build_scene(&textures.assets(), WorldKind::Overworld);
+    check_golden("overworld", &render_scene(&scene, &camera()));
+}
+
+/// Solo geometría y mapas de normales: aísla regresiones de intersección.
+#[test]
+fn overworld_normals() {
+    let textures = TextureStorage::load();
+    let scene = raytracer::build_scene(&textures.assets(), WorldKind::Overworld);
+    check_golden("overworld_normals", &render_view(&scene, &camera(), RenderView::Normals));
+}
+
+#[test]
+fn nether() {
+    let textures = TextureStorage::load();
+    let scene = raytracer::build_scene(&textures.assets(), WorldKind::Nether);
+    check_golden("nether", &render_scene(&scene, &camera()));
+}
+
+#[test]
+fn material_board() {
+    let textures = TextureStorage::load();
+    let scene = raytracer::build_material_board(&textures.assets());
+    let cam = OrbitCamera::new(0.0, 0.35, 6.0, Vec3::new(0.0, 1.0, 0.0)).to_camera(60.0);
+    check_golden("material_board", &render_scene(&scene, &cam));
+}
+
+/// Dispersión y cáusticas: el diamante, el portal y el hielo vistos desde el lado de la sombra.
+#[test]
+fn material_board_spectral() {
+    let textures = TextureStorage::load();
+    let scene = raytracer::build_spectral_board(&textures.assets());
+    let cam = OrbitCamera::new(3.8, 0.9, 5.0, Vec3::new(3.0, 0.5, -1.0)).to_camera(60.0);
+    let mut settings = RenderSettings { max_depth: 3, ..RenderSettings::default() };
+    settings.spectral.enabled = true;
+    check_golden("material_board_spectral", &render_with(&scene, &cam, &settings));
+}
+
+#[test]
+fn procedural_terrain() {
+    let textures = TextureStorage::load();
+    let scene = raytracer::build_terrain_scene(&textures.assets(), &TerrainConfig::with_seed(7, 12));
+    check_golden("procedural_terrain", &render_scene(&scene, &camera()));
+}